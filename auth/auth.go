@@ -7,6 +7,7 @@ import (
 	"strings"
 	"time"
 
+	"github.com/99designs/gqlgen/graphql/handler/transport"
 	jwt "github.com/golang-jwt/jwt/v5"
 	"golang.org/x/crypto/bcrypt"
 )
@@ -19,6 +20,15 @@ type ctxWriterKey string
 
 const writerKey ctxWriterKey = "writerKey"
 
+type ctxClientKey string
+
+const clientKey ctxClientKey = "clientKey"
+
+// ClientHeader is the header (and connection_init payload key) the app sends
+// with the id it generates for itself, so that the events caused by its own
+// requests aren't sent back to it.
+const ClientHeader = "X-Client-Id"
+
 // User is a struct that holds user information.
 type User struct {
 	Email, PasswordHash string
@@ -63,6 +73,13 @@ func userFromToken(tok *jwt.Token) (User, bool) {
 	return User{Email: email}, true
 }
 
+// ClientFromContext returns the id of the app instance the request came from,
+// or an empty string if it didn't send one.
+func ClientFromContext(ctx context.Context) string {
+	client, _ := ctx.Value(clientKey).(string)
+	return client
+}
+
 func writerFromContext(ctx context.Context) (http.ResponseWriter, bool) {
 	// TODO: Return specific error?
 	iWriter := ctx.Value(writerKey)
@@ -114,6 +131,9 @@ func CheckHash(password, hash string) bool {
 func Middleware(h http.Handler) http.Handler {
 	return http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
 		r = r.WithContext(context.WithValue(r.Context(), writerKey, w))
+		if client := r.Header.Get(ClientHeader); client != "" {
+			r = r.WithContext(context.WithValue(r.Context(), clientKey, client))
+		}
 		tokStr := ""
 		cookie, err := r.Cookie(cookieName)
 		if err == nil {
@@ -126,16 +146,49 @@ func Middleware(h http.Handler) http.Handler {
 			}
 		}
 		if tokStr != "" {
-			if tok, err := parseToken(tokStr); err == nil {
-				if user, ok := userFromToken(tok); ok {
-					r = r.WithContext(context.WithValue(r.Context(), userKey, user))
-				}
+			if ctx, ok := contextWithToken(r.Context(), tokStr); ok {
+				r = r.WithContext(ctx)
 			}
 		}
 		h.ServeHTTP(w, r)
 	})
 }
 
+// WebsocketInit authenticates a websocket connection using the token in the
+// connection_init payload, since browsers can't set headers on websockets. If
+// the user was already gotten from the cookie, the payload's token is ignored.
+// The app's client id is also taken from the payload.
+func WebsocketInit(ctx context.Context, payload transport.InitPayload) (context.Context, error) {
+	if client := payload.GetString(ClientHeader); client != "" {
+		ctx = context.WithValue(ctx, clientKey, client)
+	}
+	if _, ok := UserFromContext(ctx); ok {
+		return ctx, nil
+	}
+	authParts := strings.Split(payload.Authorization(), " ")
+	if len(authParts) == 2 {
+		if userCtx, ok := contextWithToken(ctx, authParts[1]); ok {
+			return userCtx, nil
+		}
+	}
+	// Let the connection through; resolvers deny access without a user
+	return ctx, nil
+}
+
+// contextWithToken adds the user from the token to the context, returning
+// false if the token is invalid.
+func contextWithToken(ctx context.Context, tokStr string) (context.Context, bool) {
+	tok, err := parseToken(tokStr)
+	if err != nil {
+		return ctx, false
+	}
+	user, ok := userFromToken(tok)
+	if !ok {
+		return ctx, false
+	}
+	return context.WithValue(ctx, userKey, user), true
+}
+
 // TestMiddleware returns middleware for testing
 func TestMiddleware(h http.Handler) http.Handler {
 	return http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
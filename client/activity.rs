@@ -0,0 +1,313 @@
+// Helpers for a part's activity log and comments: describing the changes made to a part in
+// words and threading the comments for display
+use crate::graphql::{ActivityKind, Comment, Part};
+use std::collections::{BTreeMap, HashMap};
+
+type PartsMap = BTreeMap<i64, Part>;
+
+// Returns the log entries describing the changes between the part before and after it was
+// updated (none if nothing changed). Parents are named using the parts.
+pub fn describe_update(
+    before: &Part,
+    after: &Part,
+    parts: &PartsMap,
+) -> Vec<(ActivityKind, String)> {
+    let mut entries = Vec::new();
+    if before.name != after.name {
+        entries.push((
+            ActivityKind::Renamed,
+            format!("Renamed from \"{}\" to \"{}\"", before.name, after.name),
+        ));
+    }
+    if before.deadline != after.deadline {
+        let message = match (before.deadline, after.deadline) {
+            (None, _) => format!("Set the deadline to {}", after.deadline_to_string()),
+            (_, None) => format!("Removed the deadline (was {})", before.deadline_to_string()),
+            _ => format!(
+                "Moved the deadline from {} to {}",
+                before.deadline_to_string(),
+                after.deadline_to_string(),
+            ),
+        };
+        entries.push((ActivityKind::DeadlineMoved, message));
+    }
+    match (before.completed_at, after.completed_at) {
+        (None, Some(_)) => entries.push((ActivityKind::Completed, String::from("Completed"))),
+        (Some(_), None) => entries.push((ActivityKind::Reopened, String::from("Reopened"))),
+        (Some(old), Some(new)) if old != new => entries.push((
+            ActivityKind::Completed,
+            format!(
+                "Changed the completion time to {}",
+                after.completed_at_to_string()
+            ),
+        )),
+        _ => (),
+    }
+    if before.parent_id != after.parent_id {
+        entries.push((
+            ActivityKind::Moved,
+            format!(
+                "Moved from {} to {}",
+                parent_name(parts, before.parent_id),
+                parent_name(parts, after.parent_id),
+            ),
+        ));
+    }
+    if before.description != after.description {
+        let message = match after.description {
+            Some(_) => "Edited the description",
+            None => "Removed the description",
+        };
+        entries.push((ActivityKind::Edited, String::from(message)));
+    }
+    if before.tags != after.tags {
+        let message = match after.tags.as_slice() {
+            [] => String::from("Removed the tags"),
+            tags => format!(
+                "Changed the tags to {}",
+                tags.iter()
+                    .map(|tag| format!("#{}", tag))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+        };
+        entries.push((ActivityKind::Edited, message));
+    }
+    if before.assignee != after.assignee {
+        let message = match (&before.assignee, &after.assignee) {
+            (_, Some(assignee)) => format!("Assigned to {}", assignee),
+            (Some(assignee), None) => format!("Unassigned {}", assignee),
+            (None, None) => unreachable!(),
+        };
+        entries.push((ActivityKind::Edited, message));
+    }
+    entries
+}
+
+// Returns the log entry for the creation of the part
+pub fn describe_creation(part: &Part, parts: &PartsMap) -> (ActivityKind, String) {
+    let message = match part.parent_id {
+        Some(pid) => format!("Created under {}", parent_name(parts, Some(pid))),
+        None => String::from("Created as a project"),
+    };
+    (ActivityKind::Created, message)
+}
+
+fn parent_name(parts: &PartsMap, parent_id: Option<i64>) -> String {
+    match parent_id {
+        Some(pid) => parts
+            .get(&pid)
+            .map(|parent| format!("\"{}\"", parent.name))
+            .unwrap_or_else(|| format!("#{}", pid)),
+        None => String::from("the top level"),
+    }
+}
+
+// Returns the comments in display order, each with its depth in the thread: replies follow the
+// comment they reply to (and its earlier replies). Replies to missing comments are shown as
+// top-level comments.
+pub fn thread(comments: &[Comment]) -> Vec<(usize, &Comment)> {
+    let mut replies = HashMap::<i64, Vec<&Comment>>::new();
+    let mut roots = Vec::new();
+    for comment in comments {
+        match comment.reply_to {
+            Some(rid) if comments.iter().any(|c| c.id == rid) => {
+                replies.entry(rid).or_default().push(comment)
+            }
+            _ => roots.push(comment),
+        }
+    }
+    let mut threaded = Vec::with_capacity(comments.len());
+    let mut stack = roots.into_iter().rev().map(|c| (0, c)).collect::<Vec<_>>();
+    while let Some((depth, comment)) = stack.pop() {
+        threaded.push((depth, comment));
+        if let Some(replies) = replies.get(&comment.id) {
+            stack.extend(replies.iter().rev().map(|&reply| (depth + 1, reply)));
+        }
+    }
+    threaded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::prelude::*;
+
+    fn part(id: i64, name: &str) -> Part {
+        Part {
+            id,
+            name: String::from(name),
+            ..Default::default()
+        }
+    }
+
+    fn parts() -> PartsMap {
+        [part(1, "Project"), part(2, "Other")]
+            .into_iter()
+            .map(|part| (part.id, part))
+            .collect()
+    }
+
+    fn comment(id: i64, reply_to: Option<i64>) -> Comment {
+        Comment {
+            id,
+            part_id: 1,
+            reply_to,
+            author: String::from("jane@example.com"),
+            body: format!("Comment {}", id),
+            created_at: Local::now(),
+        }
+    }
+
+    #[test]
+    fn describes_nothing_when_unchanged() {
+        let before = part(3, "Task");
+        assert!(describe_update(&before, &before.clone(), &parts()).is_empty());
+    }
+
+    #[test]
+    fn describes_renames_and_moves() {
+        let before = Part {
+            parent_id: Some(1),
+            ..part(3, "Task")
+        };
+        let after = Part {
+            parent_id: Some(2),
+            ..part(3, "Renamed")
+        };
+        assert_eq!(
+            describe_update(&before, &after, &parts()),
+            vec![
+                (
+                    ActivityKind::Renamed,
+                    String::from("Renamed from \"Task\" to \"Renamed\"")
+                ),
+                (
+                    ActivityKind::Moved,
+                    String::from("Moved from \"Project\" to \"Other\"")
+                ),
+            ],
+        );
+        let after = Part {
+            parent_id: None,
+            ..before.clone()
+        };
+        assert_eq!(
+            describe_update(&before, &after, &parts()),
+            vec![(
+                ActivityKind::Moved,
+                String::from("Moved from \"Project\" to the top level")
+            )],
+        );
+    }
+
+    #[test]
+    fn describes_deadlines() {
+        let dt = |day| Local.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap();
+        let before = Part {
+            deadline: Some(dt(1)),
+            ..part(3, "Task")
+        };
+        let after = Part {
+            deadline: Some(dt(2)),
+            ..before.clone()
+        };
+        assert_eq!(
+            describe_update(&before, &after, &parts()),
+            vec![(
+                ActivityKind::DeadlineMoved,
+                format!(
+                    "Moved the deadline from {} to {}",
+                    before.deadline_to_string(),
+                    after.deadline_to_string()
+                ),
+            )],
+        );
+        let after = Part {
+            deadline: None,
+            ..before.clone()
+        };
+        assert_eq!(
+            describe_update(&before, &after, &parts())[0].1,
+            format!("Removed the deadline (was {})", before.deadline_to_string()),
+        );
+    }
+
+    #[test]
+    fn describes_completion() {
+        let before = part(3, "Task");
+        let after = Part {
+            completed_at: Some(Local::now()),
+            ..before.clone()
+        };
+        assert_eq!(
+            describe_update(&before, &after, &parts()),
+            vec![(ActivityKind::Completed, String::from("Completed"))],
+        );
+        assert_eq!(
+            describe_update(&after, &before, &parts()),
+            vec![(ActivityKind::Reopened, String::from("Reopened"))],
+        );
+    }
+
+    #[test]
+    fn describes_other_edits() {
+        let before = part(3, "Task");
+        let after = Part {
+            description: Some(String::from("Details")),
+            tags: vec![String::from("a"), String::from("b")],
+            assignee: Some(String::from("jane@example.com")),
+            ..before.clone()
+        };
+        assert_eq!(
+            describe_update(&before, &after, &parts()),
+            vec![
+                (ActivityKind::Edited, String::from("Edited the description")),
+                (
+                    ActivityKind::Edited,
+                    String::from("Changed the tags to #a #b")
+                ),
+                (
+                    ActivityKind::Edited,
+                    String::from("Assigned to jane@example.com")
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn describes_creation() {
+        let child = Part {
+            parent_id: Some(1),
+            ..part(3, "Task")
+        };
+        assert_eq!(
+            describe_creation(&child, &parts()).1,
+            "Created under \"Project\""
+        );
+        assert_eq!(
+            describe_creation(&part(4, "New"), &parts()).1,
+            "Created as a project"
+        );
+    }
+
+    #[test]
+    fn threads_replies_under_their_comments() {
+        let comments = [
+            comment(1, None),
+            comment(2, None),
+            comment(3, Some(1)),
+            comment(4, Some(3)),
+            comment(5, Some(1)),
+            comment(6, Some(99)),
+        ];
+        let threaded = thread(&comments)
+            .into_iter()
+            .map(|(depth, c)| (depth, c.id))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            threaded,
+            vec![(0, 1), (1, 3), (2, 4), (1, 5), (0, 2), (0, 6)]
+        );
+    }
+}
@@ -1,38 +1,74 @@
 // TODO: Make dtl_from_input return and uses better
-// TODO: Make create/edit HTML reusable
 // TODO: Create filter dropdown
 // TODO: Fix filtering method: right now, a child would be displayed if the parent doesn't pass the
 // filter
 use crate::{
-    console,
+    activity, avatar, console, dep_graph,
     graphql::{
+        add_blocker_mutation::Variables as AddBlockerVars,
+        batch_delete_parts_mutation::Variables as BatchDeletePartsVars,
+        batch_update_parts_mutation::Variables as BatchUpdatePartsVars,
         create_part_mutation::{
             CreatePartMutationCreatePart as CreatePartPart, Variables as CreatePartVars,
         },
-        create_user_mutation::{CreateUserInput, Variables as CreateUserVars},
+        create_time_entry_mutation::{CreateTimeEntryInput, Variables as CreateTimeEntryVars},
         delete_part_mutation::Variables as DeletePartVars,
-        err_is_access, get_parts,
-        login_user_mutation::{LoginUserInput, Variables as LoginUserVars},
-        parts_query::PartsQueryParts,
-        send_create_part, send_create_user, send_delete_part, send_login_user, send_logout_user,
-        send_update_part,
+        delete_time_entry_mutation::Variables as DeleteTimeEntryVars,
+        err_is_access, get_assigned_parts, get_burndown, get_children, get_memberships,
+        get_part_activity, get_part_tree, get_parts_by_id, get_parts_since, get_shared_projects,
+        get_stats, get_time_entries, get_trash,
+        remove_blocker_mutation::Variables as RemoveBlockerVars,
+        send_add_blocker, send_add_comment, send_batch_delete_parts, send_batch_update_parts,
+        send_change_email, send_change_password, send_create_part, send_create_time_entry,
+        send_delete_account, send_delete_comment, send_delete_part, send_delete_time_entry,
+        send_logout_user, send_purge_part, send_record_activity, send_remove_blocker,
+        send_reset_password, send_restore_part, send_set_archived, send_share_project,
+        send_start_timer, send_stop_timer, send_unshare_project, send_update_part,
+        start_timer_mutation::Variables as StartTimerVars,
+        stop_timer_mutation::Variables as StopTimerVars,
+        time_entries_query::TimeEntriesQueryTimeEntries,
         update_part_mutation::{
             UpdatePartMutationUpdatePart as UpdatePartPart, Variables as UpdatePartVars,
         },
-        Part, PartialResult, DTL_INPUT_FMT, TZ,
+        Activity, ActivityKind, AssignedPart, BatchItem, BurndownData, ChildrenPage, Comment,
+        Membership, Part, PartActivity, PartEvent, PartialOk, PartsDelta, Role, SharedProject,
+        StatsData, TimeEntry, TrashedPart, DTL_INPUT_FMT, TZ,
     },
+    history::{Change, Entry, History, Outcome, Snapshot},
+    login_form::LoginForm,
+    markdown,
+    modal::{Confirm, ConfirmModal},
+    palette::CommandPalette,
+    part_form::{ParentOption, PartDraft, PartForm},
+    parts_cache::{Loaded, PartsCache},
+    quick_add::{self, ParentMatch, QuickAdd},
+    requests::Requests,
+    route::Route,
+    stats::{self, Burndown, Stats},
+    sync::{self, PartsSync, SyncEvent},
+    templates::{self, Template},
+    time_tracking::{self, WeekReport},
+    toast::{Toast, Toasts},
+    tree_state::{self, TreeState},
+    validation,
+    virtual_list::{Slot, VirtualList},
 };
 use chrono::prelude::*;
-use futures::FutureExt;
+use futures::{Future, FutureExt};
+use gloo::{
+    events::EventListener,
+    timers::callback::{Interval, Timeout},
+};
 use std::{
     cell::{Ref, RefCell, RefMut},
-    collections::{BTreeMap, BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    ops::Range,
     rc::Rc,
 };
-use wasm_bindgen::JsCast;
+use wasm_bindgen::{JsCast, JsValue};
 use web_sys::{
     Element, HtmlButtonElement, HtmlElement, HtmlFormElement, HtmlInputElement, HtmlSelectElement,
-    HtmlTextAreaElement,
+    HtmlTextAreaElement, ScrollIntoViewOptions, ScrollLogicalPosition,
 };
 use yew::{html::TargetCast, prelude::*};
 
@@ -43,6 +79,23 @@ lazy_static::lazy_static! {
 
 type PartsMap = BTreeMap<i64, Part>;
 
+// The estimated height (in px) of a part tree row that hasn't been rendered yet
+const TREE_ROW_HEIGHT: f64 = 30.0;
+// The indentation (in px) of each level of the part tree
+const TREE_INDENT: usize = 40;
+// The number of weeks of completions shown in the stats
+const STATS_WEEKS: usize = 12;
+// The number of parts listed at a time in the select of blockers to add to a part
+const BLOCKER_PAGE_SIZE: usize = 50;
+
+// A row of the (flattened) part tree
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum TreeRow {
+    Part { id: i64, depth: usize },
+    // Shows that more of the parent's children are loading or can be loaded
+    More { parent_id: i64, depth: usize },
+}
+
 #[derive(Default, PartialEq)]
 pub struct PartsMaps {
     // BTreeMap<id, PartsQueryParts>
@@ -56,34 +109,282 @@ pub struct PartsMaps {
 
     filter_method: FilterMethod,
     filtered_ids: Option<BTreeSet<i64>>,
+
+    // BTreeMap<part_id, time entries>
+    time_entries: BTreeMap<i64, Vec<TimeEntry>>,
+
+    // Ids of the parts selected for batch operations
+    selected: BTreeSet<i64>,
+    // The last part (de)selected without shift, which shift-click ranges start from
+    select_anchor: Option<i64>,
+
+    tree: TreeState,
+    // The part with the keyboard focus
+    focused: Option<i64>,
+    // The command for the focused part's component to carry out next time it's rendered
+    command: Option<PartCommand>,
+    // The part linked to (/part/:id), which is highlighted
+    linked: Option<i64>,
+    // The parts with mutations in flight, whose rows are kept mounted (like the focused one)
+    // since unmounting a row cancels its requests
+    pending: BTreeSet<i64>,
+
+    // The cursor the parts were last synced at (None until first synced)
+    cursor: Option<String>,
+    // Which parts' children (None for the projects) have been fetched. Children are fetched
+    // when their parent is first shown expanded.
+    loaded: BTreeMap<Option<i64>, Loaded>,
+
+    // Who the user's projects are shared with (or for a shared project, who else it's shared
+    // with)
+    memberships: Vec<Membership>,
+    // The email of the user whose parts these are, if known
+    owner: Option<String>,
 }
 
 impl PartsMaps {
-    fn new(parts_vec: Vec<PartsQueryParts>) -> Self {
+    fn from_cache(cache: PartsCache) -> Self {
         let mut parts_maps = Self::default();
-        for part in parts_vec {
-            // TODO: Handle better?
-            let id = part.id.parse().unwrap();
-            parts_maps.fams.entry(id).or_insert(Vec::new());
-
-            if let Some(pid_str) = part.parent_id.as_ref() {
-                parts_maps
-                    .fams
-                    .entry(pid_str.parse().unwrap()) // TODO: Handle better?
-                    .and_modify(|v| v.push(id))
-                    .or_insert(vec![id]);
-            } else {
-                parts_maps.projects.push(id);
+        cache
+            .parts
+            .into_iter()
+            .for_each(|part| parts_maps.insert_part(part));
+        parts_maps.cursor = Some(cache.cursor);
+        parts_maps.loaded = cache.loaded.into_iter().collect();
+        parts_maps.sort_all();
+        parts_maps
+    }
+
+    fn to_cache(&self) -> Option<PartsCache> {
+        Some(PartsCache {
+            cursor: self.cursor.clone()?,
+            parts: self.parts.values().cloned().collect(),
+            loaded: self
+                .loaded
+                .iter()
+                .map(|(&pid, loaded)| (pid, loaded.clone()))
+                .collect(),
+        })
+    }
+
+    // Creates the maps of all the parts of a (shared) project
+    fn from_parts(parts: Vec<Part>) -> Self {
+        let mut parts_maps = Self::default();
+        parts
+            .into_iter()
+            .for_each(|part| parts_maps.insert_part(part));
+        parts_maps.loaded = parts_maps
+            .parts
+            .keys()
+            .map(|&id| Some(id))
+            .chain([None])
+            .map(|pid| (pid, Loaded::Fully))
+            .collect();
+        parts_maps.sort_all();
+        parts_maps.refilter();
+        parts_maps
+    }
+
+    // Returns who the project is shared with
+    fn project_members(&self, project_id: i64) -> Vec<&Membership> {
+        self.memberships
+            .iter()
+            .filter(|m| m.project_id == project_id)
+            .collect()
+    }
+
+    // Returns who the part (or a new project if None) can be assigned to: the owner and the
+    // members of its project
+    fn assignees(&self, id: Option<i64>) -> Rc<Vec<String>> {
+        let mut project_id = id;
+        while let Some(pid) = project_id.and_then(|id| self.parts.get(&id)?.parent_id) {
+            project_id = Some(pid);
+        }
+        let members = project_id
+            .map(|pid| self.project_members(pid))
+            .unwrap_or_default();
+        Rc::new(
+            self.owner
+                .iter()
+                .cloned()
+                .chain(members.into_iter().map(|m| m.member.clone()))
+                .collect(),
+        )
+    }
+
+    // Adds the part without sorting or refiltering
+    fn insert_part(&mut self, part: Part) {
+        self.fams.entry(part.id).or_default();
+        if part.child_count == 0 {
+            self.loaded.entry(Some(part.id)).or_insert(Loaded::Fully);
+        }
+        match part.parent_id {
+            Some(pid) => self.fams.entry(pid).or_default().push(part.id),
+            None => self.projects.push(part.id),
+        }
+        self.parts.insert(part.id, part);
+    }
+
+    // Adds (or updates) the parts of a page of the parent's children
+    fn apply_page(&mut self, parent_id: Option<i64>, page: ChildrenPage) {
+        for part in page.parts {
+            if let Err(part) = self.replace_part(part) {
+                self.insert_part(*part);
             }
-            parts_maps.parts.insert(id, part.try_into().unwrap());
         }
-        parts_maps
+        let loaded = page.next.map_or(Loaded::Fully, Loaded::Partially);
+        self.loaded.insert(parent_id, loaded);
+        // Changes since an earlier page are fetched again, which is harmless
+        self.cursor.get_or_insert(page.sync_cursor);
+        self.sort_all();
+        self.refilter();
+    }
+
+    // Returns whether the children of the part (or the projects) have been (at least partly)
+    // fetched. Changes to parts whose parents' children haven't been are ignored, since the
+    // parts will be fetched with the rest.
+    fn children_fetched(&self, parent_id: Option<i64>) -> bool {
+        self.loaded.contains_key(&parent_id)
+    }
+
+    // Returns whether the part has children on the server that haven't been fetched
+    fn has_unfetched_children(&self, id: i64) -> bool {
+        !self.children_fetched(Some(id))
+            && self
+                .parts
+                .get(&id)
+                .map(|part| part.child_count > 0)
+                .unwrap_or_default()
+    }
+
+    // Returns whether the part, its ancestors and all its descendants have been fetched
+    fn tree_loaded(&self, id: i64) -> bool {
+        let Some(part) = self.parts.get(&id) else {
+            return false;
+        };
+        let mut parent_id = part.parent_id;
+        while let Some(pid) = parent_id {
+            match self.parts.get(&pid) {
+                Some(parent) => parent_id = parent.parent_id,
+                None => return false,
+            }
+        }
+        let mut stack = vec![id];
+        while let Some(cur) = stack.pop() {
+            if !matches!(self.loaded.get(&Some(cur)), Some(Loaded::Fully)) {
+                return false;
+            }
+            stack.extend(self.fams.get(&cur).into_iter().flatten().copied());
+        }
+        true
+    }
+
+    // Adds (or updates) the part along with its ancestors and all its descendants, whose
+    // children have all been fetched with them
+    fn apply_tree(&mut self, id: i64, parts: Vec<Part>) {
+        for part in parts {
+            if let Err(part) = self.replace_part(part) {
+                self.insert_part(*part);
+            }
+        }
+        let mut stack = vec![id];
+        while let Some(cur) = stack.pop() {
+            self.loaded.insert(Some(cur), Loaded::Fully);
+            stack.extend(self.fams.get(&cur).into_iter().flatten().copied());
+        }
+        self.sort_all();
+        self.refilter();
+    }
+
+    // Adds (or updates) the parts fetched by id along with their ancestors, whose children
+    // haven't necessarily been fetched
+    fn apply_parts(&mut self, parts: Vec<Part>) {
+        for part in parts {
+            if let Err(part) = self.replace_part(part) {
+                self.insert_part(*part);
+            }
+        }
+        self.sort_all();
+        self.refilter();
+    }
+
+    // Returns the ids of the blockers of the parts with the given ids that haven't been fetched
+    fn missing_blockers(&self, ids: impl IntoIterator<Item = i64>) -> BTreeSet<i64> {
+        ids.into_iter()
+            .filter_map(|id| self.parts.get(&id))
+            .flat_map(|part| &part.blocked_by)
+            .filter(|bid| !self.parts.contains_key(bid))
+            .copied()
+            .collect()
+    }
+
+    // Returns the cursor to get the next page of the part's (or the projects') children with
+    fn next_page(&self, parent_id: Option<i64>) -> Option<&str> {
+        match self.loaded.get(&parent_id) {
+            Some(Loaded::Partially(cursor)) => Some(cursor),
+            _ => None,
+        }
+    }
+
+    // Returns the parents (None for the projects) shown expanded whose children haven't been
+    // fetched
+    fn unfetched_shown(&self) -> Vec<Option<i64>> {
+        if !self.children_fetched(None) {
+            return vec![None];
+        }
+        self.visible_ids()
+            .into_iter()
+            .filter(|&id| self.tree.expanded.contains(&id) && self.has_unfetched_children(id))
+            .map(Some)
+            .collect()
+    }
+
+    // Applies the changes fetched since the parts were last synced, keeping the sorting,
+    // filtering, selection and tree state
+    fn apply_delta(&mut self, delta: PartsDelta) {
+        if delta.full {
+            let ids = delta
+                .parts
+                .iter()
+                .map(|part| part.id)
+                .collect::<BTreeSet<_>>();
+            let stale = self
+                .parts
+                .keys()
+                .filter(|id| !ids.contains(id))
+                .copied()
+                .collect::<Vec<_>>();
+            stale.into_iter().for_each(|id| self.delete_part(id));
+        }
+        delta
+            .deleted
+            .into_iter()
+            .for_each(|id| self.delete_part(id));
+        for part in delta.parts {
+            if let Err(part) = self.replace_part(part) {
+                if delta.full || self.children_fetched(part.parent_id) {
+                    self.insert_part(*part);
+                }
+            }
+        }
+        if delta.full {
+            self.loaded = self
+                .parts
+                .keys()
+                .map(|&id| Some(id))
+                .chain([None])
+                .map(|pid| (pid, Loaded::Fully))
+                .collect();
+        }
+        self.sort_all();
+        self.refilter();
+        self.cursor = Some(delta.cursor);
     }
 
     // Returns the part if a part with the id already existed
-    fn add_part(&mut self, part: Part) -> Result<(), Part> {
+    fn add_part(&mut self, part: Part) -> Result<(), Box<Part>> {
         if self.parts.contains_key(&part.id) {
-            return Err(part);
+            return Err(Box::new(part));
         }
         if let Some(pid) = part.parent_id {
             let children = self
@@ -96,26 +397,18 @@ impl PartsMaps {
             self.projects.push(part.id);
             self.sort_projects();
         }
-        if !self.filter_method.passes_filter(&part) {
-            // Shouldn't be None if the part fails (i.e., the filter method isn't All)
-            self.filtered_ids
-                .as_mut()
-                .expect("None filtered_ids with non-All method")
-                .insert(part.id);
-        }
         self.fams.insert(part.id, Vec::new());
+        self.loaded.insert(Some(part.id), Loaded::Fully);
         self.parts.insert(part.id, part);
         self.sort_all();
+        self.refilter();
         Ok(())
     }
 
     // Returns the part if the part doesn't exist
-    fn update_part(&mut self, part: Part) -> Result<(), Part> {
-        if !self.parts.contains_key(&part.id) {
-            return Err(part);
-        }
+    fn update_part(&mut self, part: Part) -> Result<(), Box<Part>> {
         let pid = part.parent_id;
-        self.parts.insert(part.id, part);
+        self.replace_part(part)?;
         if let Some(pid) = pid {
             let children = self
                 .fams
@@ -125,6 +418,44 @@ impl PartsMaps {
         } else {
             self.sort_projects();
         }
+        // Completing a part can change whether the parts it blocks pass the filter
+        self.refilter();
+        Ok(())
+    }
+
+    // Updates all the parts at once, only sorting and refiltering after all have been updated.
+    // Returns the parts that don't exist.
+    fn update_parts(&mut self, parts: Vec<Part>) -> Vec<Part> {
+        let missing = parts
+            .into_iter()
+            .filter_map(|part| self.replace_part(part).err().map(|part| *part))
+            .collect();
+        self.sort_all();
+        self.refilter();
+        missing
+    }
+
+    // Replaces the existing part, moving it if its parent changed (without sorting)
+    fn replace_part(&mut self, part: Part) -> Result<(), Box<Part>> {
+        let Some(old_pid) = self.parts.get(&part.id).map(|old| old.parent_id) else {
+            return Err(Box::new(part));
+        };
+        let (id, pid) = (part.id, part.parent_id);
+        self.parts.insert(id, part);
+        if old_pid != pid {
+            match old_pid {
+                Some(old_pid) => {
+                    if let Some(children) = self.fams.get_mut(&old_pid) {
+                        children.retain(|&cid| cid != id);
+                    }
+                }
+                None => self.projects.retain(|&proj_id| proj_id != id),
+            }
+            match pid {
+                Some(pid) => self.fams.entry(pid).or_default().push(id),
+                None => self.projects.push(id),
+            }
+        }
         Ok(())
     }
 
@@ -141,9 +472,300 @@ impl PartsMaps {
         if let Some(children) = self.fams.remove(&id) {
             children.into_iter().for_each(|cid| self.delete_part(cid));
         }
+        self.time_entries.remove(&id);
+        self.parts
+            .values_mut()
+            .for_each(|part| part.blocked_by.retain(|&bid| bid != id));
         if let Some(s) = self.filtered_ids.as_mut() {
             s.remove(&id);
         }
+        self.selected.remove(&id);
+        if self.select_anchor == Some(id) {
+            self.select_anchor = None;
+        }
+        self.tree.remove(id);
+        self.loaded.remove(&Some(id));
+        if self.focused == Some(id) {
+            self.focused = None;
+            self.command = None;
+        }
+    }
+
+    // Captures the part and its descendants (parents before children), the blockers involving
+    // them, and their time entries so they can be re-created
+    fn snapshot(&self, id: i64) -> Snapshot {
+        let mut parts = Vec::new();
+        let mut queue = VecDeque::from([id]);
+        while let Some(id) = queue.pop_front() {
+            let Some(part) = self.parts.get(&id) else {
+                continue;
+            };
+            parts.push(part.clone());
+            queue.extend(self.fams.get(&id).into_iter().flatten().copied());
+        }
+        let ids = parts.iter().map(|part| part.id).collect::<BTreeSet<_>>();
+        let blockers = self
+            .parts
+            .values()
+            .flat_map(|part| part.blocked_by.iter().map(|&bid| (part.id, bid)))
+            .filter(|(pid, bid)| ids.contains(pid) || ids.contains(bid))
+            .collect();
+        let now = Local::now();
+        let time_entries = ids
+            .iter()
+            .flat_map(|&id| self.part_time_entries(id))
+            .map(|entry| TimeEntry {
+                ended_at: Some(entry.ended_at.unwrap_or(now)),
+                ..entry.clone()
+            })
+            .collect();
+        Snapshot {
+            parts,
+            blockers,
+            time_entries,
+        }
+    }
+
+    // Applies a change made by another client. Applying an event that has already been applied
+    // (or one for a part that hasn't been fetched) is a no-op. Returns whether anything changed.
+    fn apply_event(&mut self, event: PartEvent) -> bool {
+        match event {
+            PartEvent::Upserted(part) => {
+                if self.parts.get(&part.id) == Some(&part) {
+                    return false;
+                }
+                if self.parts.contains_key(&part.id) {
+                    let _ = self.update_part(*part);
+                } else if self.children_fetched(part.parent_id) {
+                    self.insert_part(*part);
+                    self.sort_all();
+                    self.refilter();
+                } else {
+                    return false;
+                }
+            }
+            PartEvent::Deleted(id) => {
+                if !self.parts.contains_key(&id) {
+                    return false;
+                }
+                self.delete_part(id);
+            }
+        }
+        true
+    }
+
+    // Applies the changes made while undoing/redoing a history entry
+    fn apply_outcome(&mut self, outcome: Outcome) {
+        outcome
+            .deleted
+            .into_iter()
+            .for_each(|id| self.delete_part(id));
+        for part in outcome.created {
+            if let Err(part) = self.add_part(part) {
+                console::log!("Part already exists: {:?}", part);
+            }
+        }
+        for part in self.update_parts(outcome.updated) {
+            console::log!("Part doesn't exist: {:?}", part);
+        }
+        outcome
+            .time_entries
+            .into_iter()
+            .for_each(|entry| self.upsert_time_entry(entry));
+    }
+
+    // Returns whether the part with the given id is a (strict) descendant of the ancestor
+    fn is_descendant(&self, id: i64, ancestor_id: i64) -> bool {
+        let mut cur = self.parts.get(&id).and_then(|part| part.parent_id);
+        while let Some(pid) = cur {
+            if pid == ancestor_id {
+                return true;
+            }
+            cur = self.parts.get(&pid).and_then(|part| part.parent_id);
+        }
+        false
+    }
+
+    // Returns the ids of the parts shown in the tree, in the order they're shown
+    fn visible_ids(&self) -> Vec<i64> {
+        self.visible_rows()
+            .into_iter()
+            .filter_map(|row| match row {
+                TreeRow::Part { id, .. } => Some(id),
+                TreeRow::More { .. } => None,
+            })
+            .collect()
+    }
+
+    // Returns the rows of the part tree as shown, in order
+    fn visible_rows(&self) -> Vec<TreeRow> {
+        let mut rows = Vec::new();
+        let mut stack = self
+            .projects
+            .iter()
+            .rev()
+            .filter(|&&id| self.id_not_filtered(id) && self.project_shown(id))
+            .map(|&id| TreeRow::Part { id, depth: 0 })
+            .collect::<Vec<_>>();
+        while let Some(row) = stack.pop() {
+            rows.push(row);
+            let TreeRow::Part { id, depth } = row else {
+                continue;
+            };
+            if !self.tree.expanded.contains(&id) {
+                continue;
+            }
+            if self.has_unfetched_children(id) || self.next_page(Some(id)).is_some() {
+                stack.push(TreeRow::More {
+                    parent_id: id,
+                    depth: depth + 1,
+                });
+            }
+            stack.extend(
+                self.fams[&id]
+                    .iter()
+                    .rev()
+                    .filter(|&&cid| self.id_not_filtered(cid))
+                    .map(|&cid| TreeRow::Part {
+                        id: cid,
+                        depth: depth + 1,
+                    }),
+            );
+        }
+        rows
+    }
+
+    // Expands the parts less than the given depth deep (projects being 0 deep) that have
+    // children, collapsing the rest. None expands all the parts. Parts whose children haven't
+    // been fetched are expanded too, and their children are expanded by expand_children once
+    // they've been.
+    fn expand_to_depth(&mut self, depth: Option<usize>) {
+        self.tree.expanded.clear();
+        let projects = self.projects.iter().map(|&id| (id, 0)).collect();
+        self.expand_from(projects, depth);
+    }
+
+    // Expands the part's (or the projects') children as expand_to_depth would have if they'd
+    // been fetched then
+    fn expand_children(&mut self, parent_id: Option<i64>, depth: Option<usize>) {
+        let children = match parent_id {
+            Some(pid) => {
+                let d = self.depth(pid) + 1;
+                self.fams[&pid].iter().map(|&id| (id, d)).collect()
+            }
+            None => self.projects.iter().map(|&id| (id, 0)).collect(),
+        };
+        self.expand_from(children, depth);
+    }
+
+    // Expands the parts (with their depths) and their descendants less than the depth deep
+    fn expand_from(&mut self, mut stack: Vec<(i64, usize)>, depth: Option<usize>) {
+        while let Some((id, d)) = stack.pop() {
+            let children = &self.fams[&id];
+            if (children.is_empty() && !self.has_unfetched_children(id))
+                || depth.map(|depth| d >= depth).unwrap_or_default()
+            {
+                continue;
+            }
+            self.tree.expanded.insert(id);
+            stack.extend(children.iter().map(|&cid| (cid, d + 1)));
+        }
+    }
+
+    // Returns how deep the part is (projects being 0 deep)
+    fn depth(&self, id: i64) -> usize {
+        let mut depth = 0;
+        let mut cur = self.parts.get(&id).and_then(|part| part.parent_id);
+        while let Some(pid) = cur {
+            depth += 1;
+            cur = self.parts.get(&pid).and_then(|part| part.parent_id);
+        }
+        depth
+    }
+
+    // Returns the depth of the deepest part (projects being 0 deep)
+    fn max_depth(&self) -> usize {
+        let mut max = 0;
+        let mut stack = self.projects.iter().map(|&id| (id, 0)).collect::<Vec<_>>();
+        while let Some((id, d)) = stack.pop() {
+            max = max.max(d);
+            stack.extend(self.fams[&id].iter().map(|&cid| (cid, d + 1)));
+        }
+        max
+    }
+
+    // Returns whether any of the part's children pass the filter (or haven't been fetched)
+    fn has_visible_children(&self, id: i64) -> bool {
+        self.has_unfetched_children(id)
+            || self
+                .fams
+                .get(&id)
+                .into_iter()
+                .flatten()
+                .any(|&cid| self.id_not_filtered(cid))
+    }
+
+    // Expands all the part's ancestors so the part is shown
+    fn reveal(&mut self, id: i64) {
+        let mut cur = self.parts.get(&id).and_then(|part| part.parent_id);
+        while let Some(pid) = cur {
+            self.tree.expanded.insert(pid);
+            cur = self.parts.get(&pid).and_then(|part| part.parent_id);
+        }
+    }
+
+    // Returns the selected ids that don't have a selected ancestor
+    fn selected_roots(&self) -> Vec<i64> {
+        self.selected
+            .iter()
+            .copied()
+            .filter(|&id| !self.selected.iter().any(|&sid| self.is_descendant(id, sid)))
+            .collect()
+    }
+
+    // Returns the parts that can be picked as the parent of the part with the given id (all
+    // parts except the part and its descendants), sorted by path
+    fn parent_options(&self, id: Option<i64>) -> Rc<Vec<ParentOption>> {
+        let mut options = self
+            .parts
+            .values()
+            .filter(|part| {
+                id.map(|id| part.id != id && !self.is_descendant(part.id, id))
+                    .unwrap_or(true)
+            })
+            .map(|part| ParentOption {
+                id: part.id,
+                path: time_tracking::part_path(&self.parts, part.id),
+                started_at: self
+                    .part_time_entries(part.id)
+                    .iter()
+                    .map(|e| e.started_at)
+                    .min(),
+            })
+            .collect::<Vec<_>>();
+        options.sort_by_cached_key(|opt| opt.path.to_lowercase());
+        Rc::new(options)
+    }
+
+    // Returns the part's name (or id if it doesn't exist) for use in messages
+    fn label(&self, id: i64) -> String {
+        self.parts
+            .get(&id)
+            .map(|part| part.name.clone())
+            .unwrap_or_else(|| format!("#{}", id))
+    }
+
+    // Returns the ids of the parts the selected parts can be moved under (those that aren't
+    // selected or descendants of a selected part)
+    fn move_candidates(&self) -> Vec<i64> {
+        self.parts
+            .keys()
+            .copied()
+            .filter(|&id| {
+                !self.selected.contains(&id)
+                    && !self.selected.iter().any(|&sid| self.is_descendant(id, sid))
+            })
+            .collect()
     }
 
     fn apply_sort(&mut self, method: SortMethod) {
@@ -167,17 +789,96 @@ impl PartsMaps {
     }
 
     fn apply_filter(&mut self, filter_method: FilterMethod) {
+        self.filter_method = filter_method;
+        self.refilter();
+    }
+
+    // Recomputes the filtered out ids using the current filter method
+    fn refilter(&mut self) {
         self.filtered_ids = None;
-        if filter_method.allows_all() {
-            let filter_fn = filter_method.get_fn();
+        if !self.filter_method.allows_all() {
+            let filter_fn = self.filter_method.get_fn();
+            let actionable = self.filter_method.actionable;
             self.filtered_ids = Some(
                 self.parts
                     .iter()
-                    .filter_map(|(&id, part)| filter_fn(part).then_some(id))
+                    .filter_map(|(&id, part)| {
+                        let keep = filter_fn(part)
+                            && (!actionable || dep_graph::is_actionable(&self.parts, part));
+                        (!keep).then_some(id)
+                    })
                     .collect(),
             );
         }
-        self.filter_method = filter_method;
+    }
+
+    fn set_time_entries(&mut self, entries: Vec<TimeEntry>) {
+        self.time_entries.clear();
+        entries
+            .into_iter()
+            .for_each(|entry| self.upsert_time_entry(entry));
+    }
+
+    // Adds the entry or replaces the existing entry with the same id
+    fn upsert_time_entry(&mut self, entry: TimeEntry) {
+        let entries = self.time_entries.entry(entry.part_id).or_default();
+        if let Some(existing) = entries.iter_mut().find(|e| e.id == entry.id) {
+            *existing = entry;
+        } else {
+            entries.push(entry);
+            entries.sort_by_key(|e| (e.started_at, e.id));
+        }
+    }
+
+    fn remove_time_entry(&mut self, id: i64) {
+        self.time_entries
+            .values_mut()
+            .for_each(|entries| entries.retain(|e| e.id != id));
+    }
+
+    fn part_time_entries(&self, part_id: i64) -> &[TimeEntry] {
+        self.time_entries
+            .get(&part_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn running_entry(&self, part_id: i64) -> Option<&TimeEntry> {
+        self.part_time_entries(part_id)
+            .iter()
+            .find(|e| e.is_running())
+    }
+
+    fn has_running_timer(&self) -> bool {
+        self.time_entries
+            .values()
+            .flatten()
+            .any(TimeEntry::is_running)
+    }
+
+    // Returns the time logged for the part and all its descendants
+    fn total_time(&self, id: i64, now: DateTime<Local>) -> chrono::Duration {
+        let own = self
+            .part_time_entries(id)
+            .iter()
+            .fold(chrono::Duration::zero(), |acc, e| acc + e.duration(now));
+        self.fams
+            .get(&id)
+            .map(|children| {
+                children
+                    .iter()
+                    .fold(own, |acc, &cid| acc + self.total_time(cid, now))
+            })
+            .unwrap_or(own)
+    }
+
+    // Returns whether the project is shown or not (archived ones only if asked for)
+    fn project_shown(&self, id: i64) -> bool {
+        self.filter_method.archived
+            || self
+                .parts
+                .get(&id)
+                .is_none_or(|part| part.archived_at.is_none())
     }
 
     // Returns whether the id isn't filtered out or not
@@ -238,6 +939,10 @@ pub struct FilterMethod {
     completed_at: Option<(Option<DateTime<Local>>, Option<DateTime<Local>>)>,
     // Same as Completed but for Deadline
     deadline: Option<(Option<DateTime<Local>>, Option<DateTime<Local>>)>,
+    // Only keep incomplete parts with no incomplete blockers
+    actionable: bool,
+    // Show the archived projects too
+    archived: bool,
 }
 
 // FilterFn should return true if the part should be kept
@@ -245,7 +950,7 @@ type FilterFn = Box<dyn Fn(&Part) -> bool>;
 
 impl FilterMethod {
     fn allows_all(&self) -> bool {
-        self.deadline.is_none() && self.completed_at.is_none()
+        self.deadline.is_none() && self.completed_at.is_none() && !self.actionable
     }
 
     fn get_fn(&self) -> FilterFn {
@@ -288,10 +993,6 @@ impl FilterMethod {
         Box::new(move |part| deadline_fn(part) && completed_at_fn(part))
     }
 
-    fn passes_filter(&self, part: &Part) -> bool {
-        self.get_fn()(part)
-    }
-
     fn all_completed(&self) -> bool {
         matches!(self.completed_at, Some((Some(start), Some(end))) if start == *MIN_DT && end == *MAX_DT)
     }
@@ -329,28 +1030,359 @@ impl FilterMethod {
     }
 }
 
+// What the subtree of a part is fetched for. It isn't added to the tree, which only holds the
+// parts that have been shown.
+#[derive(Clone, Copy)]
+pub enum SubtreeUse {
+    Template,
+    Duplicate,
+}
+
 #[allow(dead_code)]
 pub enum AppMsg {
     ToggleCreating,
-    GetParts(PartialResult<Vec<PartsQueryParts>>),
-    CreateUser(anyhow::Result<String>),
-    LoginUser(anyhow::Result<String>),
+    // The changes since the parts were last synced
+    GetParts(anyhow::Result<PartsDelta>),
+    // The window or size of the part tree may have changed
+    TreeScrolled,
+    GetMemberships(anyhow::Result<Vec<Membership>>),
+    // Fetches the projects shared with the user again
+    RefreshShared,
+    GetSharedProjects(anyhow::Result<Vec<SharedProject>>),
+    // Fetches the next page of the part's (or the projects' if None) children
+    LoadChildren(Option<i64>),
+    GotChildren(Option<i64>, anyhow::Result<ChildrenPage>),
+    // Fetches the parts again after they failed to load
+    RetryLoad,
+    // Fetches the parts with the given ids that haven't been (e.g., blockers elsewhere in the
+    // tree) along with their ancestors
+    LoadParts(BTreeSet<i64>),
+    GotParts(BTreeSet<i64>, anyhow::Result<Vec<Part>>),
+    GotPartTree(i64, anyhow::Result<Vec<Part>>),
+    // Reveals the linked part once it's been fetched
+    ShowLinkedPart,
+    Sync(SyncEvent),
+    // Sent by the login form once the user's logged in or signed up
+    LoggedIn {
+        email: String,
+        jwt: String,
+    },
+    Logout,
     LogoutUser(anyhow::Result<bool>),
+    ResetPassword {
+        token: String,
+        new_password: String,
+    },
+    PasswordReset(anyhow::Result<bool>),
+    // Leaves the password reset page for the login
+    CancelReset,
+    // Shows the route the browser went to
+    Navigate(Route),
+    ChangePassword {
+        current: String,
+        new: String,
+    },
+    PasswordChanged(anyhow::Result<bool>),
+    ChangeEmail {
+        password: String,
+        new_email: String,
+    },
+    // The new email and the new token for it
+    EmailChanged(String, anyhow::Result<String>),
+    // Deletes the account (after confirming), given the password
+    DeleteAccount(String),
+    SendDeleteAccount(String),
+    AccountDeleted(anyhow::Result<bool>),
     SendCreatePart(Part),
+    SetQuickAdd(String),
+    // Creates the part described by the quick-add input
+    QuickAdd,
     CreatePart(anyhow::Result<CreatePartPart>),
+    // The entries recorded in a part's activity log
+    ActivityRecorded(anyhow::Result<Vec<Activity>>),
     Sort(SortMethod),
     Filter(FilterMethod),
+    // Sent when a part component changes the parts maps in a way that can affect other parts
+    PartsChanged,
+    GetTimeEntries(anyhow::Result<Vec<TimeEntriesQueryTimeEntries>>),
+    // Sent every second while a timer is running
+    TimerTick,
+    SetView(MainView),
+    GetAssigned(anyhow::Result<Vec<AssignedPart>>),
+    GetTrash(anyhow::Result<Vec<TrashedPart>>),
+    // Restores the part with the given id (and its children) from the trash
+    RestorePart(i64),
+    PartRestored(i64, anyhow::Result<Vec<Part>>),
+    // Permanently deletes the part with the given id (and its children) from the trash
+    PurgePart(i64),
+    SendPurgePart(i64),
+    PartPurged(i64, anyhow::Result<i64>),
+    // Saves the subtree of the part with the given id as a template (asking for its name)
+    SaveTemplate(i64),
+    // Saves the template captured from the subtree with the name entered
+    SaveTemplateAs(Rc<Template>, String),
+    DeleteTemplate(usize),
+    // Deletes the template with the given name once confirmed
+    RemoveTemplate(String),
+    // Creates a project from the template with the given index, its reference date at the anchor
+    NewFromTemplate {
+        index: usize,
+        anchor: DateTime<Local>,
+    },
+    // Creates a copy of the part with the given id (and its children) under the same parent
+    Duplicate(i64),
+    // The subtree of the part with the given id, fetched to be saved as a template or duplicated
+    GotSubtree(i64, SubtreeUse, anyhow::Result<Vec<Part>>),
+    // The parts created from a template (or a duplicated subtree), with the history label
+    Instantiated(String, PartialOk<Vec<Part>>),
+    SetReportWeek(NaiveDate),
+    GotStats(anyhow::Result<StatsData>),
+    // Sets the project whose burndown is shown (None for the default)
+    SetStatsProject(Option<i64>),
+    GotBurndown(i64, anyhow::Result<BurndownData>),
+    // Selects or deselects a part. If range is true (shift-click), all parts displayed between
+    // the last (de)selected part and this one are changed as well.
+    SelectPart {
+        id: i64,
+        selected: bool,
+        range: bool,
+    },
+    ClearSelection,
+    Batch(BatchAction),
+    // Carries out the batch action once the user confirmed it
+    BatchConfirmed(BatchAction),
+    BatchUpdated(BatchAction, anyhow::Result<Vec<BatchItem<Part>>>),
+    BatchDeleted(anyhow::Result<Vec<BatchItem<()>>>),
+    // Records a change made by the user in the history
+    Record(Entry),
+    Undo,
+    Redo,
+    HistoryApplied(PartialOk<Outcome>),
+    ToggleHistory,
+    Nav(NavKey),
+    // Expands the parts up to the given depth (None expands all)
+    ExpandToDepth(Option<usize>),
+    // Gives the part the keyboard focus
+    Focus(i64),
+    // Shows the part in the projects view
+    GoToPart(i64),
+    TogglePalette,
+    // Runs the palette command with the given index
+    RunCommand(usize),
+    // Shows the error in a toast
     ErrorMsg(String),
+    Toast(Toast),
+    DismissToast(u64),
+    // Asks the user to confirm something in the modal
+    Confirm(Confirm),
+    CloseConfirm,
     DisplayErrLogout,
 }
 
+// The requests the app doesn't send again while one is pending
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum AppRequest {
+    // Fetching the changes since the parts were last synced
+    Sync,
+    CreatePart,
+    Batch,
+    ResetPassword,
+    ChangePassword,
+    ChangeEmail,
+    DeleteAccount,
+    Restore(i64),
+    Purge(i64),
+    // Creating parts from a template or duplicating them
+    Instantiate,
+    Logout,
+    Stats,
+    Burndown,
+    // Fetching the part with the given id along with its ancestors and descendants
+    PartTree(i64),
+    // Fetching the subtree of the part with the given id for a template or a duplicate
+    Subtree(i64),
+}
+
+// An operation applied to all the selected parts
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BatchAction {
+    Complete,
+    Uncomplete,
+    SetDeadline(DateTime<Local>),
+    ClearDeadline,
+    // Moves the parts under the given parent (None makes them projects)
+    Move(Option<i64>),
+    Delete,
+}
+
+impl BatchAction {
+    // Returns the history label for the action applied to the given number of parts
+    fn label(self, num_parts: usize) -> String {
+        let action = match self {
+            BatchAction::Complete => "Complete",
+            BatchAction::Uncomplete => "Un-complete",
+            BatchAction::SetDeadline(_) => "Set deadline of",
+            BatchAction::ClearDeadline => "Clear deadline of",
+            BatchAction::Move(_) => "Move",
+            BatchAction::Delete => "Delete",
+        };
+        format!("{} {} parts", action, num_parts)
+    }
+}
+
+// A key handled by the part tree
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NavKey {
+    Up,
+    Down,
+    First,
+    Last,
+    // Expands the focused part or moves to its first child if already expanded
+    Expand,
+    // Collapses the focused part or moves to its parent if already collapsed
+    Collapse,
+    Blur,
+    Part(PartCommand),
+}
+
+// An action on a single part, carried out by the part's component
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PartCommand {
+    ToggleDetails,
+    NewChild,
+    Edit,
+    ToggleComplete,
+    Delete,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PaletteCommand {
+    NewProject,
+    Undo,
+    Redo,
+    ToggleHistory,
+    ShowProjects,
+    ShowTimeReport,
+    ShowAssigned,
+    ShowTrash,
+    ShowStats,
+    ShowAccount,
+    ClearSelection,
+    ExpandAll,
+    CollapseAll,
+    Logout,
+    GoToPart(i64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MainView {
+    Projects,
+    TimeReport,
+    // The parts assigned to the user across all projects
+    Assigned,
+    // The parts the user deleted
+    Trash,
+    // Statistics on the parts
+    Stats,
+    // The user's account settings
+    Account,
+}
+
+// A project another user shared with the user, shown in its own tree
+struct SharedTree {
+    membership: Rc<Membership>,
+    parts_maps: Rc<RefCell<PartsMaps>>,
+}
+
 pub struct App {
     jwt: Rc<str>,
+    // The email of the logged in user, if known
+    user: Option<String>,
+    // The token from the password reset link the app was opened with, whose page is shown
+    // until the password is reset
+    reset_token: Option<String>,
+    // The route in the URL, which is kept in step with what's shown (None if the URL isn't one
+    // of the app's)
+    route: Option<Route>,
+    // Where to go once logged in, if the user was sent to the login from a page behind it
+    after_login: Option<Route>,
+    // Whether the linked part is still to be revealed once it's loaded
+    link_pending: bool,
     parts_maps: Rc<RefCell<PartsMaps>>,
+    // Incremented whenever the parts maps change so that all part components rerender
+    parts_rev: u64,
+    view: MainView,
+    // The parts assigned to the user (None until fetched)
+    assigned: Option<Vec<AssignedPart>>,
+    // The parts in the trash (None until fetched)
+    trash: Option<Vec<TrashedPart>>,
+    // The Monday of the week shown in the time report
+    report_week: NaiveDate,
+    // The statistics (None until fetched, which is done whenever they're shown)
+    stats: Option<Stats>,
+    // The project whose burndown is shown in the statistics
+    stats_project: Option<i64>,
+    // The burndown of the project with the given id, once fetched
+    burndown: Option<(i64, Burndown)>,
+    // Ticks while any timer is running so the elapsed times stay current
+    timer_interval: Option<Interval>,
     creating: bool,
-    create_form_ref: NodeRef,
+    // The quick-add input
+    quick_add: String,
     filter_dd_div_ref: NodeRef,
-    error_msg: String,
+    batch_deadline_ref: NodeRef,
+    batch_parent_ref: NodeRef,
+    // The user's project templates
+    templates: Vec<Template>,
+    template_select_ref: NodeRef,
+    template_anchor_ref: NodeRef,
+    history: History,
+    show_history: bool,
+    // The commands of the open command palette and their labels
+    palette: Option<(Vec<PaletteCommand>, Rc<Vec<String>>)>,
+    // Whether to scroll the focused part into view once rendered
+    scroll_to_focus: bool,
+    // The tree state last saved to storage
+    saved_tree: TreeState,
+    // Handles the keyboard shortcuts
+    _keydown_listener: EventListener,
+    // Only the part tree rows near the viewport are rendered
+    tree_list: VirtualList<TreeRow>,
+    tree_list_ref: NodeRef,
+    // The top and bottom of the viewport relative to the top of the part tree
+    tree_viewport: (f64, f64),
+    // The rows in the window when last rendered
+    tree_window: Range<usize>,
+    // Re-render the part tree window on scrolling and resizing
+    _viewport_listeners: [EventListener; 2],
+    // Shows the route the browser goes back or forward to
+    _popstate_listener: EventListener,
+    // Applies changes made elsewhere while logged in
+    sync: Option<PartsSync>,
+    // Whether the sync connection dropped (and is reconnecting)
+    sync_offline: bool,
+    // The parents (None for the projects) whose children are being fetched
+    loading: BTreeSet<Option<i64>>,
+    // The ids of the parts being fetched by id
+    loading_parts: BTreeSet<i64>,
+    // The depth the tree is being expanded to (see PartsMaps::expand_to_depth) while the
+    // children of the parts expanded are fetched
+    expanding: Option<Option<usize>>,
+    // What to do once the trees of the parts with the given ids have been fetched
+    after_tree_loaded: BTreeMap<i64, Vec<AppMsg>>,
+    // Whether the parts are being fetched for the first time, which confirms whether there's a
+    // session. Neither the login nor the projects are shown until it's known.
+    checking_session: bool,
+    // Why the projects couldn't be fetched, shown in their place until retried
+    load_error: Option<String>,
+    requests: Requests<AppRequest>,
+    // The projects other users have shared with the user
+    shared: Vec<SharedTree>,
+    toasts: Toasts,
+    // Dismiss the toasts after a while (dropping one cancels it)
+    toast_timers: HashMap<u64, Timeout>,
+    // What's waiting to be confirmed in the modal
+    confirm: Option<Confirm>,
     send_to_app: Rc<Callback<AppMsg>>,
 }
 
@@ -359,293 +1391,2144 @@ impl Component for App {
     type Properties = ();
 
     fn create(ctx: &Context<Self>) -> Self {
-        let res = get_parts(String::new());
-        ctx.link().send_future(res.map(AppMsg::GetParts));
+        let document = web_sys::window()
+            .expect("no window")
+            .document()
+            .expect("no document");
         let link = ctx.link().clone();
-        Self {
+        let keydown_listener = EventListener::new(&document, "keydown", move |e| {
+            let Some(e) = e.dyn_ref::<KeyboardEvent>() else {
+                return;
+            };
+            let msg = if e.ctrl_key() || e.meta_key() {
+                match e.key().to_lowercase().as_str() {
+                    _ if e.alt_key() => return,
+                    "k" => AppMsg::TogglePalette,
+                    _ if target_is_text_input(e) => return,
+                    "z" if e.shift_key() => AppMsg::Redo,
+                    "z" => AppMsg::Undo,
+                    "y" => AppMsg::Redo,
+                    _ => return,
+                }
+            } else {
+                if e.alt_key() || target_is_text_input(e) || target_is_control(e) {
+                    return;
+                }
+                let key = match e.key().as_str() {
+                    "ArrowUp" => NavKey::Up,
+                    "ArrowDown" => NavKey::Down,
+                    "Home" => NavKey::First,
+                    "End" => NavKey::Last,
+                    "ArrowRight" => NavKey::Expand,
+                    "ArrowLeft" => NavKey::Collapse,
+                    "Escape" => NavKey::Blur,
+                    "Enter" => NavKey::Part(PartCommand::ToggleDetails),
+                    "n" => NavKey::Part(PartCommand::NewChild),
+                    "e" => NavKey::Part(PartCommand::Edit),
+                    "x" => NavKey::Part(PartCommand::ToggleComplete),
+                    "Delete" => NavKey::Part(PartCommand::Delete),
+                    _ => return,
+                };
+                AppMsg::Nav(key)
+            };
+            e.prevent_default();
+            link.send_message(msg);
+        });
+        let window = web_sys::window().expect("no window");
+        let link = ctx.link().clone();
+        let scroll_listener = EventListener::new(&window, "scroll", move |_| {
+            link.send_message(AppMsg::TreeScrolled);
+        });
+        let link = ctx.link().clone();
+        let resize_listener = EventListener::new(&window, "resize", move |_| {
+            link.send_message(AppMsg::TreeScrolled);
+        });
+        let link = ctx.link().clone();
+        let popstate_listener = EventListener::new(&window, "popstate", move |_| {
+            link.send_message(AppMsg::Navigate(
+                Route::current().unwrap_or(Route::Projects),
+            ));
+        });
+        let link = ctx.link().clone();
+        let mut app = Self {
             jwt: Rc::from(""),
+            user: tree_state::last_user(),
+            reset_token: None,
+            route: Route::current(),
+            after_login: None,
+            link_pending: false,
             parts_maps: Default::default(),
+            parts_rev: 0,
+            view: MainView::Projects,
+            assigned: None,
+            trash: None,
+            report_week: time_tracking::week_start(Local::now().date_naive()),
+            stats: None,
+            stats_project: None,
+            burndown: None,
+            timer_interval: None,
             creating: false,
-            create_form_ref: NodeRef::default(),
+            quick_add: String::new(),
             filter_dd_div_ref: NodeRef::default(),
-            error_msg: String::new(),
+            batch_deadline_ref: NodeRef::default(),
+            batch_parent_ref: NodeRef::default(),
+            templates: Vec::new(),
+            template_select_ref: NodeRef::default(),
+            template_anchor_ref: NodeRef::default(),
+            history: History::default(),
+            show_history: false,
+            palette: None,
+            scroll_to_focus: false,
+            saved_tree: TreeState::default(),
+            _keydown_listener: keydown_listener,
+            tree_list: VirtualList::new(TREE_ROW_HEIGHT),
+            tree_list_ref: NodeRef::default(),
+            tree_viewport: (0.0, 0.0),
+            tree_window: 0..0,
+            _viewport_listeners: [scroll_listener, resize_listener],
+            _popstate_listener: popstate_listener,
+            sync: None,
+            sync_offline: false,
+            loading: BTreeSet::new(),
+            loading_parts: BTreeSet::new(),
+            expanding: None,
+            after_tree_loaded: BTreeMap::new(),
+            checking_session: true,
+            load_error: None,
+            requests: Requests::default(),
+            shared: Vec::new(),
+            toasts: Toasts::default(),
+            toast_timers: HashMap::new(),
+            confirm: None,
             send_to_app: Rc::new(Callback::from(move |msg| {
                 link.send_message(msg);
             })),
+        };
+        app.load_parts(ctx);
+        if let Some(route) = app.route.clone() {
+            app.apply_route(ctx, route);
         }
+        app
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         html! {
             <div id="app">
-                if &*self.jwt == "" {
+                if let Some(token) = self.reset_token.as_ref() {
+                    { self.render_reset_password(ctx, token) }
+                } else if self.checking_session && self.jwt.is_empty() {
+                    <p class="loading-p"><span class="spinner"></span>{ "Loading..." }</p>
+                } else if &*self.jwt == "" {
                     { self.render_login(ctx) }
                 } else {
                     { self.render_main(ctx) }
                 }
+                { self.toasts.render(ctx.link().callback(AppMsg::DismissToast)) }
+                if let Some(confirm) = self.confirm.clone() {
+                    <ConfirmModal {confirm} onclose={ctx.link().callback(|_| AppMsg::CloseConfirm)} />
+                }
             </div>
         }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
-        self.error_msg = String::new();
-        // TODO: Display errors
         // TODO: Handle PartialResults
         match msg {
             AppMsg::ToggleCreating => self.creating = !self.creating,
-            AppMsg::GetParts(res) => match res {
-                Ok(res) => {
-                    if let Some(e) = res.1 {
-                        if err_is_access(e.as_ref()) {
-                            if &*self.jwt != "" {
-                                self.display_err_logout_alert();
-                            }
-                            return true;
+            AppMsg::GetParts(res) => {
+                self.requests.finish(&AppRequest::Sync);
+                self.checking_session = false;
+                match res {
+                    Ok(delta) => {
+                        let mut parts_maps = self.pm_mut();
+                        parts_maps.apply_delta(delta);
+                        if let (Some(user), Some(cache)) =
+                            (self.user.as_ref(), parts_maps.to_cache())
+                        {
+                            cache.save(user);
+                        }
+                        drop(parts_maps);
+                        self.parts_rev += 1;
+                        self.load_error = None;
+                        self.start_session(ctx);
+                    }
+                    Err(e) if err_is_access(e.as_ref()) => {
+                        if !self.jwt.is_empty() {
+                            self.display_err_logout_alert();
                         }
-                        self.error_msg = format!("Partial error getting projects/parts: {}", e);
-                        console::log!("{}", self.error_msg);
                     }
-                    *self.pm_mut() = PartsMaps::new(res.0);
-                    self.jwt = Rc::from("1");
+                    Err(e) => self.show_load_error(format!("Error getting projects/parts: {}", e)),
                 }
-                Err(e) => {
-                    self.error_msg = format!("Error getting projects/parts: {}", e);
-                    console::log!("{}", self.error_msg);
+            }
+            AppMsg::TreeScrolled => {
+                if self.tree_list_ref.get().is_none() {
+                    return false;
                 }
-            },
-            AppMsg::CreateUser(res) => match res {
-                Ok(jwt) => self.jwt = jwt.into(),
+                self.tree_viewport = tree_viewport(&self.tree_list_ref);
+                let rows = self.pm().visible_rows();
+                let (top, bottom) = self.tree_viewport;
+                if self.tree_list.window(&rows, top, bottom) == self.tree_window {
+                    return false;
+                }
+            }
+            AppMsg::GetMemberships(res) => match res {
+                Ok(memberships) => self.pm_mut().memberships = memberships,
                 Err(e) => {
-                    self.error_msg = format!("Error creating user: {}", e);
-                    console::log!("{}", self.error_msg);
+                    self.show_error(format!("Error getting project members: {}", e));
                 }
             },
-            AppMsg::LoginUser(res) => match res {
-                Ok(jwt) => {
-                    self.jwt = jwt.clone().into();
-                    ctx.link().send_future(get_parts(jwt).map(AppMsg::GetParts));
+            AppMsg::RefreshShared => {
+                let res = get_shared_projects(self.jwt.to_string());
+                ctx.link().send_future(res.map(AppMsg::GetSharedProjects));
+                return false;
+            }
+            AppMsg::GetSharedProjects(res) => match res {
+                Ok(projects) => {
+                    let old = std::mem::take(&mut self.shared);
+                    self.shared = projects
+                        .into_iter()
+                        .map(|project| {
+                            let mut parts_maps = PartsMaps::from_parts(project.parts);
+                            parts_maps.memberships = project.members;
+                            parts_maps.owner = Some(project.membership.owner.clone());
+                            // Keep what was expanded before refreshing
+                            if let Some(tree) = old
+                                .iter()
+                                .find(|tree| *tree.membership == project.membership)
+                            {
+                                parts_maps.tree = tree.parts_maps.borrow().tree.clone();
+                            }
+                            SharedTree {
+                                membership: Rc::new(project.membership),
+                                parts_maps: Rc::new(RefCell::new(parts_maps)),
+                            }
+                        })
+                        .collect();
                 }
                 Err(e) => {
-                    self.error_msg = format!("Error logging in: {}", e);
-                    console::log!("{}", self.error_msg);
+                    self.show_error(format!("Error getting shared projects: {}", e));
                 }
             },
-            AppMsg::LogoutUser(res) => {
-                let window = web_sys::window().expect("no window");
-                let _ = match res {
-                    Ok(true) => window.alert_with_message("Successfully logged out!"),
-                    Ok(false) => window.alert_with_message(
-                        "Unknown error while logging out. Logging out anyway...",
-                    ),
-                    Err(e) => {
-                        console::log!("Error logging out: {}", e);
-                        window.alert_with_message(&format!(
-                            "Error logging out:\n{}\nLogging out anyway...",
-                            e
-                        ))
-                    }
-                };
-                self.jwt = Rc::from("");
+            AppMsg::LoadChildren(parent_id) => {
+                if !self.loading.insert(parent_id) {
+                    return false;
+                }
+                let after = self.pm().next_page(parent_id).map(String::from);
+                let res = get_children(self.jwt.to_string(), parent_id, after);
+                ctx.link()
+                    .send_future(res.map(move |res| AppMsg::GotChildren(parent_id, res)));
+                return false;
             }
-            AppMsg::SendCreatePart(part) => {
-                self.creating = false;
-                let res =
-                    send_create_part(self.jwt.to_string(), CreatePartVars { input: part.into() });
-                ctx.link().send_future(res.map(AppMsg::CreatePart));
+            AppMsg::GotChildren(parent_id, res) => {
+                self.loading.remove(&parent_id);
+                self.checking_session = false;
+                match res {
+                    Ok(page) => {
+                        if parent_id.is_none() {
+                            self.load_error = None;
+                        }
+                        let mut parts_maps = self.pm_mut();
+                        parts_maps.apply_page(parent_id, page);
+                        let mut expanded = false;
+                        if let Some(depth) = self.expanding {
+                            parts_maps.expand_children(parent_id, depth);
+                            // Done once none of the parts expanded are waiting for children
+                            expanded = parts_maps.unfetched_shown().is_empty();
+                        }
+                        if let (Some(user), Some(cache)) =
+                            (self.user.as_ref(), parts_maps.to_cache())
+                        {
+                            cache.save(user);
+                        }
+                        drop(parts_maps);
+                        if expanded {
+                            self.expanding = None;
+                        }
+                        self.parts_rev += 1;
+                        self.load_filtered_blockers(ctx);
+                        if self.jwt.is_empty() {
+                            self.start_session(ctx);
+                        }
+                    }
+                    Err(e) if err_is_access(e.as_ref()) => {
+                        if !self.jwt.is_empty() {
+                            self.display_err_logout_alert();
+                        }
+                    }
+                    Err(e) if parent_id.is_none() => {
+                        self.show_load_error(format!("Error getting projects: {}", e));
+                    }
+                    Err(e) => {
+                        let msg = format!("Error getting parts: {}", e);
+                        console::log!("{}", msg);
+                        let retry = ctx
+                            .link()
+                            .callback(move |_| AppMsg::LoadChildren(parent_id));
+                        self.toast(Toast::error(msg).with_action("Retry", retry));
+                    }
+                }
             }
-            AppMsg::CreatePart(res) => match res {
-                Ok(part) => match part.try_into() {
-                    Ok(part) => {
-                        console::log!("New part: {:?}", part);
-                        let res = self.pm_mut().add_part(part);
-                        if let Err(part) = res {
-                            console::log!("Part already exists: {:?}", part);
-                            self.error_msg = String::from("Part already exists???");
+            AppMsg::Sync(event) => match event {
+                SyncEvent::Connected { reconnected } => {
+                    self.sync_offline = false;
+                    if reconnected {
+                        self.sync_parts(ctx);
+                    }
+                }
+                SyncEvent::Event(event) => {
+                    if !self.pm_mut().apply_event(event) {
+                        return false;
+                    }
+                    self.parts_rev += 1;
+                }
+                SyncEvent::SharedEvent(owner, event) => {
+                    // Applied to the shared project with the part (or its parent)
+                    let (id, parent_id) = match &event {
+                        PartEvent::Upserted(part) => (part.id, part.parent_id),
+                        PartEvent::Deleted(id) => (*id, None),
+                    };
+                    let tree = self.shared.iter().find(|tree| {
+                        let parts = &tree.parts_maps.borrow().parts;
+                        tree.membership.owner == owner
+                            && (parts.contains_key(&id)
+                                || parent_id.is_some_and(|pid| parts.contains_key(&pid)))
+                    });
+                    match tree {
+                        Some(tree) => {
+                            if !tree.parts_maps.borrow_mut().apply_event(event) {
+                                return false;
+                            }
+                        }
+                        // The part was moved into a shared project, or the project was shared
+                        // since they were fetched
+                        None if matches!(event, PartEvent::Upserted(_)) => {
+                            ctx.link().send_message(AppMsg::RefreshShared);
+                            return false;
+                        }
+                        None => return false,
+                    }
+                    self.parts_rev += 1;
+                }
+                SyncEvent::Disconnected => self.sync_offline = true,
+            },
+            AppMsg::RetryLoad => {
+                self.load_error = None;
+                self.sync_parts(ctx);
+            }
+            AppMsg::LoadParts(ids) => {
+                self.load_parts_by_id(ctx, ids);
+                return false;
+            }
+            AppMsg::GotParts(ids, res) => {
+                self.loading_parts.retain(|id| !ids.contains(id));
+                match res {
+                    Ok(parts) => {
+                        self.pm_mut().apply_parts(parts);
+                        self.parts_rev += 1;
+                        self.load_filtered_blockers(ctx);
+                    }
+                    Err(e) if err_is_access(e.as_ref()) => self.display_err_logout_alert(),
+                    Err(e) => self.show_error(format!("Error getting parts: {}", e)),
+                }
+            }
+            AppMsg::GotPartTree(id, res) => {
+                self.requests.finish(&AppRequest::PartTree(id));
+                let after = self.after_tree_loaded.remove(&id).unwrap_or_default();
+                match res {
+                    Ok(parts) => {
+                        self.pm_mut().apply_tree(id, parts);
+                        self.parts_rev += 1;
+                        after
+                            .into_iter()
+                            .for_each(|msg| ctx.link().send_message(msg));
+                    }
+                    Err(e) if err_is_access(e.as_ref()) => self.display_err_logout_alert(),
+                    Err(e) => {
+                        if self.pm().linked == Some(id) {
+                            self.link_pending = false;
+                        }
+                        self.show_error(format!("Error getting part: {}", e));
+                    }
+                }
+            }
+            AppMsg::ShowLinkedPart => self.show_linked_part(),
+            AppMsg::LoggedIn { email, jwt } => {
+                self.jwt = jwt.into();
+                tree_state::remember_user(&email);
+                self.user = Some(email);
+                self.load_parts(ctx);
+                self.follow_after_login(ctx);
+            }
+            AppMsg::ResetPassword {
+                token,
+                new_password,
+            } => {
+                let res = send_reset_password(token, new_password);
+                return self.requests.send(
+                    ctx.link(),
+                    AppRequest::ResetPassword,
+                    res.map(AppMsg::PasswordReset),
+                );
+            }
+            AppMsg::PasswordReset(res) => {
+                self.requests.finish(&AppRequest::ResetPassword);
+                match res {
+                    Ok(_) => {
+                        self.toast(Toast::success(
+                            "Your password was reset, log in with the new one",
+                        ));
+                        ctx.link().send_message(AppMsg::CancelReset);
+                    }
+                    Err(e) => {
+                        self.show_error(format!("Error resetting password: {}", e));
+                    }
+                }
+            }
+            AppMsg::CancelReset => self.reset_token = None,
+            AppMsg::Navigate(route) => {
+                self.route = Some(route.clone());
+                self.apply_route(ctx, route);
+            }
+            AppMsg::ChangePassword { current, new } => {
+                let res = send_change_password(self.jwt.to_string(), current, new);
+                return self.requests.send(
+                    ctx.link(),
+                    AppRequest::ChangePassword,
+                    res.map(AppMsg::PasswordChanged),
+                );
+            }
+            AppMsg::PasswordChanged(res) => {
+                self.requests.finish(&AppRequest::ChangePassword);
+                match res {
+                    Ok(_) => self.toast(Toast::success("Your password was changed")),
+                    Err(e) => {
+                        if err_is_access(e.as_ref()) {
+                            self.display_err_logout_alert();
+                            return true;
                         }
+                        self.show_error(format!("Error changing password: {}", e));
+                    }
+                }
+            }
+            AppMsg::ChangeEmail {
+                password,
+                new_email,
+            } => {
+                let res = send_change_email(self.jwt.to_string(), password, new_email.clone());
+                return self.requests.send(
+                    ctx.link(),
+                    AppRequest::ChangeEmail,
+                    res.map(move |res| AppMsg::EmailChanged(new_email, res)),
+                );
+            }
+            AppMsg::EmailChanged(email, res) => {
+                self.requests.finish(&AppRequest::ChangeEmail);
+                match res {
+                    Ok(jwt) => {
+                        self.jwt = jwt.into();
+                        self.move_user(email);
+                        // Everything's fetched again under the new email
+                        self.stop_sync();
+                        self.shared.clear();
+                        self.assigned = None;
+                        self.trash = None;
+                        self.load_parts(ctx);
+                        self.toast(Toast::success("Your email was changed"));
                     }
                     Err(e) => {
                         if err_is_access(e.as_ref()) {
                             self.display_err_logout_alert();
                             return true;
                         }
-                        console::log!("Bad create part from server: {}", e);
-                        self.error_msg = String::from("Internal server error");
+                        self.show_error(format!("Error changing email: {}", e));
                     }
-                },
+                }
+            }
+            AppMsg::DeleteAccount(password) => {
+                let confirm = Confirm::new(
+                    "Permanently delete your account and all your projects? \
+                    This can't be undone.",
+                    "Delete Account",
+                    ctx.link()
+                        .callback(move |_| AppMsg::SendDeleteAccount(password.clone())),
+                );
+                self.confirm = Some(confirm.danger());
+            }
+            AppMsg::SendDeleteAccount(password) => {
+                let res = send_delete_account(self.jwt.to_string(), password);
+                return self.requests.send(
+                    ctx.link(),
+                    AppRequest::DeleteAccount,
+                    res.map(AppMsg::AccountDeleted),
+                );
+            }
+            AppMsg::AccountDeleted(res) => {
+                self.requests.finish(&AppRequest::DeleteAccount);
+                match res {
+                    Ok(_) => {
+                        // Nothing of the user's is kept, unlike when logging out
+                        if let Some(user) = self.user.as_deref() {
+                            TreeState::clear(user);
+                            templates::clear(user);
+                        }
+                        self.jwt = Rc::from("");
+                        self.history = History::default();
+                        self.view = MainView::Projects;
+                        self.forget_user();
+                        self.stop_sync();
+                        self.toast(Toast::info("Your account was deleted"));
+                    }
+                    Err(e) => {
+                        if err_is_access(e.as_ref()) {
+                            self.display_err_logout_alert();
+                            return true;
+                        }
+                        self.show_error(format!("Error deleting account: {}", e));
+                    }
+                }
+            }
+            AppMsg::Logout => {
+                let res = send_logout_user().map(AppMsg::LogoutUser);
+                return self.requests.send(ctx.link(), AppRequest::Logout, res);
+            }
+            AppMsg::LogoutUser(res) => {
+                self.requests.finish(&AppRequest::Logout);
+                let toast = match res {
+                    Ok(true) => Toast::success("Successfully logged out!"),
+                    Ok(false) => {
+                        Toast::warning("Unknown error while logging out. Logged out anyway.")
+                    }
+                    Err(e) => {
+                        console::log!("Error logging out: {}", e);
+                        Toast::warning(format!("Error logging out: {}. Logged out anyway.", e))
+                    }
+                };
+                self.toast(toast);
+                self.jwt = Rc::from("");
+                self.history = History::default();
+                self.forget_user();
+                self.stop_sync();
+            }
+            AppMsg::SendCreatePart(part) => {
+                let vars = CreatePartVars {
+                    input: part.into(),
+                    owner: None,
+                };
+                let res = send_create_part(self.jwt.to_string(), vars);
+                // The form's kept open if a quick add is still being created
+                if !self.requests.send(
+                    ctx.link(),
+                    AppRequest::CreatePart,
+                    res.map(AppMsg::CreatePart),
+                ) {
+                    self.toast(Toast::warning(
+                        "Another part is still being created, try again in a moment",
+                    ));
+                    return true;
+                }
+                self.creating = false;
+            }
+            AppMsg::SetQuickAdd(text) => self.quick_add = text,
+            AppMsg::QuickAdd => {
+                if self.requests.is_pending(&AppRequest::CreatePart) {
+                    return false;
+                }
+                let part = match quick_add_part(&self.parse_quick_add()) {
+                    Ok(part) => part,
+                    Err(e) => {
+                        self.show_error(e);
+                        return true;
+                    }
+                };
+                // Show the new part
+                if let Some(pid) = part.parent_id {
+                    let mut parts_maps = self.pm_mut();
+                    parts_maps.reveal(pid);
+                    parts_maps.tree.expanded.insert(pid);
+                }
+                self.quick_add.clear();
+                let vars = CreatePartVars {
+                    input: part.into(),
+                    owner: None,
+                };
+                let res = send_create_part(self.jwt.to_string(), vars);
+                self.requests.send(
+                    ctx.link(),
+                    AppRequest::CreatePart,
+                    res.map(AppMsg::CreatePart),
+                );
+            }
+            AppMsg::CreatePart(res) => {
+                self.requests.finish(&AppRequest::CreatePart);
+                match res {
+                    Ok(part) => match part.try_into() {
+                        Ok(part) => {
+                            let part: Part = part;
+                            console::log!("New part: {:?}", part);
+                            let id = part.id;
+                            let res = self.pm_mut().add_part(part);
+                            if res.is_ok() {
+                                self.record_creation(ctx, id);
+                                let snapshot = self.pm().snapshot(id);
+                                let label = format!("Create \"{}\"", snapshot.parts[0].name);
+                                self.history
+                                    .record(Entry::new(label, vec![Change::Created(snapshot)]));
+                            }
+                            if let Err(part) = res {
+                                console::log!("Part already exists: {:?}", part);
+                                self.show_error(String::from("Part already exists???"));
+                            }
+                        }
+                        Err(e) => {
+                            if err_is_access(e.as_ref()) {
+                                self.display_err_logout_alert();
+                                return true;
+                            }
+                            console::log!("Bad create part from server: {}", e);
+                            self.show_error(String::from("Internal server error"));
+                        }
+                    },
+                    Err(e) => {
+                        self.show_error(format!("Error creating part: {}", e));
+                    }
+                }
+            }
+            AppMsg::ActivityRecorded(res) => {
+                if let Err(e) = res {
+                    self.show_error(format!("Error recording activity: {}", e));
+                    return true;
+                }
+                return false;
+            }
+            AppMsg::Sort(method) => self.pm_mut().apply_sort(method),
+            AppMsg::Filter(method) => {
+                self.pm_mut().apply_filter(method);
+                self.load_filtered_blockers(ctx);
+            }
+            AppMsg::PartsChanged => self.parts_rev += 1,
+            AppMsg::GetTimeEntries(res) => {
+                match res.and_then(|entries| {
+                    entries
+                        .into_iter()
+                        .map(TimeEntry::try_from)
+                        .collect::<anyhow::Result<Vec<_>>>()
+                }) {
+                    Ok(entries) => self.pm_mut().set_time_entries(entries),
+                    Err(e) => {
+                        self.show_error(format!("Error getting time entries: {}", e));
+                    }
+                }
+                self.parts_rev += 1;
+            }
+            AppMsg::TimerTick => self.parts_rev += 1,
+            AppMsg::SetView(view) => self.set_view(ctx, view),
+            AppMsg::GetAssigned(res) => match res {
+                Ok(assigned) => self.assigned = Some(assigned),
                 Err(e) => {
-                    self.error_msg = format!("Error creating part: {}", e);
-                    console::log!("{}", self.error_msg);
+                    self.show_error(format!("Error getting assigned parts: {}", e));
                 }
             },
-            AppMsg::Sort(method) => self.pm_mut().apply_sort(method),
-            AppMsg::Filter(method) => self.pm_mut().apply_filter(method),
-            AppMsg::ErrorMsg(err_msg) => self.error_msg = err_msg,
+            AppMsg::GetTrash(res) => match res {
+                Ok(trash) => self.trash = Some(trash),
+                Err(e) => {
+                    self.show_error(format!("Error getting the trash: {}", e));
+                }
+            },
+            AppMsg::RestorePart(id) => {
+                let res = send_restore_part(self.jwt.to_string(), id);
+                return self.requests.send(
+                    ctx.link(),
+                    AppRequest::Restore(id),
+                    res.map(move |res| AppMsg::PartRestored(id, res)),
+                );
+            }
+            AppMsg::PartRestored(id, res) => {
+                self.requests.finish(&AppRequest::Restore(id));
+                match res {
+                    Ok(parts) => {
+                        let mut parts_maps = self.pm_mut();
+                        for part in parts {
+                            parts_maps.apply_event(PartEvent::Upserted(Box::new(part)));
+                        }
+                        drop(parts_maps);
+                        if let Some(trash) = self.trash.as_mut() {
+                            trash.retain(|trashed| trashed.part.id != id);
+                        }
+                        // The restored parts' time entries come back with them
+                        let res = get_time_entries(self.jwt.to_string());
+                        ctx.link().send_future(res.map(AppMsg::GetTimeEntries));
+                        self.parts_rev += 1;
+                    }
+                    Err(e) => {
+                        self.show_error(format!("Error restoring part: {}", e));
+                    }
+                }
+            }
+            AppMsg::PurgePart(id) => {
+                let Some(trashed) = self
+                    .trash
+                    .as_ref()
+                    .and_then(|trash| trash.iter().find(|trashed| trashed.part.id == id))
+                else {
+                    return false;
+                };
+                let confirm = Confirm::new(
+                    format!(
+                        "Permanently delete the following project/part and ALL its \
+                        children? This can't be undone.\n{}",
+                        trashed.part.name,
+                    ),
+                    "Delete Forever",
+                    ctx.link().callback(move |_| AppMsg::SendPurgePart(id)),
+                );
+                self.confirm = Some(confirm.danger());
+            }
+            AppMsg::SendPurgePart(id) => {
+                let res = send_purge_part(self.jwt.to_string(), id);
+                return self.requests.send(
+                    ctx.link(),
+                    AppRequest::Purge(id),
+                    res.map(move |res| AppMsg::PartPurged(id, res)),
+                );
+            }
+            AppMsg::PartPurged(id, res) => {
+                self.requests.finish(&AppRequest::Purge(id));
+                match res {
+                    Ok(_) => {
+                        if let Some(trash) = self.trash.as_mut() {
+                            trash.retain(|trashed| trashed.part.id != id);
+                        }
+                    }
+                    Err(e) => {
+                        self.show_error(format!("Error permanently deleting part: {}", e));
+                    }
+                }
+            }
+            AppMsg::SaveTemplate(id) => return self.with_subtree(ctx, id, SubtreeUse::Template),
+            AppMsg::SaveTemplateAs(template, name) => {
+                let name = name.trim().to_string();
+                if name.is_empty() {
+                    self.show_error(String::from("Templates must have a name"));
+                    return true;
+                }
+                let template = Template {
+                    name,
+                    ..(*template).clone()
+                };
+                // Templates with the same name are replaced
+                self.templates
+                    .retain(|existing| existing.name != template.name);
+                self.templates.push(template);
+                self.templates.sort_by_cached_key(|t| t.name.to_lowercase());
+                if let Some(user) = self.user.as_ref() {
+                    templates::save(user, &self.templates);
+                }
+            }
+            AppMsg::DeleteTemplate(index) => {
+                let Some(template) = self.templates.get(index) else {
+                    return false;
+                };
+                let name = template.name.clone();
+                let confirm = Confirm::new(
+                    format!("Delete the template \"{}\"?", name),
+                    "Delete",
+                    ctx.link()
+                        .callback(move |_| AppMsg::RemoveTemplate(name.clone())),
+                );
+                self.confirm = Some(confirm.danger());
+            }
+            AppMsg::RemoveTemplate(name) => {
+                self.templates.retain(|template| template.name != name);
+                if let Some(user) = self.user.as_ref() {
+                    templates::save(user, &self.templates);
+                }
+            }
+            AppMsg::NewFromTemplate { index, anchor } => {
+                let Some(template) = self.templates.get(index) else {
+                    return false;
+                };
+                let label = format!("Create \"{}\" from template", template.root.name);
+                let res =
+                    templates::create(self.jwt.to_string(), None, template.instantiate(anchor));
+                return self.requests.send(
+                    ctx.link(),
+                    AppRequest::Instantiate,
+                    res.map(move |res| AppMsg::Instantiated(label, res)),
+                );
+            }
+            AppMsg::Duplicate(id) => return self.with_subtree(ctx, id, SubtreeUse::Duplicate),
+            AppMsg::GotSubtree(id, subtree_use, res) => {
+                self.requests.finish(&AppRequest::Subtree(id));
+                match res {
+                    Ok(parts) => {
+                        let parts = parts
+                            .into_iter()
+                            .map(|part| (part.id, part))
+                            .collect::<PartsMap>();
+                        return self.use_subtree(ctx, id, subtree_use, &parts);
+                    }
+                    Err(e) if err_is_access(e.as_ref()) => self.display_err_logout_alert(),
+                    Err(e) => self.show_error(format!("Error getting part: {}", e)),
+                }
+            }
+            AppMsg::Instantiated(label, PartialOk(parts, err)) => {
+                self.requests.finish(&AppRequest::Instantiate);
+                let ids = parts.iter().map(|part| part.id).collect::<Vec<_>>();
+                let mut parts_maps = self.pm_mut();
+                for part in parts {
+                    if let Err(part) = parts_maps.add_part(part) {
+                        console::log!("Part already exists: {:?}", part);
+                    }
+                }
+                drop(parts_maps);
+                ids.iter().for_each(|&id| self.record_creation(ctx, id));
+                // The parts created before an error are kept (and can be undone)
+                if let Some(&root_id) = ids.first() {
+                    let snapshot = self.pm().snapshot(root_id);
+                    self.history
+                        .record(Entry::new(label, vec![Change::Created(snapshot)]));
+                }
+                if let Some(e) = err {
+                    if err_is_access(e.as_ref()) {
+                        self.display_err_logout_alert();
+                        return true;
+                    }
+                    self.show_error(format!("Error creating parts: {}", e));
+                }
+                self.parts_rev += 1;
+            }
+            AppMsg::SetReportWeek(week) => self.report_week = week,
+            AppMsg::GotStats(res) => {
+                self.requests.finish(&AppRequest::Stats);
+                match res {
+                    Ok(data) => {
+                        self.stats = Some(Stats::new(data, STATS_WEEKS, Local::now()));
+                        self.load_burndown(ctx);
+                    }
+                    Err(e) if err_is_access(e.as_ref()) => self.display_err_logout_alert(),
+                    Err(e) => self.show_error(format!("Error getting the stats: {}", e)),
+                }
+            }
+            AppMsg::SetStatsProject(id) => {
+                self.stats_project = id;
+                self.load_burndown(ctx);
+            }
+            AppMsg::GotBurndown(id, res) => {
+                self.requests.finish(&AppRequest::Burndown);
+                match res {
+                    Ok(data) => {
+                        self.burndown = Some((id, Burndown::new(&data, Local::now().date_naive())))
+                    }
+                    Err(e) if err_is_access(e.as_ref()) => self.display_err_logout_alert(),
+                    Err(e) => self.show_error(format!("Error getting the burndown: {}", e)),
+                }
+            }
+            AppMsg::SelectPart {
+                id,
+                selected,
+                range,
+            } => {
+                let mut parts_maps = self.pm_mut();
+                let ids = match parts_maps.select_anchor.filter(|_| range) {
+                    Some(anchor) => displayed_range(anchor, id),
+                    None => {
+                        parts_maps.select_anchor = Some(id);
+                        vec![id]
+                    }
+                };
+                for id in ids {
+                    if selected {
+                        parts_maps.selected.insert(id);
+                    } else {
+                        parts_maps.selected.remove(&id);
+                    }
+                }
+                drop(parts_maps);
+                self.parts_rev += 1;
+            }
+            AppMsg::ClearSelection => {
+                let mut parts_maps = self.pm_mut();
+                parts_maps.selected.clear();
+                parts_maps.select_anchor = None;
+                drop(parts_maps);
+                self.parts_rev += 1;
+            }
+            AppMsg::Batch(action) => self.send_batch(ctx, action, false),
+            AppMsg::BatchConfirmed(action) => self.send_batch(ctx, action, true),
+            AppMsg::BatchUpdated(action, res) => {
+                self.requests.finish(&AppRequest::Batch);
+                match res {
+                    Ok(items) => {
+                        let total = items.len();
+                        let mut parts_maps = self.pm_mut();
+                        let mut failures = Vec::new();
+                        let mut updated = Vec::new();
+                        let mut changes = Vec::new();
+                        for item in items {
+                            match item.result {
+                                Ok(part) => {
+                                    if let Some(before) = parts_maps.parts.get(&part.id) {
+                                        changes.push(Change::Updated {
+                                            before: Box::new(before.clone()),
+                                            after: Box::new(part.clone()),
+                                        });
+                                    }
+                                    updated.push(part);
+                                }
+                                Err(e) => {
+                                    failures.push(format!("{}: {}", parts_maps.label(item.id), e))
+                                }
+                            }
+                        }
+                        for part in parts_maps.update_parts(updated) {
+                            console::log!("Part doesn't exist: {:?}", part);
+                            failures.push(format!("#{}: part doesn't exist", part.id));
+                        }
+                        drop(parts_maps);
+                        self.record_updates(
+                            ctx,
+                            changes.iter().filter_map(|change| match change {
+                                Change::Updated { before, after } => Some((&**before, &**after)),
+                                _ => None,
+                            }),
+                        );
+                        if !changes.is_empty() {
+                            let label = action.label(changes.len());
+                            self.history.record(Entry::new(label, changes));
+                        }
+                        if let Some(msg) = batch_failures_msg("updating", total, &failures) {
+                            self.show_error(msg);
+                        }
+                        self.parts_rev += 1;
+                    }
+                    Err(e) => {
+                        self.show_error(format!("Error updating parts: {}", e));
+                    }
+                }
+            }
+            AppMsg::BatchDeleted(res) => {
+                self.requests.finish(&AppRequest::Batch);
+                match res {
+                    Ok(items) => {
+                        let total = items.len();
+                        let mut parts_maps = self.pm_mut();
+                        let mut failures = Vec::new();
+                        let mut changes = Vec::new();
+                        for item in items {
+                            match item.result {
+                                Ok(()) => {
+                                    changes.push(Change::Deleted(parts_maps.snapshot(item.id)));
+                                    parts_maps.delete_part(item.id);
+                                }
+                                Err(e) => {
+                                    failures.push(format!("{}: {}", parts_maps.label(item.id), e))
+                                }
+                            }
+                        }
+                        drop(parts_maps);
+                        if !changes.is_empty() {
+                            let num_deleted = changes.len();
+                            let label = BatchAction::Delete.label(num_deleted);
+                            self.history.record(Entry::new(label, changes));
+                            self.toast(
+                                Toast::success(format!(
+                                    "Moved {} projects/parts to the trash",
+                                    num_deleted
+                                ))
+                                .with_action("Undo", ctx.link().callback(|_| AppMsg::Undo)),
+                            );
+                        }
+                        if let Some(msg) = batch_failures_msg("deleting", total, &failures) {
+                            self.show_error(msg);
+                        }
+                        self.parts_rev += 1;
+                    }
+                    Err(e) => {
+                        self.show_error(format!("Error deleting parts: {}", e));
+                    }
+                }
+            }
+            AppMsg::Record(entry) => self.history.record(entry),
+            AppMsg::Undo => self.start_history(ctx, true),
+            AppMsg::Redo => self.start_history(ctx, false),
+            AppMsg::HistoryApplied(PartialOk(mut outcome, err)) => {
+                self.history.finish(&mut outcome, err.is_none());
+                let updates = outcome
+                    .updated
+                    .iter()
+                    .filter_map(|after| {
+                        Some((self.pm().parts.get(&after.id)?.clone(), after.clone()))
+                    })
+                    .collect::<Vec<_>>();
+                self.pm_mut().apply_outcome(outcome);
+                self.record_updates(ctx, updates.iter().map(|(before, after)| (before, after)));
+                self.parts_rev += 1;
+                if let Some(e) = err {
+                    if err_is_access(e.as_ref()) {
+                        self.display_err_logout_alert();
+                        return true;
+                    }
+                    self.show_error(format!("Error undoing/redoing: {}", e));
+                }
+            }
+            AppMsg::ToggleHistory => self.show_history = !self.show_history,
+            AppMsg::Nav(key) => return self.navigate(key),
+            AppMsg::ExpandToDepth(depth) => {
+                let mut parts_maps = self.pm_mut();
+                parts_maps.expand_to_depth(depth);
+                // The children of the parts expanded are fetched as they're shown
+                let expanding = !parts_maps.unfetched_shown().is_empty();
+                drop(parts_maps);
+                self.expanding = expanding.then_some(depth);
+                self.parts_rev += 1;
+            }
+            AppMsg::Focus(id) => {
+                self.pm_mut().focused = Some(id);
+                self.parts_rev += 1;
+            }
+            AppMsg::GoToPart(id) => self.run_command(ctx, PaletteCommand::GoToPart(id)),
+            AppMsg::TogglePalette => {
+                self.palette = match self.palette {
+                    Some(_) => None,
+                    None if self.jwt.is_empty() => None,
+                    None => Some(self.palette_commands()),
+                };
+            }
+            AppMsg::RunCommand(index) => {
+                let Some(command) = self
+                    .palette
+                    .take()
+                    .and_then(|(commands, _)| commands.get(index).copied())
+                else {
+                    return true;
+                };
+                self.run_command(ctx, command);
+            }
+            AppMsg::ErrorMsg(err_msg) => self.show_error(err_msg),
+            AppMsg::Toast(toast) => self.toast(toast),
+            AppMsg::DismissToast(id) => {
+                self.toast_timers.remove(&id);
+                return self.toasts.dismiss(id);
+            }
+            AppMsg::Confirm(confirm) => self.confirm = Some(confirm),
+            AppMsg::CloseConfirm => self.confirm = None,
             AppMsg::DisplayErrLogout => self.display_err_logout_alert(),
         }
         true
     }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        let route = self.current_route();
+        // The URL's left as it is until it's known whether the user's logged in
+        if !self.checking_session && self.route.as_ref() != Some(&route) {
+            // Redirects (to and from the pages shown when logged out) replace the URL rather than
+            // adding to the history
+            let replace =
+                !route.needs_login() || !self.route.as_ref().is_some_and(Route::needs_login);
+            route.go(replace);
+            self.route = Some(route);
+        }
+        if !self.jwt.is_empty() {
+            for parent_id in self.pm().unfetched_shown() {
+                if !self.loading.contains(&parent_id) {
+                    ctx.link().send_message(AppMsg::LoadChildren(parent_id));
+                }
+            }
+        }
+        self.measure_tree(ctx);
+        if std::mem::take(&mut self.scroll_to_focus) {
+            scroll_to_focused();
+        }
+        if let Some(user) = self.user.as_ref() {
+            let parts_maps = self.parts_maps.borrow();
+            if parts_maps.tree != self.saved_tree {
+                parts_maps.tree.save(user);
+                self.saved_tree = parts_maps.tree.clone();
+            }
+        }
+        let running = self.pm().has_running_timer();
+        if running && self.timer_interval.is_none() {
+            let link = ctx.link().clone();
+            self.timer_interval = Some(Interval::new(1000, move || {
+                link.send_message(AppMsg::TimerTick)
+            }));
+        } else if !running {
+            self.timer_interval = None;
+        }
+    }
 }
 
 impl App {
     fn render_login(&self, ctx: &Context<Self>) -> Html {
-        let link = ctx.link().clone();
+        let onlogin = ctx
+            .link()
+            .callback(|(email, jwt)| AppMsg::LoggedIn { email, jwt });
+        html! {
+            <LoginForm {onlogin} />
+        }
+    }
+
+    fn render_reset_password(&self, ctx: &Context<Self>, token: &str) -> Html {
+        let token = token.to_string();
         let onsubmit = ctx.link().batch_callback(move |e: SubmitEvent| {
             e.prevent_default();
-            let Some(submit_button) = e
-                .submitter()
-                .and_then(|e| e.dyn_into::<HtmlButtonElement>().ok()) else {
-                    console::log!("missing or invalid submitter button");
-                    return None;
-                };
             let form: HtmlFormElement = e.target_unchecked_into();
             let elems = form.elements();
-            let Some(email) = elems.get_with_name("email").and_then(value_from_input) else {
-                console::log!("missing or invalid email input element");
-                return None;
-            };
-            let Some(password) = elems.get_with_name("password").and_then(value_from_input) else {
-                console::log!("missing or invalid password input element");
-                return None;
-            };
-            match submit_button.name().as_str() {
-                "login" => {
-                    link.send_future(
-                        send_login_user(LoginUserVars {
-                            input: LoginUserInput { email, password },
-                        })
-                        .map(AppMsg::LoginUser),
-                    );
-                }
-                "register" => {
-                    link.send_future(
-                        send_create_user(CreateUserVars {
-                            input: CreateUserInput { email, password },
-                        })
-                        .map(AppMsg::CreateUser),
-                    );
-                }
-                name => {
-                    console::log!("invalid button name: {}", name);
-                }
+            let new_password = elems.get_with_name("password").and_then(value_from_input)?;
+            let confirm = elems.get_with_name("confirm").and_then(value_from_input)?;
+            if let Some(error) = validation::new_password_error(&new_password) {
+                return Some(AppMsg::ErrorMsg(String::from(error)));
             }
-            None
+            if new_password != confirm {
+                return Some(AppMsg::ErrorMsg(String::from("The passwords don't match")));
+            }
+            Some(AppMsg::ResetPassword {
+                token: token.clone(),
+                new_password,
+            })
         });
+        let cancel = ctx.link().callback(|_| AppMsg::CancelReset);
         html! {
             <form id="login-register-div" {onsubmit}>
+                <h3 style="text-align:center">{ "Reset Password" }</h3>
                 <input
-                    type="email"
-                    name="email"
+                    type="password"
+                    name="password"
                     size="40"
-                    placeholder="Email"
+                    placeholder="New Password"
                     required=true
                 /><br />
                 <input
                     type="password"
-                    name="password"
+                    name="confirm"
                     size="40"
-                    placeholder="Password"
+                    placeholder="Confirm New Password"
                     required=true
                 /><br />
                 <div id="logreg-button-div">
-                    <button type="submit" name="login">{ "Login" }</button>
-                    <button type="submit" name="register">{ "Register" }</button>
+                    { submit_button(
+                        "Reset Password",
+                        None,
+                        self.requests.is_pending(&AppRequest::ResetPassword),
+                    ) }
+                    <button type="button" onclick={cancel}>{ "Back to Login" }</button>
                 </div>
-                <p style="text-align:center; color:red;">{ &self.error_msg }</p>
             </form>
         }
     }
 
     fn render_main(&self, ctx: &Context<Self>) -> Html {
-        let link = ctx.link().clone();
-        let logout = ctx.link().batch_callback(move |_| {
-            link.send_future(send_logout_user().map(AppMsg::LogoutUser));
-            None
-        });
+        let logout = ctx.link().callback(|_| AppMsg::Logout);
+        let toggle_account = match self.view {
+            MainView::Account => ctx.link().callback(|_| AppMsg::SetView(MainView::Projects)),
+            _ => ctx.link().callback(|_| AppMsg::SetView(MainView::Account)),
+        };
         html! {
             <div id="main-div">
-                <button id="logout-button" onclick={logout}>{ "Logout" }</button>
+                <div id="account-buttons-div">
+                    <button
+                        onclick={logout}
+                        disabled={self.requests.is_pending(&AppRequest::Logout)}
+                    >{ "Logout" }</button>
+                    <button onclick={toggle_account}>{
+                        if self.view == MainView::Account { "Projects" } else { "Account" }
+                    }</button>
+                </div>
                 <h1 style="text-align:center">{ "Let's Get Productive!" }</h1>
-                <p id="err-msg-p">{self.error_msg.as_str()}</p>
+                if self.sync_offline {
+                    <p id="sync-status-p">{ "Offline: changes made elsewhere will show up once reconnected" }</p>
+                }
                 { self.render_controls(ctx) }
-                { self.render_projects(ctx) }
+                if self.show_history {
+                    { self.render_history(ctx) }
+                }
+                if self.view == MainView::Projects && !self.pm().selected.is_empty() {
+                    { self.render_batch_bar(ctx) }
+                }
+                if self.view == MainView::Projects {
+                    { self.render_quick_add(ctx) }
+                }
+                if let Some((_, labels)) = self.palette.as_ref() {
+                    <CommandPalette
+                        items={Rc::clone(labels)}
+                        onselect={ctx.link().callback(AppMsg::RunCommand)}
+                        onclose={ctx.link().callback(|_| AppMsg::TogglePalette)}
+                    />
+                }
+                {
+                    match self.view {
+                        MainView::Projects => self.render_projects(ctx),
+                        MainView::TimeReport => self.render_time_report(ctx),
+                        MainView::Assigned => self.render_assigned(ctx),
+                        MainView::Trash => self.render_trash(ctx),
+                        MainView::Stats => self.render_stats(ctx),
+                        MainView::Account => self.render_account(ctx),
+                    }
+                }
+            </div>
+        }
+    }
+
+    // TODO: Rename?
+    fn render_controls(&self, ctx: &Context<Self>) -> Html {
+        let change_sort = ctx.link().batch_callback(|e: Event| {
+            let Some(select) = e.target_dyn_into::<HtmlSelectElement>() else {
+                console::log!("missing or invalid sort select element");
+                return None;
+            };
+            match select.value().as_str() {
+                "id" => Some(AppMsg::Sort(SortMethod::Id)),
+                "name" => Some(AppMsg::Sort(SortMethod::Name)),
+                "deadline" => Some(AppMsg::Sort(SortMethod::Deadline)),
+                "completedAt" => Some(AppMsg::Sort(SortMethod::CompletedAt)),
+                val => {
+                    console::log!("invalid select value: {}", val);
+                    None
+                }
+            }
+        });
+
+        let dropdown = self.filter_dd_div_ref.clone();
+        let show_filter = ctx.link().batch_callback(move |_| {
+            let elem = dropdown.cast::<HtmlElement>().unwrap();
+            elem.set_hidden(!elem.hidden());
+            None
+        });
+
+        let parts_maps = Rc::clone(&self.parts_maps);
+        let toggle_actionable = ctx.link().batch_callback(move |e: Event| {
+            let Some(checkbox) = e.target_dyn_into::<HtmlInputElement>() else {
+                console::log!("missing or invalid actionable checkbox element");
+                return None;
+            };
+            let mut method = parts_maps.borrow().filter_method;
+            method.actionable = checkbox.checked();
+            Some(AppMsg::Filter(method))
+        });
+        let parts_maps = Rc::clone(&self.parts_maps);
+        let toggle_archived = ctx.link().batch_callback(move |e: Event| {
+            let checkbox = e.target_dyn_into::<HtmlInputElement>()?;
+            let mut method = parts_maps.borrow().filter_method;
+            method.archived = checkbox.checked();
+            Some(AppMsg::Filter(method))
+        });
+
+        let toggle_report = match self.view {
+            MainView::TimeReport => ctx.link().callback(|_| AppMsg::SetView(MainView::Projects)),
+            _ => ctx
+                .link()
+                .callback(|_| AppMsg::SetView(MainView::TimeReport)),
+        };
+        let toggle_assigned = match self.view {
+            MainView::Assigned => ctx.link().callback(|_| AppMsg::SetView(MainView::Projects)),
+            _ => ctx.link().callback(|_| AppMsg::SetView(MainView::Assigned)),
+        };
+
+        let toggle_trash = match self.view {
+            MainView::Trash => ctx.link().callback(|_| AppMsg::SetView(MainView::Projects)),
+            _ => ctx.link().callback(|_| AppMsg::SetView(MainView::Trash)),
+        };
+
+        let toggle_stats = match self.view {
+            MainView::Stats => ctx.link().callback(|_| AppMsg::SetView(MainView::Projects)),
+            _ => ctx.link().callback(|_| AppMsg::SetView(MainView::Stats)),
+        };
+
+        let toggle_history = ctx.link().callback(|_| AppMsg::ToggleHistory);
+
+        let expand_all = ctx.link().callback(|_| AppMsg::ExpandToDepth(None));
+        let collapse_all = ctx.link().callback(|_| AppMsg::ExpandToDepth(Some(0)));
+        let expand_to_depth = ctx.link().batch_callback(|e: Event| {
+            let select = e.target_dyn_into::<HtmlSelectElement>()?;
+            let depth = select.value().parse().ok()?;
+            // Reset so the same depth can be picked again
+            select.set_value("");
+            Some(AppMsg::ExpandToDepth(Some(depth)))
+        });
+        let max_depth = self.pm().max_depth();
+
+        let toggle_creating = ctx.link().callback(|_| AppMsg::ToggleCreating);
+        html! {
+            <div id="top-controls-div">
+                <button onclick={toggle_creating}>{ "New Project" }</button>
+                { self.render_templates(ctx) }
+                <button onclick={toggle_history}>{ "History" }</button>
+                <button onclick={toggle_report}>{
+                    if self.view == MainView::TimeReport { "Projects" } else { "Time Report" }
+                }</button>
+                <button onclick={toggle_assigned}>{
+                    if self.view == MainView::Assigned { "Projects" } else { "Assigned to Me" }
+                }</button>
+                <button onclick={toggle_trash}>{
+                    if self.view == MainView::Trash { "Projects" } else { "Trash" }
+                }</button>
+                <button onclick={toggle_stats}>{
+                    if self.view == MainView::Stats { "Projects" } else { "Stats" }
+                }</button>
+                <input type="text" placeholder="Search" />
+
+                <label for="sort">{ "Sort" }</label>
+                <select onchange={change_sort}>
+                    <option value="id" selected=true>{ "Added" }</option>
+                    <option value="name">{ "Name" }</option>
+                    <option value="deadline">{ "Deadline" }</option>
+                    <option value="completedAt">{ "Completed At" }</option>
+                </select>
+                <div style="float:left; overflow:hidden">
+                    <button onclick={show_filter}>{ "Filter" }</button>
+                    { self.render_filter_popup(ctx) }
+                </div>
+                <input
+                    type="checkbox"
+                    id="actionable"
+                    name="actionable"
+                    checked={self.pm().filter_method.actionable}
+                    onchange={toggle_actionable}
+                />
+                <label for="actionable">{ "Actionable Only" }</label>
+                <input
+                    type="checkbox"
+                    id="archived"
+                    name="archived"
+                    checked={self.pm().filter_method.archived}
+                    onchange={toggle_archived}
+                />
+                <label for="archived">{ "Show Archived" }</label>
+
+                <button onclick={expand_all}>{ "Expand All" }</button>
+                <button onclick={collapse_all}>{ "Collapse All" }</button>
+                <select onchange={expand_to_depth}>
+                    <option value="" selected=true disabled=true>{ "Expand to Depth" }</option>
+                    {
+                        (1..=max_depth).map(|depth| html! {
+                            <option value={depth.to_string()}>{ depth }</option>
+                        }).collect::<Html>()
+                    }
+                </select>
+            </div>
+        }
+    }
+
+    // Renders the controls for creating a project from a template, if there are any
+    fn render_templates(&self, ctx: &Context<Self>) -> Html {
+        if self.templates.is_empty() {
+            return html! {};
+        }
+        let select_ref = self.template_select_ref.clone();
+        let anchor_ref = self.template_anchor_ref.clone();
+        let create = ctx.link().batch_callback(move |_| {
+            let index = select_ref
+                .cast::<HtmlSelectElement>()?
+                .value()
+                .parse()
+                .ok()?;
+            let input = anchor_ref.cast::<HtmlInputElement>()?;
+            match Part::dtl_from_input(&input.value()) {
+                Some(anchor) => Some(AppMsg::NewFromTemplate { index, anchor }),
+                None => Some(AppMsg::ErrorMsg(String::from(
+                    "Must provide the date the template's deadlines are relative to",
+                ))),
+            }
+        });
+        let select_ref = self.template_select_ref.clone();
+        let delete = ctx.link().batch_callback(move |_| {
+            let index = select_ref
+                .cast::<HtmlSelectElement>()?
+                .value()
+                .parse()
+                .ok()?;
+            Some(AppMsg::DeleteTemplate(index))
+        });
+        html! {
+            <span id="templates-span">
+                <select ref={self.template_select_ref.clone()}>
+                    {
+                        self.templates.iter().enumerate().map(|(i, template)| html! {
+                            <option value={i.to_string()}>{
+                                format!("{} ({} parts)", template.name, template.num_parts())
+                            }</option>
+                        }).collect::<Html>()
+                    }
+                </select>
+                <input
+                    type="datetime-local"
+                    title="The date the template's deadlines are relative to"
+                    ref={self.template_anchor_ref.clone()}
+                />
+                <button onclick={create}>{ "New From Template" }</button>
+                <button onclick={delete}>{ "Delete Template" }</button>
+            </span>
+        }
+    }
+
+    fn render_history(&self, ctx: &Context<Self>) -> Html {
+        let undo = ctx.link().callback(|_| AppMsg::Undo);
+        let redo = ctx.link().callback(|_| AppMsg::Redo);
+        let pending = self.history.is_pending();
+        let render_entry = |entry: &Entry, class: &'static str| {
+            html! {
+                <li class={class}>
+                    { format!("{} {}", entry.at.format("%H:%M:%S"), entry.label) }
+                </li>
+            }
+        };
+        html! {
+            <div id="history-div">
+                <button
+                    onclick={undo}
+                    disabled={pending || self.history.undo_entries().is_empty()}
+                    title="Ctrl+Z"
+                >{ "Undo" }</button>
+                <button
+                    onclick={redo}
+                    disabled={pending || self.history.redo_entries().is_empty()}
+                    title="Ctrl+Shift+Z"
+                >{ "Redo" }</button>
+                if pending {
+                    <span>{ "Applying..." }</span>
+                }
+                // Newest first, with the undone entries (which can be redone) on top
+                <ul>
+                    {
+                        self.history.redo_entries().iter()
+                            .map(|entry| render_entry(entry, "history-undone"))
+                            .collect::<Html>()
+                    }
+                    {
+                        self.history.undo_entries().iter().rev()
+                            .map(|entry| render_entry(entry, "history-done"))
+                            .collect::<Html>()
+                    }
+                </ul>
+                if self.history.undo_entries().is_empty() && self.history.redo_entries().is_empty() {
+                    <p>{ "No changes this session" }</p>
+                }
+            </div>
+        }
+    }
+
+    fn render_batch_bar(&self, ctx: &Context<Self>) -> Html {
+        let parts_maps = self.pm();
+        let mut candidates = parts_maps
+            .move_candidates()
+            .into_iter()
+            .map(|id| (id, time_tracking::part_path(&parts_maps.parts, id)))
+            .collect::<Vec<_>>();
+        candidates.sort_by_cached_key(|(_, path)| path.to_lowercase());
+
+        let complete = ctx
+            .link()
+            .callback(|_| AppMsg::Batch(BatchAction::Complete));
+        let uncomplete = ctx
+            .link()
+            .callback(|_| AppMsg::Batch(BatchAction::Uncomplete));
+        let deadline_ref = self.batch_deadline_ref.clone();
+        let set_deadline = ctx.link().batch_callback(move |_| {
+            let input = deadline_ref.cast::<HtmlInputElement>()?;
+            match Part::dtl_from_input(&input.value()) {
+                Some(dt) => Some(AppMsg::Batch(BatchAction::SetDeadline(dt))),
+                None => Some(AppMsg::ErrorMsg(String::from("Must provide a deadline"))),
+            }
+        });
+        let clear_deadline = ctx
+            .link()
+            .callback(|_| AppMsg::Batch(BatchAction::ClearDeadline));
+        let parent_ref = self.batch_parent_ref.clone();
+        let move_parts = ctx.link().batch_callback(move |_| {
+            let select = parent_ref.cast::<HtmlSelectElement>()?;
+            let pid = match select.value().as_str() {
+                "" => None,
+                val => Some(val.parse().ok()?),
+            };
+            Some(AppMsg::Batch(BatchAction::Move(pid)))
+        });
+        let delete = ctx.link().callback(|_| AppMsg::Batch(BatchAction::Delete));
+        let clear_selection = ctx.link().callback(|_| AppMsg::ClearSelection);
+        let pending = self.requests.is_pending(&AppRequest::Batch);
+        html! {
+            <div id="batch-bar-div">
+                <span>{ format!("{} selected", parts_maps.selected.len()) }</span>
+                if pending {
+                    <span class="spinner" title="Applying..."></span>
+                }
+                <button onclick={complete} disabled={pending}>{ "Complete" }</button>
+                <button onclick={uncomplete} disabled={pending}>{ "Un-complete" }</button>
+                <input type="datetime-local" ref={self.batch_deadline_ref.clone()} />
+                <button onclick={set_deadline} disabled={pending}>{ "Set Deadline" }</button>
+                <button onclick={clear_deadline} disabled={pending}>{ "Clear Deadline" }</button>
+                <select ref={self.batch_parent_ref.clone()}>
+                    <option value="">{ "(No parent)" }</option>
+                    {
+                        candidates.into_iter().map(|(id, path)| html! {
+                            <option value={id.to_string()}>{ path }</option>
+                        }).collect::<Html>()
+                    }
+                </select>
+                <button onclick={move_parts} disabled={pending}>{ "Move" }</button>
+                <button class="delete-button" onclick={delete} disabled={pending}>
+                    { "Delete" }
+                </button>
+                <button onclick={clear_selection}>{ "Clear Selection" }</button>
+            </div>
+        }
+    }
+
+    fn render_quick_add(&self, ctx: &Context<Self>) -> Html {
+        let set_text = ctx.link().callback(|e: InputEvent| {
+            AppMsg::SetQuickAdd(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onsubmit = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            AppMsg::QuickAdd
+        });
+        let parsed = self.parse_quick_add();
+        let parts_maps = self.pm();
+        let parent = match parsed.parent.as_ref() {
+            None => String::from("(none)"),
+            Some(ParentMatch::Found(id)) => time_tracking::part_path(&parts_maps.parts, *id),
+            Some(ParentMatch::NotFound(name)) => format!("no part named \"{}\"", name),
+            Some(ParentMatch::Ambiguous(name, ids)) => format!(
+                "\"{}\" matches {} parts: {}",
+                name,
+                ids.len(),
+                ids.iter()
+                    .map(|&id| time_tracking::part_path(&parts_maps.parts, id))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+        };
+        let error = quick_add_part(&parsed).err();
+        let creating = self.requests.is_pending(&AppRequest::CreatePart);
+        html! {
+            <form id="quick-add-form" {onsubmit}>
+                <input
+                    type="text"
+                    size="60"
+                    placeholder="Quick add: Write release notes tomorrow 5pm #docs under \"v2 launch\""
+                    value={self.quick_add.clone()}
+                    oninput={set_text}
+                />
+                <button type="submit" disabled={error.is_some() || creating}>
+                    if creating {
+                        <span class="spinner"></span>
+                    }
+                    { "Add" }
+                </button>
+                if !self.quick_add.trim().is_empty() {
+                    <div id="quick-add-preview">
+                        <u>{ "Name" }</u>{ format!(": {} ", parsed.name) }
+                        <u>{ "Deadline" }</u>{
+                            format!(
+                                ": {} ",
+                                parsed.deadline
+                                    .map(|dt| dt.format("%H:%M %b %d, %Y").to_string())
+                                    .unwrap_or_else(|| String::from("(none)")),
+                            )
+                        }
+                        <u>{ "Tags" }</u>{
+                            if parsed.tags.is_empty() {
+                                String::from(": (none) ")
+                            } else {
+                                format!(": #{} ", parsed.tags.join(" #"))
+                            }
+                        }
+                        <u>{ "Parent" }</u>{ format!(": {}", parent) }
+                        if let Some(error) = error {
+                            <span class="field-error">{ error }</span>
+                        }
+                    </div>
+                }
+            </form>
+        }
+    }
+
+    fn parse_quick_add(&self) -> QuickAdd {
+        let parts_maps = self.pm();
+        quick_add::parse(
+            &self.quick_add,
+            Local::now().naive_local(),
+            parts_maps
+                .parts
+                .values()
+                .map(|part| (part.id, part.name.as_str())),
+        )
+    }
+
+    fn render_projects(&self, ctx: &Context<Self>) -> Html {
+        let parts_maps = self.pm();
+        html! {
+            <>
+            if let Some(error) = self.load_error.as_ref() {
+                <div id="load-error-div" role="alert">
+                    <p>{ error }</p>
+                    <button onclick={ctx.link().callback(|_| AppMsg::RetryLoad)}>
+                        { "Retry" }
+                    </button>
+                </div>
+            }
+            if self.requests.is_pending(&AppRequest::Sync) {
+                <p class="syncing-p"><span class="spinner"></span>{ "Syncing changes..." }</p>
+            }
+            if parts_maps.parts.len() != 0 {
+                <div id="projects-div">
+                    { self.render_create_project(ctx) }
+                    { self.render_tree(ctx) }
+                    if parts_maps.next_page(None).is_some() {
+                        <button
+                            onclick={ctx.link().callback(|_| AppMsg::LoadChildren(None))}
+                            disabled={self.loading.contains(&None)}
+                        >{ "Load more" }</button>
+                    }
+                </div>
+            } else if !parts_maps.children_fetched(None) {
+                // Failing to load is shown instead
+                if self.load_error.is_none() {
+                    <p class="loading-p"><span class="spinner"></span>{ "Loading projects..." }</p>
+                }
+            } else {
+                <p class="empty-p">
+                    { "No projects yet. Add one with the quick-add bar or New Project." }
+                </p>
+                { self.render_create_project(ctx) }
+            }
+            { self.render_shared(ctx) }
+            </>
+        }
+    }
+
+    // Renders the projects shared with the user, each in its own (small, so not virtualized) tree
+    fn render_shared(&self, ctx: &Context<Self>) -> Html {
+        if self.shared.is_empty() {
+            return html! {};
+        }
+        html! {
+            <div id="shared-div">
+                <h3>
+                    { "Shared With You" }
+                    <button onclick={ctx.link().callback(|_| AppMsg::RefreshShared)}>
+                        { "Refresh" }
+                    </button>
+                </h3>
+                {
+                    self.shared.iter().map(|tree| {
+                        let m = &tree.membership;
+                        let rows = tree.parts_maps.borrow().visible_rows();
+                        html! {
+                            <div key={format!("{}:{}", m.owner, m.project_id)} class="shared-project">
+                                <p class="shared-owner">
+                                    { format!("Shared by {} ({})", m.owner, m.role) }
+                                </p>
+                                <ul class="parts-list">
+                                {
+                                    rows.into_iter().filter_map(|row| match row {
+                                        TreeRow::Part { id, depth } => Some(html! {
+                                            <PartComponent
+                                                key={id}
+                                                id={id}
+                                                depth={depth}
+                                                parts_maps={Rc::clone(&tree.parts_maps)}
+                                                parts_rev={self.parts_rev}
+                                                jwt={Rc::clone(&self.jwt)}
+                                                send_to_app={Rc::clone(&self.send_to_app)}
+                                                shared={Rc::clone(&tree.membership)}
+                                            />
+                                        }),
+                                        // Shared projects are fetched whole
+                                        TreeRow::More { .. } => None,
+                                    }).collect::<Html>()
+                                }
+                                </ul>
+                            </div>
+                        }
+                    }).collect::<Html>()
+                }
+            </div>
+        }
+    }
+
+    // Renders the rows of the part tree in (or near) the viewport, along with the focused one so
+    // it keeps its state and can carry out commands
+    fn render_tree(&self, ctx: &Context<Self>) -> Html {
+        let (rows, slots) = self.tree_slots();
+        let mut spacers = 0;
+        let slots = slots.into_iter().map(|slot| match slot {
+            // Keyed like the rows, since yew matches unkeyed children by position and would
+            // otherwise reuse the row components for other parts as spacers come and go
+            Slot::Spacer(height) => {
+                spacers += 1;
+                html! {
+                    <li
+                        key={format!("spacer-{}", spacers)}
+                        class="tree-spacer"
+                        style={format!("height: {}px", height)}
+                    ></li>
+                }
+            }
+            Slot::Row(i) => match rows[i] {
+                TreeRow::Part { id, depth } => html! {
+                    <PartComponent
+                        key={id}
+                        id={id}
+                        depth={depth}
+                        parts_maps={Rc::clone(&self.parts_maps)}
+                        parts_rev={self.parts_rev}
+                        jwt={Rc::clone(&self.jwt)}
+                        send_to_app={Rc::clone(&self.send_to_app)}
+                    />
+                },
+                TreeRow::More { parent_id, depth } => html! {
+                    <li
+                        key={format!("more-{}", parent_id)}
+                        class="tree-row"
+                        style={format!("padding-left: {}px", depth * TREE_INDENT)}
+                    >
+                        if self.loading.contains(&Some(parent_id))
+                            || self.pm().has_unfetched_children(parent_id)
+                        {
+                            { "Loading..." }
+                        } else {
+                            <button onclick={
+                                ctx.link().callback(move |_| AppMsg::LoadChildren(Some(parent_id)))
+                            }>{ "Load more" }</button>
+                        }
+                    </li>
+                },
+            },
+        });
+        html! {
+            <ul class="parts-list" ref={self.tree_list_ref.clone()}>
+                { for slots }
+            </ul>
+        }
+    }
+
+    // Returns the rows of the part tree and which of them to render
+    fn tree_slots(&self) -> (Vec<TreeRow>, Vec<Slot>) {
+        let parts_maps = self.pm();
+        let rows = parts_maps.visible_rows();
+        let (top, bottom) = self.tree_viewport;
+        let window = self.tree_list.window(&rows, top, bottom);
+        let pinned = parts_maps
+            .focused
+            .iter()
+            .chain(&parts_maps.pending)
+            .filter_map(|&id| {
+                rows.iter()
+                    .position(|row| matches!(row, TreeRow::Part { id: rid, .. } if *rid == id))
+            })
+            .collect::<Vec<_>>();
+        let slots = self.tree_list.slots(&rows, window, &pinned);
+        (rows, slots)
+    }
+
+    // Records the heights of the rendered part tree rows, checking whether the window changed
+    fn measure_tree(&mut self, ctx: &Context<Self>) {
+        let Some(list) = self.tree_list_ref.cast::<Element>() else {
+            return;
+        };
+        let (rows, slots) = self.tree_slots();
+        let children = list.children();
+        for (slot, i) in slots.into_iter().zip(0..) {
+            let Some(elem) = children.item(i) else {
+                break;
+            };
+            if let Slot::Row(row) = slot {
+                let height = elem.get_bounding_client_rect().height();
+                self.tree_list.measure(rows[row], height);
+            }
+        }
+        self.tree_list.retain(&rows);
+        let (top, bottom) = self.tree_viewport;
+        self.tree_window = self.tree_list.window(&rows, top, bottom);
+        ctx.link().send_message(AppMsg::TreeScrolled);
+    }
+
+    fn render_assigned(&self, ctx: &Context<Self>) -> Html {
+        let Some(assigned) = self.assigned.as_ref() else {
+            return html! { <p>{ "Loading..." }</p> };
+        };
+        let own = self.pm();
+        let mine = |owner: &str| self.user.is_none() || self.user.as_deref() == Some(owner);
+        html! {
+            <div id="assigned-div">
+                <h3>{ "Assigned to Me" }</h3>
+                if assigned.is_empty() {
+                    <p>{ "Nothing is assigned to you" }</p>
+                } else {
+                    <table>
+                        <tr>
+                            <th>{ "Part" }</th>
+                            <th>{ "Owner" }</th>
+                            <th>{ "Deadline" }</th>
+                            <th>{ "Completed At" }</th>
+                        </tr>
+                        {
+                            assigned.iter().map(|AssignedPart { owner, part }| {
+                                let id = part.id;
+                                // Own parts can be gone to if they've been fetched
+                                let shown = mine(owner) && own.parts.contains_key(&id);
+                                let path = if shown {
+                                    time_tracking::part_path(&own.parts, id)
+                                } else {
+                                    part.name.clone()
+                                };
+                                let overdue = part.completed_at.is_none()
+                                    && part.deadline.is_some_and(|dt| dt < Local::now());
+                                html! {
+                                    <tr key={format!("{}:{}", owner, id)}>
+                                        <td>
+                                            if shown {
+                                                <a
+                                                    href="#"
+                                                    onclick={ctx.link().callback(move |e: MouseEvent| {
+                                                        e.prevent_default();
+                                                        AppMsg::GoToPart(id)
+                                                    })}
+                                                >{ path }</a>
+                                            } else {
+                                                { path }
+                                            }
+                                        </td>
+                                        <td>{ if mine(owner) { "You" } else { owner.as_str() } }</td>
+                                        <td class={classes!(overdue.then_some("overdue"))}>
+                                            { part.deadline_to_string() }
+                                        </td>
+                                        <td>{ part.completed_at_to_string() }</td>
+                                    </tr>
+                                }
+                            }).collect::<Html>()
+                        }
+                    </table>
+                }
+            </div>
+        }
+    }
+
+    fn render_trash(&self, ctx: &Context<Self>) -> Html {
+        let Some(trash) = self.trash.as_ref() else {
+            return html! { <p>{ "Loading..." }</p> };
+        };
+        let parts_maps = self.pm();
+        html! {
+            <div id="trash-div">
+                <h3>{ "Trash" }</h3>
+                if trash.is_empty() {
+                    <p>{ "The trash is empty" }</p>
+                } else {
+                    <table>
+                        <tr>
+                            <th>{ "Part" }</th>
+                            <th>{ "Was In" }</th>
+                            <th>{ "Children" }</th>
+                            <th>{ "Deleted At" }</th>
+                            <th></th>
+                        </tr>
+                        {
+                            trash.iter().map(|TrashedPart { part, trashed_at }| {
+                                let id = part.id;
+                                // The parent may be in the trash itself (or not fetched yet)
+                                let was_in = match part.parent_id {
+                                    Some(pid) if parts_maps.parts.contains_key(&pid) => {
+                                        time_tracking::part_path(&parts_maps.parts, pid)
+                                    }
+                                    Some(_) => String::from("(deleted or not loaded)"),
+                                    None => String::from("(project)"),
+                                };
+                                let pending = self.requests.is_pending(&AppRequest::Restore(id))
+                                    || self.requests.is_pending(&AppRequest::Purge(id));
+                                html! {
+                                    <tr key={id} class={classes!(pending.then_some("pending"))}>
+                                        <td>{ &part.name }</td>
+                                        <td>{ was_in }</td>
+                                        <td>{ part.child_count }</td>
+                                        <td>{ trashed_at.format("%H:%M %b %d, %Y").to_string() }</td>
+                                        <td>
+                                            <button
+                                                onclick={ctx.link().callback(move |_| {
+                                                    AppMsg::RestorePart(id)
+                                                })}
+                                                disabled={pending}
+                                            >{ "Restore" }</button>
+                                            <button
+                                                class="delete-button"
+                                                onclick={ctx.link().callback(move |_| {
+                                                    AppMsg::PurgePart(id)
+                                                })}
+                                                disabled={pending}
+                                            >{ "Delete Forever" }</button>
+                                        </td>
+                                    </tr>
+                                }
+                            }).collect::<Html>()
+                        }
+                    </table>
+                }
+            </div>
+        }
+    }
+
+    fn render_stats(&self, ctx: &Context<Self>) -> Html {
+        let Some(stats) = self.stats.as_ref() else {
+            return html! {
+                <div id="stats-div">
+                    <h3>{ "Stats" }</h3>
+                    if self.requests.is_pending(&AppRequest::Stats) {
+                        <p class="loading-p"><span class="spinner"></span>{ "Loading the stats..." }</p>
+                    }
+                </div>
+            };
+        };
+        let weekly = stats
+            .weekly
+            .iter()
+            .map(|(week, count)| (week.format("%b %d").to_string(), *count))
+            .collect::<Vec<_>>();
+        let project_id = self.burndown_project();
+        let burndown = self
+            .burndown
+            .as_ref()
+            .filter(|(id, _)| Some(*id) == project_id)
+            .map(|(_, burndown)| burndown);
+        let select_project = ctx.link().batch_callback(|e: Event| {
+            let select = e.target_dyn_into::<HtmlSelectElement>()?;
+            Some(AppMsg::SetStatsProject(select.value().parse().ok()))
+        });
+        html! {
+            <div id="stats-div">
+                <h3>{ "Stats" }</h3>
+                <h4>{ format!("Completed per Week (last {} weeks)", STATS_WEEKS) }</h4>
+                { stats::bar_chart(&weekly) }
+                <h4>{ "Deadlines" }</h4>
+                <table>
+                    <tr>
+                        <th>{ "Completed On Time" }</th>
+                        <td>{ stats.lateness.on_time }</td>
+                    </tr>
+                    <tr>
+                        <th>{ "Completed Late" }</th>
+                        <td>{ stats.lateness.late }</td>
+                    </tr>
+                    <tr>
+                        <th>{ "On-Time Rate" }</th>
+                        <td>{
+                            stats.lateness.on_time_rate()
+                                .map(|rate| format!("{:.0}%", rate * 100.0))
+                                .unwrap_or_else(|| String::from("-"))
+                        }</td>
+                    </tr>
+                    <tr>
+                        <th>{ "Average Lateness" }</th>
+                        <td>{
+                            stats.lateness.average()
+                                .map(stats::format_lateness)
+                                .unwrap_or_else(|| String::from("-"))
+                        }</td>
+                    </tr>
+                </table>
+                <h4>{ "Open Work per Project" }</h4>
+                if stats.open_work.is_empty() {
+                    <p>{ "No projects" }</p>
+                } else {
+                    <table>
+                        <tr>
+                            <th>{ "Project" }</th>
+                            <th>{ "Open" }</th>
+                            <th>{ "Total" }</th>
+                        </tr>
+                        {
+                            stats.open_work.iter().map(|work| html! {
+                                <tr key={work.id}>
+                                    <td>{ &work.name }</td>
+                                    <td>{ work.open }</td>
+                                    <td>{ work.total }</td>
+                                </tr>
+                            }).collect::<Html>()
+                        }
+                    </table>
+                    <h4>
+                        { "Burndown for " }
+                        <select onchange={select_project}>
+                            {
+                                stats.open_work.iter().map(|work| html! {
+                                    <option
+                                        value={work.id.to_string()}
+                                        selected={Some(work.id) == project_id}
+                                    >{ &work.name }</option>
+                                }).collect::<Html>()
+                            }
+                        </select>
+                    </h4>
+                    if let Some(burndown) = burndown {
+                        if burndown.total == 0 {
+                            <p>{ "The project has no parts to burn down" }</p>
+                        } else {
+                            { stats::burndown_chart(burndown) }
+                        }
+                    }
+                }
             </div>
         }
     }
 
-    // TODO: Rename?
-    fn render_controls(&self, ctx: &Context<Self>) -> Html {
-        let change_sort = ctx.link().batch_callback(|e: Event| {
-            let Some(select) = e.target_dyn_into::<HtmlSelectElement>() else {
-                console::log!("missing or invalid sort select element");
-                return None;
-            };
-            match select.value().as_str() {
-                "id" => Some(AppMsg::Sort(SortMethod::Id)),
-                "name" => Some(AppMsg::Sort(SortMethod::Name)),
-                "deadline" => Some(AppMsg::Sort(SortMethod::Deadline)),
-                "completedAt" => Some(AppMsg::Sort(SortMethod::CompletedAt)),
-                val => {
-                    console::log!("invalid select value: {}", val);
-                    None
-                }
+    fn render_account(&self, ctx: &Context<Self>) -> Html {
+        // The forms are cleared once submitted so the passwords aren't left in them
+        let change_password = ctx.link().batch_callback(|e: SubmitEvent| {
+            e.prevent_default();
+            let form: HtmlFormElement = e.target_unchecked_into();
+            let elems = form.elements();
+            let current = elems.get_with_name("current").and_then(value_from_input)?;
+            let new = elems.get_with_name("new").and_then(value_from_input)?;
+            let confirm = elems.get_with_name("confirm").and_then(value_from_input)?;
+            form.reset();
+            if let Some(error) = validation::new_password_error(&new) {
+                return Some(AppMsg::ErrorMsg(format!("New password: {}", error)));
             }
+            if new != confirm {
+                return Some(AppMsg::ErrorMsg(String::from(
+                    "The new passwords don't match",
+                )));
+            }
+            Some(AppMsg::ChangePassword { current, new })
         });
-
-        let dropdown = self.filter_dd_div_ref.clone();
-        let show_filter = ctx.link().batch_callback(move |_| {
-            let elem = dropdown.cast::<HtmlElement>().unwrap();
-            elem.set_hidden(!elem.hidden());
-            None
+        let change_email = ctx.link().batch_callback(|e: SubmitEvent| {
+            e.prevent_default();
+            let form: HtmlFormElement = e.target_unchecked_into();
+            let elems = form.elements();
+            let new_email = elems.get_with_name("email").and_then(value_from_input)?;
+            let password = elems.get_with_name("password").and_then(value_from_input)?;
+            form.reset();
+            if let Some(error) = validation::email_error(new_email.trim()) {
+                return Some(AppMsg::ErrorMsg(String::from(error)));
+            }
+            Some(AppMsg::ChangeEmail {
+                password,
+                new_email: new_email.trim().to_string(),
+            })
+        });
+        let delete_account = ctx.link().batch_callback(|e: SubmitEvent| {
+            e.prevent_default();
+            let form: HtmlFormElement = e.target_unchecked_into();
+            let password = form
+                .elements()
+                .get_with_name("password")
+                .and_then(value_from_input)?;
+            form.reset();
+            Some(AppMsg::DeleteAccount(password))
         });
-
-        let toggle_creating = ctx.link().callback(|_| AppMsg::ToggleCreating);
         html! {
-            <div id="top-controls-div">
-                <button onclick={toggle_creating}>{ "New Project" }</button>
-                <input type="text" placeholder="Search" />
-
-                <label for="sort">{ "Sort" }</label>
-                <select onchange={change_sort}>
-                    <option value="id" selected=true>{ "Added" }</option>
-                    <option value="name">{ "Name" }</option>
-                    <option value="deadline">{ "Deadline" }</option>
-                    <option value="completedAt">{ "Completed At" }</option>
-                </select>
-                <div style="float:left; overflow:hidden">
-                    <button onclick={show_filter}>{ "Filter" }</button>
-                    { self.render_filter_popup(ctx) }
-                </div>
+            <div id="account-div">
+                <h3>{ "Account" }</h3>
+                <p>{ format!("Logged in as {}", self.user.as_deref().unwrap_or("(unknown)")) }</p>
+                <form onsubmit={change_password}>
+                    <h4>{ "Change Password" }</h4>
+                    <input
+                        type="password"
+                        name="current"
+                        placeholder="Current Password"
+                        required=true
+                    /><br />
+                    <input type="password" name="new" placeholder="New Password" required=true /><br />
+                    <input
+                        type="password"
+                        name="confirm"
+                        placeholder="Confirm New Password"
+                        required=true
+                    /><br />
+                    { submit_button(
+                        "Change Password",
+                        None,
+                        self.requests.is_pending(&AppRequest::ChangePassword),
+                    ) }
+                </form>
+                <form onsubmit={change_email}>
+                    <h4>{ "Change Email" }</h4>
+                    <input type="email" name="email" placeholder="New Email" required=true /><br />
+                    <input type="password" name="password" placeholder="Password" required=true /><br />
+                    { submit_button(
+                        "Change Email",
+                        None,
+                        self.requests.is_pending(&AppRequest::ChangeEmail),
+                    ) }
+                </form>
+                <form onsubmit={delete_account}>
+                    <h4>{ "Delete Account" }</h4>
+                    <p>{ "Permanently deletes your account and all your projects. \
+                        Projects you've shared stop being shared." }</p>
+                    <input type="password" name="password" placeholder="Password" required=true /><br />
+                    { submit_button(
+                        "Delete Account",
+                        Some("delete-button"),
+                        self.requests.is_pending(&AppRequest::DeleteAccount),
+                    ) }
+                </form>
             </div>
         }
     }
 
-    fn render_projects(&self, ctx: &Context<Self>) -> Html {
+    fn render_time_report(&self, ctx: &Context<Self>) -> Html {
         let parts_maps = self.pm();
+        let report = WeekReport::new(
+            self.report_week,
+            parts_maps.time_entries.values().flatten(),
+            &parts_maps.parts,
+            Local::now(),
+        );
+
+        let week = self.report_week;
+        let prev_week = ctx
+            .link()
+            .callback(move |_| AppMsg::SetReportWeek(week - chrono::Duration::weeks(1)));
+        let next_week = ctx
+            .link()
+            .callback(move |_| AppMsg::SetReportWeek(week + chrono::Duration::weeks(1)));
+        let csv = report.to_csv();
+        let export_csv = ctx.link().batch_callback(move |_| {
+            let filename = format!("time-report-{}.csv", week.format("%Y-%m-%d"));
+            if let Err(e) = download_file(&filename, "text/csv", &csv) {
+                console::log!("error exporting csv: {:?}", e);
+            }
+            None
+        });
         html! {
-            if parts_maps.parts.len() != 0 {
-                <div id="projects-div">
-                    <ul class="parts-list">
-                        { self.render_create_project(ctx) }
+            <div id="time-report-div">
+                <button onclick={prev_week}>{ "<" }</button>
+                <span>{ format!("Week of {}", week.format("%b %d, %Y")) }</span>
+                <button onclick={next_week}>{ ">" }</button>
+                <button onclick={export_csv}>{ "Export CSV" }</button>
+                if report.rows.is_empty() {
+                    <p>{ "No time logged this week" }</p>
+                } else {
+                    <table>
+                        <tr>
+                            <th>{ "Part" }</th>
+                            { report.day_headers().into_iter().map(|h| html! { <th>{ h }</th> }).collect::<Html>() }
+                            <th>{ "Total" }</th>
+                        </tr>
                         {
-                            parts_maps.projects.iter()
-                                .map(|&id| {
-                                    html! {
-                                        <PartComponent
-                                            key={id}
-                                            id={id}
-                                            parts_maps={Rc::clone(&self.parts_maps)}
-                                            jwt={Rc::clone(&self.jwt)}
-                                            send_to_app={Rc::clone(&self.send_to_app)}
-                                        />
+                            report.rows.iter().map(|row| html! {
+                                <tr key={row.part_id}>
+                                    <td>{ &row.path }</td>
+                                    {
+                                        row.days.iter().map(|&d| html! {
+                                            <td>{ time_tracking::format_duration(d) }</td>
+                                        }).collect::<Html>()
                                     }
-                                }).collect::<Html>()
+                                    <td>{ time_tracking::format_duration(row.total()) }</td>
+                                </tr>
+                            }).collect::<Html>()
                         }
-                    </ul>
-                </div>
-            } else {
-                <p>{ "No projects" }</p>
-                { self.render_create_project(ctx) }
-            }
+                        <tr>
+                            <th>{ "Total" }</th>
+                            {
+                                report.day_totals().iter().map(|&d| html! {
+                                    <th>{ time_tracking::format_duration(d) }</th>
+                                }).collect::<Html>()
+                            }
+                            <th>{ time_tracking::format_duration(report.total()) }</th>
+                        </tr>
+                    </table>
+                }
+            </div>
         }
     }
 
@@ -731,97 +3614,684 @@ impl App {
 
     fn render_create_project(&self, ctx: &Context<Self>) -> Html {
         let toggle_creating = ctx.link().callback(|_| AppMsg::ToggleCreating);
+        let send_create = ctx
+            .link()
+            .callback(|draft: PartDraft| AppMsg::SendCreatePart(draft.into_part()));
+        html! {
+            if self.creating {
+                <PartForm
+                    parents={self.pm().parent_options(None)}
+                    assignees={self.pm().assignees(None)}
+                    submit_label="Create"
+                    onsubmit={send_create}
+                    oncancel={toggle_creating}
+                />
+            }
+        }
+    }
 
-        let create_form_ref = self.create_form_ref.clone();
-        let send_create = ctx.link().batch_callback(move |_| {
-            let form = create_form_ref.cast::<HtmlFormElement>().unwrap();
-            let elems = form.elements();
-            let mut part = Part::default();
-
-            if let Some(name) = elems.get_with_name("part-name").and_then(value_from_input) {
-                if name.trim() == "" {
-                    // TODO: Display error
-                    return None;
+    // Moves the keyboard focus in the part tree or has the focused part's component carry out a
+    // command
+    fn navigate(&mut self, key: NavKey) -> bool {
+        if self.view != MainView::Projects || self.palette.is_some() {
+            return false;
+        }
+        let mut parts_maps = self.pm_mut();
+        let visible = parts_maps.visible_ids();
+        let pos = parts_maps
+            .focused
+            .and_then(|id| visible.iter().position(|&vid| vid == id));
+        let focused = match (key, pos) {
+            (NavKey::Blur, _) => None,
+            (NavKey::First, _) | (NavKey::Down, None) => visible.first().copied(),
+            (NavKey::Last, _) | (NavKey::Up, None) => visible.last().copied(),
+            (_, None) => return false,
+            (NavKey::Down, Some(i)) => visible.get(i + 1).or(visible.get(i)).copied(),
+            (NavKey::Up, Some(i)) => Some(visible[i.saturating_sub(1)]),
+            (NavKey::Expand, Some(i)) => {
+                let id = visible[i];
+                if !parts_maps.has_visible_children(id) {
+                    return false;
                 }
-                part.name = name;
-            } else {
-                console::log!("missing or invalid name input element");
-                return None;
-            };
-            if let Some(desc) = elems.get_with_name("part-desc").and_then(value_from_area) {
-                if desc.trim() != "" {
-                    part.description = Some(desc);
+                if parts_maps.tree.expanded.insert(id) {
+                    Some(id)
+                } else {
+                    // The first child is displayed right after the part
+                    visible.get(i + 1).copied()
                 }
-            } else {
-                console::log!("missing or invalid description text area element");
-                return None;
-            };
-            if let Some(dtl) = elems
-                .get_with_name("part-deadline")
-                .and_then(dtl_from_input)
-            {
-                // TODO: Do better
-                if dtl != DateTime::<Local>::default() {
-                    part.deadline = Some(dtl);
+            }
+            (NavKey::Collapse, Some(i)) => {
+                let id = visible[i];
+                if parts_maps.tree.expanded.remove(&id) {
+                    Some(id)
+                } else {
+                    parts_maps.parts[&id].parent_id.or(Some(id))
                 }
-            } else {
-                console::log!("missing or invalid deadline input element");
-                return None;
-            };
-            if let Some(dtl) = elems.get_with_name("part-comp-at").and_then(dtl_from_input) {
-                // TODO: Do better
-                if dtl != DateTime::<Local>::default() {
-                    part.completed_at = Some(dtl);
+            }
+            (NavKey::Part(command), Some(i)) => {
+                parts_maps.command = Some(command);
+                Some(visible[i])
+            }
+        };
+        let moved = parts_maps.focused != focused;
+        parts_maps.focused = focused;
+        drop(parts_maps);
+        self.scroll_to_focus = moved;
+        self.parts_rev += 1;
+        true
+    }
+
+    // Returns the commands offered by the command palette and their labels. Every part can be
+    // jumped to by its path.
+    fn palette_commands(&self) -> (Vec<PaletteCommand>, Rc<Vec<String>>) {
+        let mut actions = vec![
+            (PaletteCommand::NewProject, "New project"),
+            (PaletteCommand::Undo, "Undo"),
+            (PaletteCommand::Redo, "Redo"),
+            (
+                PaletteCommand::ToggleHistory,
+                if self.show_history {
+                    "Hide history"
+                } else {
+                    "Show history"
+                },
+            ),
+        ];
+        actions.extend(
+            [
+                (
+                    MainView::Projects,
+                    PaletteCommand::ShowProjects,
+                    "Show projects",
+                ),
+                (
+                    MainView::TimeReport,
+                    PaletteCommand::ShowTimeReport,
+                    "Show time report",
+                ),
+                (
+                    MainView::Assigned,
+                    PaletteCommand::ShowAssigned,
+                    "Show assigned to me",
+                ),
+                (MainView::Trash, PaletteCommand::ShowTrash, "Show trash"),
+                (MainView::Stats, PaletteCommand::ShowStats, "Show stats"),
+                (
+                    MainView::Account,
+                    PaletteCommand::ShowAccount,
+                    "Show account",
+                ),
+            ]
+            .into_iter()
+            .filter(|&(view, _, _)| view != self.view)
+            .map(|(_, command, label)| (command, label)),
+        );
+        let parts_maps = self.pm();
+        if !parts_maps.selected.is_empty() {
+            actions.push((PaletteCommand::ClearSelection, "Clear selection"));
+        }
+        actions.push((PaletteCommand::ExpandAll, "Expand all"));
+        actions.push((PaletteCommand::CollapseAll, "Collapse all"));
+        actions.push((PaletteCommand::Logout, "Logout"));
+        let (mut commands, mut labels): (Vec<_>, Vec<_>) = actions
+            .into_iter()
+            .map(|(command, label)| (command, String::from(label)))
+            .unzip();
+
+        let mut paths = parts_maps
+            .parts
+            .keys()
+            .map(|&id| (id, time_tracking::part_path(&parts_maps.parts, id)))
+            .collect::<Vec<_>>();
+        paths.sort_by_cached_key(|(_, path)| path.to_lowercase());
+        for (id, path) in paths {
+            commands.push(PaletteCommand::GoToPart(id));
+            labels.push(path);
+        }
+        (commands, Rc::new(labels))
+    }
+
+    fn set_view(&mut self, ctx: &Context<Self>, view: MainView) {
+        // The assignments may have changed in any project, so they're fetched each time
+        if view == MainView::Assigned {
+            let res = get_assigned_parts(self.jwt.to_string());
+            ctx.link().send_future(res.map(AppMsg::GetAssigned));
+        }
+        // So is the trash, which parts are deleted into from anywhere
+        if view == MainView::Trash {
+            let res = get_trash(self.jwt.to_string());
+            ctx.link().send_future(res.map(AppMsg::GetTrash));
+        }
+        // The stats are computed by the server, which has all the parts
+        if view == MainView::Stats {
+            let since = Stats::since(STATS_WEEKS, Local::now());
+            let res = get_stats(self.jwt.to_string(), since);
+            self.requests
+                .send(ctx.link(), AppRequest::Stats, res.map(AppMsg::GotStats));
+        }
+        self.pm_mut().linked = None;
+        self.view = view;
+    }
+
+    // Shows the part in the project tree, expanding its ancestors, and focuses and highlights it
+    fn go_to_part(&mut self, id: i64) {
+        self.view = MainView::Projects;
+        let mut parts_maps = self.pm_mut();
+        parts_maps.reveal(id);
+        parts_maps.linked = Some(id);
+        let shown = parts_maps.visible_ids().contains(&id);
+        if shown {
+            parts_maps.focused = Some(id);
+        }
+        drop(parts_maps);
+        if shown {
+            self.scroll_to_focus = true;
+        } else {
+            self.toast(Toast::warning("The part is hidden by the current filter"));
+        }
+        self.parts_rev += 1;
+    }
+
+    // Returns the route of what's shown
+    fn current_route(&self) -> Route {
+        if let Some(token) = self.reset_token.as_ref() {
+            return Route::Reset(token.clone());
+        }
+        if self.jwt.is_empty() {
+            return Route::Login(self.after_login.clone().map(Box::new));
+        }
+        match self.view {
+            MainView::Projects => self.pm().linked.map_or(Route::Projects, Route::Part),
+            MainView::Account => Route::Settings,
+            view => Route::View(view),
+        }
+    }
+
+    // Shows what's at the route, or the login if it's behind it and the user isn't logged in
+    fn apply_route(&mut self, ctx: &Context<Self>, route: Route) {
+        self.reset_token = None;
+        match route {
+            Route::Reset(token) => self.reset_token = Some(token),
+            Route::Login(next) => {
+                self.after_login = next.map(|next| *next);
+                if !self.jwt.is_empty() {
+                    self.follow_after_login(ctx);
                 }
-            } else {
-                console::log!("missing or invalid comp-at input element");
-                return None;
-            };
-            Some(AppMsg::SendCreatePart(part))
-        });
-        html! {
-            <>
-            if self.creating {
-                // TODO: Make more reusable (see other instances of this form
-                <button onclick={send_create} style="margin:10px">{ "Create" }</button>
-                <button onclick={toggle_creating}>{ "Cancel" }</button>
-                <form ref={self.create_form_ref.clone()}>
-                    <label for="part-name"><u>{ "Name" }</u>{ ": " }</label>
-                    <input
-                        name="part-name"
-                        type="text"
-                        placeholder="Name"
-                        required=true
-                    /><br />
+            }
+            route if self.jwt.is_empty() => self.after_login = Some(route),
+            Route::Projects => self.set_view(ctx, MainView::Projects),
+            Route::Part(id) => {
+                self.set_view(ctx, MainView::Projects);
+                self.pm_mut().linked = Some(id);
+                self.link_pending = true;
+                // It may be under parts that haven't been fetched yet
+                if self.pm().parts.contains_key(&id) {
+                    self.show_linked_part();
+                } else {
+                    self.load_part_tree(ctx, id, AppMsg::ShowLinkedPart);
+                }
+            }
+            Route::View(view) => self.set_view(ctx, view),
+            Route::Settings => self.set_view(ctx, MainView::Account),
+        }
+    }
 
-                    <label for="part-desc"><u>{ "Description" }</u>{ ":" }</label><br />
-                    <textarea name="part-desc" placeholder="Description">
-                    </textarea>
-                    <br />
+    // Goes where the user was sent to the login from, now that they're logged in
+    fn follow_after_login(&mut self, ctx: &Context<Self>) {
+        if let Some(route) = self.after_login.take() {
+            self.apply_route(ctx, route);
+        }
+    }
 
-                    <label for="part-deadline"><u>{ "Deadline" }</u>{ ": " }</label>
-                    <input
-                        name="part-deadline"
-                        type="datetime-local"
-                    /><br />
+    // Reveals the linked part if it's waiting to be (once it's been fetched)
+    fn show_linked_part(&mut self) {
+        let Some(id) = self.pm().linked.filter(|_| self.link_pending) else {
+            return;
+        };
+        self.link_pending = false;
+        if !self.pm().parts.contains_key(&id) {
+            self.toast(Toast::warning("The linked part doesn't exist"));
+            return;
+        }
+        self.go_to_part(id);
+    }
 
-                    <label for="part-comp-at"><u>{ "Completed At" }</u>{ ": " }</label>
-                    <input
-                        name="part-comp-at"
-                        type="datetime-local"
-                    /><br />
-                </form>
+    // Returns the project whose burndown is shown, which defaults to the one with the most open
+    // work
+    fn burndown_project(&self) -> Option<i64> {
+        let stats = self.stats.as_ref()?;
+        self.stats_project
+            .filter(|id| stats.open_work.iter().any(|work| work.id == *id))
+            .or_else(|| stats.open_work.first().map(|work| work.id))
+    }
+
+    // Fetches the burndown of the project shown unless it has been
+    fn load_burndown(&mut self, ctx: &Context<Self>) {
+        let Some(id) = self.burndown_project() else {
+            return;
+        };
+        if self.burndown.as_ref().is_some_and(|(bid, _)| *bid == id) {
+            return;
+        }
+        // Only the last project picked's is wanted
+        self.requests.cancel(&AppRequest::Burndown);
+        let res = get_burndown(self.jwt.to_string(), id);
+        self.requests.send(
+            ctx.link(),
+            AppRequest::Burndown,
+            res.map(move |res| AppMsg::GotBurndown(id, res)),
+        );
+    }
+
+    // Fetches the parts with the given ids that haven't been (or aren't being) fetched
+    fn load_parts_by_id(&mut self, ctx: &Context<Self>, ids: BTreeSet<i64>) {
+        let parts_maps = self.pm();
+        let ids = ids
+            .into_iter()
+            .filter(|id| !parts_maps.parts.contains_key(id) && !self.loading_parts.contains(id))
+            .collect::<BTreeSet<_>>();
+        drop(parts_maps);
+        if ids.is_empty() {
+            return;
+        }
+        self.loading_parts.extend(&ids);
+        let res = get_parts_by_id(self.jwt.to_string(), ids.iter().copied().collect());
+        ctx.link()
+            .send_future(res.map(move |res| AppMsg::GotParts(ids, res)));
+    }
+
+    // Whether a part is actionable depends on its blockers, which may be anywhere in the tree, so
+    // those of the fetched parts are fetched while filtering by it
+    fn load_filtered_blockers(&mut self, ctx: &Context<Self>) {
+        let parts_maps = self.pm();
+        if !parts_maps.filter_method.actionable {
+            return;
+        }
+        let ids = parts_maps.missing_blockers(parts_maps.parts.keys().copied());
+        drop(parts_maps);
+        self.load_parts_by_id(ctx, ids);
+    }
+
+    // Saves the subtree of the part with the given id as a template or duplicates it, fetching
+    // it first unless it's all been fetched
+    fn with_subtree(&mut self, ctx: &Context<Self>, id: i64, subtree_use: SubtreeUse) -> bool {
+        let parts_maps = Rc::clone(&self.parts_maps);
+        let parts_maps = parts_maps.borrow();
+        if parts_maps.tree_loaded(id) {
+            return self.use_subtree(ctx, id, subtree_use, &parts_maps.parts);
+        }
+        drop(parts_maps);
+        let res = get_part_tree(self.jwt.to_string(), id);
+        self.requests.send(
+            ctx.link(),
+            AppRequest::Subtree(id),
+            res.map(move |res| AppMsg::GotSubtree(id, subtree_use, res)),
+        );
+        false
+    }
+
+    // Saves the subtree of the part with the given id (from the parts, which include it) as a
+    // template, asking for its name, or duplicates it
+    fn use_subtree(
+        &mut self,
+        ctx: &Context<Self>,
+        id: i64,
+        subtree_use: SubtreeUse,
+        parts: &PartsMap,
+    ) -> bool {
+        let Some(part) = parts.get(&id) else {
+            return false;
+        };
+        match subtree_use {
+            SubtreeUse::Template => {
+                let Some(template) = Template::from_subtree(part.name.clone(), parts, id) else {
+                    return false;
+                };
+                let template = Rc::new(template);
+                self.confirm = Some(Confirm::prompt(
+                    "Template name",
+                    "Save",
+                    part.name.clone(),
+                    ctx.link()
+                        .callback(move |name| AppMsg::SaveTemplateAs(Rc::clone(&template), name)),
+                ));
+                true
+            }
+            SubtreeUse::Duplicate => {
+                let name = format!("{} (copy)", part.name);
+                let parent_id = part.parent_id;
+                let Some(mut template) = Template::from_subtree(name, parts, id) else {
+                    return false;
+                };
+                template.root.name = template.name.clone();
+                // Anchored at its own reference date, the copy has the same deadlines
+                let anchor = templates::reference_date(parts, id).unwrap_or_else(Local::now);
+                let label = format!("Duplicate \"{}\"", template.name);
+                let res = templates::create(
+                    self.jwt.to_string(),
+                    parent_id,
+                    template.instantiate(anchor),
+                );
+                self.requests.send(
+                    ctx.link(),
+                    AppRequest::Instantiate,
+                    res.map(move |res| AppMsg::Instantiated(label, res)),
+                )
+            }
+        }
+    }
+
+    // Fetches the part along with its ancestors and all its descendants, sending the message once
+    // they have been
+    fn load_part_tree(&mut self, ctx: &Context<Self>, id: i64, then: AppMsg) {
+        self.after_tree_loaded.entry(id).or_default().push(then);
+        let res = get_part_tree(self.jwt.to_string(), id);
+        self.requests.send(
+            ctx.link(),
+            AppRequest::PartTree(id),
+            res.map(move |res| AppMsg::GotPartTree(id, res)),
+        );
+    }
+
+    fn run_command(&mut self, ctx: &Context<Self>, command: PaletteCommand) {
+        match command {
+            PaletteCommand::NewProject => {
+                self.set_view(ctx, MainView::Projects);
+                self.creating = true;
+            }
+            PaletteCommand::Undo => self.start_history(ctx, true),
+            PaletteCommand::Redo => self.start_history(ctx, false),
+            PaletteCommand::ToggleHistory => self.show_history = !self.show_history,
+            PaletteCommand::ShowProjects => self.set_view(ctx, MainView::Projects),
+            PaletteCommand::ShowTimeReport => self.set_view(ctx, MainView::TimeReport),
+            PaletteCommand::ShowAssigned => self.set_view(ctx, MainView::Assigned),
+            PaletteCommand::ShowTrash => self.set_view(ctx, MainView::Trash),
+            PaletteCommand::ShowStats => self.set_view(ctx, MainView::Stats),
+            PaletteCommand::ShowAccount => self.set_view(ctx, MainView::Account),
+            PaletteCommand::ClearSelection => ctx.link().send_message(AppMsg::ClearSelection),
+            PaletteCommand::ExpandAll => ctx.link().send_message(AppMsg::ExpandToDepth(None)),
+            PaletteCommand::CollapseAll => ctx.link().send_message(AppMsg::ExpandToDepth(Some(0))),
+            PaletteCommand::Logout => ctx.link().send_message(AppMsg::Logout),
+            PaletteCommand::GoToPart(id) => {
+                self.link_pending = false;
+                self.go_to_part(id);
             }
-            </>
         }
     }
 
-    // Displayed if unexpected Access denied is returned
-    fn display_err_logout_alert(&mut self) {
-        self.jwt = Rc::from("");
-        let _ = web_sys::window()
-            .expect("no window")
-            .alert_with_message("Unexpectedly logged out");
+    // Starts undoing (or redoing) the last entry in the history
+    fn start_history(&mut self, ctx: &Context<Self>, undo: bool) {
+        if self.jwt.is_empty() {
+            return;
+        }
+        if self.history.is_pending() {
+            self.toast(Toast::warning("Still applying the last undo/redo"));
+            return;
+        }
+        if let Some(fut) = self.history.start(self.jwt.to_string(), undo) {
+            ctx.link().send_future(fut.map(AppMsg::HistoryApplied));
+        }
+    }
+
+    // Sends the batch request for the action on the selected parts. Parts the action wouldn't
+    // change are left out.
+    fn send_batch(&mut self, ctx: &Context<Self>, action: BatchAction, confirmed: bool) {
+        if self.requests.is_pending(&AppRequest::Batch) {
+            self.toast(Toast::warning("Still applying the last batch action"));
+            return;
+        }
+        let parts_maps = self.pm();
+        let selected = parts_maps.selected.iter().copied();
+        let parts = &parts_maps.parts;
+        let (ids, field, value): (Vec<_>, _, _) = match action {
+            BatchAction::Complete => (
+                selected
+                    .filter(|id| parts[id].completed_at.is_none())
+                    .collect(),
+                "completed_at",
+                Local::now().timestamp().to_string(),
+            ),
+            BatchAction::Uncomplete => (
+                selected
+                    .filter(|id| parts[id].completed_at.is_some())
+                    .collect(),
+                "completed_at",
+                String::new(),
+            ),
+            BatchAction::SetDeadline(dt) => {
+                (selected.collect(), "deadline", dt.timestamp().to_string())
+            }
+            BatchAction::ClearDeadline => (
+                selected.filter(|id| parts[id].deadline.is_some()).collect(),
+                "deadline",
+                String::new(),
+            ),
+            BatchAction::Move(pid) => (
+                selected.filter(|id| parts[id].parent_id != pid).collect(),
+                "parent_id",
+                pid.map(|pid| pid.to_string()).unwrap_or_default(),
+            ),
+            BatchAction::Delete => {
+                // Children are deleted along with their parents
+                let ids = parts_maps.selected_roots();
+                let num_selected = parts_maps.selected.len();
+                drop(parts_maps);
+                if !confirmed {
+                    self.confirm = Some(Confirm::new(
+                        format!(
+                            "Move the {} selected projects/parts and ALL their children to the \
+                            trash?",
+                            num_selected,
+                        ),
+                        "Move to Trash",
+                        ctx.link().callback(move |_| AppMsg::BatchConfirmed(action)),
+                    ));
+                    return;
+                }
+                let res = send_batch_delete_parts(
+                    self.jwt.to_string(),
+                    BatchDeletePartsVars {
+                        ids: ids.into_iter().map(|id| id.to_string()).collect(),
+                    },
+                );
+                self.requests
+                    .send(ctx.link(), AppRequest::Batch, res.map(AppMsg::BatchDeleted));
+                return;
+            }
+        };
+        if ids.is_empty() {
+            drop(parts_maps);
+            self.toast(Toast::warning("None of the selected parts would change"));
+            return;
+        }
+        if action == BatchAction::Complete && !confirmed {
+            let num_blocked = ids
+                .iter()
+                .filter(|&&id| dep_graph::is_blocked(parts, id))
+                .count();
+            if num_blocked != 0 {
+                drop(parts_maps);
+                self.confirm = Some(Confirm::new(
+                    format!(
+                        "{} of the parts are still blocked by incomplete parts. Complete them \
+                        anyway?",
+                        num_blocked,
+                    ),
+                    "Complete Anyway",
+                    ctx.link().callback(move |_| AppMsg::BatchConfirmed(action)),
+                ));
+                return;
+            }
+        }
+        drop(parts_maps);
+        let res = send_batch_update_parts(
+            self.jwt.to_string(),
+            BatchUpdatePartsVars {
+                ids: ids.into_iter().map(|id| id.to_string()).collect(),
+                changes: HashMap::from([(String::from(field), value)]),
+            },
+        );
+        self.requests.send(
+            ctx.link(),
+            AppRequest::Batch,
+            res.map(move |res| AppMsg::BatchUpdated(action, res)),
+        );
+    }
+
+    // Shows why the projects couldn't be fetched in their place (with a retry button) once logged
+    // in, or in a toast on the login
+    fn show_load_error(&mut self, msg: String) {
+        if self.jwt.is_empty() {
+            self.show_error(msg);
+        } else {
+            console::log!("{}", msg);
+            self.load_error = Some(msg);
+        }
+    }
+
+    // Displayed if unexpected Access denied is returned
+    fn display_err_logout_alert(&mut self) {
+        self.jwt = Rc::from("");
+        self.history = History::default();
+        self.forget_user();
+        self.stop_sync();
+        self.toast(Toast::warning("Unexpectedly logged out, log in again"));
+    }
+
+    // Shows the user's cached parts (once the session is confirmed) and fetches the changes since
+    // they were cached
+    fn load_parts(&mut self, ctx: &Context<Self>) {
+        let mut parts_maps = self
+            .user
+            .as_deref()
+            .and_then(PartsCache::load)
+            .map(PartsMaps::from_cache)
+            .unwrap_or_default();
+        if let Some(user) = self.user.as_ref() {
+            parts_maps.tree = TreeState::load(user);
+            self.templates = templates::load(user);
+        }
+        parts_maps.owner = self.user.clone();
+        self.saved_tree = parts_maps.tree.clone();
+        *self.pm_mut() = parts_maps;
+        self.parts_rev += 1;
+        // Changes fetched for the parts being replaced (e.g., another user's) no longer apply
+        self.requests.cancel(&AppRequest::Sync);
+        self.requests.cancel(&AppRequest::Stats);
+        self.requests.cancel(&AppRequest::Burndown);
+        self.stats = None;
+        self.burndown = None;
+        for id in std::mem::take(&mut self.after_tree_loaded).into_keys() {
+            self.requests.cancel(&AppRequest::PartTree(id));
+        }
+        self.load_error = None;
+        self.sync_parts(ctx);
+    }
+
+    // Fetches the changes since the parts were last synced, or the first page of projects if
+    // they haven't been
+    fn sync_parts(&mut self, ctx: &Context<Self>) {
+        let Some(cursor) = self.pm().cursor.clone() else {
+            ctx.link().send_message(AppMsg::LoadChildren(None));
+            return;
+        };
+        let res = get_parts_since(self.jwt.to_string(), Some(cursor));
+        self.requests
+            .send(ctx.link(), AppRequest::Sync, res.map(AppMsg::GetParts));
+    }
+
+    // Starts what's needed once the parts have been fetched (and the session is confirmed)
+    fn start_session(&mut self, ctx: &Context<Self>) {
+        if self.jwt.is_empty() {
+            self.jwt = Rc::from("1");
+            self.follow_after_login(ctx);
+        }
+        let res = get_time_entries(self.jwt.to_string());
+        ctx.link().send_future(res.map(AppMsg::GetTimeEntries));
+        let res = get_memberships(self.jwt.to_string());
+        ctx.link().send_future(res.map(AppMsg::GetMemberships));
+        ctx.link().send_message(AppMsg::RefreshShared);
+        self.start_sync(ctx);
+    }
+
+    fn start_sync(&mut self, ctx: &Context<Self>) {
+        if self.sync.is_none() {
+            let on_event = ctx.link().callback(AppMsg::Sync);
+            self.sync = Some(sync::start(self.jwt.to_string(), on_event));
+        }
+    }
+
+    fn stop_sync(&mut self) {
+        self.sync = None;
+        self.sync_offline = false;
+    }
+
+    // Records the creation of the (own) part in its activity log
+    fn record_creation(&self, ctx: &Context<Self>, id: i64) {
+        let parts_maps = self.pm();
+        let Some(part) = parts_maps.parts.get(&id) else {
+            return;
+        };
+        let entry = activity::describe_creation(part, &parts_maps.parts);
+        let res = send_record_activity(self.jwt.to_string(), id, vec![entry], None);
+        ctx.link().send_future(res.map(AppMsg::ActivityRecorded));
+    }
+
+    // Records the changes made to the (own) parts in their activity logs, given the parts before
+    // and after being updated
+    fn record_updates<'a>(
+        &self,
+        ctx: &Context<Self>,
+        updates: impl IntoIterator<Item = (&'a Part, &'a Part)>,
+    ) {
+        let parts_maps = self.pm();
+        for (before, after) in updates {
+            let entries = activity::describe_update(before, after, &parts_maps.parts);
+            if !entries.is_empty() {
+                let res = send_record_activity(self.jwt.to_string(), after.id, entries, None);
+                ctx.link().send_future(res.map(AppMsg::ActivityRecorded));
+            }
+        }
+    }
+
+    // Moves what's kept locally for the user over to their new email
+    fn move_user(&mut self, email: String) {
+        tree_state::remember_user(&email);
+        if let Some(old) = self.user.replace(email.clone()) {
+            if let Some(cache) = PartsCache::load(&old) {
+                cache.save(&email);
+            }
+            PartsCache::clear(&old);
+            TreeState::clear(&old);
+            templates::clear(&old);
+        }
+        self.pm().tree.save(&email);
+        templates::save(&email, &self.templates);
+    }
+
+    fn forget_user(&mut self) {
+        self.shared.clear();
+        self.assigned = None;
+        self.trash = None;
+        self.templates.clear();
+        if let Some(user) = self.user.take() {
+            PartsCache::clear(&user);
+        }
+        tree_state::forget_user();
+    }
+
+    // Shows the toast until it's dismissed or times out
+    fn toast(&mut self, toast: Toast) {
+        let duration = toast.duration();
+        let id = self.toasts.push(toast);
+        let send_to_app = Rc::clone(&self.send_to_app);
+        let timer = Timeout::new(duration, move || send_to_app.emit(AppMsg::DismissToast(id)));
+        self.toast_timers.insert(id, timer);
+    }
+
+    fn show_error(&mut self, msg: String) {
+        console::log!("{}", msg);
+        self.toast(Toast::error(msg));
     }
 
     fn pm(&self) -> Ref<'_, PartsMaps> {
@@ -836,28 +4306,137 @@ impl App {
 #[derive(Properties, PartialEq)]
 pub struct PartProps {
     id: i64,
+    // How deep in the tree the part is shown (projects being 0 deep)
+    depth: usize,
     parts_maps: Rc<RefCell<PartsMaps>>,
+    parts_rev: u64,
     jwt: Rc<str>,
     send_to_app: Rc<Callback<AppMsg>>,
+    // How the part is shared with the user (None for the user's own parts)
+    #[prop_or_default]
+    shared: Option<Rc<Membership>>,
+}
+
+impl PartProps {
+    // The owner to pass to the part mutations
+    fn owner(&self) -> Option<String> {
+        self.shared.as_ref().map(|m| m.owner.clone())
+    }
+
+    // Whether the user can change the part
+    fn can_edit(&self) -> bool {
+        self.shared.as_ref().map(|m| m.role) != Some(Role::Viewer)
+    }
+
+    // The email of the user, if known
+    fn user(&self) -> Option<String> {
+        match self.shared.as_ref() {
+            Some(m) => Some(m.member.clone()),
+            None => self.parts_maps.borrow().owner.clone(),
+        }
+    }
+
+    // Records the change in the undo history. Changes to shared parts aren't recorded since
+    // undoing them would change the user's own parts.
+    fn record(&self, entry: Entry) {
+        if self.shared.is_none() {
+            self.send_to_app.emit(AppMsg::Record(entry));
+        }
+    }
+
+    fn toast(&self, toast: Toast) {
+        self.send_to_app.emit(AppMsg::Toast(toast));
+    }
+
+    fn confirm(&self, confirm: Confirm) {
+        self.send_to_app.emit(AppMsg::Confirm(confirm));
+    }
 }
 
 pub enum PartMsg {
     ToggleCreating,
     ToggleEditing,
     ToggleHide,
+    ToggleDetails,
+    ToggleComplete,
+    // Completes (or un-completes) the part
+    SendCompleted(bool),
+    ConfirmDelete,
+    SendDelete,
     CreatePart(anyhow::Result<CreatePartPart>),
     UpdatePart(anyhow::Result<UpdatePartPart>),
     DeletePart(anyhow::Result<String>),
+    AddBlocker(i64),
+    RemoveBlocker(i64),
+    // Lists the page of the parts that can be added as blockers with the given index
+    BlockerPage(usize),
+    UpdateBlockers(anyhow::Result<Part>),
+    StartTimer,
+    StopTimer(i64),
+    SendCreateTimeEntry,
+    UpdateTimeEntry(anyhow::Result<TimeEntry>),
+    SendDeleteTimeEntry(i64),
+    DeleteTimeEntry(anyhow::Result<String>),
+    // Toggles the task list checkbox with the given index in the description
+    ToggleTask(usize),
+    SendCreatePart(PartDraft),
+    SendUpdates(PartDraft),
+    // Sends the changes to the part from the edit form once confirmed
+    SendChanges(HashMap<String, String>),
+    // Shares the project with the user in the sharing form
+    Share,
+    Unshare(String),
+    UpdateMembers(anyhow::Result<Membership>),
+    RemoveMember(String, anyhow::Result<bool>),
+    GotActivity(anyhow::Result<PartActivity>),
+    // The entries recorded in the activity log of the part with the given id (this part or a
+    // child it created)
+    ActivityRecorded(i64, Vec<Activity>),
+    // Replies to the comment with the given id with the next comment (or stops replying if None)
+    ReplyTo(Option<i64>),
+    SendComment,
+    AddComment(anyhow::Result<Comment>),
+    ConfirmDeleteComment(i64),
+    SendDeleteComment(i64),
+    DeleteComment(anyhow::Result<i64>),
+    // Archives (or unarchives) the completed project
+    SetArchived(bool),
+    Archived(anyhow::Result<Part>),
+}
+
+// The requests a part component doesn't send again while one is pending
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum PartRequest {
+    Create,
+    Update,
+    Delete,
+    Blockers,
+    Archive,
+    // Starting or stopping the timer, or adding an entry
+    TimeEntry,
+    DeleteTimeEntry,
+    Share,
+    Unshare(String),
+    Activity,
+    Comment,
+    DeleteComment,
 }
 
 pub struct PartComponent {
     creating: bool,
     editing: bool,
-    child_list_ref: NodeRef,
-    details_div_ref: NodeRef,
-    create_form_ref: NodeRef,
-    updates_form_ref: NodeRef,
-    hide_children: bool,
+    blocker_select_ref: NodeRef,
+    // The index of the page of the parts that can be added as blockers listed
+    blocker_page: usize,
+    time_entry_form_ref: NodeRef,
+    share_form_ref: NodeRef,
+    // The part's comments and activity log (None until fetched, which is done whenever the
+    // details are opened)
+    activity: Option<PartActivity>,
+    // Dropped (cancelling them) along with the component
+    requests: Requests<PartRequest>,
+    replying_to: Option<i64>,
+    comment_ref: NodeRef,
 }
 
 impl Component for PartComponent {
@@ -868,68 +4447,213 @@ impl Component for PartComponent {
         Self {
             creating: false,
             editing: false,
-            child_list_ref: NodeRef::default(),
-            details_div_ref: NodeRef::default(),
-            create_form_ref: NodeRef::default(),
-            updates_form_ref: NodeRef::default(),
-            hide_children: true,
+            blocker_select_ref: NodeRef::default(),
+            blocker_page: 0,
+            time_entry_form_ref: NodeRef::default(),
+            share_form_ref: NodeRef::default(),
+            activity: None,
+            requests: Requests::default(),
+            replying_to: None,
+            comment_ref: NodeRef::default(),
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
+        let id = ctx.props().id;
+        let parts_maps = ctx.props().parts_maps.borrow();
+        let showing = parts_maps.tree.details.contains(&id);
+        // The part's blockers may be anywhere in the tree. A shared project's parts are all
+        // fetched with it.
+        let missing = parts_maps.missing_blockers([id]);
+        if first_render && !missing.is_empty() && ctx.props().shared.is_none() {
+            ctx.props().send_to_app.emit(AppMsg::LoadParts(missing));
+        }
+        drop(parts_maps);
+        if showing && self.activity.is_none() {
+            let res = get_part_activity(ctx.props().jwt.to_string(), id, ctx.props().owner());
+            self.requests.send(
+                ctx.link(),
+                PartRequest::Activity,
+                res.map(PartMsg::GotActivity),
+            );
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        // Reused for another part, so nothing of the old one's carries over
+        if old_props.id != ctx.props().id {
+            old_props
+                .parts_maps
+                .borrow_mut()
+                .pending
+                .remove(&old_props.id);
+            self.creating = false;
+            self.editing = false;
+            self.activity = None;
+            self.requests = Requests::default();
+            self.replying_to = None;
+            self.blocker_page = 0;
         }
+        let mut parts_maps = ctx.props().parts_maps.borrow_mut();
+        if parts_maps.focused == Some(ctx.props().id) {
+            if let Some(command) = parts_maps.command.take() {
+                ctx.link().send_message(match command {
+                    PartCommand::ToggleDetails => PartMsg::ToggleDetails,
+                    PartCommand::NewChild => PartMsg::ToggleCreating,
+                    PartCommand::Edit => PartMsg::ToggleEditing,
+                    PartCommand::ToggleComplete => PartMsg::ToggleComplete,
+                    PartCommand::Delete => PartMsg::ConfirmDelete,
+                });
+            }
+        }
+        true
+    }
+
+    fn destroy(&mut self, ctx: &Context<Self>) {
+        let id = ctx.props().id;
+        ctx.props().parts_maps.borrow_mut().pending.remove(&id);
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         let id = ctx.props().id;
         let parts_maps = ctx.props().parts_maps.borrow();
-        let part = &parts_maps.parts[&id];
+        // The part may have just been deleted (by this component or an ancestor)
+        let Some(part) = parts_maps.parts.get(&id) else {
+            return html! {};
+        };
         let children = &parts_maps.fams[&id];
+        let expanded = parts_maps.tree.expanded.contains(&id);
+        let focused = parts_maps.focused == Some(id);
+        let linked = parts_maps.linked == Some(id);
+        let open_blockers = dep_graph::open_blockers(&parts_maps.parts, id);
+        let now = Local::now();
+        let total_time = parts_maps.total_time(id, now);
+        let running_entry = parts_maps.running_entry(id);
+        let unfetched = parts_maps.has_unfetched_children(id);
+
+        let show_children = ctx.link().callback(|_| PartMsg::ToggleHide);
 
-        let child_list_ref = self.child_list_ref.clone();
-        let show_children = ctx.link().batch_callback(move |_| {
-            if false {
-                let elem = child_list_ref.cast::<HtmlElement>().unwrap();
-                elem.set_hidden(!elem.hidden());
+        let show_details = ctx.link().callback(|_| PartMsg::ToggleDetails);
+
+        let toggle_timer = match running_entry {
+            Some(entry) => {
+                let entry_id = entry.id;
+                ctx.link().callback(move |_| PartMsg::StopTimer(entry_id))
             }
-            //None
-            Some(PartMsg::ToggleHide)
-        });
+            None => ctx.link().callback(|_| PartMsg::StartTimer),
+        };
 
-        let details_div_ref = self.details_div_ref.clone();
-        let show_details = ctx.link().batch_callback(move |_| {
-            let elem = details_div_ref.cast::<HtmlElement>().unwrap();
-            elem.set_hidden(!elem.hidden());
-            None
+        // Keyboard focus and selection are for the user's own parts
+        let own = ctx.props().shared.is_none();
+        let can_edit = ctx.props().can_edit();
+        let send_to_app = ctx.props().send_to_app.clone();
+        let focus = own.then(|| Callback::from(move |_| send_to_app.emit(AppMsg::Focus(id))));
+        let members = parts_maps.project_members(id);
+
+        let send_to_app = ctx.props().send_to_app.clone();
+        let select = Callback::from(move |e: MouseEvent| {
+            let Some(checkbox) = e.target_dyn_into::<HtmlInputElement>() else {
+                console::log!("missing or invalid select checkbox element");
+                return;
+            };
+            send_to_app.emit(AppMsg::SelectPart {
+                id,
+                selected: checkbox.checked(),
+                range: e.shift_key(),
+            });
         });
 
         let toggle_creating = ctx.link().callback(|_| PartMsg::ToggleCreating);
+        // Fetching the activity isn't a change being saved
+        let saving = self.requests.any(|key| *key != PartRequest::Activity);
         html! {
-            <li class="part">
-                <span>{ &part.name }</span>
+            <li
+                class={classes!(
+                    "part",
+                    "tree-row",
+                    focused.then_some("part-focused"),
+                    linked.then_some("part-linked"),
+                    saving.then_some("part-saving"),
+                )}
+                style={format!("padding-left: {}px", ctx.props().depth * TREE_INDENT)}
+            >
+                if own {
+                    <input
+                        type="checkbox"
+                        class="part-select"
+                        data-part-id={id.to_string()}
+                        checked={parts_maps.selected.contains(&id)}
+                        onclick={select}
+                    />
+                }
+                <span class="part-name" onclick={focus}>{ &part.name }</span>
+                if saving {
+                    <span class="spinner" title="Saving..."></span>
+                }
+                if let Some(assignee) = part.assignee.as_deref() {
+                    { assignee_badge(assignee) }
+                }
+                if !members.is_empty() {
+                    <span
+                        class="part-shared"
+                        title={format!(
+                            "Shared with: {}",
+                            members.iter()
+                                .map(|m| format!("{} ({})", m.member, m.role))
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        )}
+                    >{ "Shared" }</span>
+                }
+                {
+                    part.tags.iter().map(|tag| html! {
+                        <span class="part-tag">{ format!("#{}", tag) }</span>
+                    }).collect::<Html>()
+                }
+                if !open_blockers.is_empty() {
+                    <span
+                        class="blocked-indicator"
+                        title={format!(
+                            "Blocked by: {}",
+                            open_blockers.iter()
+                                .map(|bid| parts_maps.parts[bid].name.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        )}
+                    >{ "Blocked" }</span>
+                }
+                if total_time > chrono::Duration::zero() {
+                    <span class="part-time">{ time_tracking::format_duration(total_time) }</span>
+                }
+                // Time entries are kept with the user's own parts
+                if own {
+                    <button
+                        onclick={toggle_timer}
+                        disabled={self.requests.is_pending(&PartRequest::TimeEntry)}
+                    >{
+                        match running_entry {
+                            Some(entry) => format!(
+                                "Stop Timer ({})",
+                                time_tracking::format_duration(entry.duration(now)),
+                            ),
+                            None => String::from("Start Timer"),
+                        }
+                    }</button>
+                }
                 <button onclick={show_details}>{ "Details" }</button>
-                <button onclick={toggle_creating}>{ "New Part" }</button>
-                if children.len() != 0 {
-                    <button onclick={show_children}>{ 
-                        if self.hide_children { "Show Children" } else { "Hide Children" }
+                if can_edit {
+                    <button onclick={toggle_creating}>{ "New Part" }</button>
+                }
+                if children.len() != 0 || unfetched {
+                    <button onclick={show_children}>{
+                        if expanded { "Hide Children" } else { "Show Children" }
                     }</button>
                 }
 
                 { self.render_details(ctx, part) }
 
-                <ul class="parts-list" ref={self.child_list_ref.clone()} hidden=false>
+                <ul class="parts-list">
                 { self.render_create_part(ctx) }
-                if !self.hide_children {
-                    {
-                        children.into_iter().filter_map(|&child_id| {
-                            parts_maps.id_not_filtered(child_id).then(|| html! {
-                                <PartComponent
-                                    key={child_id}
-                                    id={child_id}
-                                    parts_maps={Rc::clone(&ctx.props().parts_maps)}
-                                    jwt={Rc::clone(&ctx.props().jwt)}
-                                    send_to_app={Rc::clone(&ctx.props().send_to_app)}
-                                />
-                            })
-                        }).collect::<Html>()
-                    }
-                }
                 </ul>
                 /*
                 if children.len() != 0 {
@@ -945,186 +4669,882 @@ impl Component for PartComponent {
         // TODO: Show logout error on err_is_access
         match msg {
             PartMsg::ToggleCreating => self.creating = !self.creating,
-            PartMsg::ToggleEditing => self.editing = !self.editing,
-            PartMsg::ToggleHide => self.hide_children = !self.hide_children,
-            PartMsg::CreatePart(res) => match res {
-                Ok(part) => match part.try_into() {
-                    Ok(part) => {
-                        let res = ctx.props().parts_maps.borrow_mut().add_part(part);
-                        if let Err(part) = res {
-                            console::log!("Part already exists: {:?}", part);
+            PartMsg::ToggleEditing => {
+                self.editing = !self.editing;
+                // Editing happens in the details
+                if self.editing {
+                    let id = ctx.props().id;
+                    if ctx.props().parts_maps.borrow_mut().tree.details.insert(id) {
+                        ctx.props().send_to_app.emit(AppMsg::PartsChanged);
+                    }
+                }
+            }
+            PartMsg::ToggleHide => {
+                let id = ctx.props().id;
+                let mut parts_maps = ctx.props().parts_maps.borrow_mut();
+                if !parts_maps.tree.expanded.remove(&id) {
+                    parts_maps.tree.expanded.insert(id);
+                }
+                drop(parts_maps);
+                ctx.props().send_to_app.emit(AppMsg::PartsChanged);
+                return false;
+            }
+            PartMsg::ToggleDetails => {
+                let id = ctx.props().id;
+                let mut parts_maps = ctx.props().parts_maps.borrow_mut();
+                if !parts_maps.tree.details.remove(&id) {
+                    parts_maps.tree.details.insert(id);
+                    // Others may have commented since
+                    self.activity = None;
+                }
+                drop(parts_maps);
+                ctx.props().send_to_app.emit(AppMsg::PartsChanged);
+                return false;
+            }
+            PartMsg::ToggleComplete => {
+                let id = ctx.props().id;
+                let parts_maps = ctx.props().parts_maps.borrow();
+                let Some(part) = parts_maps.parts.get(&id) else {
+                    return false;
+                };
+                let complete = part.completed_at.is_none();
+                let blocked_msg = blocked_completion_msg(&parts_maps, id);
+                drop(parts_maps);
+                match blocked_msg {
+                    Some(msg) if complete => ctx.props().confirm(Confirm::new(
+                        msg,
+                        "Complete Anyway",
+                        ctx.link().callback(|_| PartMsg::SendCompleted(true)),
+                    )),
+                    _ => ctx.link().send_message(PartMsg::SendCompleted(complete)),
+                }
+                return false;
+            }
+            PartMsg::SendCompleted(complete) => {
+                let id = ctx.props().id;
+                let completed_at = match complete {
+                    true => Local::now().timestamp().to_string(),
+                    false => String::new(),
+                };
+                let res = send_update_part(
+                    ctx.props().jwt.to_string(),
+                    UpdatePartVars {
+                        id: id.to_string(),
+                        changes: HashMap::from([(String::from("completed_at"), completed_at)]),
+                        owner: ctx.props().owner(),
+                    },
+                );
+                self.mutate(ctx, PartRequest::Update, res.map(PartMsg::UpdatePart));
+                return false;
+            }
+            PartMsg::ConfirmDelete => {
+                let id = ctx.props().id;
+                ctx.props().confirm(Confirm::new(
+                    format!(
+                        "Move the following project/part and ALL its children to the trash?\n{}",
+                        ctx.props().parts_maps.borrow().label(id),
+                    ),
+                    "Move to Trash",
+                    ctx.link().callback(|_| PartMsg::SendDelete),
+                ));
+                return false;
+            }
+            PartMsg::SendDelete => {
+                let res = send_delete_part(
+                    ctx.props().jwt.to_string(),
+                    DeletePartVars {
+                        id: ctx.props().id.to_string(),
+                        owner: ctx.props().owner(),
+                    },
+                );
+                self.mutate(ctx, PartRequest::Delete, res.map(PartMsg::DeletePart));
+                self.editing = false;
+            }
+            PartMsg::CreatePart(res) => {
+                self.finish(ctx, &PartRequest::Create);
+                match res {
+                    Ok(part) => match part.try_into() {
+                        Ok(part) => {
+                            self.creating = false;
+                            let part: Part = part;
+                            let label = format!("Create \"{}\"", part.name);
+                            let snapshot = Snapshot {
+                                parts: vec![part.clone()],
+                                blockers: Vec::new(),
+                                time_entries: Vec::new(),
+                            };
+                            let new_id = part.id;
+                            let res = ctx.props().parts_maps.borrow_mut().add_part(part);
+                            if res.is_ok() {
+                                ctx.props()
+                                    .record(Entry::new(label, vec![Change::Created(snapshot)]));
+                                let parts_maps = ctx.props().parts_maps.borrow();
+                                let entry = activity::describe_creation(
+                                    &parts_maps.parts[&new_id],
+                                    &parts_maps.parts,
+                                );
+                                Self::record_activity(ctx, new_id, vec![entry]);
+                            }
+                            if let Err(part) = res {
+                                console::log!("Part already exists: {:?}", part);
+                                ctx.props()
+                                    .send_to_app
+                                    .emit(AppMsg::ErrorMsg(String::from("Part already exists???")));
+                            }
+                        }
+                        Err(e) => {
+                            console::log!("Bad create part from server: {}", e);
                             ctx.props()
                                 .send_to_app
-                                .emit(AppMsg::ErrorMsg(String::from("Part already exists???")));
+                                .emit(AppMsg::ErrorMsg(String::from("Internal Server Error")));
+                        }
+                    },
+                    Err(e) => {
+                        if err_is_access(e.as_ref()) {
+                            ctx.props().send_to_app.emit(AppMsg::DisplayErrLogout);
+                            return true;
+                        }
+                        let error_msg = format!("Error creating part: {}", e);
+                        console::log!("{}", error_msg);
+                        ctx.props().send_to_app.emit(AppMsg::ErrorMsg(error_msg));
+                    }
+                }
+            }
+            PartMsg::UpdatePart(res) => {
+                self.finish(ctx, &PartRequest::Update);
+                match res {
+                    Ok(part) => match part.try_into() {
+                        Ok(part) => {
+                            self.editing = false;
+                            let part: Part = part;
+                            let before =
+                                ctx.props().parts_maps.borrow().parts.get(&part.id).cloned();
+                            let after = part.clone();
+                            if let Err(part) = ctx.props().parts_maps.borrow_mut().update_part(part)
+                            {
+                                console::log!("Part doesn't exist: {:?}", part);
+                                ctx.props()
+                                    .send_to_app
+                                    .emit(AppMsg::ErrorMsg(String::from("Part doesn't exist???")));
+                            } else {
+                                if let Some(before) = before.as_ref() {
+                                    let entries = activity::describe_update(
+                                        before,
+                                        &after,
+                                        &ctx.props().parts_maps.borrow().parts,
+                                    );
+                                    if !entries.is_empty() {
+                                        Self::record_activity(ctx, after.id, entries);
+                                    }
+                                }
+                                if let Some(before) = before {
+                                    let label = format!("Edit \"{}\"", before.name);
+                                    ctx.props().record(Entry::new(
+                                        label,
+                                        vec![Change::Updated {
+                                            before: Box::new(before),
+                                            after: Box::new(after),
+                                        }],
+                                    ));
+                                }
+                                ctx.props().send_to_app.emit(AppMsg::PartsChanged);
+                            }
+                        }
+                        Err(e) => console::log!("bad update part from server: {}", e),
+                    },
+                    Err(e) => {
+                        console::log!("error updating part: {}", e);
+                        ctx.props()
+                            .send_to_app
+                            .emit(AppMsg::ErrorMsg(e.to_string()));
+                    }
+                }
+            }
+            PartMsg::DeletePart(res) => {
+                self.finish(ctx, &PartRequest::Delete);
+                match res {
+                    Ok(_) => {
+                        // TODO: Possibly use returned id (string)
+                        let mut parts_maps = ctx.props().parts_maps.borrow_mut();
+                        let snapshot = parts_maps.snapshot(ctx.props().id);
+                        parts_maps.delete_part(ctx.props().id);
+                        drop(parts_maps);
+                        if !snapshot.parts.is_empty() {
+                            let label = match snapshot.parts.len() - 1 {
+                                0 => format!("Delete \"{}\"", snapshot.parts[0].name),
+                                n => format!(
+                                    "Delete \"{}\" (and {} children)",
+                                    snapshot.parts[0].name, n
+                                ),
+                            };
+                            let mut toast = Toast::success(format!(
+                                "Moved \"{}\" to the trash",
+                                snapshot.parts[0].name
+                            ));
+                            // Only the user's own parts can be undone
+                            if ctx.props().shared.is_none() {
+                                let send_to_app = ctx.props().send_to_app.clone();
+                                toast = toast.with_action(
+                                    "Undo",
+                                    Callback::from(move |_| send_to_app.emit(AppMsg::Undo)),
+                                );
+                            }
+                            ctx.props()
+                                .record(Entry::new(label, vec![Change::Deleted(snapshot)]));
+                            ctx.props().toast(toast);
+                        }
+                        ctx.props().send_to_app.emit(AppMsg::PartsChanged);
+                    }
+                    Err(e) => {
+                        console::log!("error deleting part: {}", e);
+                        ctx.props()
+                            .send_to_app
+                            .emit(AppMsg::ErrorMsg(e.to_string()));
+                    }
+                }
+            }
+            PartMsg::BlockerPage(page) => self.blocker_page = page,
+            PartMsg::AddBlocker(blocker_id) => {
+                let id = ctx.props().id;
+                if dep_graph::would_create_cycle(
+                    &ctx.props().parts_maps.borrow().parts,
+                    id,
+                    blocker_id,
+                ) {
+                    ctx.props().send_to_app.emit(AppMsg::ErrorMsg(String::from(
+                        "Blocker would create a dependency cycle",
+                    )));
+                    return false;
+                }
+                let res = send_add_blocker(
+                    ctx.props().jwt.to_string(),
+                    AddBlockerVars {
+                        id: id.to_string(),
+                        blocker_id: blocker_id.to_string(),
+                        owner: ctx.props().owner(),
+                    },
+                );
+                self.mutate(
+                    ctx,
+                    PartRequest::Blockers,
+                    res.map(|res| res.and_then(Part::try_from))
+                        .map(PartMsg::UpdateBlockers),
+                );
+                return false;
+            }
+            PartMsg::RemoveBlocker(blocker_id) => {
+                let res = send_remove_blocker(
+                    ctx.props().jwt.to_string(),
+                    RemoveBlockerVars {
+                        id: ctx.props().id.to_string(),
+                        blocker_id: blocker_id.to_string(),
+                        owner: ctx.props().owner(),
+                    },
+                );
+                self.mutate(
+                    ctx,
+                    PartRequest::Blockers,
+                    res.map(|res| res.and_then(Part::try_from))
+                        .map(PartMsg::UpdateBlockers),
+                );
+                return false;
+            }
+            PartMsg::UpdateBlockers(res) => {
+                self.finish(ctx, &PartRequest::Blockers);
+                match res {
+                    Ok(part) => {
+                        if let Err(part) = ctx.props().parts_maps.borrow_mut().update_part(part) {
+                            console::log!("Part doesn't exist: {:?}", part);
+                        }
+                        ctx.props().send_to_app.emit(AppMsg::PartsChanged);
+                    }
+                    Err(e) => {
+                        if err_is_access(e.as_ref()) {
+                            ctx.props().send_to_app.emit(AppMsg::DisplayErrLogout);
+                            return true;
+                        }
+                        console::log!("error updating blockers: {}", e);
+                        ctx.props()
+                            .send_to_app
+                            .emit(AppMsg::ErrorMsg(format!("Error updating blockers: {}", e)));
+                    }
+                }
+            }
+            PartMsg::SetArchived(archived) => {
+                let res = send_set_archived(ctx.props().jwt.to_string(), ctx.props().id, archived);
+                self.mutate(ctx, PartRequest::Archive, res.map(PartMsg::Archived));
+                return false;
+            }
+            PartMsg::Archived(res) => {
+                self.finish(ctx, &PartRequest::Archive);
+                match res {
+                    Ok(part) => {
+                        if let Err(part) = ctx.props().parts_maps.borrow_mut().update_part(part) {
+                            console::log!("Part doesn't exist: {:?}", part);
+                        }
+                        ctx.props().send_to_app.emit(AppMsg::PartsChanged);
+                    }
+                    Err(e) => {
+                        if err_is_access(e.as_ref()) {
+                            ctx.props().send_to_app.emit(AppMsg::DisplayErrLogout);
+                            return true;
+                        }
+                        console::log!("error archiving project: {}", e);
+                        ctx.props()
+                            .send_to_app
+                            .emit(AppMsg::ErrorMsg(format!("Error archiving project: {}", e)));
+                    }
+                }
+            }
+            PartMsg::StartTimer => {
+                let res = send_start_timer(
+                    ctx.props().jwt.to_string(),
+                    StartTimerVars {
+                        part_id: ctx.props().id.to_string(),
+                    },
+                );
+                self.mutate(
+                    ctx,
+                    PartRequest::TimeEntry,
+                    res.map(|res| res.and_then(TimeEntry::try_from))
+                        .map(PartMsg::UpdateTimeEntry),
+                );
+                return false;
+            }
+            PartMsg::StopTimer(entry_id) => {
+                let res = send_stop_timer(
+                    ctx.props().jwt.to_string(),
+                    StopTimerVars {
+                        id: entry_id.to_string(),
+                    },
+                );
+                self.mutate(
+                    ctx,
+                    PartRequest::TimeEntry,
+                    res.map(|res| res.and_then(TimeEntry::try_from))
+                        .map(PartMsg::UpdateTimeEntry),
+                );
+                return false;
+            }
+            PartMsg::SendCreateTimeEntry => {
+                let form = self.time_entry_form_ref.cast::<HtmlFormElement>().unwrap();
+                let elems = form.elements();
+                let (Some(start), Some(end)) = (
+                    elems.get_with_name("entry-start").and_then(dtl_from_input),
+                    elems.get_with_name("entry-end").and_then(dtl_from_input),
+                ) else {
+                    console::log!("missing or invalid time entry input elements");
+                    return false;
+                };
+                if start == DateTime::<Local>::default() || end == DateTime::<Local>::default() {
+                    ctx.props().send_to_app.emit(AppMsg::ErrorMsg(String::from(
+                        "Must provide a start and end time",
+                    )));
+                    return false;
+                }
+                if end <= start {
+                    ctx.props().send_to_app.emit(AppMsg::ErrorMsg(String::from(
+                        "Time entry must end after it starts",
+                    )));
+                    return false;
+                }
+                let res = send_create_time_entry(
+                    ctx.props().jwt.to_string(),
+                    CreateTimeEntryVars {
+                        input: CreateTimeEntryInput {
+                            part_id: ctx.props().id.to_string(),
+                            started_at: start.timestamp().to_string(),
+                            ended_at: end.timestamp().to_string(),
+                        },
+                    },
+                );
+                if self.mutate(
+                    ctx,
+                    PartRequest::TimeEntry,
+                    res.map(|res| res.and_then(TimeEntry::try_from))
+                        .map(PartMsg::UpdateTimeEntry),
+                ) {
+                    form.reset();
+                }
+                return false;
+            }
+            PartMsg::UpdateTimeEntry(res) => {
+                self.finish(ctx, &PartRequest::TimeEntry);
+                match res {
+                    Ok(entry) => {
+                        ctx.props().parts_maps.borrow_mut().upsert_time_entry(entry);
+                        ctx.props().send_to_app.emit(AppMsg::PartsChanged);
+                    }
+                    Err(e) => {
+                        if err_is_access(e.as_ref()) {
+                            ctx.props().send_to_app.emit(AppMsg::DisplayErrLogout);
+                            return true;
+                        }
+                        console::log!("error updating time entry: {}", e);
+                        ctx.props().send_to_app.emit(AppMsg::ErrorMsg(format!(
+                            "Error updating time entry: {}",
+                            e
+                        )));
+                    }
+                }
+            }
+            PartMsg::SendDeleteTimeEntry(entry_id) => {
+                let res = send_delete_time_entry(
+                    ctx.props().jwt.to_string(),
+                    DeleteTimeEntryVars {
+                        id: entry_id.to_string(),
+                    },
+                );
+                self.mutate(
+                    ctx,
+                    PartRequest::DeleteTimeEntry,
+                    res.map(PartMsg::DeleteTimeEntry),
+                );
+                return false;
+            }
+            PartMsg::DeleteTimeEntry(res) => {
+                self.finish(ctx, &PartRequest::DeleteTimeEntry);
+                match res.and_then(|id| Ok(id.parse::<i64>()?)) {
+                    Ok(entry_id) => {
+                        ctx.props()
+                            .parts_maps
+                            .borrow_mut()
+                            .remove_time_entry(entry_id);
+                        ctx.props().send_to_app.emit(AppMsg::PartsChanged);
+                    }
+                    Err(e) => {
+                        console::log!("error deleting time entry: {}", e);
+                        ctx.props().send_to_app.emit(AppMsg::ErrorMsg(format!(
+                            "Error deleting time entry: {}",
+                            e
+                        )));
+                    }
+                }
+            }
+            PartMsg::ToggleTask(index) => {
+                if !ctx.props().can_edit() {
+                    return false;
+                }
+                let id = ctx.props().id;
+                let toggled = ctx
+                    .props()
+                    .parts_maps
+                    .borrow()
+                    .parts
+                    .get(&id)
+                    .and_then(|part| part.description.as_ref())
+                    .and_then(|desc| markdown::toggle_task(desc, index));
+                let Some(desc) = toggled else {
+                    console::log!("invalid task index: {}", index);
+                    return false;
+                };
+                let res = send_update_part(
+                    ctx.props().jwt.to_string(),
+                    UpdatePartVars {
+                        id: id.to_string(),
+                        changes: HashMap::from([(String::from("description"), desc)]),
+                        owner: ctx.props().owner(),
+                    },
+                );
+                self.mutate(ctx, PartRequest::Update, res.map(PartMsg::UpdatePart));
+                return false;
+            }
+            PartMsg::SendCreatePart(draft) => {
+                let res = send_create_part(
+                    ctx.props().jwt.to_string(),
+                    CreatePartVars {
+                        input: draft.into_part().into(),
+                        owner: ctx.props().owner(),
+                    },
+                );
+                self.mutate(ctx, PartRequest::Create, res.map(PartMsg::CreatePart));
+            }
+            PartMsg::SendUpdates(draft) => {
+                let id = ctx.props().id;
+                let parts_maps = ctx.props().parts_maps.borrow();
+                let Some(part) = parts_maps.parts.get(&id) else {
+                    return false;
+                };
+                let changes = draft.changes_from(part);
+                let completing = part.completed_at.is_none() && draft.completed_at.is_some();
+                if let Some(msg) = blocked_completion_msg(&parts_maps, id).filter(|_| completing) {
+                    drop(parts_maps);
+                    ctx.props().confirm(Confirm::new(
+                        msg,
+                        "Complete Anyway",
+                        ctx.link()
+                            .callback(move |_| PartMsg::SendChanges(changes.clone())),
+                    ));
+                    return false;
+                }
+                drop(parts_maps);
+                ctx.link().send_message(PartMsg::SendChanges(changes));
+                return false;
+            }
+            PartMsg::SendChanges(changes) => {
+                let id = ctx.props().id;
+                if !changes.is_empty() {
+                    let res = send_update_part(
+                        ctx.props().jwt.to_string(),
+                        UpdatePartVars {
+                            id: id.to_string(),
+                            changes,
+                            owner: ctx.props().owner(),
+                        },
+                    );
+                    self.mutate(ctx, PartRequest::Update, res.map(PartMsg::UpdatePart));
+                } else {
+                    self.editing = false;
+                }
+            }
+            PartMsg::Share => {
+                let form = self.share_form_ref.cast::<HtmlFormElement>().unwrap();
+                let elems = form.elements();
+                let (Some(email), Some(role)) = (
+                    elems
+                        .get_with_name("share-email")
+                        .and_then(|elem| elem.dyn_into::<HtmlInputElement>().ok()),
+                    elems
+                        .get_with_name("share-role")
+                        .and_then(|elem| elem.dyn_into::<HtmlSelectElement>().ok()),
+                ) else {
+                    console::log!("missing or invalid share input elements");
+                    return false;
+                };
+                let email = email.value().trim().to_string();
+                if email.is_empty() {
+                    ctx.props()
+                        .send_to_app
+                        .emit(AppMsg::ErrorMsg(String::from("Must provide an email")));
+                    return false;
+                }
+                let role = match role.value().as_str() {
+                    "editor" => Role::Editor,
+                    _ => Role::Viewer,
+                };
+                let res =
+                    send_share_project(ctx.props().jwt.to_string(), ctx.props().id, email, role);
+                if self.mutate(ctx, PartRequest::Share, res.map(PartMsg::UpdateMembers)) {
+                    form.reset();
+                }
+                return false;
+            }
+            PartMsg::Unshare(email) => {
+                let res = send_unshare_project(
+                    ctx.props().jwt.to_string(),
+                    ctx.props().id,
+                    email.clone(),
+                );
+                self.mutate(
+                    ctx,
+                    PartRequest::Unshare(email.clone()),
+                    res.map(move |res| PartMsg::RemoveMember(email, res)),
+                );
+                return false;
+            }
+            PartMsg::UpdateMembers(res) => {
+                self.finish(ctx, &PartRequest::Share);
+                match res {
+                    Ok(membership) => {
+                        let mut parts_maps = ctx.props().parts_maps.borrow_mut();
+                        parts_maps.memberships.retain(|m| {
+                            m.project_id != membership.project_id || m.member != membership.member
+                        });
+                        parts_maps.memberships.push(membership);
+                        drop(parts_maps);
+                        ctx.props().send_to_app.emit(AppMsg::PartsChanged);
+                    }
+                    Err(e) => {
+                        console::log!("error sharing project: {}", e);
+                        ctx.props()
+                            .send_to_app
+                            .emit(AppMsg::ErrorMsg(format!("Error sharing project: {}", e)));
+                    }
+                }
+            }
+            PartMsg::RemoveMember(email, res) => {
+                self.finish(ctx, &PartRequest::Unshare(email.clone()));
+                match res {
+                    Ok(_) => {
+                        let id = ctx.props().id;
+                        ctx.props()
+                            .parts_maps
+                            .borrow_mut()
+                            .memberships
+                            .retain(|m| m.project_id != id || m.member != email);
+                        ctx.props().send_to_app.emit(AppMsg::PartsChanged);
+                    }
+                    Err(e) => {
+                        console::log!("error unsharing project: {}", e);
+                        ctx.props()
+                            .send_to_app
+                            .emit(AppMsg::ErrorMsg(format!("Error unsharing project: {}", e)));
+                    }
+                }
+            }
+            PartMsg::GotActivity(res) => {
+                self.requests.finish(&PartRequest::Activity);
+                match res {
+                    Ok(activity) => self.activity = Some(activity),
+                    Err(e) => {
+                        console::log!("error getting activity: {}", e);
+                        // Shown as empty rather than fetched again on every render
+                        self.activity = Some(PartActivity::default());
+                        ctx.props()
+                            .send_to_app
+                            .emit(AppMsg::ErrorMsg(format!("Error getting activity: {}", e)));
+                    }
+                }
+            }
+            PartMsg::ActivityRecorded(id, entries) => match self.activity.as_mut() {
+                Some(activity) if id == ctx.props().id => activity.activity.extend(entries),
+                _ => return false,
+            },
+            PartMsg::ReplyTo(comment_id) => self.replying_to = comment_id,
+            PartMsg::SendComment => {
+                let Some(area) = self.comment_ref.cast::<HtmlTextAreaElement>() else {
+                    console::log!("missing comment textarea element");
+                    return false;
+                };
+                let body = area.value();
+                if body.trim().is_empty() {
+                    return false;
+                }
+                let res = send_add_comment(
+                    ctx.props().jwt.to_string(),
+                    ctx.props().id,
+                    body,
+                    self.replying_to,
+                    ctx.props().owner(),
+                );
+                self.mutate(ctx, PartRequest::Comment, res.map(PartMsg::AddComment));
+                return false;
+            }
+            PartMsg::AddComment(res) => {
+                self.finish(ctx, &PartRequest::Comment);
+                match res {
+                    Ok(comment) => {
+                        if let Some(area) = self.comment_ref.cast::<HtmlTextAreaElement>() {
+                            area.set_value("");
+                        }
+                        self.replying_to = None;
+                        if let Some(activity) = self.activity.as_mut() {
+                            activity.comments.push(comment);
                         }
                     }
                     Err(e) => {
-                        if err_is_access(e.as_ref()) {
-                            ctx.props().send_to_app.emit(AppMsg::DisplayErrLogout);
-                            return true;
-                        }
-                        console::log!("Bad create part from server: {}", e);
+                        console::log!("error adding comment: {}", e);
                         ctx.props()
                             .send_to_app
-                            .emit(AppMsg::ErrorMsg(String::from("Internal Server Error")));
+                            .emit(AppMsg::ErrorMsg(format!("Error adding comment: {}", e)));
                     }
-                },
-                Err(e) => {
-                    let error_msg = format!("Error creating part: {}", e);
-                    console::log!("{}", error_msg);
-                    ctx.props().send_to_app.emit(AppMsg::ErrorMsg(error_msg));
                 }
-            },
-            PartMsg::UpdatePart(res) => match res {
-                Ok(part) => match part.try_into() {
-                    Ok(part) => {
-                        if let Err(part) = ctx.props().parts_maps.borrow_mut().update_part(part) {
-                            console::log!("Part doesn't exist: {:?}", part);
-                            ctx.props()
-                                .send_to_app
-                                .emit(AppMsg::ErrorMsg(String::from("Part doesn't exist???")));
+            }
+            PartMsg::ConfirmDeleteComment(comment_id) => {
+                ctx.props().confirm(Confirm::new(
+                    "Delete the comment and its replies?",
+                    "Delete",
+                    ctx.link()
+                        .callback(move |_| PartMsg::SendDeleteComment(comment_id)),
+                ));
+                return false;
+            }
+            PartMsg::SendDeleteComment(comment_id) => {
+                let res = send_delete_comment(
+                    ctx.props().jwt.to_string(),
+                    ctx.props().id,
+                    comment_id,
+                    ctx.props().owner(),
+                );
+                self.mutate(
+                    ctx,
+                    PartRequest::DeleteComment,
+                    res.map(PartMsg::DeleteComment),
+                );
+                return false;
+            }
+            PartMsg::DeleteComment(res) => {
+                self.finish(ctx, &PartRequest::DeleteComment);
+                match res {
+                    Ok(comment_id) => {
+                        if let Some(activity) = self.activity.as_mut() {
+                            // The replies were deleted too
+                            let mut deleted = BTreeSet::from([comment_id]);
+                            for (_, comment) in activity::thread(&activity.comments) {
+                                if comment.reply_to.is_some_and(|rid| deleted.contains(&rid)) {
+                                    deleted.insert(comment.id);
+                                }
+                            }
+                            activity.comments.retain(|c| !deleted.contains(&c.id));
                         }
                     }
-                    Err(e) => console::log!("bad update part from server: {}", e),
-                },
-                Err(e) => {
-                    console::log!("error updating part: {}", e);
-                    ctx.props()
-                        .send_to_app
-                        .emit(AppMsg::ErrorMsg(e.to_string()));
-                }
-            },
-            PartMsg::DeletePart(res) => match res {
-                Ok(_) => {
-                    // TODO: Possibly use returned id (string)
-                    ctx.props()
-                        .parts_maps
-                        .borrow_mut()
-                        .delete_part(ctx.props().id);
-                }
-                Err(e) => {
-                    console::log!("error deleting part: {}", e);
-                    ctx.props()
-                        .send_to_app
-                        .emit(AppMsg::ErrorMsg(e.to_string()));
+                    Err(e) => {
+                        console::log!("error deleting comment: {}", e);
+                        ctx.props()
+                            .send_to_app
+                            .emit(AppMsg::ErrorMsg(format!("Error deleting comment: {}", e)));
+                    }
                 }
-            },
+            }
         }
         true
     }
 }
 
 impl PartComponent {
-    fn render_details(&self, ctx: &Context<Self>, part: &Part) -> Html {
-        let toggle_editing = ctx.link().callback(|_| PartMsg::ToggleEditing);
+    // Sends the mutation (see Requests::send), keeping the part's row mounted until it's finished
+    // so that scrolling it out of view doesn't cancel it
+    fn mutate<F>(&mut self, ctx: &Context<Self>, key: PartRequest, fut: F) -> bool
+    where
+        F: Future<Output = PartMsg> + 'static,
+    {
+        if !self.requests.send(ctx.link(), key, fut) {
+            return false;
+        }
+        let id = ctx.props().id;
+        ctx.props().parts_maps.borrow_mut().pending.insert(id);
+        true
+    }
 
-        let id = part.id;
-        let parts_maps = Rc::clone(&ctx.props().parts_maps);
-        let jwt = Rc::clone(&ctx.props().jwt);
-        let updates_form_ref = self.updates_form_ref.clone();
-        let link = ctx.link().clone();
-        let send_updates = ctx.link().batch_callback(move |_| {
-            let form = updates_form_ref.cast::<HtmlFormElement>().unwrap();
-            let elems = form.elements();
-            let mut changes = HashMap::new();
-            let part = &parts_maps.borrow().parts[&id];
+    // Finishes the mutation, letting the row be unmounted again once none are pending
+    fn finish(&mut self, ctx: &Context<Self>, key: &PartRequest) {
+        self.requests.finish(key);
+        if !self.requests.any(|key| *key != PartRequest::Activity) {
+            let id = ctx.props().id;
+            ctx.props().parts_maps.borrow_mut().pending.remove(&id);
+        }
+    }
 
-            if let Some(name) = elems.get_with_name("part-name").and_then(value_from_input) {
-                if name != part.name {
-                    changes.insert(String::from("name"), name);
-                }
-            } else {
-                console::log!("missing or invalid name input element");
-                return None;
-            };
-            if let Some(desc) = elems.get_with_name("part-desc").and_then(value_from_area) {
-                if part
-                    .description
-                    .as_ref()
-                    .map(|d| d != &desc)
-                    .unwrap_or(desc.len() != 0)
-                {
-                    changes.insert(String::from("description"), desc);
-                }
-            } else {
-                console::log!("missing or invalid description text area element");
-                return None;
-            };
-            if let Some(dtl) = elems
-                .get_with_name("part-deadline")
-                .and_then(dtl_from_input)
-            {
-                if part
-                    .deadline
-                    .map(|d| d != dtl)
-                    .unwrap_or(dtl != DateTime::<Local>::default())
-                {
-                    changes.insert(String::from("deadline"), dtl.timestamp().to_string());
-                }
-            } else {
-                console::log!("missing or invalid deadline input element");
-                return None;
-            };
-            if let Some(dtl) = elems.get_with_name("part-comp-at").and_then(dtl_from_input) {
-                if part
-                    .completed_at
-                    .map(|d| d != dtl)
-                    .unwrap_or(dtl != DateTime::<Local>::default())
+    // Records the entries in the activity log of the part with the given id. Errors are shown by
+    // the app, since the request outlives the row, which only shows the entries if it's still
+    // showing the part's log.
+    fn record_activity(ctx: &Context<Self>, id: i64, entries: Vec<(ActivityKind, String)>) {
+        let res = send_record_activity(
+            ctx.props().jwt.to_string(),
+            id,
+            entries,
+            ctx.props().owner(),
+        );
+        let send_to_app = ctx.props().send_to_app.clone();
+        ctx.link().send_future_batch(res.map(move |res| match res {
+            Ok(entries) => vec![PartMsg::ActivityRecorded(id, entries)],
+            Err(e) => {
+                send_to_app.emit(AppMsg::ActivityRecorded(Err(e)));
+                Vec::new()
+            }
+        }));
+    }
+
+    // Lists who the (project) part is shared with, with a form to share it with others
+    fn render_sharing(&self, ctx: &Context<Self>, part: &Part) -> Html {
+        let parts_maps = ctx.props().parts_maps.borrow();
+        let share = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            PartMsg::Share
+        });
+        html! {
+            <div class="sharing-div">
+                <u>{ "Shared With" }</u>{ ":" }
+                <ul>
                 {
-                    changes.insert(String::from("completed_at"), dtl.timestamp().to_string());
+                    parts_maps.project_members(part.id).into_iter().map(|m| {
+                        let member = m.member.clone();
+                        let pending = self.requests.is_pending(&PartRequest::Unshare(member.clone()));
+                        let unshare = ctx.link().callback(move |_| PartMsg::Unshare(member.clone()));
+                        html! {
+                            <li key={m.member.clone()}>
+                                { format!("{} ({})", m.member, m.role) }
+                                <button onclick={unshare} disabled={pending}>{ "X" }</button>
+                            </li>
+                        }
+                    }).collect::<Html>()
                 }
-            } else {
-                console::log!("missing or invalid comp-at input element");
+                </ul>
+                <form ref={self.share_form_ref.clone()} onsubmit={share}>
+                    <input name="share-email" type="email" placeholder="Email" />
+                    <select name="share-role">
+                        <option value="viewer">{ "Viewer" }</option>
+                        <option value="editor">{ "Editor" }</option>
+                    </select>
+                    { submit_button(
+                        "Share",
+                        None,
+                        self.requests.is_pending(&PartRequest::Share),
+                    ) }
+                </form>
+            </div>
+        }
+    }
+
+    // Renders the archiving controls of the (own) project, which can only be archived once
+    // completed
+    fn render_archiving(&self, ctx: &Context<Self>, part: &Part) -> Html {
+        let pending = self.requests.is_pending(&PartRequest::Archive);
+        match part.archived_at {
+            Some(archived_at) => html! {
+                <p>
+                    { format!("Archived at {} ", archived_at.format("%H:%M %b %d, %Y")) }
+                    <button
+                        onclick={ctx.link().callback(|_| PartMsg::SetArchived(false))}
+                        disabled={pending}
+                    >{ "Unarchive" }</button>
+                </p>
+            },
+            None if part.completed_at.is_some() => html! {
+                <p>
+                    <button
+                        onclick={ctx.link().callback(|_| PartMsg::SetArchived(true))}
+                        disabled={pending}
+                    >{ "Archive" }</button>
+                </p>
+            },
+            None => html! {},
+        }
+    }
+
+    fn render_details(&self, ctx: &Context<Self>, part: &Part) -> Html {
+        let toggle_editing = ctx.link().callback(|_| PartMsg::ToggleEditing);
+
+        let can_edit = ctx.props().can_edit();
+        let toggle_task = ctx.link().batch_callback(move |e: MouseEvent| {
+            let input = e.target_dyn_into::<HtmlInputElement>()?;
+            if !can_edit {
+                // Keep viewers' checkboxes as they are
+                e.prevent_default();
                 return None;
-            };
-            if changes.len() != 0 {
-                let res = send_update_part(
-                    jwt.to_string(),
-                    UpdatePartVars {
-                        id: id.to_string(),
-                        changes,
-                    },
-                );
-                link.send_future(res.map(PartMsg::UpdatePart));
             }
-            Some(PartMsg::ToggleEditing)
+            let index = input.get_attribute("data-task-index")?.parse().ok()?;
+            Some(PartMsg::ToggleTask(index))
         });
+        let send_updates = ctx.link().callback(PartMsg::SendUpdates);
 
+        let confirm_delete = ctx.link().callback(|_| PartMsg::ConfirmDelete);
         let id = part.id;
-        let parts_maps = Rc::clone(&ctx.props().parts_maps);
-        let jwt = Rc::clone(&ctx.props().jwt);
-        let link = ctx.link().clone();
-        let confirm_delete = ctx.link().batch_callback(move |_| {
-            let res = web_sys::window()
-                .expect("no window")
-                .confirm_with_message(&format!(
-                    "Delete the following project/part and ALL its children?\n{}",
-                    parts_maps.borrow().parts[&id].name,
-                ));
-            match res {
-                Ok(true) => {
-                    let res =
-                        send_delete_part(jwt.to_string(), DeletePartVars { id: id.to_string() });
-                    link.send_future(res.map(PartMsg::DeletePart));
-                    return Some(PartMsg::ToggleEditing);
-                }
-                Ok(false) => (),
-                Err(e) => console::log!("error confirming delete: {:?}", e),
-            }
-            None
-        });
+        let send_to_app = ctx.props().send_to_app.clone();
+        let duplicate = Callback::from(move |_| send_to_app.emit(AppMsg::Duplicate(id)));
+        let send_to_app = ctx.props().send_to_app.clone();
+        let save_template = Callback::from(move |_| send_to_app.emit(AppMsg::SaveTemplate(id)));
         html! {
-            <div ref={self.details_div_ref.clone()} hidden=true>
+            <div hidden={!ctx.props().parts_maps.borrow().tree.details.contains(&part.id)}>
                 if !self.editing {
-                    <button onclick={toggle_editing}>{ "Edit" }</button>
+                    if can_edit {
+                        <button onclick={toggle_editing}>{ "Edit" }</button>
+                    }
+                    if ctx.props().shared.is_none() {
+                        <button onclick={duplicate}>{ "Duplicate" }</button>
+                        <button onclick={save_template}>{ "Save as Template" }</button>
+                    }
                     <p>
                         <u>{ "Name" }</u>{ format!(": {}", part.name) }<br />
 
                         <u>{ "Description" }</u>{ ":" }<br />
-                        if let Some(desc) = part.description.as_ref() {
-                            { desc }<br />
-                        }
-
+                    </p>
+                    if let Some(desc) = part.description.as_ref() {
+                        <div class="markdown" onclick={toggle_task}>
+                            { Html::from_html_unchecked(AttrValue::from(markdown::render(desc))) }
+                        </div>
+                    }
+                    <p>
                         <u>{ "Deadline" }</u>
                         { format!(
                             ": {}",
@@ -1137,161 +5557,608 @@ impl PartComponent {
                             part.completed_at_to_string(),
                         )}<br />
                     </p>
+                    { self.render_blockers(ctx, part) }
+                    { self.render_activity(ctx) }
+                    if ctx.props().shared.is_none() {
+                        { self.render_time_entries(ctx, part) }
+                        if part.parent_id.is_none() {
+                            { self.render_archiving(ctx, part) }
+                            { self.render_sharing(ctx, part) }
+                        }
+                    }
                 } else {
-                    <button onclick={send_updates} style="margin: 10px">{ "Save" }</button>
-                    <button onclick={toggle_editing}>{ "Cancel" }</button>
-                    <button class="delete-button" onclick={confirm_delete}>{ "Delete" }</button>
-                    <form ref={self.updates_form_ref.clone()}>
-                        <label for="part-name"><u>{ "Name" }</u>{ ": " }</label>
-                        <input
-                            name="part-name"
-                            type="text"
-                            placeholder="Name"
-                            value={part.name.clone()}
-                            required=true
-                        /><br />
-
-                        <label for="part-desc"><u>{ "Description" }</u>{ ":" }</label><br />
-                        <textarea name="part-desc" placeholder="Description">
-                            { part.description.clone().unwrap_or_default() }
-                        </textarea>
-                        <br />
-
-                        <label for="part-deadline"><u>{ "Deadline" }</u>{ ": " }</label>
-                        <input
-                            name="part-deadline"
-                            type="datetime-local"
-                            value={part.deadline_to_input()}
-                        /><br />
-
-                        <label for="part-comp-at"><u>{ "Completed At" }</u>{ ": " }</label>
-                        <input
-                            name="part-comp-at"
-                            type="datetime-local"
-                            value={part.completed_at_to_input()}
-                        /><br />
-                    </form>
+                    <button
+                        class="delete-button"
+                        onclick={confirm_delete}
+                        disabled={self.requests.is_pending(&PartRequest::Delete)}
+                    >{ "Delete" }</button>
+                    <PartForm
+                        part={part.clone()}
+                        parents={ctx.props().parts_maps.borrow().parent_options(Some(part.id))}
+                        assignees={ctx.props().parts_maps.borrow().assignees(Some(part.id))}
+                        submit_label="Save"
+                        pending={self.requests.is_pending(&PartRequest::Update)}
+                        onsubmit={send_updates}
+                        oncancel={ctx.link().callback(|_| PartMsg::ToggleEditing)}
+                    />
                 }
             </div>
         }
     }
 
-    fn render_create_part(&self, ctx: &Context<Self>) -> Html {
-        let id = ctx.props().id;
-        let send_to_app = Rc::clone(&ctx.props().send_to_app);
-        let link = ctx.link().clone();
-        let jwt = Rc::clone(&ctx.props().jwt);
-        let create_form_ref = self.create_form_ref.clone();
-        let send_create = ctx.link().batch_callback(move |_| {
-            let form = create_form_ref.cast::<HtmlFormElement>().unwrap();
-            let elems = form.elements();
-            let mut part = Part::default();
+    fn render_blockers(&self, ctx: &Context<Self>, part: &Part) -> Html {
+        let parts_maps = ctx.props().parts_maps.borrow();
+        let candidates = dep_graph::blocker_candidates(&parts_maps.parts, part.id);
+        // The candidates may have changed since the page was picked
+        let pages = candidates.len().div_ceil(BLOCKER_PAGE_SIZE);
+        let page = self.blocker_page.min(pages.saturating_sub(1));
+        let start = page * BLOCKER_PAGE_SIZE;
+        let end = candidates.len().min(start + BLOCKER_PAGE_SIZE);
+        let prev_page = ctx
+            .link()
+            .callback(move |_| PartMsg::BlockerPage(page.saturating_sub(1)));
+        let next_page = ctx.link().callback(move |_| PartMsg::BlockerPage(page + 1));
+        let can_edit = ctx.props().can_edit();
+        let pending = self.requests.is_pending(&PartRequest::Blockers);
 
-            if let Some(name) = elems.get_with_name("part-name").and_then(value_from_input) {
-                if name.trim() == "" {
-                    // TODO: Display error
-                    send_to_app.emit(AppMsg::ErrorMsg(String::from("Must provide name")));
-                    return None;
-                }
-                part.name = name;
-            } else {
-                console::log!("missing or invalid name input element");
-                send_to_app.emit(AppMsg::ErrorMsg(String::from("Bad form")));
-                return None;
-            };
-            if let Some(desc) = elems.get_with_name("part-desc").and_then(value_from_area) {
-                if desc.trim() != "" {
-                    part.description = Some(desc);
+        let blocker_select_ref = self.blocker_select_ref.clone();
+        let add_blocker = ctx.link().batch_callback(move |_| {
+            let select = blocker_select_ref.cast::<HtmlSelectElement>().unwrap();
+            select.value().parse().ok().map(PartMsg::AddBlocker)
+        });
+        html! {
+            <div class="blockers-div">
+                <u>{ "Blocked By" }</u>{ ":" }
+                <ul>
+                {
+                    part.blocked_by.iter().map(|&bid| {
+                        // The blocker may be in a subtree that hasn't been fetched
+                        let blocker = parts_maps.parts.get(&bid);
+                        let remove_blocker = ctx.link().callback(move |_| PartMsg::RemoveBlocker(bid));
+                        html! {
+                            <li key={bid}>
+                                {
+                                    blocker
+                                        .map(|blocker| blocker.name.clone())
+                                        .unwrap_or_else(|| format!("#{}", bid))
+                                }
+                                if blocker.map(|b| b.completed_at.is_some()).unwrap_or_default() {
+                                    { " (completed)" }
+                                }
+                                if can_edit {
+                                    <button onclick={remove_blocker} disabled={pending}>{ "X" }</button>
+                                }
+                            </li>
+                        }
+                    }).collect::<Html>()
                 }
-            } else {
-                console::log!("missing or invalid description text area element");
-                send_to_app.emit(AppMsg::ErrorMsg(String::from("Bad form")));
-                return None;
-            };
-            if let Some(dtl) = elems
-                .get_with_name("part-deadline")
-                .and_then(dtl_from_input)
-            {
-                // TODO: Do better
-                if dtl != DateTime::<Local>::default() {
-                    part.deadline = Some(dtl);
+                </ul>
+                if can_edit && !candidates.is_empty() {
+                    <select ref={self.blocker_select_ref.clone()}>
+                    {
+                        candidates[start..end].iter().map(|&cid| html! {
+                            <option value={cid.to_string()}>
+                                { format!("{} (#{})", parts_maps.parts[&cid].name, cid) }
+                            </option>
+                        }).collect::<Html>()
+                    }
+                    </select>
+                    if pages > 1 {
+                        <button onclick={prev_page} disabled={page == 0}>{ "<" }</button>
+                        <span class="blocker-page">
+                            { format!("{}-{} of {}", start + 1, end, candidates.len()) }
+                        </span>
+                        <button onclick={next_page} disabled={page + 1 == pages}>{ ">" }</button>
+                    }
+                    <button onclick={add_blocker} disabled={pending}>{ "Add Blocker" }</button>
                 }
-            } else {
-                console::log!("missing or invalid deadline input element");
-                send_to_app.emit(AppMsg::ErrorMsg(String::from("Bad form")));
-                return None;
-            };
-            if let Some(dtl) = elems.get_with_name("part-comp-at").and_then(dtl_from_input) {
-                // TODO: Do better
-                if dtl != DateTime::<Local>::default() {
-                    part.completed_at = Some(dtl);
+            </div>
+        }
+    }
+
+    fn render_time_entries(&self, ctx: &Context<Self>, part: &Part) -> Html {
+        let parts_maps = ctx.props().parts_maps.borrow();
+        let now = Local::now();
+        let add_entry = ctx.link().callback(|_| PartMsg::SendCreateTimeEntry);
+        let deleting = self.requests.is_pending(&PartRequest::DeleteTimeEntry);
+        html! {
+            <div class="time-entries-div">
+                <u>{ "Time Entries" }</u>{ ":" }
+                <ul>
+                {
+                    parts_maps.part_time_entries(part.id).iter().map(|entry| {
+                        let entry_id = entry.id;
+                        let delete_entry =
+                            ctx.link().callback(move |_| PartMsg::SendDeleteTimeEntry(entry_id));
+                        html! {
+                            <li key={entry_id}>
+                                { entry.started_at.format("%H:%M %b %d, %Y").to_string() }
+                                { " - " }
+                                {
+                                    entry.ended_at
+                                        .map(|dt| dt.format("%H:%M %b %d, %Y").to_string())
+                                        .unwrap_or_else(|| String::from("now"))
+                                }
+                                { format!(" ({})", time_tracking::format_duration(entry.duration(now))) }
+                                if !entry.is_running() {
+                                    <button onclick={delete_entry} disabled={deleting}>{ "X" }</button>
+                                }
+                            </li>
+                        }
+                    }).collect::<Html>()
                 }
-            } else {
-                console::log!("missing or invalid comp-at input element");
-                send_to_app.emit(AppMsg::ErrorMsg(String::from("Bad form")));
-                return None;
+                </ul>
+                <form ref={self.time_entry_form_ref.clone()}>
+                    <label for="entry-start">{ "Start: " }</label>
+                    <input name="entry-start" type="datetime-local" />
+                    <label for="entry-end">{ " End: " }</label>
+                    <input name="entry-end" type="datetime-local" />
+                    <input
+                        type="button"
+                        value="Add Entry"
+                        onclick={add_entry}
+                        disabled={self.requests.is_pending(&PartRequest::TimeEntry)}
+                    />
+                </form>
+            </div>
+        }
+    }
+
+    // The part's comments (threaded, with a form to comment) and its activity log as a timeline
+    fn render_activity(&self, ctx: &Context<Self>) -> Html {
+        let Some(activity) = self.activity.as_ref() else {
+            return html! {
+                <p class="loading-p"><span class="spinner"></span>{ "Loading comments..." }</p>
             };
-            part.parent_id = Some(id);
-            //send_to_app.emit(AppMsg::SendCreatePart(part));
-            let res = send_create_part(jwt.to_string(), CreatePartVars { input: part.into() });
-            link.send_future(res.map(PartMsg::CreatePart));
-            Some(PartMsg::ToggleCreating)
+        };
+        let user = ctx.props().user();
+        let replying_to = self
+            .replying_to
+            .and_then(|rid| activity.comments.iter().find(|c| c.id == rid));
+        let send_comment = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            PartMsg::SendComment
         });
+        let deleting = self.requests.is_pending(&PartRequest::DeleteComment);
+        html! {
+            <div class="activity-div">
+                <u>{ "Comments" }</u>{ ":" }
+                <ul class="comments-list">
+                {
+                    activity::thread(&activity.comments).into_iter().map(|(depth, comment)| {
+                        let comment_id = comment.id;
+                        let reply = ctx.link().callback(move |_| PartMsg::ReplyTo(Some(comment_id)));
+                        let delete = ctx.link().callback(move |_| PartMsg::ConfirmDeleteComment(comment_id));
+                        html! {
+                            <li
+                                key={comment_id}
+                                class="comment"
+                                style={format!("margin-left: {}px", depth * 20)}
+                            >
+                                <span class="comment-author">{ &comment.author }</span>
+                                <span class="comment-time">
+                                    { comment.created_at.format("%H:%M %b %d, %Y").to_string() }
+                                </span>
+                                <div class="markdown">
+                                    { Html::from_html_unchecked(AttrValue::from(markdown::render(&comment.body))) }
+                                </div>
+                                <button onclick={reply}>{ "Reply" }</button>
+                                if user.as_ref() == Some(&comment.author) {
+                                    <button onclick={delete} disabled={deleting}>{ "Delete" }</button>
+                                }
+                            </li>
+                        }
+                    }).collect::<Html>()
+                }
+                </ul>
+                <form class="comment-form" onsubmit={send_comment}>
+                    if let Some(comment) = replying_to {
+                        <span>{ format!("Replying to {} ", comment.author) }</span>
+                        <button
+                            type="button"
+                            onclick={ctx.link().callback(|_| PartMsg::ReplyTo(None))}
+                        >{ "Cancel" }</button>
+                        <br />
+                    }
+                    <textarea ref={self.comment_ref.clone()} placeholder="Comment (Markdown)" />
+                    <br />
+                    { submit_button(
+                        "Comment",
+                        None,
+                        self.requests.is_pending(&PartRequest::Comment),
+                    ) }
+                </form>
+
+                <u>{ "Activity" }</u>{ ":" }
+                if activity.activity.is_empty() {
+                    <p>{ "No activity recorded" }</p>
+                } else {
+                    <ul class="activity-timeline">
+                    {
+                        activity.activity.iter().rev().map(|entry| html! {
+                            <li key={entry.id}>
+                                <span class="activity-time">
+                                    { entry.created_at.format("%H:%M %b %d, %Y").to_string() }
+                                </span>
+                                { format!(" {}: {}", entry.author, entry.message) }
+                            </li>
+                        }).collect::<Html>()
+                    }
+                    </ul>
+                }
+            </div>
+        }
+    }
 
+    fn render_create_part(&self, ctx: &Context<Self>) -> Html {
+        let send_create = ctx.link().callback(PartMsg::SendCreatePart);
         let toggle_creating = ctx.link().callback(|_| PartMsg::ToggleCreating);
         html! {
-            <>
             if self.creating {
-                // TODO: Make more reusable (see other instances of this form)
                 <li>
-                <button onclick={send_create} style="margin:10px">{ "Create" }</button>
-                <button onclick={toggle_creating}>{ "Cancel" }</button>
-                <form ref={self.create_form_ref.clone()}>
-                    <label for="part-name"><u>{ "Name" }</u>{ ": " }</label>
-                    <input
-                        name="part-name"
-                        type="text"
-                        placeholder="Name"
-                        required=true
-                    /><br />
-
-                    <label for="part-desc"><u>{ "Description" }</u>{ ":" }</label><br />
-                    <textarea name="part-desc" placeholder="Description">
-                    </textarea>
-                    <br />
-
-                    <label for="part-deadline"><u>{ "Deadline" }</u>{ ": " }</label>
-                    <input
-                        name="part-deadline"
-                        type="datetime-local"
-                    /><br />
-
-                    <label for="part-comp-at"><u>{ "Completed At" }</u>{ ": " }</label>
-                    <input
-                        name="part-comp-at"
-                        type="datetime-local"
-                    /><br />
-                </form>
+                    <PartForm
+                        parent_id={Some(ctx.props().id)}
+                        parents={ctx.props().parts_maps.borrow().parent_options(None)}
+                        assignees={ctx.props().parts_maps.borrow().assignees(Some(ctx.props().id))}
+                        submit_label="Create"
+                        pending={self.requests.is_pending(&PartRequest::Create)}
+                        onsubmit={send_create}
+                        oncancel={toggle_creating}
+                    />
                 </li>
             }
-            </>
         }
     }
 }
 
+// A form's submit button, which is disabled (showing a spinner) while what it submitted is pending
+fn submit_button(label: &'static str, class: Option<&'static str>, pending: bool) -> Html {
+    html! {
+        <button type="submit" class={classes!(class)} disabled={pending}>
+            if pending {
+                <span class="spinner"></span>
+            }
+            { label }
+        </button>
+    }
+}
+
+// The initials badge of the user a part is assigned to
+fn assignee_badge(email: &str) -> Html {
+    html! {
+        <span
+            class="assignee-badge"
+            title={format!("Assigned to {}", email)}
+            style={format!("background-color: hsl({}, 55%, 45%)", avatar::hue(email))}
+        >{ avatar::initials(email) }</span>
+    }
+}
+
 fn value_from_input(elem: Element) -> Option<String> {
     elem.dyn_into::<HtmlInputElement>()
         .ok()
         .map(|input| input.value())
 }
 
-fn value_from_area(elem: Element) -> Option<String> {
-    elem.dyn_into::<HtmlTextAreaElement>()
+// Returns the ids of the parts displayed between (and including) the two parts, in the order
+// they're displayed. Returns just the end if either isn't displayed.
+fn displayed_range(start: i64, end: i64) -> Vec<i64> {
+    let document = web_sys::window()
+        .expect("no window")
+        .document()
+        .expect("no document");
+    let Ok(checkboxes) = document.query_selector_all("input.part-select") else {
+        return vec![end];
+    };
+    let ids = (0..checkboxes.length())
+        .filter_map(|i| checkboxes.get(i)?.dyn_into::<Element>().ok())
+        .filter_map(|elem| elem.get_attribute("data-part-id")?.parse().ok())
+        .collect::<Vec<i64>>();
+    let start_pos = ids.iter().position(|&id| id == start);
+    let end_pos = ids.iter().position(|&id| id == end);
+    match (start_pos, end_pos) {
+        (Some(i), Some(j)) => ids[i.min(j)..=i.max(j)].to_vec(),
+        _ => vec![end],
+    }
+}
+
+// Returns whether the event's target is an element that handles text editing (and its own
+// undo/redo)
+fn target_is_text_input(e: &KeyboardEvent) -> bool {
+    let Some(target) = e.target() else {
+        return false;
+    };
+    if target.dyn_ref::<HtmlTextAreaElement>().is_some() {
+        return true;
+    }
+    if let Some(input) = target.dyn_ref::<HtmlInputElement>() {
+        return !matches!(
+            input.type_().as_str(),
+            "checkbox" | "radio" | "button" | "submit" | "reset"
+        );
+    }
+    target
+        .dyn_ref::<HtmlElement>()
+        .map(|elem| elem.is_content_editable())
+        .unwrap_or_default()
+}
+
+// Returns the part to create from the parsed quick-add input or why it can't be created
+fn quick_add_part(parsed: &QuickAdd) -> Result<Part, String> {
+    if parsed.name.trim().is_empty() {
+        return Err(String::from("Name is required"));
+    }
+    let parent_id = match parsed.parent.as_ref() {
+        None => None,
+        Some(ParentMatch::Found(id)) => Some(*id),
+        Some(_) => return Err(String::from("Parent must match a single part")),
+    };
+    let deadline = match parsed.deadline {
+        Some(ndt) => Some(
+            Local
+                .from_local_datetime(&ndt)
+                .earliest()
+                .ok_or_else(|| String::from("Deadline doesn't exist in the local time zone"))?,
+        ),
+        None => None,
+    };
+    let mut tags = parsed.tags.clone();
+    tags.sort();
+    tags.dedup();
+    Ok(Part {
+        name: parsed.name.clone(),
+        deadline,
+        parent_id,
+        tags,
+        ..Default::default()
+    })
+}
+
+// Returns the message asking whether to complete the part anyway if it's still blocked (None if
+// it isn't)
+fn blocked_completion_msg(parts_maps: &PartsMaps, id: i64) -> Option<String> {
+    let open_blockers = dep_graph::open_blockers(&parts_maps.parts, id);
+    if open_blockers.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "This part is still blocked by the following incomplete parts:\n{}\nComplete it anyway?",
+        open_blockers
+            .iter()
+            .map(|bid| parts_maps.parts[bid].name.clone())
+            .collect::<Vec<_>>()
+            .join("\n"),
+    ))
+}
+
+// Returns whether the event's target is a control that handles the navigation keys itself
+fn target_is_control(e: &KeyboardEvent) -> bool {
+    let Some(target) = e.target() else {
+        return false;
+    };
+    target.dyn_ref::<HtmlSelectElement>().is_some()
+        || (e.key() == "Enter"
+            && (target.dyn_ref::<HtmlButtonElement>().is_some()
+                || target.dyn_ref::<HtmlInputElement>().is_some()))
+}
+
+// Returns the top and bottom of the viewport relative to the top of the part tree
+fn tree_viewport(list_ref: &NodeRef) -> (f64, f64) {
+    let Some(list) = list_ref.cast::<Element>() else {
+        return (0.0, 0.0);
+    };
+    let height = web_sys::window()
+        .expect("no window")
+        .inner_height()
         .ok()
-        .map(|area| area.value())
+        .and_then(|height| height.as_f64())
+        .unwrap_or_default();
+    let top = -list.get_bounding_client_rect().top();
+    (top, top + height)
+}
+
+// Scrolls the focused part's name into view if it isn't already
+fn scroll_to_focused() {
+    let document = web_sys::window()
+        .expect("no window")
+        .document()
+        .expect("no document");
+    if let Ok(Some(elem)) = document.query_selector(".part-focused > .part-name") {
+        let mut options = ScrollIntoViewOptions::new();
+        options.block(ScrollLogicalPosition::Nearest);
+        elem.scroll_into_view_with_scroll_into_view_options(&options);
+    }
+}
+
+// Returns the message summarizing the failures of a batch operation (None if there were none)
+fn batch_failures_msg(action: &str, total: usize, failures: &[String]) -> Option<String> {
+    if failures.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "Error {} {} of {} parts: {}",
+        action,
+        failures.len(),
+        total,
+        failures.join("; "),
+    ))
+}
+
+// Prompts the browser to download a file with the given contents
+fn download_file(filename: &str, mime: &str, contents: &str) -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .expect("no window")
+        .document()
+        .expect("no document");
+    let anchor = document.create_element("a")?.dyn_into::<HtmlElement>()?;
+    let href = format!(
+        "data:{};charset=utf-8,{}",
+        mime,
+        js_sys::encode_uri_component(contents),
+    );
+    anchor.set_attribute("href", &href)?;
+    anchor.set_attribute("download", filename)?;
+    anchor.click();
+    Ok(())
 }
 
 // TODO: Do better
 fn dtl_from_input(elem: Element) -> Option<DateTime<Local>> {
     value_from_input(elem).map(|val| Part::dtl_from_input(val.as_str()).unwrap_or_default())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part(id: i64, parent_id: Option<i64>) -> Part {
+        Part {
+            id,
+            name: format!("Part {}", id),
+            parent_id,
+            ..Default::default()
+        }
+    }
+
+    fn upserted(part: Part) -> PartEvent {
+        PartEvent::Upserted(Box::new(part))
+    }
+
+    // A project (1) with a child (2) that has a child (3)
+    fn parts_maps() -> PartsMaps {
+        PartsMaps::from_parts(vec![part(1, None), part(2, Some(1)), part(3, Some(2))])
+    }
+
+    #[test]
+    fn applies_events_once() {
+        let mut parts_maps = parts_maps();
+        assert!(parts_maps.apply_event(upserted(part(4, Some(1)))));
+        let applied = parts_maps.parts.clone();
+        assert!(!parts_maps.apply_event(upserted(part(4, Some(1)))));
+        assert_eq!(parts_maps.parts, applied);
+        assert_eq!(parts_maps.fams[&1], vec![2, 4]);
+
+        assert!(parts_maps.apply_event(PartEvent::Deleted(4)));
+        assert!(!parts_maps.apply_event(PartEvent::Deleted(4)));
+        assert_eq!(parts_maps.fams[&1], vec![2]);
+    }
+
+    #[test]
+    fn ignores_already_applied_changes() {
+        let mut parts_maps = parts_maps();
+        let mut renamed = part(2, Some(1));
+        renamed.name = String::from("Renamed");
+        parts_maps.update_part(renamed.clone()).unwrap();
+        assert!(!parts_maps.apply_event(upserted(renamed.clone())));
+        assert_eq!(parts_maps.parts[&2], renamed);
+
+        parts_maps.delete_part(3);
+        assert!(!parts_maps.apply_event(PartEvent::Deleted(3)));
+        assert!(!parts_maps.parts.contains_key(&3));
+    }
+
+    #[test]
+    fn applies_updates_and_moves() {
+        let mut parts_maps = parts_maps();
+        let mut moved = part(3, Some(1));
+        moved.name = String::from("Moved");
+        assert!(parts_maps.apply_event(upserted(moved.clone())));
+        assert_eq!(parts_maps.parts[&3], moved);
+        assert_eq!(parts_maps.fams[&1], vec![2, 3]);
+        assert!(parts_maps.fams[&2].is_empty());
+    }
+
+    #[test]
+    fn applies_part_trees() {
+        // Only the first page of projects has been fetched
+        let mut parts_maps = PartsMaps::default();
+        let mut project = part(1, None);
+        project.child_count = 1;
+        parts_maps.insert_part(project.clone());
+        parts_maps.loaded.insert(None, Loaded::Fully);
+        assert!(!parts_maps.tree_loaded(1));
+        assert!(!parts_maps.tree_loaded(3));
+
+        let mut child = part(2, Some(1));
+        child.child_count = 1;
+        parts_maps.apply_tree(3, vec![project, child, part(3, Some(2))]);
+        assert!(parts_maps.tree_loaded(3));
+        assert_eq!(parts_maps.fams[&2], vec![3]);
+        // The ancestors' other children may not have been fetched
+        assert!(!parts_maps.tree_loaded(1));
+        assert!(parts_maps.has_unfetched_children(2));
+
+        parts_maps.apply_tree(1, vec![part(1, None), part(2, Some(1)), part(3, Some(2))]);
+        assert!(parts_maps.tree_loaded(1));
+        assert!(!parts_maps.has_unfetched_children(1));
+        assert!(!parts_maps.has_unfetched_children(2));
+        assert_eq!(parts_maps.projects, vec![1]);
+    }
+
+    #[test]
+    fn expands_children_as_fetched() {
+        // Only the project has been fetched
+        let mut parts_maps = PartsMaps::default();
+        let mut project = part(1, None);
+        project.child_count = 1;
+        parts_maps.insert_part(project);
+        parts_maps.loaded.insert(None, Loaded::Fully);
+        parts_maps.expand_to_depth(Some(2));
+        assert!(parts_maps.tree.expanded.contains(&1));
+        assert_eq!(parts_maps.unfetched_shown(), vec![Some(1)]);
+
+        let mut child = part(2, Some(1));
+        child.child_count = 1;
+        let page = ChildrenPage {
+            parts: vec![child],
+            next: None,
+            sync_cursor: String::from("1"),
+        };
+        parts_maps.apply_page(Some(1), page);
+        parts_maps.expand_children(Some(1), Some(2));
+        assert!(parts_maps.tree.expanded.contains(&2));
+        let page = ChildrenPage {
+            parts: vec![part(3, Some(2))],
+            next: None,
+            sync_cursor: String::from("1"),
+        };
+        parts_maps.apply_page(Some(2), page);
+        parts_maps.expand_children(Some(2), Some(2));
+        assert!(!parts_maps.tree.expanded.contains(&3));
+        assert!(parts_maps.unfetched_shown().is_empty());
+    }
+
+    #[test]
+    fn fetches_blockers_with_their_ancestors() {
+        let mut parts_maps = parts_maps();
+        parts_maps.parts.get_mut(&3).unwrap().blocked_by = vec![2, 5];
+        assert_eq!(parts_maps.missing_blockers([1, 3]), BTreeSet::from([5]));
+
+        // The blocker's parent's other children haven't been fetched
+        let mut project = part(4, None);
+        project.child_count = 2;
+        parts_maps.apply_parts(vec![project, part(5, Some(4))]);
+        assert!(parts_maps.missing_blockers([3]).is_empty());
+        assert_eq!(parts_maps.fams[&4], vec![5]);
+        assert!(parts_maps.has_unfetched_children(4));
+    }
+
+    #[test]
+    fn ignores_out_of_order_events() {
+        let mut parts_maps = parts_maps();
+        // A child of a part that hasn't been created yet is fetched with the part instead
+        assert!(!parts_maps.apply_event(upserted(part(5, Some(4)))));
+        assert!(!parts_maps.parts.contains_key(&5));
+        let mut parent = part(4, Some(1));
+        parent.child_count = 1;
+        assert!(parts_maps.apply_event(upserted(parent)));
+        assert!(parts_maps.has_unfetched_children(4));
+
+        // Changes to the descendants of a deleted part are dropped with it
+        assert!(parts_maps.apply_event(PartEvent::Deleted(2)));
+        assert!(!parts_maps.parts.contains_key(&3));
+        assert!(!parts_maps.apply_event(upserted(part(3, Some(2)))));
+        assert!(!parts_maps.apply_event(PartEvent::Deleted(3)));
+        assert!(!parts_maps.parts.contains_key(&3));
+    }
+}
@@ -0,0 +1,53 @@
+// The badges standing in for users (e.g., a part's assignee): their initials on a color picked
+// from their email, so the same user always looks the same.
+
+// Returns up to two initials from the local part of the email, split on '.', '_', '-' and '+'
+// (e.g., "JD" for jane.doe@example.com). Emails without separators give their first two letters.
+pub fn initials(email: &str) -> String {
+    let local = email.split('@').next().unwrap_or_default();
+    let words = local
+        .split(['.', '_', '-', '+'])
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>();
+    let initials = match words.as_slice() {
+        [] => String::new(),
+        [word] => word.chars().take(2).collect(),
+        [first, .., last] => first.chars().take(1).chain(last.chars().take(1)).collect(),
+    };
+    initials.to_uppercase()
+}
+
+// Returns the hue (0 to 359) of the badge of the user with the given email
+pub fn hue(email: &str) -> u32 {
+    // FNV-1a, since the hue must be the same across sessions
+    let hash = email.to_lowercase().bytes().fold(0x811c9dc5u32, |hash, b| {
+        (hash ^ b as u32).wrapping_mul(0x01000193)
+    });
+    hash % 360
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initials_of_words() {
+        assert_eq!(initials("jane.doe@example.com"), "JD");
+        assert_eq!(initials("jane_mary-doe@example.com"), "JD");
+        assert_eq!(initials("j.doe+work@example.com"), "JW");
+    }
+
+    #[test]
+    fn initials_of_single_word() {
+        assert_eq!(initials("jane@example.com"), "JA");
+        assert_eq!(initials("j@example.com"), "J");
+        assert_eq!(initials("jane"), "JA");
+        assert_eq!(initials(".@example.com"), "");
+    }
+
+    #[test]
+    fn hue_ignores_case() {
+        assert_eq!(hue("Jane@Example.com"), hue("jane@example.com"));
+        assert!(hue("jane@example.com") < 360);
+    }
+}
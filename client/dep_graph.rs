@@ -0,0 +1,136 @@
+// Helpers for the "blocked by" relation between parts. The relation is stored on each part as
+// the list of ids of the parts blocking it, so edges point from a part to its blockers.
+use crate::graphql::Part;
+use std::collections::{BTreeMap, BTreeSet};
+
+type PartsMap = BTreeMap<i64, Part>;
+
+// Returns whether making the part with the given id blocked by blocker_id would create a cycle,
+// i.e., the blocker is the part itself or is already (transitively) blocked by the part
+pub fn would_create_cycle(parts: &PartsMap, id: i64, blocker_id: i64) -> bool {
+    let mut seen = BTreeSet::new();
+    let mut stack = vec![blocker_id];
+    while let Some(cur) = stack.pop() {
+        if cur == id {
+            return true;
+        }
+        if !seen.insert(cur) {
+            continue;
+        }
+        if let Some(part) = parts.get(&cur) {
+            stack.extend(part.blocked_by.iter().copied());
+        }
+    }
+    false
+}
+
+// Returns the ids of the incomplete parts blocking the part with the given id
+pub fn open_blockers(parts: &PartsMap, id: i64) -> Vec<i64> {
+    parts
+        .get(&id)
+        .map(|part| {
+            part.blocked_by
+                .iter()
+                .copied()
+                .filter(|bid| {
+                    parts
+                        .get(bid)
+                        .map(|b| b.completed_at.is_none())
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Returns whether any of the part's blockers are incomplete
+pub fn is_blocked(parts: &PartsMap, id: i64) -> bool {
+    !open_blockers(parts, id).is_empty()
+}
+
+// Returns whether the part can be worked on (it's incomplete and unblocked)
+pub fn is_actionable(parts: &PartsMap, part: &Part) -> bool {
+    part.completed_at.is_none() && !is_blocked(parts, part.id)
+}
+
+// Returns the ids of the parts that can be added as blockers of the part with the given id
+pub fn blocker_candidates(parts: &PartsMap, id: i64) -> Vec<i64> {
+    let Some(part) = parts.get(&id) else {
+        return Vec::new();
+    };
+    parts
+        .keys()
+        .copied()
+        .filter(|&bid| !part.blocked_by.contains(&bid) && !would_create_cycle(parts, id, bid))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+
+    fn part(id: i64, blocked_by: Vec<i64>, completed: bool) -> Part {
+        Part {
+            id,
+            name: format!("Part {}", id),
+            blocked_by,
+            completed_at: completed.then(Local::now),
+            ..Default::default()
+        }
+    }
+
+    // 1 is blocked by 2, which is blocked by 3 (completed), and 4 is unrelated
+    fn parts() -> PartsMap {
+        [
+            part(1, vec![2], false),
+            part(2, vec![3], false),
+            part(3, Vec::new(), true),
+            part(4, Vec::new(), false),
+        ]
+        .into_iter()
+        .map(|part| (part.id, part))
+        .collect()
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let parts = parts();
+        // Blocking itself
+        assert!(would_create_cycle(&parts, 4, 4));
+        // Directly, since 1 is blocked by 2
+        assert!(would_create_cycle(&parts, 2, 1));
+        // Transitively through 2
+        assert!(would_create_cycle(&parts, 3, 1));
+        assert!(!would_create_cycle(&parts, 1, 4));
+        assert!(!would_create_cycle(&parts, 4, 1));
+        // Blockers that aren't loaded can't be followed
+        assert!(!would_create_cycle(&parts, 1, 5));
+    }
+
+    #[test]
+    fn lists_open_blockers() {
+        let mut parts = parts();
+        assert_eq!(open_blockers(&parts, 1), vec![2]);
+        assert!(is_blocked(&parts, 1));
+        // Completed blockers don't block
+        assert!(open_blockers(&parts, 2).is_empty());
+        assert!(!is_blocked(&parts, 2));
+        assert!(open_blockers(&parts, 5).is_empty());
+
+        parts.get_mut(&2).unwrap().completed_at = Some(Local::now());
+        assert!(open_blockers(&parts, 1).is_empty());
+        assert!(is_actionable(&parts, &parts[&1]));
+        assert!(!is_actionable(&parts, &parts[&2]));
+    }
+
+    #[test]
+    fn lists_blocker_candidates() {
+        let parts = parts();
+        // Not itself, its current blocker (2) or what it would form a cycle with
+        assert_eq!(blocker_candidates(&parts, 1), vec![3, 4]);
+        assert_eq!(blocker_candidates(&parts, 3), vec![4]);
+        assert_eq!(blocker_candidates(&parts, 4), vec![1, 2, 3]);
+        assert!(blocker_candidates(&parts, 5).is_empty());
+    }
+}
@@ -1,12 +1,67 @@
 use self::{
+    add_blocker_mutation::{AddBlockerMutationAddBlocker, Variables as AddBlockerVars},
+    add_comment_mutation::{AddCommentMutationAddComment, Variables as AddCommentVars},
+    assigned_parts_query::{
+        AssignedPartsQueryAssignedParts, AssignedPartsQueryAssignedPartsPart,
+        Variables as AssignedPartsVars,
+    },
+    batch_delete_parts_mutation::Variables as BatchDeletePartsVars,
+    batch_update_parts_mutation::{
+        BatchUpdatePartsMutationBatchUpdatePartsPart, Variables as BatchUpdatePartsVars,
+    },
+    burndown_query::{BurndownQueryBurndown, Variables as BurndownVars},
+    change_email_mutation::{ChangeEmailInput, Variables as ChangeEmailVars},
+    change_password_mutation::{ChangePasswordInput, Variables as ChangePasswordVars},
+    children_query::{
+        ChildrenQueryChildren, ChildrenQueryChildrenEdgesNode, Variables as ChildrenVars,
+    },
     create_part_mutation::{
         CreatePartInput, CreatePartMutationCreatePart, Variables as CreatePartVars,
     },
+    create_time_entry_mutation::{
+        CreateTimeEntryMutationCreateTimeEntry, Variables as CreateTimeEntryVars,
+    },
     create_user_mutation::Variables as CreateUserVars,
+    delete_account_mutation::Variables as DeleteAccountVars,
+    delete_comment_mutation::Variables as DeleteCommentVars,
     delete_part_mutation::Variables as DeletePartVars,
+    delete_time_entry_mutation::Variables as DeleteTimeEntryVars,
     login_user_mutation::Variables as LoginUserVars,
     logout_user_mutation::Variables as LogoutUserVars,
+    memberships_query::{MembershipsQueryMemberships, Variables as MembershipsVars},
+    part_activity_query::{
+        PartActivityQueryActivity, PartActivityQueryComments, Variables as PartActivityVars,
+    },
+    part_events_subscription::{
+        PartEventKind, PartEventsSubscriptionPartEvents, PartEventsSubscriptionPartEventsPart,
+    },
+    part_tree_query::{PartTreeQueryPartTree, Variables as PartTreeVars},
+    parts_by_id_query::{PartsByIdQueryPartsById, Variables as PartsByIdVars},
     parts_query::{PartsQueryParts, Variables as PartsVars},
+    parts_since_query::{
+        PartsSinceQueryPartsSince, PartsSinceQueryPartsSinceParts, Variables as PartsSinceVars,
+    },
+    purge_part_mutation::Variables as PurgePartVars,
+    record_activity_mutation::{
+        ActivityInput, RecordActivityMutationRecordActivity, Variables as RecordActivityVars,
+    },
+    remove_blocker_mutation::{RemoveBlockerMutationRemoveBlocker, Variables as RemoveBlockerVars},
+    request_password_reset_mutation::Variables as RequestPasswordResetVars,
+    reset_password_mutation::{ResetPasswordInput, Variables as ResetPasswordVars},
+    restore_part_mutation::{RestorePartMutationRestorePart, Variables as RestorePartVars},
+    set_archived_mutation::{SetArchivedMutationSetArchived, Variables as SetArchivedVars},
+    share_project_mutation::{ShareProjectMutationShareProject, Variables as ShareProjectVars},
+    shared_projects_query::{
+        SharedProjectsQuerySharedProjects, SharedProjectsQuerySharedProjectsMembers,
+        SharedProjectsQuerySharedProjectsMembership, SharedProjectsQuerySharedProjectsParts,
+        Variables as SharedProjectsVars,
+    },
+    start_timer_mutation::{StartTimerMutationStartTimer, Variables as StartTimerVars},
+    stats_query::{StatsQueryStats, Variables as StatsVars},
+    stop_timer_mutation::{StopTimerMutationStopTimer, Variables as StopTimerVars},
+    time_entries_query::{TimeEntriesQueryTimeEntries, Variables as TimeEntriesVars},
+    trash_query::{TrashQueryTrash, Variables as TrashVars},
+    unshare_project_mutation::Variables as UnshareProjectVars,
     update_part_mutation::{UpdatePartMutationUpdatePart, Variables as UpdatePartVars},
 };
 use crate::console;
@@ -16,6 +71,7 @@ use graphql_client::{
     reqwest_crate::Client,
     GraphQLQuery,
 };
+use serde::{Deserialize, Serialize};
 
 lazy_static::lazy_static! {
     static ref CLIENT: Client = Client::new();
@@ -29,9 +85,20 @@ lazy_static::lazy_static! {
     )
         .expect("error creating query url")
         .href();
+    // The same endpoint, over a websocket (for subscriptions)
+    pub static ref WS_URL: String = QUERY_URL.replacen("http", "ws", 1);
     pub static ref TZ: FixedOffset = Local::now().offset().fix();
+    // Identifies this instance of the app (e.g., the tab) to the server, so that the part events
+    // caused by its own requests aren't sent back to it over the subscription
+    pub static ref CLIENT_ID: String = format!(
+        "{:x}{:x}",
+        Local::now().timestamp_millis(),
+        (js_sys::Math::random() * u32::MAX as f64) as u32,
+    );
 }
 
+pub const CLIENT_ID_HEADER: &str = "X-Client-Id";
+
 type Id = String;
 
 // TODO: Change to be better representation?
@@ -77,6 +144,56 @@ pub struct LoginUserMutation;
 )]
 pub struct LogoutUserMutation;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct ChangePasswordMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct RequestPasswordResetMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct ResetPasswordMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct ChangeEmailMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct DeleteAccountMutation;
+
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "graph/schema.graphql",
@@ -107,12 +224,303 @@ pub struct UpdatePartMutation;
 )]
 pub struct DeletePartMutation;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct BatchUpdatePartsMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct BatchDeletePartsMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct AddBlockerMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct RemoveBlockerMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/queries.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct TimeEntriesQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct StartTimerMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct StopTimerMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct CreateTimeEntryMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct DeleteTimeEntryMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/subscriptions.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct PartEventsSubscription;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/queries.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct PartsSinceQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/queries.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct ChildrenQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/queries.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct PartTreeQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/queries.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct PartsByIdQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/queries.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct MembershipsQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/queries.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct SharedProjectsQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/queries.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct AssignedPartsQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/queries.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct PartActivityQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct AddCommentMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct DeleteCommentMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct RecordActivityMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/queries.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct TrashQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/queries.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct StatsQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/queries.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct BurndownQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct RestorePartMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct PurgePartMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct SetArchivedMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct ShareProjectMutation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graph/schema.graphql",
+    query_path = "graph/client/mutations.graphql",
+    response_derives = "Debug",
+    variables_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct UnshareProjectMutation;
+
 // TODO: Handle errors (even when data is also returned)
 pub async fn get_parts(jwt: String) -> PartialResult<Vec<PartsQueryParts>> {
     match post_graphql_req::<PartsQuery>(
         CLIENT
             .post(QUERY_URL.as_str())
-            .header("Authorization", format!("bearer {}", jwt)),
+            .header("Authorization", format!("bearer {}", jwt))
+            .header(CLIENT_ID_HEADER, CLIENT_ID.as_str()),
         PartsVars {},
     )
     .await
@@ -136,6 +544,104 @@ pub async fn get_parts(jwt: String) -> PartialResult<Vec<PartsQueryParts>> {
     }
 }
 
+// Gets the changes to the parts since the cursor (all the parts if it's None)
+pub async fn get_parts_since(jwt: String, cursor: Option<String>) -> anyhow::Result<PartsDelta> {
+    match post_graphql_req::<PartsSinceQuery>(
+        CLIENT
+            .post(QUERY_URL.as_str())
+            .header("Authorization", format!("bearer {}", jwt))
+            .header(CLIENT_ID_HEADER, CLIENT_ID.as_str()),
+        PartsSinceVars { cursor },
+    )
+    .await
+    {
+        Ok(resp) => {
+            if let Some(errors) = resp.errors {
+                Err(anyhow::anyhow!(
+                    "{}",
+                    errors
+                        .into_iter()
+                        .map(|e| e.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ))
+            } else if let Some(resp_data) = resp.data {
+                resp_data.parts_since.try_into()
+            } else {
+                console::log!("no data or error received");
+                Err(anyhow::anyhow!("Internal server error"))
+            }
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Gets the page of the children of the part (or of the projects if parent_id is None) after the
+// given cursor
+pub async fn get_children(
+    jwt: String,
+    parent_id: Option<i64>,
+    after: Option<String>,
+) -> anyhow::Result<ChildrenPage> {
+    let vars = ChildrenVars {
+        parent_id: parent_id.map(|id| id.to_string()),
+        first: Some(CHILDREN_PAGE_SIZE),
+        after,
+    };
+    match post_graphql_req::<ChildrenQuery>(
+        CLIENT
+            .post(QUERY_URL.as_str())
+            .header("Authorization", format!("bearer {}", jwt))
+            .header(CLIENT_ID_HEADER, CLIENT_ID.as_str()),
+        vars,
+    )
+    .await
+    {
+        Ok(resp) => {
+            if let Some(errors) = resp.errors {
+                Err(anyhow::anyhow!(
+                    "{}",
+                    errors
+                        .into_iter()
+                        .map(|e| e.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ))
+            } else if let Some(resp_data) = resp.data {
+                resp_data.children.try_into()
+            } else {
+                console::log!("no data or error received");
+                Err(anyhow::anyhow!("Internal server error"))
+            }
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Gets the part along with its ancestors and all its descendants
+pub async fn get_part_tree(jwt: String, id: i64) -> anyhow::Result<Vec<Part>> {
+    let vars = PartTreeVars { id: id.to_string() };
+    post_authed::<PartTreeQuery>(jwt, vars)
+        .await?
+        .part_tree
+        .into_iter()
+        .map(Part::try_from)
+        .collect()
+}
+
+// Gets the parts with the given ids along with their ancestors
+pub async fn get_parts_by_id(jwt: String, ids: Vec<i64>) -> anyhow::Result<Vec<Part>> {
+    let vars = PartsByIdVars {
+        ids: ids.iter().map(i64::to_string).collect(),
+    };
+    post_authed::<PartsByIdQuery>(jwt, vars)
+        .await?
+        .parts_by_id
+        .into_iter()
+        .map(Part::try_from)
+        .collect()
+}
+
 pub async fn send_login_user(vars: LoginUserVars) -> anyhow::Result<String> {
     match post_graphql::<LoginUserMutation, _>(&CLIENT, QUERY_URL.as_str(), vars).await {
         Ok(resp) => {
@@ -216,7 +722,8 @@ pub async fn send_create_part(
     match post_graphql_req::<CreatePartMutation>(
         CLIENT
             .post(QUERY_URL.as_str())
-            .header("Authorization", format!("bearer {}", jwt)),
+            .header("Authorization", format!("bearer {}", jwt))
+            .header(CLIENT_ID_HEADER, CLIENT_ID.as_str()),
         vars,
     )
     .await
@@ -250,7 +757,8 @@ pub async fn send_update_part(
     match post_graphql_req::<UpdatePartMutation>(
         CLIENT
             .post(QUERY_URL.as_str())
-            .header("Authorization", format!("bearer {}", jwt)),
+            .header("Authorization", format!("bearer {}", jwt))
+            .header(CLIENT_ID_HEADER, CLIENT_ID.as_str()),
         vars,
     )
     .await
@@ -281,7 +789,8 @@ pub async fn send_delete_part(jwt: String, vars: DeletePartVars) -> anyhow::Resu
     match post_graphql_req::<DeletePartMutation>(
         CLIENT
             .post(QUERY_URL.as_str())
-            .header("Authorization", format!("bearer {}", jwt)),
+            .header("Authorization", format!("bearer {}", jwt))
+            .header(CLIENT_ID_HEADER, CLIENT_ID.as_str()),
         vars,
     )
     .await
@@ -308,6 +817,424 @@ pub async fn send_delete_part(jwt: String, vars: DeletePartVars) -> anyhow::Resu
     }
 }
 
+// Sends an authorized request for the query, combining any returned errors into one error
+async fn post_authed<Q: GraphQLQuery>(
+    jwt: String,
+    vars: Q::Variables,
+) -> anyhow::Result<Q::ResponseData> {
+    let resp = post_graphql_req::<Q>(
+        CLIENT
+            .post(QUERY_URL.as_str())
+            .header("Authorization", format!("bearer {}", jwt))
+            .header(CLIENT_ID_HEADER, CLIENT_ID.as_str()),
+        vars,
+    )
+    .await?;
+    if let Some(errors) = resp.errors {
+        Err(anyhow::anyhow!(
+            "{}",
+            errors
+                .into_iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ))
+    } else if let Some(resp_data) = resp.data {
+        Ok(resp_data)
+    } else {
+        console::log!("no data or error received");
+        Err(anyhow::anyhow!("Internal server error"))
+    }
+}
+
+// Like post_authed, but for the operations that don't need a user
+async fn post_unauthed<Q: GraphQLQuery>(vars: Q::Variables) -> anyhow::Result<Q::ResponseData> {
+    let resp = post_graphql::<Q, _>(&CLIENT, QUERY_URL.as_str(), vars).await?;
+    if let Some(errors) = resp.errors {
+        Err(anyhow::anyhow!(
+            "{}",
+            errors
+                .into_iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ))
+    } else if let Some(resp_data) = resp.data {
+        Ok(resp_data)
+    } else {
+        console::log!("no data or error received");
+        Err(anyhow::anyhow!("Internal server error"))
+    }
+}
+
+pub async fn send_request_password_reset(email: String) -> anyhow::Result<bool> {
+    let vars = RequestPasswordResetVars { email };
+    Ok(post_unauthed::<RequestPasswordResetMutation>(vars)
+        .await?
+        .request_password_reset)
+}
+
+pub async fn send_reset_password(token: String, new_password: String) -> anyhow::Result<bool> {
+    let vars = ResetPasswordVars {
+        input: ResetPasswordInput {
+            token,
+            new_password,
+        },
+    };
+    Ok(post_unauthed::<ResetPasswordMutation>(vars)
+        .await?
+        .reset_password)
+}
+
+pub async fn send_change_password(
+    jwt: String,
+    current_password: String,
+    new_password: String,
+) -> anyhow::Result<bool> {
+    let vars = ChangePasswordVars {
+        input: ChangePasswordInput {
+            current_password,
+            new_password,
+        },
+    };
+    Ok(post_authed::<ChangePasswordMutation>(jwt, vars)
+        .await?
+        .change_password)
+}
+
+// Returns the new token (for the new email)
+pub async fn send_change_email(
+    jwt: String,
+    password: String,
+    new_email: String,
+) -> anyhow::Result<String> {
+    let vars = ChangeEmailVars {
+        input: ChangeEmailInput {
+            password,
+            new_email,
+        },
+    };
+    Ok(post_authed::<ChangeEmailMutation>(jwt, vars)
+        .await?
+        .change_email)
+}
+
+pub async fn send_delete_account(jwt: String, password: String) -> anyhow::Result<bool> {
+    let vars = DeleteAccountVars { password };
+    Ok(post_authed::<DeleteAccountMutation>(jwt, vars)
+        .await?
+        .delete_account)
+}
+
+pub async fn send_batch_update_parts(
+    jwt: String,
+    vars: BatchUpdatePartsVars,
+) -> anyhow::Result<Vec<BatchItem<Part>>> {
+    post_authed::<BatchUpdatePartsMutation>(jwt, vars)
+        .await?
+        .batch_update_parts
+        .into_iter()
+        .map(|res| {
+            let result = match (res.part, res.error) {
+                (_, Some(e)) => Err(e),
+                (Some(part), None) => Ok(part.try_into()?),
+                (None, None) => Err(String::from("no part returned")),
+            };
+            Ok(BatchItem {
+                id: res.id.parse()?,
+                result,
+            })
+        })
+        .collect()
+}
+
+pub async fn send_batch_delete_parts(
+    jwt: String,
+    vars: BatchDeletePartsVars,
+) -> anyhow::Result<Vec<BatchItem<()>>> {
+    post_authed::<BatchDeletePartsMutation>(jwt, vars)
+        .await?
+        .batch_delete_parts
+        .into_iter()
+        .map(|res| {
+            Ok(BatchItem {
+                id: res.id.parse()?,
+                result: res.error.map_or(Ok(()), Err),
+            })
+        })
+        .collect()
+}
+
+pub async fn send_add_blocker(
+    jwt: String,
+    vars: AddBlockerVars,
+) -> anyhow::Result<AddBlockerMutationAddBlocker> {
+    post_authed::<AddBlockerMutation>(jwt, vars)
+        .await
+        .map(|data| data.add_blocker)
+}
+
+pub async fn send_remove_blocker(
+    jwt: String,
+    vars: RemoveBlockerVars,
+) -> anyhow::Result<RemoveBlockerMutationRemoveBlocker> {
+    post_authed::<RemoveBlockerMutation>(jwt, vars)
+        .await
+        .map(|data| data.remove_blocker)
+}
+
+pub async fn get_time_entries(jwt: String) -> anyhow::Result<Vec<TimeEntriesQueryTimeEntries>> {
+    post_authed::<TimeEntriesQuery>(jwt, TimeEntriesVars {})
+        .await
+        .map(|data| data.time_entries)
+}
+
+pub async fn send_start_timer(
+    jwt: String,
+    vars: StartTimerVars,
+) -> anyhow::Result<StartTimerMutationStartTimer> {
+    post_authed::<StartTimerMutation>(jwt, vars)
+        .await
+        .map(|data| data.start_timer)
+}
+
+pub async fn send_stop_timer(
+    jwt: String,
+    vars: StopTimerVars,
+) -> anyhow::Result<StopTimerMutationStopTimer> {
+    post_authed::<StopTimerMutation>(jwt, vars)
+        .await
+        .map(|data| data.stop_timer)
+}
+
+pub async fn send_create_time_entry(
+    jwt: String,
+    vars: CreateTimeEntryVars,
+) -> anyhow::Result<CreateTimeEntryMutationCreateTimeEntry> {
+    post_authed::<CreateTimeEntryMutation>(jwt, vars)
+        .await
+        .map(|data| data.create_time_entry)
+}
+
+pub async fn send_delete_time_entry(
+    jwt: String,
+    vars: DeleteTimeEntryVars,
+) -> anyhow::Result<String> {
+    post_authed::<DeleteTimeEntryMutation>(jwt, vars)
+        .await
+        .map(|data| data.delete_time_entry)
+}
+
+// Gets who the user's projects are shared with
+pub async fn get_memberships(jwt: String) -> anyhow::Result<Vec<Membership>> {
+    post_authed::<MembershipsQuery>(jwt, MembershipsVars)
+        .await?
+        .memberships
+        .into_iter()
+        .map(Membership::try_from)
+        .collect()
+}
+
+// Gets the projects other users have shared with the user
+pub async fn get_shared_projects(jwt: String) -> anyhow::Result<Vec<SharedProject>> {
+    post_authed::<SharedProjectsQuery>(jwt, SharedProjectsVars)
+        .await?
+        .shared_projects
+        .into_iter()
+        .map(SharedProject::try_from)
+        .collect()
+}
+
+// Gets the parts assigned to the user across their own and shared projects, by deadline
+pub async fn get_assigned_parts(jwt: String) -> anyhow::Result<Vec<AssignedPart>> {
+    post_authed::<AssignedPartsQuery>(jwt, AssignedPartsVars)
+        .await?
+        .assigned_parts
+        .into_iter()
+        .map(AssignedPart::try_from)
+        .collect()
+}
+
+// Gets the comments on the part and its activity log
+pub async fn get_part_activity(
+    jwt: String,
+    part_id: i64,
+    owner: Option<String>,
+) -> anyhow::Result<PartActivity> {
+    let vars = PartActivityVars {
+        part_id: part_id.to_string(),
+        owner,
+    };
+    let data = post_authed::<PartActivityQuery>(jwt, vars).await?;
+    Ok(PartActivity {
+        comments: data
+            .comments
+            .into_iter()
+            .map(Comment::try_from)
+            .collect::<Result<_, _>>()?,
+        activity: data
+            .activity
+            .into_iter()
+            .map(Activity::try_from)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+pub async fn send_add_comment(
+    jwt: String,
+    part_id: i64,
+    body: String,
+    reply_to: Option<i64>,
+    owner: Option<String>,
+) -> anyhow::Result<Comment> {
+    let vars = AddCommentVars {
+        part_id: part_id.to_string(),
+        body,
+        reply_to: reply_to.map(|id| id.to_string()),
+        owner,
+    };
+    post_authed::<AddCommentMutation>(jwt, vars)
+        .await?
+        .add_comment
+        .try_into()
+}
+
+// Returns the id of the deleted comment
+pub async fn send_delete_comment(
+    jwt: String,
+    part_id: i64,
+    id: i64,
+    owner: Option<String>,
+) -> anyhow::Result<i64> {
+    let vars = DeleteCommentVars {
+        part_id: part_id.to_string(),
+        id: id.to_string(),
+        owner,
+    };
+    Ok(post_authed::<DeleteCommentMutation>(jwt, vars)
+        .await?
+        .delete_comment
+        .parse()?)
+}
+
+pub async fn send_record_activity(
+    jwt: String,
+    part_id: i64,
+    entries: Vec<(ActivityKind, String)>,
+    owner: Option<String>,
+) -> anyhow::Result<Vec<Activity>> {
+    let vars = RecordActivityVars {
+        part_id: part_id.to_string(),
+        entries: entries
+            .into_iter()
+            .map(|(kind, message)| ActivityInput {
+                kind: kind.into(),
+                message,
+            })
+            .collect(),
+        owner,
+    };
+    post_authed::<RecordActivityMutation>(jwt, vars)
+        .await?
+        .record_activity
+        .into_iter()
+        .map(Activity::try_from)
+        .collect()
+}
+
+// Gets the parts the user deleted (but not their children deleted with them), most recently
+// deleted first
+pub async fn get_trash(jwt: String) -> anyhow::Result<Vec<TrashedPart>> {
+    post_authed::<TrashQuery>(jwt, TrashVars)
+        .await?
+        .trash
+        .into_iter()
+        .map(TrashedPart::try_from)
+        .collect()
+}
+
+// Gets the statistics of the user's parts, with the completions since the given time
+pub async fn get_stats(jwt: String, since: DateTime<Local>) -> anyhow::Result<StatsData> {
+    let vars = StatsVars {
+        since: since.timestamp().to_string(),
+    };
+    post_authed::<StatsQuery>(jwt, vars).await?.stats.try_into()
+}
+
+// Gets the completions of the parts of the project with the given id
+pub async fn get_burndown(jwt: String, project_id: i64) -> anyhow::Result<BurndownData> {
+    let vars = BurndownVars {
+        project_id: project_id.to_string(),
+    };
+    post_authed::<BurndownQuery>(jwt, vars)
+        .await?
+        .burndown
+        .try_into()
+}
+
+// Restores the deleted part and its children, returning them (parents first)
+pub async fn send_restore_part(jwt: String, id: i64) -> anyhow::Result<Vec<Part>> {
+    let vars = RestorePartVars { id: id.to_string() };
+    post_authed::<RestorePartMutation>(jwt, vars)
+        .await?
+        .restore_part
+        .into_iter()
+        .map(Part::try_from)
+        .collect()
+}
+
+// Permanently deletes the part in the trash and its children
+pub async fn send_purge_part(jwt: String, id: i64) -> anyhow::Result<i64> {
+    let vars = PurgePartVars { id: id.to_string() };
+    Ok(post_authed::<PurgePartMutation>(jwt, vars)
+        .await?
+        .purge_part
+        .parse()?)
+}
+
+pub async fn send_set_archived(jwt: String, id: i64, archived: bool) -> anyhow::Result<Part> {
+    let vars = SetArchivedVars {
+        id: id.to_string(),
+        archived,
+    };
+    post_authed::<SetArchivedMutation>(jwt, vars)
+        .await?
+        .set_archived
+        .try_into()
+}
+
+pub async fn send_share_project(
+    jwt: String,
+    project_id: i64,
+    email: String,
+    role: Role,
+) -> anyhow::Result<Membership> {
+    let vars = ShareProjectVars {
+        project_id: project_id.to_string(),
+        email,
+        role: role.into(),
+    };
+    post_authed::<ShareProjectMutation>(jwt, vars)
+        .await?
+        .share_project
+        .try_into()
+}
+
+// Returns whether the project was shared with the user
+pub async fn send_unshare_project(
+    jwt: String,
+    project_id: i64,
+    email: String,
+) -> anyhow::Result<bool> {
+    let vars = UnshareProjectVars {
+        project_id: project_id.to_string(),
+        email,
+    };
+    post_authed::<UnshareProjectMutation>(jwt, vars)
+        .await
+        .map(|data| data.unshare_project)
+}
+
 const DTL_FMT: &str = "%H:%M %b %d, %Y";
 pub const DTL_INPUT_FMT: &str = "%Y-%m-%dT%H:%M";
 
@@ -334,7 +1261,15 @@ impl<T: std::fmt::Debug> std::fmt::Debug for PartialOk<T> {
 
 pub type PartialResult<T> = anyhow::Result<PartialOk<T>>;
 
-#[derive(Debug, Default, PartialEq, Eq)]
+// The outcome of a batch operation for a single part
+#[derive(Debug)]
+pub struct BatchItem<T> {
+    pub id: i64,
+    // Holds the error message if the operation failed for the part
+    pub result: Result<T, String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Part {
     pub id: i64,
     pub name: String,
@@ -342,6 +1277,16 @@ pub struct Part {
     pub deadline: Option<DateTime<Local>>,
     pub completed_at: Option<DateTime<Local>>,
     pub parent_id: Option<i64>,
+    // IDs of the parts that must be completed before this one
+    pub blocked_by: Vec<i64>,
+    // Without the leading '#'
+    pub tags: Vec<String>,
+    // The number of children on the server (only needed until the children are loaded)
+    pub child_count: i64,
+    // The email of the user the part is assigned to
+    pub assignee: Option<String>,
+    // When the (completed) project was archived
+    pub archived_at: Option<DateTime<Local>>,
 }
 
 impl Part {
@@ -402,93 +1347,568 @@ impl From<PartsQueryParts> for Part {
 }
 */
 
-impl TryFrom<PartsQueryParts> for Part {
+// Implements TryFrom<$ty> for Part for each of the given (identically shaped) part selections
+macro_rules! impl_try_from_part {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+        impl TryFrom<$ty> for Part {
+            type Error = anyhow::Error;
+
+            fn try_from(part: $ty) -> Result<Self, Self::Error> {
+                Ok(Self {
+                    id: part.id.parse()?,
+                    name: part.name,
+                    description: part.description,
+                    deadline: part
+                        .deadline
+                        .map(|t_str| t_str.parse::<i64>())
+                        .transpose()?
+                        .map(|t| {
+                            NDT::from_timestamp_opt(t, 0).ok_or(anyhow::anyhow!("deadline: {}", t))
+                        })
+                        .transpose()?
+                        .map(|ndt| DateTime::<Local>::from_utc(ndt, *TZ)),
+                    completed_at: part
+                        .completed_at
+                        .map(|t_str| t_str.parse::<i64>())
+                        .transpose()?
+                        .map(|t| {
+                            NDT::from_timestamp_opt(t, 0)
+                                .ok_or(anyhow::anyhow!("completed_at: {}", t))
+                        })
+                        .transpose()?
+                        .map(|ndt| DateTime::<Local>::from_utc(ndt, *TZ)),
+                    parent_id: part.parent_id.map(|pid| pid.parse()).transpose()?,
+                    blocked_by: part
+                        .blocked_by
+                        .into_iter()
+                        .map(|bid| bid.parse())
+                        .collect::<Result<_, _>>()?,
+                    tags: part.tags,
+                    child_count: part.child_count,
+                    assignee: part.assignee,
+                    archived_at: part
+                        .archived_at
+                        .map(|t_str| dtl_from_epoch_str(&t_str, "archived_at"))
+                        .transpose()?,
+                })
+            }
+        }
+        )+
+    };
+}
+
+impl_try_from_part!(
+    PartsQueryParts,
+    CreatePartMutationCreatePart,
+    UpdatePartMutationUpdatePart,
+    BatchUpdatePartsMutationBatchUpdatePartsPart,
+    AddBlockerMutationAddBlocker,
+    RemoveBlockerMutationRemoveBlocker,
+    PartEventsSubscriptionPartEventsPart,
+    PartsSinceQueryPartsSinceParts,
+    ChildrenQueryChildrenEdgesNode,
+    PartTreeQueryPartTree,
+    PartsByIdQueryPartsById,
+    SharedProjectsQuerySharedProjectsParts,
+    AssignedPartsQueryAssignedPartsPart,
+    TrashQueryTrash,
+    RestorePartMutationRestorePart,
+    SetArchivedMutationSetArchived,
+);
+
+// The number of children fetched at a time
+const CHILDREN_PAGE_SIZE: i64 = 100;
+
+// A page of the children of a part (or of the projects)
+#[derive(Debug)]
+pub struct ChildrenPage {
+    pub parts: Vec<Part>,
+    // The cursor to get the next page with, if there is one
+    pub next: Option<String>,
+    // The partsSince cursor the page is current as of
+    pub sync_cursor: String,
+}
+
+impl TryFrom<ChildrenQueryChildren> for ChildrenPage {
     type Error = anyhow::Error;
 
-    fn try_from(part: PartsQueryParts) -> Result<Self, Self::Error> {
+    fn try_from(page: ChildrenQueryChildren) -> Result<Self, Self::Error> {
         Ok(Self {
-            id: part.id.parse()?,
-            name: part.name,
-            description: part.description,
-            deadline: part
-                .deadline
-                .map(|t_str| t_str.parse::<i64>())
-                .transpose()?
-                .map(|t| NDT::from_timestamp_opt(t, 0).ok_or(anyhow::anyhow!("deadline: {}", t)))
-                .transpose()?
-                .map(|ndt| DateTime::<Local>::from_utc(ndt, *TZ)),
-            completed_at: part
-                .completed_at
-                .map(|t_str| t_str.parse::<i64>())
-                .transpose()?
-                .map(|t| {
-                    NDT::from_timestamp_opt(t, 0).ok_or(anyhow::anyhow!("completed_at: {}", t))
+            parts: page
+                .edges
+                .into_iter()
+                .map(|edge| Part::try_from(edge.node))
+                .collect::<Result<_, _>>()?,
+            next: page
+                .page_info
+                .has_next_page
+                .then_some(page.page_info.end_cursor)
+                .flatten(),
+            sync_cursor: page.sync_cursor,
+        })
+    }
+}
+
+// The changes to the parts since a cursor
+#[derive(Debug)]
+pub struct PartsDelta {
+    // The parts created or updated
+    pub parts: Vec<Part>,
+    pub deleted: Vec<i64>,
+    // The cursor to get the changes after these
+    pub cursor: String,
+    // Whether parts holds all the parts (any others were deleted)
+    pub full: bool,
+}
+
+impl TryFrom<PartsSinceQueryPartsSince> for PartsDelta {
+    type Error = anyhow::Error;
+
+    fn try_from(delta: PartsSinceQueryPartsSince) -> Result<Self, Self::Error> {
+        Ok(Self {
+            parts: delta
+                .parts
+                .into_iter()
+                .map(Part::try_from)
+                .collect::<Result<_, _>>()?,
+            deleted: delta
+                .deleted
+                .into_iter()
+                .map(|id| id.parse())
+                .collect::<Result<_, _>>()?,
+            cursor: delta.cursor,
+            full: delta.full,
+        })
+    }
+}
+
+// A change to one of the user's parts, made by this or another client
+#[derive(Debug)]
+pub enum PartEvent {
+    // The part was created or updated
+    Upserted(Box<Part>),
+    Deleted(i64),
+}
+
+impl TryFrom<PartEventsSubscriptionPartEvents> for PartEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: PartEventsSubscriptionPartEvents) -> Result<Self, Self::Error> {
+        match (event.kind, event.part) {
+            (PartEventKind::Created | PartEventKind::Updated, Some(part)) => {
+                Ok(PartEvent::Upserted(Box::new(part.try_into()?)))
+            }
+            (PartEventKind::Deleted, _) => Ok(PartEvent::Deleted(event.id.parse()?)),
+            (kind, _) => Err(anyhow::anyhow!(
+                "invalid part event: {:?} {}",
+                kind,
+                event.id
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    // Can see the project's parts
+    Viewer,
+    // Can also create, update and delete them
+    Editor,
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Viewer => write!(f, "Viewer"),
+            Role::Editor => write!(f, "Editor"),
+        }
+    }
+}
+
+impl From<Role> for share_project_mutation::Role {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Viewer => Self::Viewer,
+            Role::Editor => Self::Editor,
+        }
+    }
+}
+
+// Another user's access to a project
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Membership {
+    pub project_id: i64,
+    // The email of the user whose project it is
+    pub owner: String,
+    // The email of the user the project is shared with
+    pub member: String,
+    pub role: Role,
+}
+
+// Implements TryFrom<$ty> for Membership for each of the given membership selections (with the
+// module of the query, whose Role enum is converted)
+macro_rules! impl_try_from_membership {
+    ($($ty:ty => $module:ident),+ $(,)?) => {
+        $(
+        impl TryFrom<$ty> for Membership {
+            type Error = anyhow::Error;
+
+            fn try_from(m: $ty) -> Result<Self, Self::Error> {
+                Ok(Self {
+                    project_id: m.project_id.parse()?,
+                    owner: m.owner,
+                    member: m.member,
+                    role: match m.role {
+                        $module::Role::Viewer => Role::Viewer,
+                        $module::Role::Editor => Role::Editor,
+                        $module::Role::Other(role) => anyhow::bail!("unknown role: {}", role),
+                    },
                 })
-                .transpose()?
-                .map(|ndt| DateTime::<Local>::from_utc(ndt, *TZ)),
-            parent_id: part.parent_id.map(|pid| pid.parse()).transpose()?,
+            }
+        }
+        )+
+    };
+}
+
+impl_try_from_membership!(
+    MembershipsQueryMemberships => memberships_query,
+    ShareProjectMutationShareProject => share_project_mutation,
+    SharedProjectsQuerySharedProjectsMembership => shared_projects_query,
+    SharedProjectsQuerySharedProjectsMembers => shared_projects_query,
+);
+
+// A project shared with the user, along with all its parts
+#[derive(Debug)]
+pub struct SharedProject {
+    pub membership: Membership,
+    // Who else the project is shared with (including the user)
+    pub members: Vec<Membership>,
+    pub parts: Vec<Part>,
+}
+
+impl TryFrom<SharedProjectsQuerySharedProjects> for SharedProject {
+    type Error = anyhow::Error;
+
+    fn try_from(project: SharedProjectsQuerySharedProjects) -> Result<Self, Self::Error> {
+        Ok(Self {
+            membership: project.membership.try_into()?,
+            members: project
+                .members
+                .into_iter()
+                .map(Membership::try_from)
+                .collect::<Result<_, _>>()?,
+            parts: project
+                .parts
+                .into_iter()
+                .map(Part::try_from)
+                .collect::<Result<_, _>>()?,
         })
     }
 }
 
-impl TryFrom<CreatePartMutationCreatePart> for Part {
+// A part assigned to the user, in their own or a shared project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssignedPart {
+    // The email of the user whose part it is
+    pub owner: String,
+    pub part: Part,
+}
+
+impl TryFrom<AssignedPartsQueryAssignedParts> for AssignedPart {
     type Error = anyhow::Error;
 
-    fn try_from(part: CreatePartMutationCreatePart) -> Result<Self, Self::Error> {
+    fn try_from(assigned: AssignedPartsQueryAssignedParts) -> Result<Self, Self::Error> {
         Ok(Self {
-            id: part.id.parse()?,
-            name: part.name,
-            description: part.description,
-            deadline: part
-                .deadline
-                .map(|t_str| t_str.parse::<i64>())
-                .transpose()?
-                .map(|t| NDT::from_timestamp_opt(t, 0).ok_or(anyhow::anyhow!("deadline: {}", t)))
-                .transpose()?
-                .map(|ndt| DateTime::<Local>::from_utc(ndt, *TZ)),
-            completed_at: part
-                .completed_at
-                .map(|t_str| t_str.parse::<i64>())
-                .transpose()?
-                .map(|t| {
-                    NDT::from_timestamp_opt(t, 0).ok_or(anyhow::anyhow!("completed_at: {}", t))
+            owner: assigned.owner,
+            part: assigned.part.try_into()?,
+        })
+    }
+}
+
+// A part the user deleted, along with when
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedPart {
+    pub part: Part,
+    pub trashed_at: DateTime<Local>,
+}
+
+impl TryFrom<TrashQueryTrash> for TrashedPart {
+    type Error = anyhow::Error;
+
+    fn try_from(trashed: TrashQueryTrash) -> Result<Self, Self::Error> {
+        let trashed_at = trashed
+            .trashed_at
+            .as_deref()
+            .ok_or(anyhow::anyhow!("trashed_at missing"))
+            .and_then(|t_str| dtl_from_epoch_str(t_str, "trashed_at"))?;
+        Ok(Self {
+            part: trashed.try_into()?,
+            trashed_at,
+        })
+    }
+}
+
+// How much work is left in one of the user's projects
+#[derive(Debug, PartialEq, Eq)]
+pub struct ProjectWork {
+    pub id: i64,
+    pub name: String,
+    // The number of incomplete parts in the project (including the project itself)
+    pub open: usize,
+    pub total: usize,
+}
+
+// The statistics of the user's parts, computed by the server
+#[derive(Debug)]
+pub struct StatsData {
+    // When the parts completed since the time asked for were completed, oldest first
+    pub completions: Vec<DateTime<Local>>,
+    // The number of parts completed by (and after) their deadlines
+    pub on_time: usize,
+    pub late: usize,
+    // How late the late parts were in total
+    pub total_late: chrono::Duration,
+    // The unarchived projects, those with the most open parts first
+    pub open_work: Vec<ProjectWork>,
+}
+
+impl TryFrom<StatsQueryStats> for StatsData {
+    type Error = anyhow::Error;
+
+    fn try_from(stats: StatsQueryStats) -> Result<Self, Self::Error> {
+        Ok(Self {
+            completions: stats
+                .completions
+                .iter()
+                .map(|t_str| dtl_from_epoch_str(t_str, "completions"))
+                .collect::<anyhow::Result<_>>()?,
+            on_time: stats.on_time.try_into()?,
+            late: stats.late.try_into()?,
+            total_late: chrono::Duration::seconds(stats.total_late.parse()?),
+            open_work: stats
+                .open_work
+                .into_iter()
+                .map(|work| {
+                    Ok(ProjectWork {
+                        id: work.project_id.parse()?,
+                        name: work.name,
+                        open: work.open.try_into()?,
+                        total: work.total.try_into()?,
+                    })
                 })
-                .transpose()?
-                .map(|ndt| DateTime::<Local>::from_utc(ndt, *TZ)),
-            parent_id: part.parent_id.map(|pid| pid.parse()).transpose()?,
+                .collect::<anyhow::Result<_>>()?,
         })
     }
 }
 
-impl TryFrom<UpdatePartMutationUpdatePart> for Part {
+// The completions of the parts of a project (besides the project itself)
+#[derive(Debug)]
+pub struct BurndownData {
+    // The number of parts
+    pub total: usize,
+    // Oldest first
+    pub completions: Vec<DateTime<Local>>,
+    pub deadline: Option<DateTime<Local>>,
+}
+
+impl TryFrom<BurndownQueryBurndown> for BurndownData {
     type Error = anyhow::Error;
 
-    fn try_from(part: UpdatePartMutationUpdatePart) -> Result<Self, Self::Error> {
+    fn try_from(burndown: BurndownQueryBurndown) -> Result<Self, Self::Error> {
         Ok(Self {
-            id: part.id.parse()?,
-            name: part.name,
-            description: part.description,
-            deadline: part
+            total: burndown.total.try_into()?,
+            completions: burndown
+                .completions
+                .iter()
+                .map(|t_str| dtl_from_epoch_str(t_str, "completions"))
+                .collect::<anyhow::Result<_>>()?,
+            deadline: burndown
                 .deadline
-                .map(|t_str| t_str.parse::<i64>())
-                .transpose()?
-                .map(|t| NDT::from_timestamp_opt(t, 0).ok_or(anyhow::anyhow!("deadline: {}", t)))
-                .transpose()?
-                .map(|ndt| DateTime::<Local>::from_utc(ndt, *TZ)),
-            completed_at: part
-                .completed_at
-                .map(|t_str| t_str.parse::<i64>())
-                .transpose()?
-                .map(|t| {
-                    NDT::from_timestamp_opt(t, 0).ok_or(anyhow::anyhow!("completed_at: {}", t))
-                })
-                .transpose()?
-                .map(|ndt| DateTime::<Local>::from_utc(ndt, *TZ)),
-            parent_id: part.parent_id.map(|pid| pid.parse()).transpose()?,
+                .map(|t_str| dtl_from_epoch_str(&t_str, "deadline"))
+                .transpose()?,
         })
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeEntry {
+    pub id: i64,
+    pub part_id: i64,
+    pub started_at: DateTime<Local>,
+    // None while the timer is running
+    pub ended_at: Option<DateTime<Local>>,
+}
+
+impl TimeEntry {
+    pub fn is_running(&self) -> bool {
+        self.ended_at.is_none()
+    }
+
+    // Returns the duration of the entry, using the given time as the end if it's running
+    pub fn duration(&self, now: DateTime<Local>) -> chrono::Duration {
+        (self.ended_at.unwrap_or(now) - self.started_at).max(chrono::Duration::zero())
+    }
+}
+
+fn dtl_from_epoch_str(t_str: &str, field: &str) -> anyhow::Result<DateTime<Local>> {
+    let t = t_str.parse::<i64>()?;
+    NDT::from_timestamp_opt(t, 0)
+        .ok_or(anyhow::anyhow!("{}: {}", field, t))
+        .map(|ndt| DateTime::<Local>::from_utc(ndt, *TZ))
+}
+
+// Implements TryFrom<$ty> for TimeEntry for each of the given time entry selections
+macro_rules! impl_try_from_time_entry {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+        impl TryFrom<$ty> for TimeEntry {
+            type Error = anyhow::Error;
+
+            fn try_from(entry: $ty) -> Result<Self, Self::Error> {
+                Ok(Self {
+                    id: entry.id.parse()?,
+                    part_id: entry.part_id.parse()?,
+                    started_at: dtl_from_epoch_str(&entry.started_at, "started_at")?,
+                    ended_at: entry
+                        .ended_at
+                        .map(|t_str| dtl_from_epoch_str(&t_str, "ended_at"))
+                        .transpose()?,
+                })
+            }
+        }
+        )+
+    };
+}
+
+impl_try_from_time_entry!(
+    TimeEntriesQueryTimeEntries,
+    StartTimerMutationStartTimer,
+    StopTimerMutationStopTimer,
+    CreateTimeEntryMutationCreateTimeEntry,
+);
+
+// A comment on a part
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub id: i64,
+    pub part_id: i64,
+    // The comment this replies to (None for top-level comments)
+    pub reply_to: Option<i64>,
+    pub author: String,
+    // Markdown
+    pub body: String,
+    pub created_at: DateTime<Local>,
+}
+
+// Implements TryFrom<$ty> for Comment for each of the given comment selections
+macro_rules! impl_try_from_comment {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+        impl TryFrom<$ty> for Comment {
+            type Error = anyhow::Error;
+
+            fn try_from(comment: $ty) -> Result<Self, Self::Error> {
+                Ok(Self {
+                    id: comment.id.parse()?,
+                    part_id: comment.part_id.parse()?,
+                    reply_to: comment.reply_to.map(|id| id.parse()).transpose()?,
+                    author: comment.author,
+                    body: comment.body,
+                    created_at: dtl_from_epoch_str(&comment.created_at, "created_at")?,
+                })
+            }
+        }
+        )+
+    };
+}
+
+impl_try_from_comment!(PartActivityQueryComments, AddCommentMutationAddComment);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActivityKind {
+    Created,
+    Renamed,
+    DeadlineMoved,
+    Completed,
+    Reopened,
+    Moved,
+    // Any other change (e.g., to the description)
+    Edited,
+}
+
+impl From<ActivityKind> for record_activity_mutation::ActivityKind {
+    fn from(kind: ActivityKind) -> Self {
+        match kind {
+            ActivityKind::Created => Self::Created,
+            ActivityKind::Renamed => Self::Renamed,
+            ActivityKind::DeadlineMoved => Self::DeadlineMoved,
+            ActivityKind::Completed => Self::Completed,
+            ActivityKind::Reopened => Self::Reopened,
+            ActivityKind::Moved => Self::Moved,
+            ActivityKind::Edited => Self::Edited,
+        }
+    }
+}
+
+// An entry in a part's activity log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Activity {
+    pub id: i64,
+    pub part_id: i64,
+    // Who made the change
+    pub author: String,
+    pub kind: ActivityKind,
+    // A human-readable description of the change
+    pub message: String,
+    pub created_at: DateTime<Local>,
+}
+
+// Implements TryFrom<$ty> for Activity for each of the given activity selections (with the
+// module of the query, whose ActivityKind enum is converted)
+macro_rules! impl_try_from_activity {
+    ($($ty:ty => $module:ident),+ $(,)?) => {
+        $(
+        impl TryFrom<$ty> for Activity {
+            type Error = anyhow::Error;
+
+            fn try_from(entry: $ty) -> Result<Self, Self::Error> {
+                use $module::ActivityKind as Kind;
+                Ok(Self {
+                    id: entry.id.parse()?,
+                    part_id: entry.part_id.parse()?,
+                    author: entry.author,
+                    kind: match entry.kind {
+                        Kind::Created => ActivityKind::Created,
+                        Kind::Renamed => ActivityKind::Renamed,
+                        Kind::DeadlineMoved => ActivityKind::DeadlineMoved,
+                        Kind::Completed => ActivityKind::Completed,
+                        Kind::Reopened => ActivityKind::Reopened,
+                        Kind::Moved => ActivityKind::Moved,
+                        Kind::Edited => ActivityKind::Edited,
+                        Kind::Other(kind) => anyhow::bail!("unknown activity kind: {}", kind),
+                    },
+                    message: entry.message,
+                    created_at: dtl_from_epoch_str(&entry.created_at, "created_at")?,
+                })
+            }
+        }
+        )+
+    };
+}
+
+impl_try_from_activity!(
+    PartActivityQueryActivity => part_activity_query,
+    RecordActivityMutationRecordActivity => record_activity_mutation,
+);
+
+// The comments on a part and its activity log, oldest first
+#[derive(Debug, Default)]
+pub struct PartActivity {
+    pub comments: Vec<Comment>,
+    pub activity: Vec<Activity>,
+}
+
 impl Into<CreatePartInput> for Part {
     fn into(self) -> CreatePartInput {
         CreatePartInput {
@@ -497,6 +1917,8 @@ impl Into<CreatePartInput> for Part {
             deadline: self.deadline.map(|dt| dt.timestamp().to_string()),
             completed_at: self.completed_at.map(|dt| dt.timestamp().to_string()),
             parent_id: self.parent_id.map(|id| id.to_string()),
+            tags: Some(self.tags),
+            assignee: self.assignee,
         }
     }
 }
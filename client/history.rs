@@ -0,0 +1,389 @@
+// Session history of the changes made to parts so they can be undone/redone. Changes are undone
+// by sending compensating requests to the server (e.g., restoring a deleted subtree from the
+// trash). Subtrees purged from the trash since are re-created instead, and since re-created parts
+// get new ids, the history keeps a map of original ids to current ones.
+use crate::{
+    console,
+    graphql::{
+        add_blocker_mutation::Variables as AddBlockerVars,
+        create_part_mutation::Variables as CreatePartVars,
+        create_time_entry_mutation::{CreateTimeEntryInput, Variables as CreateTimeEntryVars},
+        delete_part_mutation::Variables as DeletePartVars,
+        get_time_entries, send_add_blocker, send_create_part, send_create_time_entry,
+        send_delete_part, send_restore_part, send_update_part,
+        update_part_mutation::Variables as UpdatePartVars,
+        Part, PartialOk, TimeEntry,
+    },
+};
+use chrono::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+// A part and all its descendants, along with what's needed to re-create them
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    // The root comes first and parents always come before their children
+    pub parts: Vec<Part>,
+    // (part_id, blocker_id) pairs involving any of the parts
+    pub blockers: Vec<(i64, i64)>,
+    // Running entries are stored as ending when the snapshot was taken
+    pub time_entries: Vec<TimeEntry>,
+}
+
+impl Snapshot {
+    fn root_id(&self) -> i64 {
+        self.parts[0].id
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Change {
+    Created(Snapshot),
+    Deleted(Snapshot),
+    Updated { before: Box<Part>, after: Box<Part> },
+}
+
+// A user action, which may consist of multiple changes (e.g., batch operations)
+pub struct Entry {
+    pub label: String,
+    pub at: DateTime<Local>,
+    pub changes: Vec<Change>,
+}
+
+impl Entry {
+    pub fn new(label: String, changes: Vec<Change>) -> Self {
+        Self {
+            label,
+            at: Local::now(),
+            changes,
+        }
+    }
+}
+
+// The changes made to the parts on the server while undoing/redoing an entry
+#[derive(Default)]
+pub struct Outcome {
+    pub deleted: Vec<i64>,
+    // Parents come before their children
+    pub created: Vec<Part>,
+    pub updated: Vec<Part>,
+    pub time_entries: Vec<TimeEntry>,
+    ids: BTreeMap<i64, i64>,
+}
+
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+    // BTreeMap<original id, id of the re-created part>
+    ids: BTreeMap<i64, i64>,
+    // The entry being applied and whether it's being undone (or redone)
+    pending: Option<(Entry, bool)>,
+}
+
+impl History {
+    pub fn record(&mut self, entry: Entry) {
+        self.undo.push(entry);
+        self.redo.clear();
+    }
+
+    pub fn undo_entries(&self) -> &[Entry] {
+        &self.undo
+    }
+
+    pub fn redo_entries(&self) -> &[Entry] {
+        &self.redo
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    // Takes the last entry to undo (or redo), returning the future applying it. Returns None if
+    // there's nothing to undo/redo or an entry is already being applied.
+    pub fn start(
+        &mut self,
+        jwt: String,
+        undo: bool,
+    ) -> Option<impl std::future::Future<Output = PartialOk<Outcome>>> {
+        if self.pending.is_some() {
+            return None;
+        }
+        let entry = if undo {
+            self.undo.pop()?
+        } else {
+            self.redo.pop()?
+        };
+        let mut changes = entry.changes.clone();
+        if undo {
+            changes.reverse();
+        }
+        let ids = self.ids.clone();
+        self.pending = Some((entry, undo));
+        Some(apply(jwt, changes, ids, undo))
+    }
+
+    // Finishes applying the pending entry. The entry is dropped if it wasn't fully applied since
+    // it can't be reliably undone/redone anymore.
+    pub fn finish(&mut self, outcome: &mut Outcome, ok: bool) {
+        let Some((entry, undone)) = self.pending.take() else {
+            return;
+        };
+        self.ids = std::mem::take(&mut outcome.ids);
+        if !ok {
+            console::log!("dropping history entry: {}", entry.label);
+        } else if undone {
+            self.redo.push(entry);
+        } else {
+            self.undo.push(entry);
+        }
+    }
+}
+
+// Returns the current id of the part originally having the given id
+fn resolve(ids: &BTreeMap<i64, i64>, mut id: i64) -> i64 {
+    // Bounded in case of a (shouldn't happen) cycle
+    for _ in 0..=ids.len() {
+        match ids.get(&id) {
+            Some(&new_id) => id = new_id,
+            None => break,
+        }
+    }
+    id
+}
+
+async fn apply(
+    jwt: String,
+    changes: Vec<Change>,
+    mut ids: BTreeMap<i64, i64>,
+    undo: bool,
+) -> PartialOk<Outcome> {
+    let mut outcome = Outcome::default();
+    for change in &changes {
+        let res = match (change, undo) {
+            (Change::Created(snapshot), false) | (Change::Deleted(snapshot), true) => {
+                let id = resolve(&ids, snapshot.root_id());
+                match untrash(&jwt, id, &mut outcome).await {
+                    Ok(()) => Ok(()),
+                    Err(e) => {
+                        console::log!("error restoring from the trash: {}", e);
+                        restore(&jwt, snapshot, &mut ids, &mut outcome).await
+                    }
+                }
+            }
+            (Change::Created(snapshot), true) | (Change::Deleted(snapshot), false) => {
+                delete(&jwt, resolve(&ids, snapshot.root_id()), &mut outcome).await
+            }
+            (Change::Updated { before, .. }, true) => {
+                update(&jwt, before, &ids, &mut outcome).await
+            }
+            (Change::Updated { after, .. }, false) => update(&jwt, after, &ids, &mut outcome).await,
+        };
+        if let Err(e) = res {
+            outcome.ids = ids;
+            return PartialOk(outcome, Some(e));
+        }
+    }
+    outcome.ids = ids;
+    PartialOk(outcome, None)
+}
+
+async fn delete(jwt: &str, id: i64, outcome: &mut Outcome) -> anyhow::Result<()> {
+    let vars = DeletePartVars {
+        id: id.to_string(),
+        owner: None,
+    };
+    send_delete_part(jwt.to_string(), vars).await?;
+    outcome.deleted.push(id);
+    Ok(())
+}
+
+// Restores the deleted part and its children from the trash, along with their time entries
+async fn untrash(jwt: &str, id: i64, outcome: &mut Outcome) -> anyhow::Result<()> {
+    let parts = send_restore_part(jwt.to_string(), id).await?;
+    match get_time_entries(jwt.to_string()).await {
+        Ok(entries) => {
+            for entry in entries {
+                let entry: TimeEntry = entry.try_into()?;
+                if parts.iter().any(|part| part.id == entry.part_id) {
+                    outcome.time_entries.push(entry);
+                }
+            }
+        }
+        Err(e) => console::log!("error getting restored time entries: {}", e),
+    }
+    outcome.created.extend(parts);
+    Ok(())
+}
+
+// Sets all the part's fields to those of the target
+async fn update(
+    jwt: &str,
+    target: &Part,
+    ids: &BTreeMap<i64, i64>,
+    outcome: &mut Outcome,
+) -> anyhow::Result<()> {
+    let timestamp =
+        |dt: Option<DateTime<Local>>| dt.map(|dt| dt.timestamp().to_string()).unwrap_or_default();
+    let changes = HashMap::from([
+        (String::from("name"), target.name.clone()),
+        (
+            String::from("description"),
+            target.description.clone().unwrap_or_default(),
+        ),
+        (String::from("deadline"), timestamp(target.deadline)),
+        (String::from("completed_at"), timestamp(target.completed_at)),
+        (String::from("tags"), target.tags.join(" ")),
+        (
+            String::from("assignee"),
+            target.assignee.clone().unwrap_or_default(),
+        ),
+        (
+            String::from("parent_id"),
+            target
+                .parent_id
+                .map(|pid| resolve(ids, pid).to_string())
+                .unwrap_or_default(),
+        ),
+    ]);
+    let vars = UpdatePartVars {
+        id: resolve(ids, target.id).to_string(),
+        changes,
+        owner: None,
+    };
+    outcome
+        .updated
+        .push(send_update_part(jwt.to_string(), vars).await?.try_into()?);
+    Ok(())
+}
+
+// Re-creates the parts in the snapshot. Blockers and time entries are restored on a best effort
+// basis (e.g., a blocker may have been deleted since).
+async fn restore(
+    jwt: &str,
+    snapshot: &Snapshot,
+    ids: &mut BTreeMap<i64, i64>,
+    outcome: &mut Outcome,
+) -> anyhow::Result<()> {
+    for part in &snapshot.parts {
+        let mut input = Part {
+            blocked_by: Vec::new(),
+            ..part.clone()
+        };
+        input.parent_id = part.parent_id.map(|pid| resolve(ids, pid));
+        let vars = CreatePartVars {
+            input: input.into(),
+            owner: None,
+        };
+        let created: Part = send_create_part(jwt.to_string(), vars).await?.try_into()?;
+        ids.insert(part.id, created.id);
+        outcome.created.push(created);
+    }
+    for &(part_id, blocker_id) in &snapshot.blockers {
+        let vars = AddBlockerVars {
+            id: resolve(ids, part_id).to_string(),
+            blocker_id: resolve(ids, blocker_id).to_string(),
+            owner: None,
+        };
+        match send_add_blocker(jwt.to_string(), vars).await {
+            Ok(part) => outcome.updated.push(part.try_into()?),
+            Err(e) => console::log!("error restoring blocker: {}", e),
+        }
+    }
+    for entry in &snapshot.time_entries {
+        let vars = CreateTimeEntryVars {
+            input: CreateTimeEntryInput {
+                part_id: resolve(ids, entry.part_id).to_string(),
+                started_at: entry.started_at.timestamp().to_string(),
+                ended_at: entry
+                    .ended_at
+                    .map(|dt| dt.timestamp().to_string())
+                    .unwrap_or_default(),
+            },
+        };
+        match send_create_time_entry(jwt.to_string(), vars).await {
+            Ok(entry) => outcome.time_entries.push(entry.try_into()?),
+            Err(e) => console::log!("error restoring time entry: {}", e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn created(id: i64) -> Entry {
+        let part = Part {
+            id,
+            name: format!("Part {}", id),
+            ..Default::default()
+        };
+        let snapshot = Snapshot {
+            parts: vec![part],
+            blockers: Vec::new(),
+            time_entries: Vec::new(),
+        };
+        Entry::new(format!("Create {}", id), vec![Change::Created(snapshot)])
+    }
+
+    #[test]
+    fn resolves_re_created_ids() {
+        let mut ids = BTreeMap::new();
+        assert_eq!(resolve(&ids, 1), 1);
+        // Creating 1 is undone and then redone after it was purged, re-creating it as 5
+        ids.insert(1, 5);
+        assert_eq!(resolve(&ids, 1), 5);
+        assert_eq!(resolve(&ids, 5), 5);
+        // An entry recorded since with the new id is undone and redone the same way
+        ids.insert(5, 9);
+        assert_eq!(resolve(&ids, 1), 9);
+        assert_eq!(resolve(&ids, 5), 9);
+        assert_eq!(resolve(&ids, 2), 2);
+    }
+
+    #[test]
+    fn resolves_cycles() {
+        let ids = BTreeMap::from([(1, 2), (2, 1)]);
+        assert!([1, 2].contains(&resolve(&ids, 1)));
+    }
+
+    #[test]
+    fn keeps_ids_across_undo_and_redo() {
+        let mut history = History::default();
+        history.record(created(1));
+        assert!(history.start(String::new(), true).is_some());
+        assert!(history.is_pending());
+        assert!(history.start(String::new(), true).is_none());
+        history.finish(&mut Outcome::default(), true);
+        assert_eq!(history.redo_entries().len(), 1);
+
+        // Redone after being purged
+        assert!(history.start(String::new(), false).is_some());
+        let mut outcome = Outcome {
+            ids: BTreeMap::from([(1, 5)]),
+            ..Default::default()
+        };
+        history.finish(&mut outcome, true);
+        assert_eq!(history.undo_entries().len(), 1);
+        assert_eq!(resolve(&history.ids, 1), 5);
+
+        // Undone (deleting 5) and redone after being purged again, re-creating it as 9
+        assert!(history.start(String::new(), true).is_some());
+        let mut outcome = Outcome {
+            ids: BTreeMap::from([(1, 5)]),
+            deleted: vec![5],
+            ..Default::default()
+        };
+        history.finish(&mut outcome, true);
+        assert_eq!(resolve(&history.ids, 1), 5);
+        assert!(history.start(String::new(), false).is_some());
+        let mut outcome = Outcome {
+            ids: BTreeMap::from([(1, 9)]),
+            ..Default::default()
+        };
+        history.finish(&mut outcome, true);
+        assert_eq!(history.undo_entries().len(), 1);
+        assert!(history.redo_entries().is_empty());
+        assert_eq!(resolve(&history.ids, 1), 9);
+    }
+}
@@ -1,8 +1,29 @@
 use wasm_bindgen::prelude::*;
 
+mod activity;
 mod app;
+mod avatar;
 pub mod console;
+mod dep_graph;
 pub mod graphql;
+mod history;
+mod login_form;
+mod markdown;
+mod modal;
+mod palette;
+mod part_form;
+mod parts_cache;
+mod quick_add;
+mod requests;
+mod route;
+mod stats;
+mod sync;
+mod templates;
+mod time_tracking;
+mod toast;
+mod tree_state;
+mod validation;
+mod virtual_list;
 
 #[wasm_bindgen(start)]
 pub async fn run() -> Result<(), JsValue> {
@@ -0,0 +1,302 @@
+// The sign in / sign up form shown until the user's logged in
+use crate::{
+    console,
+    graphql::{
+        create_user_mutation::{CreateUserInput, Variables as CreateUserVars},
+        login_user_mutation::{LoginUserInput, Variables as LoginUserVars},
+        send_create_user, send_login_user, send_request_password_reset,
+    },
+    validation::{self, FieldErrors},
+};
+use futures::FutureExt;
+use web_sys::HtmlInputElement;
+use yew::{html::TargetCast, prelude::*};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    SignIn,
+    SignUp,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct LoginFormProps {
+    // Emits the email of the user and their token once logged in (or signed up)
+    pub onlogin: Callback<(String, String)>,
+}
+
+pub enum LoginMsg {
+    SetMode(Mode),
+    SetEmail(String),
+    SetPassword(String),
+    SetConfirm(String),
+    Submit,
+    // Asks for a password reset link to be sent to the email
+    Forgot,
+    // The email the request was for and the token
+    Done(String, anyhow::Result<String>),
+    ForgotDone(anyhow::Result<bool>),
+}
+
+pub struct LoginForm {
+    mode: Mode,
+    email: String,
+    password: String,
+    confirm: String,
+    errors: FieldErrors,
+    // Whether a request is in flight, during which the form can't be submitted again
+    pending: bool,
+    // Shown once a reset link has been asked for
+    notice: Option<String>,
+}
+
+impl Component for LoginForm {
+    type Message = LoginMsg;
+    type Properties = LoginFormProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            mode: Mode::SignIn,
+            email: String::new(),
+            password: String::new(),
+            confirm: String::new(),
+            errors: FieldErrors::default(),
+            pending: false,
+            notice: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            LoginMsg::SetMode(mode) => {
+                self.mode = mode;
+                self.confirm.clear();
+                self.errors = FieldErrors::default();
+                self.notice = None;
+            }
+            // Editing a field clears its error (and the form's, which may be about it)
+            LoginMsg::SetEmail(email) => {
+                self.email = email;
+                self.errors.email = None;
+                self.errors.form = None;
+            }
+            LoginMsg::SetPassword(password) => {
+                self.password = password;
+                self.errors.password = None;
+                self.errors.form = None;
+            }
+            LoginMsg::SetConfirm(confirm) => {
+                self.confirm = confirm;
+                self.errors.confirm = None;
+            }
+            LoginMsg::Submit => {
+                if self.pending {
+                    return false;
+                }
+                self.notice = None;
+                self.errors = self.validate();
+                if !self.errors.is_empty() {
+                    return true;
+                }
+                self.pending = true;
+                let email = self.email.trim().to_string();
+                let password = self.password.clone();
+                let res = match self.mode {
+                    Mode::SignIn => send_login_user(LoginUserVars {
+                        input: LoginUserInput {
+                            email: email.clone(),
+                            password,
+                        },
+                    })
+                    .boxed_local(),
+                    Mode::SignUp => send_create_user(CreateUserVars {
+                        input: CreateUserInput {
+                            email: email.clone(),
+                            password,
+                        },
+                    })
+                    .boxed_local(),
+                };
+                ctx.link()
+                    .send_future(res.map(move |res| LoginMsg::Done(email, res)));
+            }
+            LoginMsg::Forgot => {
+                if self.pending {
+                    return false;
+                }
+                self.notice = None;
+                self.errors = FieldErrors::default();
+                let email = self.email.trim().to_string();
+                if let Some(error) = validation::email_error(&email) {
+                    self.errors.email = Some(String::from(error));
+                    return true;
+                }
+                self.pending = true;
+                let res = send_request_password_reset(email);
+                ctx.link().send_future(res.map(LoginMsg::ForgotDone));
+            }
+            LoginMsg::Done(email, res) => {
+                self.pending = false;
+                match res {
+                    // An empty token means the credentials weren't accepted
+                    Ok(jwt) if jwt.is_empty() => {
+                        self.errors.form = Some(String::from("Incorrect email or password"));
+                    }
+                    Ok(jwt) => {
+                        self.password.clear();
+                        self.confirm.clear();
+                        ctx.props().onlogin.emit((email, jwt));
+                    }
+                    Err(e) => {
+                        console::log!("Error logging in: {}", e);
+                        self.errors = FieldErrors::from_server(&e.to_string());
+                    }
+                }
+            }
+            LoginMsg::ForgotDone(res) => {
+                self.pending = false;
+                match res {
+                    Ok(_) => {
+                        self.notice = Some(String::from(
+                            "If there's an account with that email, \
+                            a link to reset its password was sent to it",
+                        ));
+                    }
+                    Err(e) => {
+                        console::log!("Error requesting password reset: {}", e);
+                        self.errors = FieldErrors::from_server(&e.to_string());
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let onsubmit = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            LoginMsg::Submit
+        });
+        let set_email = ctx.link().callback(|e: InputEvent| {
+            LoginMsg::SetEmail(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let set_password = ctx.link().callback(|e: InputEvent| {
+            LoginMsg::SetPassword(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let set_confirm = ctx.link().callback(|e: InputEvent| {
+            LoginMsg::SetConfirm(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let forgot = ctx.link().callback(|_| LoginMsg::Forgot);
+        let sign_up = self.mode == Mode::SignUp;
+        let tab = |mode: Mode, label: &str| {
+            html! {
+                <button
+                    type="button"
+                    class={classes!("login-tab", (self.mode == mode).then_some("active"))}
+                    disabled={self.pending}
+                    onclick={ctx.link().callback(move |_| LoginMsg::SetMode(mode))}
+                >{ label }</button>
+            }
+        };
+        let field_error = |error: &Option<String>| match error {
+            Some(error) => html! { <p class="field-error">{ error }</p> },
+            None => html! {},
+        };
+        html! {
+            <form id="login-register-div" {onsubmit} novalidate=true>
+                <div id="login-tabs-div">
+                    { tab(Mode::SignIn, "Sign In") }
+                    { tab(Mode::SignUp, "Sign Up") }
+                </div>
+                <input
+                    type="email"
+                    name="email"
+                    size="40"
+                    placeholder="Email"
+                    autocomplete="email"
+                    class={classes!(self.errors.email.is_some().then_some("invalid"))}
+                    value={self.email.clone()}
+                    oninput={set_email}
+                /><br />
+                { field_error(&self.errors.email) }
+                <input
+                    type="password"
+                    name="password"
+                    size="40"
+                    placeholder="Password"
+                    autocomplete={if sign_up { "new-password" } else { "current-password" }}
+                    class={classes!(self.errors.password.is_some().then_some("invalid"))}
+                    value={self.password.clone()}
+                    oninput={set_password}
+                /><br />
+                { field_error(&self.errors.password) }
+                if sign_up {
+                    if !self.password.is_empty() {
+                        { self.render_strength() }
+                    }
+                    <input
+                        type="password"
+                        name="confirm"
+                        size="40"
+                        placeholder="Confirm Password"
+                        autocomplete="new-password"
+                        class={classes!(self.errors.confirm.is_some().then_some("invalid"))}
+                        value={self.confirm.clone()}
+                        oninput={set_confirm}
+                    /><br />
+                    { field_error(&self.errors.confirm) }
+                }
+                <div id="logreg-button-div">
+                    <button type="submit" disabled={self.pending}>
+                        if self.pending {
+                            <span class="spinner"></span>
+                        }
+                        { if sign_up { "Sign Up" } else { "Sign In" } }
+                    </button>
+                </div>
+                if !sign_up {
+                    <div id="forgot-button-div">
+                        <button type="button" disabled={self.pending} onclick={forgot}>
+                            { "Forgot Password?" }
+                        </button>
+                    </div>
+                }
+                { field_error(&self.errors.form) }
+                if let Some(notice) = self.notice.as_ref() {
+                    <p class="login-notice">{ notice }</p>
+                }
+            </form>
+        }
+    }
+}
+
+impl LoginForm {
+    fn validate(&self) -> FieldErrors {
+        let mut errors = FieldErrors {
+            email: validation::email_error(self.email.trim()).map(String::from),
+            ..Default::default()
+        };
+        match self.mode {
+            Mode::SignIn => {
+                if self.password.is_empty() {
+                    errors.password = Some(String::from("Enter your password"));
+                }
+            }
+            Mode::SignUp => {
+                errors.password = validation::new_password_error(&self.password).map(String::from);
+                if self.confirm != self.password {
+                    errors.confirm = Some(String::from("The passwords don't match"));
+                }
+            }
+        }
+        errors
+    }
+
+    fn render_strength(&self) -> Html {
+        let strength = validation::password_strength(&self.password);
+        html! {
+            <p class={classes!("password-strength", strength.label().to_lowercase())}>
+                { format!("Strength: {}", strength.label()) }
+            </p>
+        }
+    }
+}
@@ -0,0 +1,189 @@
+// Renders part descriptions (CommonMark) as sanitized HTML. Raw HTML in the source is escaped
+// rather than passed through, and links/images are only allowed to use safe URL schemes, so the
+// output can be inserted into the page as is.
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+
+// URL schemes links and images are allowed to use (relative URLs are always allowed)
+const SAFE_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+fn options() -> Options {
+    Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES
+}
+
+// Renders the markdown as sanitized HTML. Task list checkboxes are rendered with a
+// "data-task-index" attribute holding the index of the task in the source (see toggle_task).
+pub fn render(src: &str) -> String {
+    let mut task_index = 0;
+    let events = Parser::new_ext(src, options()).map(|event| match event {
+        Event::Html(raw) => Event::Text(raw),
+        Event::Start(tag) => Event::Start(sanitize_tag(tag)),
+        Event::End(tag) => Event::End(sanitize_tag(tag)),
+        Event::TaskListMarker(checked) => {
+            let input = format!(
+                r#"<input type="checkbox" class="task-checkbox" data-task-index="{}"{} />"#,
+                task_index,
+                if checked { " checked" } else { "" },
+            );
+            task_index += 1;
+            Event::Html(input.into())
+        }
+        event => event,
+    });
+    let mut out = String::with_capacity(src.len() * 3 / 2);
+    html::push_html(&mut out, events);
+    out
+}
+
+// Returns the source with the checked state of the task at the given index flipped, or None if
+// there's no such task
+pub fn toggle_task(src: &str, index: usize) -> Option<String> {
+    let (_, range) = Parser::new_ext(src, options())
+        .into_offset_iter()
+        .filter(|(event, _)| matches!(event, Event::TaskListMarker(_)))
+        .nth(index)?;
+    // The range covers the marker (e.g., "[ ]" or "[x]")
+    let open = range.start + src[range.clone()].find('[')?;
+    let mark = match &src[open + 1..open + 2] {
+        " " => "x",
+        "x" | "X" => " ",
+        _ => return None,
+    };
+    let mut toggled = String::with_capacity(src.len());
+    toggled.push_str(&src[..open + 1]);
+    toggled.push_str(mark);
+    toggled.push_str(&src[open + 2..]);
+    Some(toggled)
+}
+
+fn sanitize_tag(tag: Tag<'_>) -> Tag<'_> {
+    match tag {
+        Tag::Link(link_type, url, title) => Tag::Link(link_type, sanitize_url(url), title),
+        Tag::Image(link_type, url, title) => Tag::Image(link_type, sanitize_url(url), title),
+        tag => tag,
+    }
+}
+
+// Replaces URLs with unsafe schemes (e.g., "javascript:") with an empty URL
+fn sanitize_url(url: CowStr<'_>) -> CowStr<'_> {
+    if url_is_safe(&url) {
+        url
+    } else {
+        CowStr::Borrowed("")
+    }
+}
+
+fn url_is_safe(url: &str) -> bool {
+    // Browsers ignore whitespace and control characters in schemes (e.g., "java\nscript:")
+    let url = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_lowercase();
+    match url.find([':', '/', '?', '#']) {
+        Some(i) if url.as_bytes()[i] == b':' => SAFE_SCHEMES.contains(&&url[..i]),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Returns the values of all the given attribute in the html
+    fn attr_values<'a>(html: &'a str, attr: &str) -> Vec<&'a str> {
+        let prefix = format!(r#"{}=""#, attr);
+        html.match_indices(&prefix)
+            .map(|(i, _)| {
+                let rest = &html[i + prefix.len()..];
+                &rest[..rest.find('"').unwrap()]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn renders_commonmark() {
+        let out = render("# Title\n\n- one\n- **two**\n\n`code`\n\n[link](https://example.com)");
+        assert!(out.contains("<h1>Title</h1>"));
+        assert!(out.contains("<li><strong>two</strong></li>"));
+        assert!(out.contains("<code>code</code>"));
+        assert!(out.contains(r#"<a href="https://example.com">link</a>"#));
+    }
+
+    #[test]
+    fn escapes_script_tags() {
+        let out = render("hi <script>alert(1)</script>\n\n<script>\nalert(2)\n</script>");
+        assert!(!out.contains("<script"));
+        assert!(out.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn escapes_event_handler_attributes() {
+        let out = render(r#"<img src="x" onerror="alert(1)"> <div onclick="alert(2)">x</div>"#);
+        assert!(!out.contains("<img"));
+        assert!(!out.contains("<div"));
+    }
+
+    #[test]
+    fn strips_javascript_urls() {
+        for src in [
+            "[x](javascript:alert(1))",
+            "[x](JaVaScRiPt:alert(1))",
+            "[x](javascript&#58;alert(1))",
+            "[x](<java\tscript:alert(1)>)",
+            "<javascript:alert(1)>",
+            "![x](javascript:alert(1))",
+            "[x](data:text/html;base64,PHNjcmlwdD5hbGVydCgxKTwvc2NyaXB0Pg==)",
+            "[x](vbscript:msgbox(1))",
+            "[x][ref]\n\n[ref]: javascript:alert(1)",
+        ] {
+            let out = render(src);
+            let urls = [attr_values(&out, "href"), attr_values(&out, "src")].concat();
+            assert!(urls.iter().all(|url| url.is_empty()), "{}: {}", src, out);
+        }
+    }
+
+    #[test]
+    fn keeps_safe_urls() {
+        for url in [
+            "https://example.com",
+            "mailto:a@b.com",
+            "/part/1",
+            "#top",
+            "a/b:c",
+        ] {
+            let out = render(&format!("[x]({})", url));
+            assert!(out.contains(&format!(r#"href="{}""#, url)), "{}", out);
+        }
+    }
+
+    #[test]
+    fn escapes_link_titles() {
+        let out = render(r#"[x](https://example.com "a\" onmouseover=\"alert(1)")"#);
+        assert!(!out.contains(r#"" onmouseover"#));
+    }
+
+    #[test]
+    fn renders_task_checkboxes() {
+        let out = render("- [ ] first\n- [x] second");
+        assert!(out.contains(r#"data-task-index="0" />"#));
+        assert!(out.contains(r#"data-task-index="1" checked />"#));
+    }
+
+    #[test]
+    fn toggles_tasks() {
+        let src = "Intro [ ] not a task\n\n- [ ] first\n- [X] second\n  - [ ] nested";
+        assert_eq!(
+            toggle_task(src, 0).unwrap(),
+            "Intro [ ] not a task\n\n- [x] first\n- [X] second\n  - [ ] nested",
+        );
+        assert_eq!(
+            toggle_task(src, 1).unwrap(),
+            "Intro [ ] not a task\n\n- [ ] first\n- [ ] second\n  - [ ] nested",
+        );
+        assert_eq!(
+            toggle_task(src, 2).unwrap(),
+            "Intro [ ] not a task\n\n- [ ] first\n- [X] second\n  - [x] nested",
+        );
+        assert_eq!(toggle_task(src, 3), None);
+    }
+}
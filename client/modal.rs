@@ -0,0 +1,142 @@
+// The dialog asking the user to confirm something before it's carried out, or for some text
+// (e.g., a name) to carry it out with
+use web_sys::{HtmlButtonElement, HtmlInputElement};
+use yew::prelude::*;
+
+#[derive(Clone, PartialEq)]
+pub struct Confirm {
+    pub message: String,
+    // The label of the button that confirms (e.g., "Delete")
+    pub confirm_label: String,
+    // Whether what's confirmed destroys something, which the confirm button is styled for
+    pub danger: bool,
+    // The initial value of the text input asked for, if any
+    pub input: Option<String>,
+    // Emits the text entered (empty without an input)
+    pub onconfirm: Callback<String>,
+}
+
+impl Confirm {
+    pub fn new(message: impl Into<String>, confirm_label: &str, onconfirm: Callback<()>) -> Self {
+        Self {
+            message: message.into(),
+            confirm_label: String::from(confirm_label),
+            danger: false,
+            input: None,
+            onconfirm: onconfirm.reform(|_| ()),
+        }
+    }
+
+    // Asks for text in an input starting with the given value
+    pub fn prompt(
+        message: impl Into<String>,
+        confirm_label: &str,
+        value: impl Into<String>,
+        onconfirm: Callback<String>,
+    ) -> Self {
+        Self {
+            message: message.into(),
+            confirm_label: String::from(confirm_label),
+            danger: false,
+            input: Some(value.into()),
+            onconfirm,
+        }
+    }
+
+    pub fn danger(mut self) -> Self {
+        self.danger = true;
+        self
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ConfirmModalProps {
+    pub confirm: Confirm,
+    // Emits whether it was confirmed
+    pub onclose: Callback<bool>,
+}
+
+pub struct ConfirmModal {
+    cancel_ref: NodeRef,
+    input_ref: NodeRef,
+}
+
+impl Component for ConfirmModal {
+    type Message = bool;
+    type Properties = ConfirmModalProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            cancel_ref: NodeRef::default(),
+            input_ref: NodeRef::default(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, confirmed: bool) -> bool {
+        let props = ctx.props();
+        if confirmed {
+            let text = self
+                .input_ref
+                .cast::<HtmlInputElement>()
+                .map(|input| input.value())
+                .unwrap_or_default();
+            props.confirm.onconfirm.emit(text);
+        }
+        props.onclose.emit(confirmed);
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let confirm = &ctx.props().confirm;
+        let onkeydown = ctx.link().batch_callback(|e: KeyboardEvent| {
+            // Kept from the app's keyboard shortcuts
+            e.stop_propagation();
+            (e.key() == "Escape").then_some(false)
+        });
+        // Only clicking outside of the dialog cancels
+        let cancel_outside = ctx
+            .link()
+            .batch_callback(|e: MouseEvent| (e.target() == e.current_target()).then_some(false));
+        html! {
+            <div id="modal-backdrop" onclick={cancel_outside} {onkeydown}>
+                <div id="modal-div" role="dialog" aria-modal="true">
+                    <p>{ &confirm.message }</p>
+                    if let Some(value) = confirm.input.as_ref() {
+                        <input
+                            type="text"
+                            ref={self.input_ref.clone()}
+                            value={value.clone()}
+                            onkeydown={ctx.link().batch_callback(|e: KeyboardEvent| {
+                                (e.key() == "Enter").then_some(true)
+                            })}
+                        />
+                    }
+                    <div id="modal-buttons-div">
+                        <button
+                            ref={self.cancel_ref.clone()}
+                            onclick={ctx.link().callback(|_| false)}
+                        >{ "Cancel" }</button>
+                        <button
+                            class={classes!(confirm.danger.then_some("danger"))}
+                            onclick={ctx.link().callback(|_| true)}
+                        >{ &confirm.confirm_label }</button>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, _ctx: &Context<Self>, first_render: bool) {
+        if !first_render {
+            return;
+        }
+        if let Some(input) = self.input_ref.cast::<HtmlInputElement>() {
+            // The text is entered right away (and Enter confirms it)
+            let _ = input.focus();
+            input.select();
+        } else if let Some(button) = self.cancel_ref.cast::<HtmlButtonElement>() {
+            // Cancelling is the default so Enter doesn't confirm by accident
+            let _ = button.focus();
+        }
+    }
+}
@@ -0,0 +1,238 @@
+// The command palette (opened with Ctrl+K), which lists the items fuzzy matching what's typed
+use std::rc::Rc;
+use web_sys::{Element, HtmlInputElement, ScrollIntoViewOptions, ScrollLogicalPosition};
+use yew::{html::TargetCast, prelude::*};
+
+// The max number of matches listed
+const MAX_SHOWN: usize = 50;
+
+#[derive(Properties, PartialEq)]
+pub struct PaletteProps {
+    pub items: Rc<Vec<String>>,
+    // Emits the index of the picked item
+    pub onselect: Callback<usize>,
+    pub onclose: Callback<()>,
+}
+
+pub enum PaletteMsg {
+    SetQuery(String),
+    // Moves the highlight by the given number of matches
+    Move(isize),
+    // Picks the match at the given position
+    Select(usize),
+    Close,
+}
+
+pub struct CommandPalette {
+    query: String,
+    // The indices of the items matching the query, best first
+    matches: Vec<usize>,
+    // The position of the highlighted match
+    active: usize,
+    input_ref: NodeRef,
+    list_ref: NodeRef,
+}
+
+impl Component for CommandPalette {
+    type Message = PaletteMsg;
+    type Properties = PaletteProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        Self {
+            query: String::new(),
+            matches: (0..ctx.props().items.len()).take(MAX_SHOWN).collect(),
+            active: 0,
+            input_ref: NodeRef::default(),
+            list_ref: NodeRef::default(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            PaletteMsg::SetQuery(query) => {
+                self.matches = fuzzy_matches(&query, &ctx.props().items);
+                self.query = query;
+                self.active = 0;
+            }
+            PaletteMsg::Move(delta) => {
+                if self.matches.is_empty() {
+                    return false;
+                }
+                let len = self.matches.len() as isize;
+                self.active = (self.active as isize + delta).rem_euclid(len) as usize;
+            }
+            PaletteMsg::Select(pos) => {
+                if let Some(&index) = self.matches.get(pos) {
+                    ctx.props().onselect.emit(index);
+                }
+                return false;
+            }
+            PaletteMsg::Close => {
+                ctx.props().onclose.emit(());
+                return false;
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let items = &ctx.props().items;
+        let set_query = ctx.link().callback(|e: InputEvent| {
+            PaletteMsg::SetQuery(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let active = self.active;
+        let onkeydown = ctx.link().batch_callback(move |e: KeyboardEvent| {
+            let msg = match e.key().as_str() {
+                "ArrowDown" => PaletteMsg::Move(1),
+                "ArrowUp" => PaletteMsg::Move(-1),
+                "Enter" => PaletteMsg::Select(active),
+                "Escape" => PaletteMsg::Close,
+                _ => return None,
+            };
+            e.prevent_default();
+            Some(msg)
+        });
+        // Only clicking outside of the palette closes it
+        let close = ctx.link().batch_callback(|e: MouseEvent| {
+            (e.target() == e.current_target()).then_some(PaletteMsg::Close)
+        });
+        html! {
+            <div id="palette-backdrop" onclick={close}>
+                <div id="palette-div">
+                    <input
+                        type="text"
+                        placeholder="Type a command or part name"
+                        ref={self.input_ref.clone()}
+                        value={self.query.clone()}
+                        oninput={set_query}
+                        {onkeydown}
+                    />
+                    <ul ref={self.list_ref.clone()}>
+                        {
+                            self.matches.iter().enumerate().map(|(pos, &index)| {
+                                let select = ctx.link().callback(move |_| PaletteMsg::Select(pos));
+                                html! {
+                                    <li
+                                        class={classes!((pos == active).then_some("palette-active"))}
+                                        onclick={select}
+                                    >{ &items[index] }</li>
+                                }
+                            }).collect::<Html>()
+                        }
+                    </ul>
+                    if self.matches.is_empty() {
+                        <p>{ "No matches" }</p>
+                    }
+                </div>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, _ctx: &Context<Self>, first_render: bool) {
+        if first_render {
+            if let Some(input) = self.input_ref.cast::<HtmlInputElement>() {
+                let _ = input.focus();
+            }
+        }
+        let active = self
+            .list_ref
+            .cast::<Element>()
+            .and_then(|list| list.query_selector(".palette-active").ok().flatten());
+        if let Some(elem) = active {
+            let mut options = ScrollIntoViewOptions::new();
+            options.block(ScrollLogicalPosition::Nearest);
+            elem.scroll_into_view_with_scroll_into_view_options(&options);
+        }
+    }
+}
+
+// Returns the indices of the (at most MAX_SHOWN) items matching the query, best first. Ties
+// are broken by shorter items first, then by their order. All the items match an empty query, in
+// their order (as when the palette is opened).
+fn fuzzy_matches(query: &str, items: &[String]) -> Vec<usize> {
+    if query.trim().is_empty() {
+        return (0..items.len()).take(MAX_SHOWN).collect();
+    }
+    let mut scored = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| Some((fuzzy_score(query, item)?, item.len(), i)))
+        .collect::<Vec<_>>();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+    scored
+        .into_iter()
+        .take(MAX_SHOWN)
+        .map(|(_, _, i)| i)
+        .collect()
+}
+
+// Scores how well the query matches the text (higher is better), favoring consecutive matches
+// and matches at the start of words. Returns None if the query's characters (ignoring case and
+// whitespace) don't all appear in the text in order.
+fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text = text.to_lowercase().chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut start = 0;
+    let mut prev = None;
+    for qc in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let i = (start..text.len()).find(|&i| text[i] == qc)?;
+        score += 1;
+        if i > 0 && prev == Some(i - 1) {
+            score += 4;
+        }
+        if i == 0 || !text[i - 1].is_alphanumeric() {
+            score += 3;
+        }
+        prev = Some(i);
+        start = i + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items() -> Vec<String> {
+        ["Go to Trash", "Toggle Stats", "gt", "Logout"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    #[test]
+    fn orders_matches() {
+        // Consecutive matches and matches at the start of words score higher, and ties go to
+        // the shorter item
+        assert_eq!(fuzzy_matches("gt", &items()), vec![2, 0, 3, 1]);
+        assert_eq!(fuzzy_matches("stats", &items()), vec![1]);
+    }
+
+    #[test]
+    fn ignores_case_and_whitespace() {
+        assert_eq!(fuzzy_matches("GT", &items()), fuzzy_matches("gt", &items()));
+        assert_eq!(
+            fuzzy_matches("g t", &items()),
+            fuzzy_matches("gt", &items())
+        );
+        assert_eq!(fuzzy_matches("TRASH", &items()), vec![0]);
+    }
+
+    #[test]
+    fn matches_everything_when_empty() {
+        assert_eq!(fuzzy_matches("", &items()), vec![0, 1, 2, 3]);
+        assert_eq!(fuzzy_matches("  ", &items()), vec![0, 1, 2, 3]);
+        let many = (0..MAX_SHOWN + 10)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(fuzzy_matches("", &many).len(), MAX_SHOWN);
+    }
+
+    #[test]
+    fn excludes_non_matches() {
+        assert!(fuzzy_matches("xyz", &items()).is_empty());
+        // The characters must appear in order
+        assert_eq!(fuzzy_matches("tg", &items()), vec![1]);
+        assert_eq!(fuzzy_matches("tug", &items()), Vec::<usize>::new());
+        assert!(fuzzy_matches("gt", &[]).is_empty());
+    }
+}
@@ -0,0 +1,529 @@
+// The form used to create and edit parts. The form's state is held in the component and
+// validated as it's edited, so callers only ever get valid values.
+use crate::{graphql::Part, markdown};
+use chrono::prelude::*;
+use std::{
+    collections::BTreeSet,
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::{html::TargetCast, prelude::*};
+
+// A part that can be picked as the parent
+#[derive(Clone, PartialEq)]
+pub struct ParentOption {
+    pub id: i64,
+    // The names of the part's ancestors and the part (see time_tracking::part_path)
+    pub path: String,
+    // When the part was started, which is when time was first logged on it (parts don't have a
+    // start date otherwise)
+    pub started_at: Option<DateTime<Local>>,
+}
+
+// The validated values of the form
+#[derive(Debug)]
+pub struct PartDraft {
+    pub name: String,
+    pub description: Option<String>,
+    pub deadline: Option<DateTime<Local>>,
+    pub completed_at: Option<DateTime<Local>>,
+    pub parent_id: Option<i64>,
+    pub tags: Vec<String>,
+    pub assignee: Option<String>,
+}
+
+impl PartDraft {
+    pub fn into_part(self) -> Part {
+        Part {
+            name: self.name,
+            description: self.description,
+            deadline: self.deadline,
+            completed_at: self.completed_at,
+            parent_id: self.parent_id,
+            tags: self.tags,
+            assignee: self.assignee,
+            ..Default::default()
+        }
+    }
+
+    // Returns the changes (as expected by updatePart) needed to turn the part into the draft
+    pub fn changes_from(&self, part: &Part) -> std::collections::HashMap<String, String> {
+        let timestamp = |dt: Option<DateTime<Local>>| {
+            dt.map(|dt| dt.timestamp().to_string()).unwrap_or_default()
+        };
+        let mut changes = std::collections::HashMap::new();
+        if self.name != part.name {
+            changes.insert(String::from("name"), self.name.clone());
+        }
+        if self.description != part.description {
+            changes.insert(
+                String::from("description"),
+                self.description.clone().unwrap_or_default(),
+            );
+        }
+        if self.deadline != part.deadline {
+            changes.insert(String::from("deadline"), timestamp(self.deadline));
+        }
+        if self.completed_at != part.completed_at {
+            changes.insert(String::from("completed_at"), timestamp(self.completed_at));
+        }
+        if self.parent_id != part.parent_id {
+            changes.insert(
+                String::from("parent_id"),
+                self.parent_id
+                    .map(|pid| pid.to_string())
+                    .unwrap_or_default(),
+            );
+        }
+        if self.tags != part.tags {
+            changes.insert(String::from("tags"), self.tags.join(" "));
+        }
+        if self.assignee != part.assignee {
+            changes.insert(
+                String::from("assignee"),
+                self.assignee.clone().unwrap_or_default(),
+            );
+        }
+        changes
+    }
+}
+
+// Parses whitespace separated tags (with or without a leading '#'), sorted and deduplicated like
+// the server stores them
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags = input
+        .split_whitespace()
+        .map(|tag| tag.trim_start_matches('#'))
+        .filter(|tag| !tag.is_empty())
+        .map(String::from)
+        .collect::<Vec<_>>();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Field {
+    Name,
+    Description,
+    Deadline,
+    CompletedAt,
+    Tags,
+}
+
+pub enum PartFormMsg {
+    Set(Field, String),
+    SetParent(Option<i64>),
+    SetAssignee(Option<String>),
+    TogglePreview,
+    Submit,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct PartFormProps {
+    // The part being edited (None when creating a part)
+    #[prop_or_default]
+    pub part: Option<Part>,
+    // The parent initially picked when creating a part
+    #[prop_or_default]
+    pub parent_id: Option<i64>,
+    // The parts that can be picked as the parent
+    pub parents: Rc<Vec<ParentOption>>,
+    // The emails of the users the part can be assigned to (the owner and the project's members)
+    #[prop_or_default]
+    pub assignees: Rc<Vec<String>>,
+    pub submit_label: AttrValue,
+    // Whether the submitted part is still being saved, during which it can't be submitted again
+    #[prop_or_default]
+    pub pending: bool,
+    pub onsubmit: Callback<PartDraft>,
+    pub oncancel: Callback<()>,
+}
+
+// Numbers the forms so several can be shown at once with unique input ids
+static NEXT_FORM: AtomicUsize = AtomicUsize::new(0);
+
+pub struct PartForm {
+    // Distinguishes the form's input ids from those of other forms
+    form: usize,
+    name: String,
+    description: String,
+    // The datetime-local input values
+    deadline: String,
+    completed_at: String,
+    parent_id: Option<i64>,
+    // Whitespace separated
+    tags: String,
+    assignee: Option<String>,
+    previewing: bool,
+    // Errors are only shown for fields that have been changed, until submitting is attempted
+    touched: BTreeSet<Field>,
+    submitted: bool,
+}
+
+#[derive(Default)]
+struct FieldErrors {
+    name: Option<String>,
+    deadline: Option<String>,
+    completed_at: Option<String>,
+}
+
+impl Component for PartForm {
+    type Message = PartFormMsg;
+    type Properties = PartFormProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let props = ctx.props();
+        match props.part.as_ref() {
+            Some(part) => Self {
+                form: NEXT_FORM.fetch_add(1, Ordering::Relaxed),
+                name: part.name.clone(),
+                description: part.description.clone().unwrap_or_default(),
+                deadline: part.deadline_to_input(),
+                completed_at: part.completed_at_to_input(),
+                parent_id: part.parent_id,
+                tags: part.tags.join(" "),
+                assignee: part.assignee.clone(),
+                previewing: false,
+                touched: BTreeSet::new(),
+                submitted: false,
+            },
+            None => Self {
+                form: NEXT_FORM.fetch_add(1, Ordering::Relaxed),
+                name: String::new(),
+                description: String::new(),
+                deadline: String::new(),
+                completed_at: String::new(),
+                parent_id: props.parent_id,
+                tags: String::new(),
+                assignee: None,
+                previewing: false,
+                touched: BTreeSet::new(),
+                submitted: false,
+            },
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            PartFormMsg::Set(field, value) => {
+                match field {
+                    Field::Name => self.name = value,
+                    Field::Description => self.description = value,
+                    Field::Deadline => self.deadline = value,
+                    Field::CompletedAt => self.completed_at = value,
+                    Field::Tags => self.tags = value,
+                }
+                self.touched.insert(field);
+            }
+            PartFormMsg::SetParent(pid) => self.parent_id = pid,
+            PartFormMsg::SetAssignee(assignee) => self.assignee = assignee,
+            PartFormMsg::TogglePreview => self.previewing = !self.previewing,
+            PartFormMsg::Submit => {
+                if ctx.props().pending {
+                    return false;
+                }
+                self.submitted = true;
+                if let Ok(draft) = self.validate(ctx) {
+                    ctx.props().onsubmit.emit(draft);
+                }
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let errors = self.validate(ctx).err().unwrap_or_default();
+        let shown_error = |field: Field, error: Option<String>| {
+            let show = self.submitted || self.touched.contains(&field);
+            html! {
+                if let Some(error) = error.filter(|_| show) {
+                    <span class="field-error">{ error }</span>
+                }
+            }
+        };
+
+        let onsubmit = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            PartFormMsg::Submit
+        });
+        let oncancel = props.oncancel.reform(|_| ());
+        let set_name = ctx.link().callback(|e: InputEvent| {
+            PartFormMsg::Set(
+                Field::Name,
+                e.target_unchecked_into::<HtmlInputElement>().value(),
+            )
+        });
+        let set_desc = ctx.link().callback(|e: InputEvent| {
+            let area = e.target_unchecked_into::<HtmlTextAreaElement>();
+            PartFormMsg::Set(Field::Description, area.value())
+        });
+        let set_deadline = ctx.link().callback(|e: InputEvent| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            PartFormMsg::Set(Field::Deadline, input.value())
+        });
+        let clear_deadline = ctx
+            .link()
+            .callback(|_| PartFormMsg::Set(Field::Deadline, String::new()));
+        let set_comp_at = ctx.link().callback(|e: InputEvent| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            PartFormMsg::Set(Field::CompletedAt, input.value())
+        });
+        let clear_comp_at = ctx
+            .link()
+            .callback(|_| PartFormMsg::Set(Field::CompletedAt, String::new()));
+        let set_tags = ctx.link().callback(|e: InputEvent| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            PartFormMsg::Set(Field::Tags, input.value())
+        });
+        let set_parent = ctx.link().callback(|e: Event| {
+            let select = e.target_unchecked_into::<HtmlSelectElement>();
+            PartFormMsg::SetParent(select.value().parse().ok())
+        });
+        let set_assignee = ctx.link().callback(|e: Event| {
+            let select = e.target_unchecked_into::<HtmlSelectElement>();
+            let value = select.value();
+            PartFormMsg::SetAssignee((!value.is_empty()).then_some(value))
+        });
+        // The current assignee stays pickable even if they're no longer a member
+        let assignees = props.assignees.iter().chain(
+            self.assignee
+                .iter()
+                .filter(|a| !props.assignees.contains(a)),
+        );
+        let toggle_preview = ctx.link().callback(|_| PartFormMsg::TogglePreview);
+        let id = |name: &str| format!("{}-{}", name, self.form);
+        let parent_start = self.parent_start(ctx);
+        html! {
+            <form class="part-form" {onsubmit}>
+                <button type="submit" style="margin:10px" disabled={props.pending}>
+                    if props.pending {
+                        <span class="spinner"></span>
+                    }
+                    { props.submit_label.clone() }
+                </button>
+                <button type="button" onclick={oncancel}>{ "Cancel" }</button>
+                <br />
+
+                <label for={id("part-name")}><u>{ "Name" }</u>{ ": " }</label>
+                <input
+                    id={id("part-name")}
+                    name="part-name"
+                    type="text"
+                    placeholder="Name"
+                    value={self.name.clone()}
+                    oninput={set_name}
+                />
+                { shown_error(Field::Name, errors.name) }
+                <br />
+
+                <label for={id("part-desc")}><u>{ "Description" }</u>{ ":" }</label>
+                <input
+                    type="button"
+                    value={if self.previewing { "Edit" } else { "Preview" }}
+                    onclick={toggle_preview}
+                />
+                <br />
+                if self.previewing {
+                    <div class="markdown markdown-preview">
+                        { Html::from_html_unchecked(AttrValue::from(markdown::render(&self.description))) }
+                    </div>
+                } else {
+                    <textarea
+                        id={id("part-desc")}
+                    name="part-desc"
+                        placeholder="Description (Markdown)"
+                        value={self.description.clone()}
+                        oninput={set_desc}
+                    />
+                }
+                <br />
+
+                <label for={id("part-deadline")}><u>{ "Deadline" }</u>{ ": " }</label>
+                <input
+                    id={id("part-deadline")}
+                    name="part-deadline"
+                    type="datetime-local"
+                    value={self.deadline.clone()}
+                    oninput={set_deadline}
+                />
+                <input type="button" value="X" title="Clear" onclick={clear_deadline} />
+                if let Some(start) = parent_start.filter(|_| errors.deadline.is_none()) {
+                    <span class="field-hint">{ start_hint(start) }</span>
+                }
+                { shown_error(Field::Deadline, errors.deadline) }
+                <br />
+
+                <label for={id("part-comp-at")}><u>{ "Completed At" }</u>{ ": " }</label>
+                <input
+                    id={id("part-comp-at")}
+                    name="part-comp-at"
+                    type="datetime-local"
+                    value={self.completed_at.clone()}
+                    oninput={set_comp_at}
+                />
+                <input type="button" value="X" title="Clear" onclick={clear_comp_at} />
+                { shown_error(Field::CompletedAt, errors.completed_at) }
+                <br />
+
+                <label for={id("part-tags")}><u>{ "Tags" }</u>{ ": " }</label>
+                <input
+                    id={id("part-tags")}
+                    name="part-tags"
+                    type="text"
+                    placeholder="#tag #another"
+                    value={self.tags.clone()}
+                    oninput={set_tags}
+                />
+                <br />
+
+                <label for={id("part-parent")}><u>{ "Parent" }</u>{ ": " }</label>
+                <select id={id("part-parent")} name="part-parent" onchange={set_parent}>
+                    <option value="" selected={self.parent_id.is_none()}>{ "(No parent)" }</option>
+                    {
+                        props.parents.iter().map(|opt| html! {
+                            <option
+                                value={opt.id.to_string()}
+                                selected={self.parent_id == Some(opt.id)}
+                            >{ &opt.path }</option>
+                        }).collect::<Html>()
+                    }
+                </select>
+                <br />
+
+                <label for={id("part-assignee")}><u>{ "Assignee" }</u>{ ": " }</label>
+                <select id={id("part-assignee")} name="part-assignee" onchange={set_assignee}>
+                    <option value="" selected={self.assignee.is_none()}>{ "(Unassigned)" }</option>
+                    {
+                        assignees.map(|email| html! {
+                            <option
+                                value={email.clone()}
+                                selected={self.assignee.as_ref() == Some(email)}
+                            >{ email }</option>
+                        }).collect::<Html>()
+                    }
+                </select>
+                <br />
+            </form>
+        }
+    }
+}
+
+impl PartForm {
+    // When the picked parent was started, if it has been
+    fn parent_start(&self, ctx: &Context<Self>) -> Option<DateTime<Local>> {
+        let pid = self.parent_id?;
+        ctx.props()
+            .parents
+            .iter()
+            .find(|opt| opt.id == pid)
+            .and_then(|opt| opt.started_at)
+    }
+
+    fn validate(&self, ctx: &Context<Self>) -> Result<PartDraft, FieldErrors> {
+        let mut errors = FieldErrors::default();
+        if self.name.trim().is_empty() {
+            errors.name = Some(String::from("Name is required"));
+        }
+
+        let deadline = parse_input(&self.deadline);
+        errors.deadline = deadline_error(deadline, self.parent_start(ctx));
+
+        let completed_at = parse_input(&self.completed_at);
+        match completed_at {
+            Err(()) => errors.completed_at = Some(String::from("Invalid date")),
+            Ok(Some(dt)) if dt > Local::now() => {
+                errors.completed_at = Some(String::from("Must not be in the future"));
+            }
+            _ => (),
+        }
+
+        match (deadline, completed_at) {
+            (Ok(deadline), Ok(completed_at))
+                if errors.name.is_none()
+                    && errors.deadline.is_none()
+                    && errors.completed_at.is_none() =>
+            {
+                let description = self.description.trim();
+                Ok(PartDraft {
+                    name: self.name.clone(),
+                    description: (!description.is_empty()).then(|| self.description.clone()),
+                    deadline,
+                    completed_at,
+                    parent_id: self.parent_id,
+                    tags: parse_tags(&self.tags),
+                    assignee: self.assignee.clone(),
+                })
+            }
+            _ => Err(errors),
+        }
+    }
+}
+
+// Returns what's wrong with the deadline input, if anything. It must be after the parent's start,
+// so parts without a parent or whose parent hasn't had time logged yet can have any deadline.
+fn deadline_error(
+    deadline: Result<Option<DateTime<Local>>, ()>,
+    parent_start: Option<DateTime<Local>>,
+) -> Option<String> {
+    match (deadline, parent_start) {
+        (Err(()), _) => Some(String::from("Invalid date")),
+        (Ok(Some(dt)), Some(start)) if dt <= start => Some(format!(
+            "Must be after the parent's start ({})",
+            start.format("%H:%M %b %d, %Y"),
+        )),
+        _ => None,
+    }
+}
+
+// Explains the limit on the deadline set by the parent's start
+fn start_hint(start: DateTime<Local>) -> String {
+    format!(
+        "After the parent's start ({}, when time was first logged on it)",
+        start.format("%H:%M %b %d, %Y"),
+    )
+}
+
+// Parses a datetime-local input value (empty means no datetime)
+fn parse_input(value: &str) -> Result<Option<DateTime<Local>>, ()> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    Part::dtl_from_input(value).map(Some).ok_or(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(day: u32, hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn limits_deadline_to_after_parent_start() {
+        let start = Some(dt(10, 9));
+        assert_eq!(deadline_error(Ok(Some(dt(10, 10))), start), None);
+        assert!(deadline_error(Ok(Some(dt(10, 9))), start)
+            .is_some_and(|e| e.starts_with("Must be after the parent's start")));
+        assert!(deadline_error(Ok(Some(dt(9, 12))), start).is_some());
+        // No deadline, or nothing to limit it
+        assert_eq!(deadline_error(Ok(None), start), None);
+        assert_eq!(deadline_error(Ok(Some(dt(9, 12))), None), None);
+    }
+
+    #[test]
+    fn rejects_invalid_deadlines() {
+        assert_eq!(
+            deadline_error(Err(()), None),
+            Some(String::from("Invalid date"))
+        );
+        assert_eq!(
+            deadline_error(parse_input("not a date"), Some(dt(10, 9))),
+            Some(String::from("Invalid date"))
+        );
+        assert_eq!(deadline_error(parse_input(""), Some(dt(10, 9))), None);
+    }
+}
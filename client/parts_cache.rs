@@ -0,0 +1,41 @@
+// A snapshot of the user's (loaded) parts, with the cursor it's current as of, kept in local
+// storage so that only the changes since need to be fetched on startup
+use crate::{console, graphql::Part};
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct PartsCache {
+    pub cursor: String,
+    pub parts: Vec<Part>,
+    // Which parts' children (None for the projects) have been fetched
+    pub loaded: Vec<(Option<i64>, Loaded)>,
+}
+
+// How many of a part's (or the projects') children have been fetched
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Loaded {
+    // Some pages, with the cursor to get the next one
+    Partially(String),
+    Fully,
+}
+
+impl PartsCache {
+    pub fn load(user: &str) -> Option<Self> {
+        LocalStorage::get(key(user)).ok()
+    }
+
+    pub fn save(&self, user: &str) {
+        if let Err(e) = LocalStorage::set(key(user), self) {
+            console::log!("error saving parts cache: {}", e);
+        }
+    }
+
+    pub fn clear(user: &str) {
+        LocalStorage::delete(key(user));
+    }
+}
+
+fn key(user: &str) -> String {
+    format!("parts-cache:{}", user)
+}
@@ -0,0 +1,638 @@
+// Parses one-line quick-add input (e.g., `Write release notes tomorrow 5pm #docs under "v2
+// launch"`) into the fields of a new part. Recognized phrases are taken out of the input
+// (ignoring case) and the rest becomes the name:
+// - Tags: "#docs" (must start with a letter, so "#123" is left in the name)
+// - The parent: `under "v2 launch"` or `under v2` (a single word), matched against part names
+// - Dates: "today", "tomorrow", weekdays ("friday" is today if it's Friday, "next friday" is at
+//   least a day away), "next week" (next Monday), "in 3 days", "in a week", "2024-05-01",
+//   "5/1", "5/1/2024", "may 1", "1st may", "may 1, 2025" (dates without a year that have
+//   passed are taken to be next year's)
+// - Times: "5pm", "5:30 pm", "17:00", "noon", "in 2 hours", "in 30 minutes" (a time without a
+//   date is the next time it comes around)
+// - "by", "on", "at" or "due" right before a date or time
+// Only the first date and time are used; later ones are left in the name. Text in double quotes
+// is always left in the name as is.
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+
+// The time of day used when only a date is given
+const DEFAULT_HOUR: u32 = 23;
+const DEFAULT_MINUTE: u32 = 59;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct QuickAdd {
+    pub name: String,
+    pub deadline: Option<NaiveDateTime>,
+    // Without the leading '#', in the order given
+    pub tags: Vec<String>,
+    pub parent: Option<ParentMatch>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParentMatch {
+    Found(i64),
+    // Holds the name searched for
+    NotFound(String),
+    // Holds the name searched for and the ids of the parts matching it
+    Ambiguous(String, Vec<i64>),
+}
+
+// Parses the input, matching the parent against the given (id, name) pairs. Relative dates and
+// times are relative to now.
+pub fn parse<'a>(
+    input: &str,
+    now: NaiveDateTime,
+    parts: impl IntoIterator<Item = (i64, &'a str)>,
+) -> QuickAdd {
+    let tokens = tokenize(input);
+    // The lowercased words (None for quoted text) the dates and times are matched against
+    let words = tokens
+        .iter()
+        .map(|token| match token {
+            Token::Word(word) => Some(word.to_lowercase()),
+            Token::Quoted(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut parsed = QuickAdd::default();
+    let mut name = Vec::new();
+    let (mut date, mut time) = (None, None);
+    let mut parent_name = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        let word = match tokens[i] {
+            Token::Quoted(text) => {
+                name.push(text);
+                i += 1;
+                continue;
+            }
+            Token::Word(word) => word,
+        };
+        if let Some(tag) = parse_tag(word) {
+            if !parsed.tags.iter().any(|t| t == tag) {
+                parsed.tags.push(tag.to_string());
+            }
+            i += 1;
+            continue;
+        }
+        if words[i].as_deref() == Some("under") && parent_name.is_none() {
+            if let Some(token) = tokens.get(i + 1) {
+                parent_name = Some(token.text());
+                i += 2;
+                continue;
+            }
+        }
+        let start = match words[i].as_deref() {
+            Some("by" | "on" | "at" | "due") => i + 1,
+            _ => i,
+        };
+        if let Some((len, when)) = match_when(&words[start..], now) {
+            let unused = match when {
+                When::Date(_) => date.is_none(),
+                When::Time(_) => time.is_none(),
+                When::DateTime(_) => date.is_none() && time.is_none(),
+            };
+            if unused {
+                match when {
+                    When::Date(d) => date = Some(d),
+                    When::Time(t) => time = Some(t),
+                    When::DateTime(dt) => (date, time) = (Some(dt.date()), Some(dt.time())),
+                }
+                i = start + len;
+                continue;
+            }
+        }
+        name.push(word);
+        i += 1;
+    }
+
+    parsed.name = name.join(" ");
+    parsed.deadline = match (date, time) {
+        (None, None) => None,
+        (Some(date), time) => Some(date.and_time(time.unwrap_or_else(default_time))),
+        (None, Some(time)) => {
+            let today = now.date().and_time(time);
+            Some(if today > now {
+                today
+            } else {
+                today + Duration::days(1)
+            })
+        }
+    };
+    parsed.parent = parent_name.map(|parent_name| match_parent(parent_name, parts));
+    parsed
+}
+
+#[derive(Debug)]
+enum Token<'a> {
+    Word(&'a str),
+    Quoted(&'a str),
+}
+
+impl<'a> Token<'a> {
+    fn text(&self) -> &'a str {
+        match self {
+            Token::Word(text) | Token::Quoted(text) => text,
+        }
+    }
+}
+
+const OPEN_QUOTES: [char; 2] = ['"', '“'];
+const CLOSE_QUOTES: [char; 2] = ['"', '”'];
+
+// Splits the input into whitespace separated words and quoted text (an unclosed quote runs to
+// the end of the input)
+fn tokenize(input: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if OPEN_QUOTES.contains(&c) {
+            let quoted = &rest[c.len_utf8()..];
+            let end = quoted.find(CLOSE_QUOTES).unwrap_or(quoted.len());
+            tokens.push(Token::Quoted(&quoted[..end]));
+            let close_len = quoted[end..].chars().next().map_or(0, char::len_utf8);
+            rest = &quoted[end + close_len..];
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            tokens.push(Token::Word(&rest[..end]));
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+    tokens
+}
+
+// Returns the tag (without the '#' and any trailing punctuation) if the word is one
+fn parse_tag(word: &str) -> Option<&str> {
+    let tag = word
+        .strip_prefix('#')?
+        .trim_end_matches([',', '.', ';', ':', '!', '?']);
+    let mut chars = tag.chars();
+    let valid = chars.next()?.is_alphabetic()
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'));
+    valid.then_some(tag)
+}
+
+fn match_parent<'a>(name: &str, parts: impl IntoIterator<Item = (i64, &'a str)>) -> ParentMatch {
+    let query = name.trim().to_lowercase();
+    if query.is_empty() {
+        return ParentMatch::NotFound(name.to_string());
+    }
+    // Exact (case-insensitive) matches win over partial ones
+    let (mut exact, mut partial) = (Vec::new(), Vec::new());
+    for (id, part_name) in parts {
+        let part_name = part_name.to_lowercase();
+        if part_name == query {
+            exact.push(id);
+        } else if part_name.contains(&query) {
+            partial.push(id);
+        }
+    }
+    let matches = if exact.is_empty() { partial } else { exact };
+    match matches[..] {
+        [] => ParentMatch::NotFound(name.to_string()),
+        [id] => ParentMatch::Found(id),
+        _ => ParentMatch::Ambiguous(name.to_string(), matches),
+    }
+}
+
+#[derive(Debug)]
+enum When {
+    Date(NaiveDate),
+    Time(NaiveTime),
+    DateTime(NaiveDateTime),
+}
+
+fn default_time() -> NaiveTime {
+    NaiveTime::from_hms_opt(DEFAULT_HOUR, DEFAULT_MINUTE, 0).unwrap()
+}
+
+// Matches a date or time at the start of the words, returning the number of words it spans
+fn match_when(words: &[Option<String>], now: NaiveDateTime) -> Option<(usize, When)> {
+    let word = |i: usize| words.get(i).and_then(|w| w.as_deref());
+    let today = now.date();
+    let first = word(0)?;
+
+    // Relative dates and times
+    if first == "in" {
+        let n = match word(1)? {
+            "a" | "an" => 1,
+            n => n.parse::<u32>().ok()?,
+        };
+        let n = i64::from(n);
+        let when = match word(2)? {
+            "day" | "days" => When::Date(today + Duration::days(n)),
+            "week" | "weeks" => When::Date(today + Duration::weeks(n)),
+            "hour" | "hours" | "hr" | "hrs" => When::DateTime(now + Duration::hours(n)),
+            "minute" | "minutes" | "min" | "mins" => When::DateTime(now + Duration::minutes(n)),
+            _ => return None,
+        };
+        return Some((3, when));
+    }
+    match first {
+        "today" => return Some((1, When::Date(today))),
+        "tomorrow" | "tmrw" => return Some((1, When::Date(today + Duration::days(1)))),
+        "noon" => return Some((1, When::Time(NaiveTime::from_hms_opt(12, 0, 0)?))),
+        "next" => {
+            let date = match word(1)? {
+                "week" => next_weekday(today, Weekday::Mon, false),
+                w => next_weekday(today, parse_weekday(w)?, false),
+            };
+            return Some((2, When::Date(date)));
+        }
+        _ => (),
+    }
+    if let Some(weekday) = parse_weekday(first) {
+        return Some((1, When::Date(next_weekday(today, weekday, true))));
+    }
+
+    // Absolute dates
+    if let Ok(date) = NaiveDate::parse_from_str(first, "%Y-%m-%d") {
+        return Some((1, When::Date(date)));
+    }
+    if let Some(date) = parse_slash_date(first, today) {
+        return Some((1, When::Date(date)));
+    }
+    // "may 1[st][,] [2025]" or "1[st] may [2025]"
+    let month_day = parse_month(first)
+        .zip(word(1).and_then(parse_day))
+        .or_else(|| {
+            parse_day(first)
+                .zip(word(1).and_then(parse_month))
+                .map(|(d, m)| (m, d))
+        });
+    if let Some((month, day)) = month_day {
+        let (year, len) = match word(2).and_then(parse_year) {
+            Some(year) => (Some(year), 3),
+            None => (None, 2),
+        };
+        return Some((len, When::Date(resolve_date(year, month, day, today)?)));
+    }
+
+    // Times ("5 pm" or "5:30 pm" before "5:30")
+    if let Some(meridiem) = word(1).filter(|w| matches!(*w, "am" | "pm")) {
+        if let Some(time) = parse_time(&format!("{}{}", first, meridiem)) {
+            return Some((2, When::Time(time)));
+        }
+    }
+    parse_time(first).map(|time| (1, When::Time(time)))
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    Some(match word {
+        "monday" => Weekday::Mon,
+        "tuesday" => Weekday::Tue,
+        "wednesday" => Weekday::Wed,
+        "thursday" => Weekday::Thu,
+        "friday" => Weekday::Fri,
+        "saturday" => Weekday::Sat,
+        "sunday" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+// Returns the next date falling on the weekday, which may be today if allowed
+fn next_weekday(today: NaiveDate, weekday: Weekday, allow_today: bool) -> NaiveDate {
+    let days = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    let days = if days == 0 && !allow_today { 7 } else { days };
+    today + Duration::days(i64::from(days))
+}
+
+fn parse_month(word: &str) -> Option<u32> {
+    Some(match word {
+        "january" | "jan" => 1,
+        "february" | "feb" => 2,
+        "march" | "mar" => 3,
+        "april" | "apr" => 4,
+        "may" => 5,
+        "june" | "jun" => 6,
+        "july" | "jul" => 7,
+        "august" | "aug" => 8,
+        "september" | "sept" | "sep" => 9,
+        "october" | "oct" => 10,
+        "november" | "nov" => 11,
+        "december" | "dec" => 12,
+        _ => return None,
+    })
+}
+
+// Parses a day of the month (e.g., "1", "1st", "22nd,")
+fn parse_day(word: &str) -> Option<u32> {
+    let word = word.trim_end_matches(',');
+    let digits = ["st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| word.strip_suffix(suffix))
+        .unwrap_or(word);
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+fn parse_year(word: &str) -> Option<i32> {
+    if word.len() != 4 {
+        return None;
+    }
+    word.parse().ok()
+}
+
+// Parses "m/d", "m/d/yy" or "m/d/yyyy"
+fn parse_slash_date(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let mut parts = word.split('/');
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    let year = match parts.next() {
+        Some(year) if year.len() == 2 => Some(2000 + year.parse::<i32>().ok()?),
+        Some(year) => Some(parse_year(year)?),
+        None => None,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    resolve_date(year, month, day, today)
+}
+
+// Returns the date, taking a date without a year to be the next one on or after today
+fn resolve_date(year: Option<i32>, month: u32, day: u32, today: NaiveDate) -> Option<NaiveDate> {
+    if let Some(year) = year {
+        return NaiveDate::from_ymd_opt(year, month, day);
+    }
+    match NaiveDate::from_ymd_opt(today.year(), month, day) {
+        Some(date) if date >= today => Some(date),
+        // Either it's passed or it's Feb 29 and this isn't a leap year
+        _ => (1..=4).find_map(|i| NaiveDate::from_ymd_opt(today.year() + i, month, day)),
+    }
+}
+
+// Parses "5pm", "5:30am", "12am" or "17:00"
+fn parse_time(word: &str) -> Option<NaiveTime> {
+    let (clock, meridiem) = match word.strip_suffix("am") {
+        Some(clock) => (clock, Some(false)),
+        None => match word.strip_suffix("pm") {
+            Some(clock) => (clock, Some(true)),
+            None => (word, None),
+        },
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => {
+            (hour.parse::<u32>().ok()?, minute.parse().ok()?)
+        }
+        Some(_) => return None,
+        // A bare number is only a time with am/pm
+        None if meridiem.is_some() => (clock.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+    let hour = match meridiem {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARTS: [(i64, &str); 5] = [
+        (1, "v2 launch"),
+        (2, "Docs"),
+        (3, "Docs cleanup"),
+        (4, "Website"),
+        (5, "website"),
+    ];
+
+    // Wednesday, May 15, 2024 at 10:00
+    fn now() -> NaiveDateTime {
+        dt(2024, 5, 15, 10, 0)
+    }
+
+    fn dt(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    // The date at the default time
+    fn date(year: i32, month: u32, day: u32) -> Option<NaiveDateTime> {
+        Some(dt(year, month, day, DEFAULT_HOUR, DEFAULT_MINUTE))
+    }
+
+    fn parse_now(input: &str) -> QuickAdd {
+        parse(input, now(), PARTS)
+    }
+
+    fn deadline(input: &str) -> Option<NaiveDateTime> {
+        parse_now(input).deadline
+    }
+
+    #[test]
+    fn parses_the_example() {
+        let parsed = parse_now(r#"Write release notes tomorrow 5pm #docs under "v2 launch""#);
+        assert_eq!(
+            parsed,
+            QuickAdd {
+                name: String::from("Write release notes"),
+                deadline: Some(dt(2024, 5, 16, 17, 0)),
+                tags: vec![String::from("docs")],
+                parent: Some(ParentMatch::Found(1)),
+            }
+        );
+    }
+
+    #[test]
+    fn plain_names_are_kept() {
+        let parsed = parse_now("  Buy   milk ");
+        assert_eq!(parsed.name, "Buy milk");
+        assert_eq!(parsed.deadline, None);
+        assert!(parsed.tags.is_empty());
+        assert_eq!(parsed.parent, None);
+        assert_eq!(parse_now(""), QuickAdd::default());
+    }
+
+    #[test]
+    fn parses_tags() {
+        let parsed = parse_now("Fix #bug-123 the #UI, and #ui #docs/api #bug-123 issue #123 # #");
+        assert_eq!(parsed.name, "Fix the and issue #123 # #");
+        assert_eq!(parsed.tags, ["bug-123", "UI", "ui", "docs/api"]);
+    }
+
+    #[test]
+    fn matches_parents() {
+        let parent = |input: &str| parse_now(input).parent;
+        assert_eq!(
+            parent("x under website"),
+            Some(ParentMatch::Ambiguous(String::from("website"), vec![4, 5]))
+        );
+        // Exact matches win over partial ones
+        assert_eq!(parent("x under DOCS"), Some(ParentMatch::Found(2)));
+        assert_eq!(parent("x under cleanup"), Some(ParentMatch::Found(3)));
+        assert_eq!(parent("x under “V2 Launch”"), Some(ParentMatch::Found(1)));
+        assert_eq!(
+            parent("x under nothing"),
+            Some(ParentMatch::NotFound(String::from("nothing")))
+        );
+        assert_eq!(
+            parent(r#"x under """#),
+            Some(ParentMatch::NotFound(String::new()))
+        );
+        // Only a single word is taken without quotes
+        let parsed = parse_now("x under v2 launch");
+        assert_eq!(parsed.name, "x launch");
+        assert_eq!(parsed.parent, Some(ParentMatch::Found(1)));
+    }
+
+    #[test]
+    fn under_without_a_name_is_kept() {
+        let parsed = parse_now("Look under");
+        assert_eq!(parsed.name, "Look under");
+        assert_eq!(parsed.parent, None);
+        // Only the first parent is used
+        let parsed = parse_now("x under docs under website");
+        assert_eq!(parsed.name, "x under website");
+        assert_eq!(parsed.parent, Some(ParentMatch::Found(2)));
+    }
+
+    #[test]
+    fn parses_relative_days() {
+        assert_eq!(deadline("x today"), date(2024, 5, 15));
+        assert_eq!(deadline("x Tomorrow"), date(2024, 5, 16));
+        assert_eq!(deadline("x tmrw"), date(2024, 5, 16));
+        assert_eq!(deadline("x in 3 days"), date(2024, 5, 18));
+        assert_eq!(deadline("x in 1 day"), date(2024, 5, 16));
+        assert_eq!(deadline("x in a week"), date(2024, 5, 22));
+        assert_eq!(deadline("x in 2 weeks"), date(2024, 5, 29));
+        assert_eq!(deadline("x in 2 hours"), Some(dt(2024, 5, 15, 12, 0)));
+        assert_eq!(deadline("x in an hour"), Some(dt(2024, 5, 15, 11, 0)));
+        assert_eq!(deadline("x in 90 mins"), Some(dt(2024, 5, 15, 11, 30)));
+        assert_eq!(deadline("x in 15 hours"), Some(dt(2024, 5, 16, 1, 0)));
+    }
+
+    #[test]
+    fn parses_weekdays() {
+        // Today is a Wednesday
+        assert_eq!(deadline("x wednesday"), date(2024, 5, 15));
+        assert_eq!(deadline("x next wednesday"), date(2024, 5, 22));
+        assert_eq!(deadline("x friday"), date(2024, 5, 17));
+        assert_eq!(deadline("x next friday"), date(2024, 5, 17));
+        assert_eq!(deadline("x Monday"), date(2024, 5, 20));
+        assert_eq!(deadline("x next week"), date(2024, 5, 20));
+        assert_eq!(deadline("x sunday"), date(2024, 5, 19));
+    }
+
+    #[test]
+    fn parses_absolute_dates() {
+        assert_eq!(deadline("x 2024-06-01"), date(2024, 6, 1));
+        assert_eq!(deadline("x 2023-01-01"), date(2023, 1, 1));
+        assert_eq!(deadline("x 6/1"), date(2024, 6, 1));
+        assert_eq!(deadline("x 6/1/25"), date(2025, 6, 1));
+        assert_eq!(deadline("x 6/1/2026"), date(2026, 6, 1));
+        assert_eq!(deadline("x may 20"), date(2024, 5, 20));
+        assert_eq!(deadline("x May 20th"), date(2024, 5, 20));
+        assert_eq!(deadline("x 1st June"), date(2024, 6, 1));
+        assert_eq!(deadline("x dec 3, 2025"), date(2025, 12, 3));
+        assert_eq!(deadline("x 3 Dec 2025"), date(2025, 12, 3));
+        assert_eq!(deadline("x sept 9"), date(2024, 9, 9));
+    }
+
+    #[test]
+    fn dates_without_a_year_are_not_in_the_past() {
+        assert_eq!(deadline("x may 15"), date(2024, 5, 15));
+        assert_eq!(deadline("x may 14"), date(2025, 5, 14));
+        assert_eq!(deadline("x 1/2"), date(2025, 1, 2));
+        // The next leap day
+        assert_eq!(deadline("x feb 29"), date(2028, 2, 29));
+    }
+
+    #[test]
+    fn invalid_dates_are_kept_in_the_name() {
+        for input in [
+            "x feb 30",
+            "x 2024-13-01",
+            "x 13/1",
+            "x 1/2/3/4",
+            "x may 32",
+            "x feb 29 2023",
+            "x in many days",
+            "x in 3 lightyears",
+            "x next thing",
+        ] {
+            let parsed = parse_now(input);
+            assert_eq!(parsed.name, input, "{}", input);
+            assert_eq!(parsed.deadline, None, "{}", input);
+        }
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(deadline("x 5pm"), Some(dt(2024, 5, 15, 17, 0)));
+        assert_eq!(deadline("x 5:30PM"), Some(dt(2024, 5, 15, 17, 30)));
+        assert_eq!(deadline("x 5 pm"), Some(dt(2024, 5, 15, 17, 0)));
+        assert_eq!(deadline("x 5:30 pm"), Some(dt(2024, 5, 15, 17, 30)));
+        assert_eq!(deadline("x 17:45"), Some(dt(2024, 5, 15, 17, 45)));
+        assert_eq!(deadline("x noon"), Some(dt(2024, 5, 15, 12, 0)));
+        assert_eq!(deadline("x 12pm"), Some(dt(2024, 5, 15, 12, 0)));
+        // Times that have passed today are tomorrow's
+        assert_eq!(deadline("x 12am"), Some(dt(2024, 5, 16, 0, 0)));
+        assert_eq!(deadline("x 9am"), Some(dt(2024, 5, 16, 9, 0)));
+        assert_eq!(deadline("x 10:00"), Some(dt(2024, 5, 16, 10, 0)));
+    }
+
+    #[test]
+    fn invalid_times_are_kept_in_the_name() {
+        for input in [
+            "x 5", "x 13pm", "x 0am", "x 25:00", "x 5:3", "x 5:60", "x pm",
+        ] {
+            let parsed = parse_now(input);
+            assert_eq!(parsed.name, input, "{}", input);
+            assert_eq!(parsed.deadline, None, "{}", input);
+        }
+    }
+
+    #[test]
+    fn combines_dates_and_times() {
+        assert_eq!(deadline("x tomorrow at 9am"), Some(dt(2024, 5, 16, 9, 0)));
+        assert_eq!(deadline("x 9am tomorrow"), Some(dt(2024, 5, 16, 9, 0)));
+        assert_eq!(
+            deadline("x by friday at noon"),
+            Some(dt(2024, 5, 17, 12, 0))
+        );
+        assert_eq!(deadline("x on 6/1 at 8:15 am"), Some(dt(2024, 6, 1, 8, 15)));
+        // A time before now is fine on a later date
+        assert_eq!(deadline("x tomorrow 9am"), Some(dt(2024, 5, 16, 9, 0)));
+        assert_eq!(deadline("x today 9am"), Some(dt(2024, 5, 15, 9, 0)));
+    }
+
+    #[test]
+    fn only_the_first_date_and_time_are_used() {
+        let parsed = parse_now("Move meeting from friday to monday 3pm 4pm");
+        assert_eq!(parsed.name, "Move meeting from to monday 4pm");
+        assert_eq!(parsed.deadline, Some(dt(2024, 5, 17, 15, 0)));
+        // A relative time sets both the date and time
+        let parsed = parse_now("x tomorrow in 2 hours");
+        assert_eq!(parsed.name, "x in 2 hours");
+        assert_eq!(parsed.deadline, date(2024, 5, 16));
+    }
+
+    #[test]
+    fn filler_words_go_with_dates() {
+        let parsed = parse_now("Meet at the cafe due tomorrow");
+        assert_eq!(parsed.name, "Meet at the cafe");
+        assert_eq!(parsed.deadline, date(2024, 5, 16));
+        let parsed = parse_now("Sign off on budget by");
+        assert_eq!(parsed.name, "Sign off on budget by");
+        assert_eq!(parsed.deadline, None);
+    }
+
+    #[test]
+    fn quoted_text_is_kept() {
+        let parsed = parse_now(r#""Meet at 5pm" with #team friday"#);
+        assert_eq!(parsed.name, "Meet at 5pm with");
+        assert_eq!(parsed.tags, ["team"]);
+        assert_eq!(parsed.deadline, date(2024, 5, 17));
+        let parsed = parse_now(r##"Read "#1 book" today"##);
+        assert_eq!(parsed.name, "Read #1 book");
+        assert!(parsed.tags.is_empty());
+        // An unclosed quote runs to the end
+        let parsed = parse_now(r#"Say "hi tomorrow"#);
+        assert_eq!(parsed.name, "Say hi tomorrow");
+        assert_eq!(parsed.deadline, None);
+    }
+}
@@ -0,0 +1,67 @@
+// Tracks a component's requests that are in flight, so the same one isn't sent twice while it's
+// pending (e.g., by double clicking a button) and the responses are dropped if the component is
+// destroyed before they arrive
+use futures::{
+    future::{AbortHandle, Abortable},
+    Future, FutureExt,
+};
+use std::{collections::HashMap, hash::Hash};
+use yew::{html::Scope, BaseComponent};
+
+pub struct Requests<K> {
+    in_flight: HashMap<K, AbortHandle>,
+}
+
+impl<K> Default for Requests<K> {
+    fn default() -> Self {
+        Self {
+            in_flight: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash> Requests<K> {
+    // Sends the request, unless one with the key is already pending, returning whether it was sent.
+    // Its response needs to be passed to finish once handled.
+    pub fn send<COMP, F>(&mut self, link: &Scope<COMP>, key: K, fut: F) -> bool
+    where
+        COMP: BaseComponent,
+        F: Future<Output = COMP::Message> + 'static,
+    {
+        if self.in_flight.contains_key(&key) {
+            return false;
+        }
+        let (handle, registration) = AbortHandle::new_pair();
+        self.in_flight.insert(key, handle);
+        link.send_future_batch(Abortable::new(fut, registration).map(Result::ok));
+        true
+    }
+
+    pub fn finish(&mut self, key: &K) {
+        self.in_flight.remove(key);
+    }
+
+    // Drops the request's response if it's still pending
+    pub fn cancel(&mut self, key: &K) {
+        if let Some(handle) = self.in_flight.remove(key) {
+            handle.abort();
+        }
+    }
+
+    pub fn is_pending(&self, key: &K) -> bool {
+        self.in_flight.contains_key(key)
+    }
+
+    // Whether any of the pending requests' keys match
+    pub fn any(&self, f: impl Fn(&K) -> bool) -> bool {
+        self.in_flight.keys().any(f)
+    }
+}
+
+impl<K> Drop for Requests<K> {
+    fn drop(&mut self) {
+        for handle in self.in_flight.values() {
+            handle.abort();
+        }
+    }
+}
@@ -0,0 +1,189 @@
+// The app's URLs, so parts and views can be linked to and the browser's back and forward buttons
+// work. The server serves the app for any path without a file.
+use crate::{app::MainView, console};
+use wasm_bindgen::JsValue;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Route {
+    // The project tree (/)
+    Projects,
+    // The login page, which goes to the route after it once logged in (/login?next=...)
+    Login(Option<Box<Route>>),
+    // The project tree with the part revealed and highlighted (/part/:id)
+    Part(i64),
+    // One of the other main views (/view/:name)
+    View(MainView),
+    // The account settings (/settings)
+    Settings,
+    // The page the password reset links go to (/reset?token=...)
+    Reset(String),
+}
+
+// The names of the views in their /view/:name routes
+const VIEW_NAMES: [(MainView, &str); 4] = [
+    (MainView::TimeReport, "time-report"),
+    (MainView::Assigned, "assigned"),
+    (MainView::Trash, "trash"),
+    (MainView::Stats, "stats"),
+];
+
+impl Route {
+    // Returns the route of the path and query (without the '?') of a URL, or None if it isn't
+    // one of the app's
+    pub fn parse(path: &str, query: &str) -> Option<Route> {
+        let path = match path.trim_end_matches('/') {
+            "" => "/",
+            path => path,
+        };
+        let route = match path {
+            "/" => Route::Projects,
+            "/login" => Route::Login(
+                query_param(query, "next")
+                    .and_then(|next| {
+                        let (path, query) = next.split_once('?').unwrap_or((next, ""));
+                        Route::parse(path, query)
+                    })
+                    .filter(Route::needs_login)
+                    .map(Box::new),
+            ),
+            "/settings" => Route::Settings,
+            "/reset" => Route::Reset(query_param(query, "token")?.to_string()),
+            _ => {
+                if let Some(id) = path.strip_prefix("/part/") {
+                    Route::Part(id.parse().ok()?)
+                } else if let Some(name) = path.strip_prefix("/view/") {
+                    let (view, _) = VIEW_NAMES.iter().find(|(_, n)| *n == name)?;
+                    Route::View(*view)
+                } else {
+                    return None;
+                }
+            }
+        };
+        Some(route)
+    }
+
+    pub fn to_url(&self) -> String {
+        match self {
+            Route::Projects => String::from("/"),
+            Route::Login(None) => String::from("/login"),
+            Route::Login(Some(next)) => format!("/login?next={}", next.to_url()),
+            Route::Part(id) => format!("/part/{}", id),
+            Route::View(view) => match VIEW_NAMES.iter().find(|(v, _)| v == view) {
+                Some((_, name)) => format!("/view/{}", name),
+                None => String::from("/"),
+            },
+            Route::Settings => String::from("/settings"),
+            Route::Reset(token) => format!("/reset?token={}", token),
+        }
+    }
+
+    // Whether the route is only shown to a logged in user
+    pub fn needs_login(&self) -> bool {
+        !matches!(self, Route::Login(_) | Route::Reset(_))
+    }
+
+    // Returns the route of the current URL, or None if it isn't one of the app's
+    pub fn current() -> Option<Route> {
+        let location = web_sys::window()?.location();
+        let path = location.pathname().ok()?;
+        let search = location.search().ok()?;
+        Route::parse(&path, search.trim_start_matches('?'))
+    }
+
+    // Changes the URL to the route's, adding to the browser's history unless replacing
+    pub fn go(&self, replace: bool) {
+        let Some(history) = web_sys::window().and_then(|w| w.history().ok()) else {
+            return;
+        };
+        let url = self.to_url();
+        let res = if replace {
+            history.replace_state_with_url(&JsValue::NULL, "", Some(&url))
+        } else {
+            history.push_state_with_url(&JsValue::NULL, "", Some(&url))
+        };
+        if let Err(e) = res {
+            console::log!("error changing url: {:?}", e);
+        }
+    }
+}
+
+// Returns the value of the first parameter with the name in the query, if it isn't empty. The
+// values the app uses don't need decoding.
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .find_map(|param| param.strip_prefix(name)?.strip_prefix('='))
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_routes() {
+        assert_eq!(Route::parse("/", ""), Some(Route::Projects));
+        assert_eq!(Route::parse("", ""), Some(Route::Projects));
+        assert_eq!(Route::parse("/part/12", ""), Some(Route::Part(12)));
+        assert_eq!(Route::parse("/part/12/", ""), Some(Route::Part(12)));
+        assert_eq!(
+            Route::parse("/view/time-report", ""),
+            Some(Route::View(MainView::TimeReport))
+        );
+        assert_eq!(Route::parse("/settings", ""), Some(Route::Settings));
+        assert_eq!(
+            Route::parse("/reset", "token=abc123"),
+            Some(Route::Reset(String::from("abc123")))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_routes() {
+        assert_eq!(Route::parse("/part/abc", ""), None);
+        assert_eq!(Route::parse("/part/", ""), None);
+        assert_eq!(Route::parse("/view/calendar", ""), None);
+        assert_eq!(Route::parse("/reset", ""), None);
+        assert_eq!(Route::parse("/reset", "token="), None);
+        assert_eq!(Route::parse("/pkg/missing.js", ""), None);
+    }
+
+    #[test]
+    fn logins_go_back_to_where_they_were_from() {
+        assert_eq!(Route::parse("/login", ""), Some(Route::Login(None)));
+        assert_eq!(
+            Route::parse("/login", "next=/part/3"),
+            Some(Route::Login(Some(Box::new(Route::Part(3)))))
+        );
+        // Only to pages behind the login
+        assert_eq!(
+            Route::parse("/login", "next=/reset?token=abc"),
+            Some(Route::Login(None))
+        );
+        assert_eq!(
+            Route::parse("/login", "next=/login"),
+            Some(Route::Login(None))
+        );
+        assert_eq!(
+            Route::parse("/login", "next=https://example.com"),
+            Some(Route::Login(None))
+        );
+    }
+
+    #[test]
+    fn urls_parse_back_to_their_routes() {
+        for route in [
+            Route::Projects,
+            Route::Login(None),
+            Route::Login(Some(Box::new(Route::View(MainView::Stats)))),
+            Route::Part(7),
+            Route::View(MainView::Assigned),
+            Route::View(MainView::Trash),
+            Route::Settings,
+            Route::Reset(String::from("f00d")),
+        ] {
+            let url = route.to_url();
+            let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+            assert_eq!(Route::parse(path, query), Some(route), "{}", url);
+        }
+    }
+}
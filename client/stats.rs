@@ -0,0 +1,356 @@
+// Statistics for the dashboard, from the counts and completions the server computes, and the SVG
+// charts showing them. Parts don't record when they were created, so a project's burndown counts
+// all of its current parts as the scope from the start.
+use crate::{
+    graphql::{BurndownData, ProjectWork, StatsData},
+    time_tracking::week_start,
+};
+use chrono::{prelude::*, Duration};
+use std::collections::BTreeMap;
+use yew::prelude::*;
+
+const CHART_WIDTH: f64 = 600.0;
+const CHART_HEIGHT: f64 = 240.0;
+// Room for the axis labels around the plot
+const MARGIN_LEFT: f64 = 40.0;
+const MARGIN_RIGHT: f64 = 10.0;
+const MARGIN_TOP: f64 = 20.0;
+const MARGIN_BOTTOM: f64 = 30.0;
+
+// How the completed parts with deadlines did against them
+#[derive(Debug, PartialEq, Eq)]
+pub struct Lateness {
+    pub on_time: usize,
+    pub late: usize,
+    // The sum of how late the late parts were
+    pub total_late: Duration,
+}
+
+impl Default for Lateness {
+    fn default() -> Self {
+        Self {
+            on_time: 0,
+            late: 0,
+            total_late: Duration::zero(),
+        }
+    }
+}
+
+impl Lateness {
+    // Returns the fraction of the parts completed by their deadlines (None if there are none)
+    pub fn on_time_rate(&self) -> Option<f64> {
+        let total = self.on_time + self.late;
+        (total != 0).then(|| self.on_time as f64 / total as f64)
+    }
+
+    // Returns how late the late parts were on average (None if none were late)
+    pub fn average(&self) -> Option<Duration> {
+        (self.late != 0).then(|| self.total_late / self.late as i32)
+    }
+}
+
+pub struct Stats {
+    // The number of parts completed each week, by the week's Monday, oldest first
+    pub weekly: Vec<(NaiveDate, usize)>,
+    pub lateness: Lateness,
+    // The unarchived projects, those with the most open parts first
+    pub open_work: Vec<ProjectWork>,
+}
+
+impl Stats {
+    // Returns the start of the first of the given number of weeks up to now, which the
+    // completions are fetched since
+    pub fn since(weeks: usize, now: DateTime<Local>) -> DateTime<Local> {
+        let first_week = week_start(now.date_naive()) - Duration::weeks(weeks as i64 - 1);
+        Local
+            .from_local_datetime(&first_week.and_time(NaiveTime::MIN))
+            .earliest()
+            .unwrap_or(now)
+    }
+
+    // Buckets the completions into the given number of weeks up to now
+    pub fn new(data: StatsData, weeks: usize, now: DateTime<Local>) -> Self {
+        let this_week = week_start(now.date_naive());
+        let mut weekly = (0..weeks)
+            .rev()
+            .map(|i| (this_week - Duration::weeks(i as i64), 0))
+            .collect::<Vec<_>>();
+        for completed_at in data.completions {
+            let week = week_start(completed_at.date_naive());
+            if let Some((_, count)) = weekly.iter_mut().find(|(start, _)| *start == week) {
+                *count += 1;
+            }
+        }
+        Self {
+            weekly,
+            lateness: Lateness {
+                on_time: data.on_time,
+                late: data.late,
+                total_late: data.total_late,
+            },
+            open_work: data.open_work,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Burndown {
+    // The number of parts in the project (besides the project itself)
+    pub total: usize,
+    // The number of incomplete parts at the end of each day, from the first completion until
+    // today (or the last completion, if there's nothing left)
+    pub remaining: Vec<(NaiveDate, usize)>,
+    pub deadline: Option<NaiveDate>,
+}
+
+impl Burndown {
+    pub fn new(data: &BurndownData, today: NaiveDate) -> Self {
+        let total = data.total;
+        let mut by_day = BTreeMap::<NaiveDate, usize>::new();
+        for completed_at in &data.completions {
+            *by_day.entry(completed_at.date_naive()).or_default() += 1;
+        }
+        let start = by_day.keys().next().copied().unwrap_or(today).min(today);
+        let end = if data.completions.len() < total {
+            today
+        } else {
+            by_day.keys().next_back().copied().unwrap_or(today)
+        };
+        let mut remaining = Vec::new();
+        let mut left = total;
+        let mut day = start;
+        while day <= end {
+            left = left.saturating_sub(by_day.get(&day).copied().unwrap_or_default());
+            remaining.push((day, left));
+            day += Duration::days(1);
+        }
+        Self {
+            total,
+            remaining,
+            deadline: data.deadline.map(|dt| dt.date_naive()),
+        }
+    }
+}
+
+// Formats a positive duration as, e.g., "2d 5h" (or "5h 30m" if less than a day)
+pub fn format_lateness(d: Duration) -> String {
+    let mins = d.num_minutes().max(0);
+    let (days, hours, mins) = (mins / (24 * 60), mins % (24 * 60) / 60, mins % 60);
+    if days != 0 {
+        format!("{}d {}h", days, hours)
+    } else {
+        format!("{}h {:02}m", hours, mins)
+    }
+}
+
+// Renders a bar chart of the values, labelled underneath
+pub fn bar_chart(bars: &[(String, usize)]) -> Html {
+    let max = bars
+        .iter()
+        .map(|&(_, value)| value)
+        .max()
+        .unwrap_or_default()
+        .max(1);
+    let plot_width = CHART_WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = CHART_HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let slot = plot_width / bars.len().max(1) as f64;
+    let bottom = MARGIN_TOP + plot_height;
+    html! {
+        <svg class="chart" viewBox={format!("0 0 {} {}", CHART_WIDTH, CHART_HEIGHT)}>
+            { y_axis(max, plot_height) }
+            {
+                bars.iter().enumerate().map(|(i, (label, value))| {
+                    let height = plot_height * *value as f64 / max as f64;
+                    let x = MARGIN_LEFT + slot * i as f64;
+                    html! {
+                        <g>
+                            <rect
+                                class="chart-bar"
+                                x={(x + slot * 0.15).to_string()}
+                                y={(bottom - height).to_string()}
+                                width={(slot * 0.7).to_string()}
+                                height={height.to_string()}
+                            />
+                            <text
+                                x={(x + slot / 2.0).to_string()}
+                                y={(bottom - height - 4.0).to_string()}
+                                text-anchor="middle"
+                            >{ value }</text>
+                            <text
+                                x={(x + slot / 2.0).to_string()}
+                                y={(bottom + 16.0).to_string()}
+                                text-anchor="middle"
+                            >{ label }</text>
+                        </g>
+                    }
+                }).collect::<Html>()
+            }
+        </svg>
+    }
+}
+
+// Renders the remaining parts over time, along with the ideal line down to zero at the deadline
+pub fn burndown_chart(burndown: &Burndown) -> Html {
+    let Some(&(start, _)) = burndown.remaining.first() else {
+        return html! {};
+    };
+    let last = burndown.remaining.last().map_or(start, |&(day, _)| day);
+    let end = burndown
+        .deadline
+        .map_or(last, |deadline| deadline.max(last));
+    let days = (end - start).num_days().max(1) as f64;
+    let max = burndown.total.max(1);
+    let plot_width = CHART_WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = CHART_HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let bottom = MARGIN_TOP + plot_height;
+    let x = |day: NaiveDate| MARGIN_LEFT + plot_width * (day - start).num_days() as f64 / days;
+    let y = |count: usize| bottom - plot_height * count as f64 / max as f64;
+    let points = burndown
+        .remaining
+        .iter()
+        .map(|&(day, count)| format!("{:.1},{:.1}", x(day), y(count)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    html! {
+        <svg class="chart" viewBox={format!("0 0 {} {}", CHART_WIDTH, CHART_HEIGHT)}>
+            { y_axis(max, plot_height) }
+            if let Some(deadline) = burndown.deadline.filter(|&deadline| deadline >= start) {
+                <line
+                    class="chart-ideal"
+                    x1={x(start).to_string()}
+                    y1={y(burndown.total).to_string()}
+                    x2={x(deadline).to_string()}
+                    y2={y(0).to_string()}
+                />
+                <text x={x(deadline).to_string()} y={(bottom + 16.0).to_string()} text-anchor="end">
+                    { deadline.format("%b %d").to_string() }
+                </text>
+            }
+            <polyline class="chart-line" {points} />
+            <text x={x(start).to_string()} y={(bottom + 16.0).to_string()}>
+                { start.format("%b %d").to_string() }
+            </text>
+        </svg>
+    }
+}
+
+// Renders the y axis from zero up to the max along the left of the plot
+fn y_axis(max: usize, plot_height: f64) -> Html {
+    let bottom = MARGIN_TOP + plot_height;
+    html! {
+        <g class="chart-axis">
+            <line
+                x1={MARGIN_LEFT.to_string()}
+                y1={MARGIN_TOP.to_string()}
+                x2={MARGIN_LEFT.to_string()}
+                y2={bottom.to_string()}
+            />
+            <line
+                x1={MARGIN_LEFT.to_string()}
+                y1={bottom.to_string()}
+                x2={(CHART_WIDTH - MARGIN_RIGHT).to_string()}
+                y2={bottom.to_string()}
+            />
+            <text x={(MARGIN_LEFT - 6.0).to_string()} y={(MARGIN_TOP + 4.0).to_string()} text-anchor="end">
+                { max }
+            </text>
+            <text x={(MARGIN_LEFT - 6.0).to_string()} y={bottom.to_string()} text-anchor="end">
+                { 0 }
+            </text>
+        </g>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(month: u32, day: u32, hour: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, month, day, hour, 0, 0)
+            .unwrap()
+    }
+
+    fn stats_data(completions: Vec<DateTime<Local>>) -> StatsData {
+        StatsData {
+            completions,
+            on_time: 0,
+            late: 0,
+            total_late: Duration::zero(),
+            open_work: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn counts_completions_per_week() {
+        // Wednesday, Mar 13
+        let now = dt(3, 13, 12);
+        assert_eq!(Stats::since(3, now), dt(2, 26, 0));
+        let data = stats_data(vec![dt(3, 10, 9), dt(3, 11, 9), dt(3, 13, 9)]);
+        let stats = Stats::new(data, 3, now);
+        let weekly = stats
+            .weekly
+            .iter()
+            .map(|&(week, count)| (week.format("%m-%d").to_string(), count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            weekly,
+            vec![
+                (String::from("02-26"), 0),
+                (String::from("03-04"), 1),
+                (String::from("03-11"), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn measures_lateness() {
+        let data = StatsData {
+            on_time: 1,
+            late: 2,
+            total_late: Duration::hours(26),
+            ..stats_data(Vec::new())
+        };
+        let stats = Stats::new(data, 1, dt(3, 13, 12));
+        assert_eq!(stats.lateness.on_time_rate(), Some(1.0 / 3.0));
+        assert_eq!(stats.lateness.average(), Some(Duration::hours(13)));
+        assert_eq!(format_lateness(Duration::hours(13)), "13h 00m");
+        assert_eq!(format_lateness(Duration::hours(50)), "2d 2h");
+        assert_eq!(Lateness::default().on_time_rate(), None);
+    }
+
+    #[test]
+    fn burns_down_to_today() {
+        let data = BurndownData {
+            total: 4,
+            completions: vec![dt(3, 11, 9), dt(3, 11, 18), dt(3, 13, 9)],
+            deadline: Some(dt(3, 20, 12)),
+        };
+        let today = dt(3, 14, 12).date_naive();
+        let burndown = Burndown::new(&data, today);
+        assert_eq!(burndown.total, 4);
+        assert_eq!(burndown.deadline, Some(dt(3, 20, 0).date_naive()));
+        let remaining = burndown
+            .remaining
+            .iter()
+            .map(|&(day, left)| (day.day(), left))
+            .collect::<Vec<_>>();
+        assert_eq!(remaining, vec![(11, 2), (12, 2), (13, 1), (14, 1)]);
+    }
+
+    #[test]
+    fn finished_burndowns_end_at_the_last_completion() {
+        let data = BurndownData {
+            total: 2,
+            completions: vec![dt(3, 10, 9), dt(3, 11, 9)],
+            deadline: None,
+        };
+        let burndown = Burndown::new(&data, dt(4, 1, 9).date_naive());
+        let remaining = burndown
+            .remaining
+            .iter()
+            .map(|&(day, left)| (day.day(), left))
+            .collect::<Vec<_>>();
+        assert_eq!(remaining, vec![(10, 1), (11, 0)]);
+    }
+}
@@ -0,0 +1,189 @@
+// Keeps the parts in sync with changes made in other tabs or on other devices by subscribing to
+// the server's part events over a websocket (using the graphql-transport-ws protocol)
+use crate::{
+    console,
+    graphql::{
+        part_events_subscription::{ResponseData, Variables},
+        PartEvent, PartEventsSubscription, CLIENT_ID, WS_URL,
+    },
+};
+use futures::{
+    future::{AbortHandle, Abortable},
+    SinkExt, StreamExt,
+};
+use gloo::{
+    net::websocket::{futures::WebSocket, Message},
+    timers::future::TimeoutFuture,
+};
+use graphql_client::{GraphQLQuery, QueryBody, Response};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen_futures::spawn_local;
+use yew::Callback;
+
+const PROTOCOL: &str = "graphql-transport-ws";
+// The id of the (only) subscription on the connection
+const SUBSCRIPTION_ID: &str = "part-events";
+// Reconnect delays double after each failed attempt, up to the max (in ms)
+const MIN_RETRY_DELAY: u32 = 1_000;
+const MAX_RETRY_DELAY: u32 = 30_000;
+
+pub enum SyncEvent {
+    // The subscription started. reconnected is true if an earlier connection dropped, in which
+    // case events may have been missed.
+    Connected { reconnected: bool },
+    Event(PartEvent),
+    // A change to a part of a project the user with the given email shared with the user
+    SharedEvent(String, PartEvent),
+    Disconnected,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    ConnectionInit {
+        payload: InitPayload,
+    },
+    Subscribe {
+        id: &'static str,
+        payload: QueryBody<Variables>,
+    },
+    Pong,
+}
+
+#[derive(Serialize)]
+struct InitPayload {
+    #[serde(rename = "Authorization")]
+    authorization: String,
+    #[serde(rename = "X-Client-Id")]
+    client_id: &'static str,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    ConnectionAck,
+    Next {
+        payload: Box<Response<ResponseData>>,
+    },
+    Error {
+        payload: Vec<graphql_client::Error>,
+    },
+    Complete,
+    Ping,
+    Pong,
+}
+
+// Stops syncing when dropped
+pub struct PartsSync(AbortHandle);
+
+impl Drop for PartsSync {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+// Starts syncing, reconnecting whenever the connection drops
+pub fn start(jwt: String, on_event: Callback<SyncEvent>) -> PartsSync {
+    let (handle, registration) = AbortHandle::new_pair();
+    spawn_local(async move {
+        let _ = Abortable::new(run(jwt, on_event), registration).await;
+    });
+    PartsSync(handle)
+}
+
+async fn run(jwt: String, on_event: Callback<SyncEvent>) {
+    let mut delay = MIN_RETRY_DELAY;
+    let mut reconnected = false;
+    loop {
+        let mut connected = false;
+        if let Err(e) = subscribe(&jwt, &on_event, reconnected, &mut connected).await {
+            console::log!("part events subscription error: {}", e);
+        }
+        if connected {
+            reconnected = true;
+            delay = MIN_RETRY_DELAY;
+            on_event.emit(SyncEvent::Disconnected);
+        } else {
+            delay = (delay * 2).min(MAX_RETRY_DELAY);
+        }
+        TimeoutFuture::new(delay).await;
+    }
+}
+
+// Subscribes to the part events and emits them until the connection drops. Sets connected once
+// the subscription has started.
+async fn subscribe(
+    jwt: &str,
+    on_event: &Callback<SyncEvent>,
+    reconnected: bool,
+    connected: &mut bool,
+) -> anyhow::Result<()> {
+    let mut ws = WebSocket::open_with_protocol(&WS_URL, PROTOCOL)
+        .map_err(|e| anyhow::anyhow!("error opening websocket: {}", e))?;
+    let init = ClientMessage::ConnectionInit {
+        payload: InitPayload {
+            authorization: format!("bearer {}", jwt),
+            client_id: CLIENT_ID.as_str(),
+        },
+    };
+    send(&mut ws, &init).await?;
+    loop {
+        match receive(&mut ws).await? {
+            ServerMessage::ConnectionAck => break,
+            ServerMessage::Ping => send(&mut ws, &ClientMessage::Pong).await?,
+            _ => anyhow::bail!("unexpected message before connection ack"),
+        }
+    }
+    let subscribe = ClientMessage::Subscribe {
+        id: SUBSCRIPTION_ID,
+        payload: PartEventsSubscription::build_query(Variables),
+    };
+    send(&mut ws, &subscribe).await?;
+    *connected = true;
+    on_event.emit(SyncEvent::Connected { reconnected });
+    loop {
+        match receive(&mut ws).await? {
+            ServerMessage::Next { payload } => {
+                if let Some(errors) = payload.errors {
+                    anyhow::bail!("{}", join_errors(errors));
+                }
+                let Some(data) = payload.data else {
+                    continue;
+                };
+                let owner = data.part_events.owner.clone();
+                match (PartEvent::try_from(data.part_events), owner) {
+                    (Ok(event), None) => on_event.emit(SyncEvent::Event(event)),
+                    (Ok(event), Some(owner)) => on_event.emit(SyncEvent::SharedEvent(owner, event)),
+                    (Err(e), _) => console::log!("error reading part event: {}", e),
+                }
+            }
+            ServerMessage::Error { payload } => anyhow::bail!("{}", join_errors(payload)),
+            ServerMessage::Complete => anyhow::bail!("subscription ended by the server"),
+            ServerMessage::Ping => send(&mut ws, &ClientMessage::Pong).await?,
+            ServerMessage::ConnectionAck | ServerMessage::Pong => {}
+        }
+    }
+}
+
+async fn send(ws: &mut WebSocket, msg: &ClientMessage) -> anyhow::Result<()> {
+    ws.send(Message::Text(serde_json::to_string(msg)?))
+        .await
+        .map_err(|e| anyhow::anyhow!("error sending message: {}", e))
+}
+
+async fn receive(ws: &mut WebSocket) -> anyhow::Result<ServerMessage> {
+    match ws.next().await {
+        Some(Ok(Message::Text(text))) => Ok(serde_json::from_str(&text)?),
+        Some(Ok(Message::Bytes(bytes))) => Ok(serde_json::from_slice(&bytes)?),
+        Some(Err(e)) => Err(anyhow::anyhow!("{}", e)),
+        None => Err(anyhow::anyhow!("connection closed")),
+    }
+}
+
+fn join_errors(errors: Vec<graphql_client::Error>) -> String {
+    errors
+        .into_iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,301 @@
+// Project templates: the structure of a subtree (names, descriptions, tags and deadlines) kept in
+// local storage per user so it can be instantiated again later. Deadlines are stored as offsets
+// from the template's reference date (the root's deadline, or else the earliest deadline in the
+// subtree), so instantiating a template at an anchor date moves them all along with it.
+// Duplicating a subtree instantiates it as a template anchored at its own reference date.
+use crate::{
+    console,
+    graphql::{
+        create_part_mutation::Variables as CreatePartVars, send_create_part, Part, PartialOk,
+    },
+};
+use chrono::prelude::*;
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+type PartsMap = BTreeMap<i64, Part>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Template {
+    pub name: String,
+    pub root: TemplatePart,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplatePart {
+    pub name: String,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    // The deadline as the number of seconds after (or before, if negative) the reference date
+    pub deadline_offset: Option<i64>,
+    pub children: Vec<TemplatePart>,
+}
+
+// A part to create when instantiating a template, in an order where parents come first
+#[derive(Debug, PartialEq, Eq)]
+pub struct PlannedPart {
+    // The index of the parent among the planned parts (None for the root)
+    pub parent: Option<usize>,
+    pub part: Part,
+}
+
+impl Template {
+    // Captures the subtree of the part with the given id (None if it doesn't exist). Children
+    // are kept in the order they were created.
+    pub fn from_subtree(name: String, parts: &PartsMap, id: i64) -> Option<Self> {
+        let root = parts.get(&id)?;
+        let mut children = BTreeMap::<i64, Vec<&Part>>::new();
+        for part in parts.values() {
+            if let Some(pid) = part.parent_id {
+                children.entry(pid).or_default().push(part);
+            }
+        }
+        Some(Self {
+            name,
+            root: TemplatePart::capture(root, &children, reference_date(parts, id)),
+        })
+    }
+
+    // Returns the parts to create (parents first) to instantiate the template with its
+    // reference date at the anchor
+    pub fn instantiate(&self, anchor: DateTime<Local>) -> Vec<PlannedPart> {
+        let mut planned = Vec::new();
+        let mut queue = VecDeque::from([(None, &self.root)]);
+        while let Some((parent, tpart)) = queue.pop_front() {
+            let index = planned.len();
+            planned.push(PlannedPart {
+                parent,
+                part: Part {
+                    name: tpart.name.clone(),
+                    description: tpart.description.clone(),
+                    tags: tpart.tags.clone(),
+                    deadline: tpart
+                        .deadline_offset
+                        .map(|secs| anchor + chrono::Duration::seconds(secs)),
+                    ..Default::default()
+                },
+            });
+            queue.extend(tpart.children.iter().map(|child| (Some(index), child)));
+        }
+        planned
+    }
+
+    // Returns the number of parts in the template
+    pub fn num_parts(&self) -> usize {
+        let mut stack = vec![&self.root];
+        let mut len = 0;
+        while let Some(tpart) = stack.pop() {
+            len += 1;
+            stack.extend(&tpart.children);
+        }
+        len
+    }
+}
+
+impl TemplatePart {
+    fn capture(
+        part: &Part,
+        children: &BTreeMap<i64, Vec<&Part>>,
+        reference: Option<DateTime<Local>>,
+    ) -> Self {
+        Self {
+            name: part.name.clone(),
+            description: part.description.clone(),
+            tags: part.tags.clone(),
+            deadline_offset: part
+                .deadline
+                .zip(reference)
+                .map(|(deadline, reference)| (deadline - reference).num_seconds()),
+            children: children
+                .get(&part.id)
+                .into_iter()
+                .flatten()
+                .map(|child| Self::capture(child, children, reference))
+                .collect(),
+        }
+    }
+}
+
+// Returns the date the deadlines of a template captured from the subtree of the part with the
+// given id are relative to. Instantiating the template at it gives the subtree's deadlines.
+pub fn reference_date(parts: &PartsMap, id: i64) -> Option<DateTime<Local>> {
+    parts.get(&id)?.deadline.or_else(|| {
+        parts
+            .values()
+            .filter(|part| is_in_subtree(parts, part.id, id))
+            .filter_map(|part| part.deadline)
+            .min()
+    })
+}
+
+// Returns whether the part with the given id is a (strict) descendant of the ancestor
+fn is_in_subtree(parts: &PartsMap, id: i64, ancestor_id: i64) -> bool {
+    let mut cur = parts.get(&id).and_then(|part| part.parent_id);
+    while let Some(pid) = cur {
+        if pid == ancestor_id {
+            return true;
+        }
+        cur = parts.get(&pid).and_then(|part| part.parent_id);
+    }
+    false
+}
+
+pub fn load(user: &str) -> Vec<Template> {
+    LocalStorage::get(key(user)).unwrap_or_default()
+}
+
+pub fn save(user: &str, templates: &[Template]) {
+    if let Err(e) = LocalStorage::set(key(user), templates) {
+        console::log!("error saving templates: {}", e);
+    }
+}
+
+pub fn clear(user: &str) {
+    LocalStorage::delete(key(user));
+}
+
+fn key(user: &str) -> String {
+    format!("templates:{}", user)
+}
+
+// Creates the planned parts one at a time (parents first), the root going under the given parent
+// (None makes it a project). Returns the parts created, which are all of them unless there's an
+// error.
+pub async fn create(
+    jwt: String,
+    parent_id: Option<i64>,
+    planned: Vec<PlannedPart>,
+) -> PartialOk<Vec<Part>> {
+    let mut created = Vec::<Part>::with_capacity(planned.len());
+    for PlannedPart { parent, part } in planned {
+        let input = Part {
+            parent_id: match parent {
+                Some(index) => Some(created[index].id),
+                None => parent_id,
+            },
+            ..part
+        };
+        let vars = CreatePartVars {
+            input: input.into(),
+            owner: None,
+        };
+        let res = send_create_part(jwt.clone(), vars)
+            .await
+            .and_then(Part::try_from);
+        match res {
+            Ok(part) => created.push(part),
+            Err(e) => return PartialOk(created, Some(e)),
+        }
+    }
+    PartialOk(created, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(day: u32, hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn part(id: i64, parent_id: Option<i64>, deadline: Option<DateTime<Local>>) -> Part {
+        Part {
+            id,
+            name: format!("Part {}", id),
+            parent_id,
+            deadline,
+            ..Default::default()
+        }
+    }
+
+    fn parts(parts: impl IntoIterator<Item = Part>) -> PartsMap {
+        parts.into_iter().map(|part| (part.id, part)).collect()
+    }
+
+    #[test]
+    fn offsets_are_from_the_roots_deadline() {
+        let parts = parts([
+            part(1, None, Some(dt(10, 12))),
+            part(2, Some(1), Some(dt(8, 12))),
+            part(3, Some(1), None),
+            part(4, Some(3), Some(dt(11, 18))),
+            part(5, None, Some(dt(1, 0))),
+        ]);
+        let template = Template::from_subtree(String::from("Release"), &parts, 1).unwrap();
+        assert_eq!(template.num_parts(), 4);
+        let root = &template.root;
+        assert_eq!(root.deadline_offset, Some(0));
+        assert_eq!(root.children[0].deadline_offset, Some(-2 * 24 * 3600));
+        assert_eq!(root.children[1].deadline_offset, None);
+        assert_eq!(
+            root.children[1].children[0].deadline_offset,
+            Some(30 * 3600)
+        );
+    }
+
+    #[test]
+    fn offsets_are_from_the_earliest_deadline_without_a_root_deadline() {
+        let parts = parts([
+            part(1, None, None),
+            part(2, Some(1), Some(dt(8, 12))),
+            part(3, Some(1), Some(dt(6, 12))),
+        ]);
+        let template = Template::from_subtree(String::new(), &parts, 1).unwrap();
+        let offsets = template
+            .root
+            .children
+            .iter()
+            .map(|child| child.deadline_offset)
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![Some(2 * 24 * 3600), Some(0)]);
+        assert_eq!(reference_date(&parts, 1), Some(dt(6, 12)));
+    }
+
+    #[test]
+    fn instantiates_parents_first_at_the_anchor() {
+        let parts = parts([
+            part(1, None, Some(dt(10, 12))),
+            part(2, Some(1), None),
+            part(3, Some(2), Some(dt(9, 12))),
+            part(4, Some(1), None),
+        ]);
+        let template = Template::from_subtree(String::new(), &parts, 1).unwrap();
+        let planned = template.instantiate(dt(20, 9));
+        let shape = planned
+            .iter()
+            .map(|planned| (planned.parent, planned.part.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            shape,
+            vec![
+                (None, "Part 1"),
+                (Some(0), "Part 2"),
+                (Some(0), "Part 4"),
+                (Some(1), "Part 3"),
+            ]
+        );
+        assert_eq!(planned[0].part.deadline, Some(dt(20, 9)));
+        assert_eq!(planned[3].part.deadline, Some(dt(19, 9)));
+        assert!(planned
+            .iter()
+            .all(|planned| planned.part.completed_at.is_none()));
+    }
+
+    #[test]
+    fn duplicates_keep_their_deadlines() {
+        let parts = parts([
+            part(1, None, None),
+            part(2, Some(1), Some(dt(8, 12))),
+            part(3, Some(1), Some(dt(6, 12))),
+        ]);
+        let template = Template::from_subtree(String::new(), &parts, 1).unwrap();
+        let reference = reference_date(&parts, 1).unwrap();
+        let deadlines = template
+            .instantiate(reference)
+            .into_iter()
+            .map(|planned| planned.part.deadline)
+            .collect::<Vec<_>>();
+        assert_eq!(deadlines, vec![None, Some(dt(8, 12)), Some(dt(6, 12))]);
+    }
+}
@@ -0,0 +1,278 @@
+// Helpers for time tracking: formatting durations and building the weekly time report
+use crate::graphql::{Part, TimeEntry};
+use chrono::{prelude::*, Duration};
+use std::collections::BTreeMap;
+
+type PartsMap = BTreeMap<i64, Part>;
+
+const DAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+// Formats the duration as, e.g., "1h 02m 05s" (or "2m 05s" if less than an hour)
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.num_seconds().max(0);
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if h != 0 {
+        format!("{}h {:02}m {:02}s", h, m, s)
+    } else {
+        format!("{}m {:02}s", m, s)
+    }
+}
+
+// Returns the Monday of the week containing the given date
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+// Returns the start of the given day in local time
+fn day_start(date: NaiveDate) -> DateTime<Local> {
+    let ndt = date.and_hms_opt(0, 0, 0).expect("midnight should be valid");
+    Local
+        .from_local_datetime(&ndt)
+        .earliest()
+        .unwrap_or_else(|| DateTime::from_utc(ndt, *Local::now().offset()))
+}
+
+// Returns the names of the part's ancestors and the part, joined by " / "
+pub fn part_path(parts: &PartsMap, id: i64) -> String {
+    let mut names = Vec::new();
+    let mut cur = parts.get(&id);
+    while let Some(part) = cur {
+        names.push(part.name.as_str());
+        cur = part.parent_id.and_then(|pid| parts.get(&pid));
+    }
+    names.reverse();
+    names.join(" / ")
+}
+
+pub struct WeekReportRow {
+    pub part_id: i64,
+    pub path: String,
+    // Time logged on each day of the week, starting on Monday
+    pub days: [Duration; 7],
+}
+
+impl WeekReportRow {
+    pub fn total(&self) -> Duration {
+        self.days.iter().fold(Duration::zero(), |acc, &d| acc + d)
+    }
+}
+
+pub struct WeekReport {
+    // The Monday the report starts on
+    pub start: NaiveDate,
+    pub rows: Vec<WeekReportRow>,
+}
+
+impl WeekReport {
+    // Builds the report for the week starting on the given Monday. Running entries are counted up
+    // until now. Only parts with time logged during the week get a row.
+    pub fn new<'a>(
+        start: NaiveDate,
+        entries: impl Iterator<Item = &'a TimeEntry>,
+        parts: &PartsMap,
+        now: DateTime<Local>,
+    ) -> Self {
+        let bounds = (0..=7)
+            .map(|i| day_start(start + Duration::days(i)))
+            .collect::<Vec<_>>();
+        let mut by_part: BTreeMap<i64, [Duration; 7]> = BTreeMap::new();
+        for entry in entries {
+            let (entry_start, entry_end) = (entry.started_at, entry.ended_at.unwrap_or(now));
+            for (i, day) in bounds.windows(2).enumerate() {
+                let overlap = entry_end.min(day[1]) - entry_start.max(day[0]);
+                if overlap > Duration::zero() {
+                    let days = by_part
+                        .entry(entry.part_id)
+                        .or_insert([Duration::zero(); 7]);
+                    days[i] = days[i] + overlap;
+                }
+            }
+        }
+        let mut rows = by_part
+            .into_iter()
+            .map(|(part_id, days)| WeekReportRow {
+                part_id,
+                path: part_path(parts, part_id),
+                days,
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by_cached_key(|row| row.path.to_lowercase());
+        Self { start, rows }
+    }
+
+    // Returns the header for each day (e.g., "Mon 10/12")
+    pub fn day_headers(&self) -> Vec<String> {
+        DAY_NAMES
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let date = self.start + Duration::days(i as i64);
+                format!("{} {}", name, date.format("%m/%d"))
+            })
+            .collect()
+    }
+
+    pub fn day_totals(&self) -> [Duration; 7] {
+        let mut totals = [Duration::zero(); 7];
+        for row in &self.rows {
+            for (total, &d) in totals.iter_mut().zip(row.days.iter()) {
+                *total = *total + d;
+            }
+        }
+        totals
+    }
+
+    pub fn total(&self) -> Duration {
+        self.rows
+            .iter()
+            .fold(Duration::zero(), |acc, row| acc + row.total())
+    }
+
+    // Returns the report as CSV with durations in (decimal) hours
+    pub fn to_csv(&self) -> String {
+        let hours = |d: Duration| format!("{:.2}", d.num_seconds() as f64 / 3600.0);
+        let mut csv = String::from("Part");
+        for header in self
+            .start
+            .iter_days()
+            .take(7)
+            .map(|date| date.format("%Y-%m-%d").to_string())
+        {
+            csv.push(',');
+            csv.push_str(&header);
+        }
+        csv.push_str(",Total\n");
+        for row in &self.rows {
+            csv.push_str(&csv_field(&row.path));
+            row.days.iter().for_each(|&d| {
+                csv.push(',');
+                csv.push_str(&hours(d));
+            });
+            csv.push(',');
+            csv.push_str(&hours(row.total()));
+            csv.push('\n');
+        }
+        csv.push_str("Total");
+        self.day_totals().iter().for_each(|&d| {
+            csv.push(',');
+            csv.push_str(&hours(d));
+        });
+        csv.push(',');
+        csv.push_str(&hours(self.total()));
+        csv.push('\n');
+        csv
+    }
+}
+
+// Quotes the field if it contains characters special to CSV
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The week starting on Monday, January 8, 2024
+    fn monday() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, 8).unwrap()
+    }
+
+    fn dt(day: u32, hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap()
+    }
+
+    fn entry(
+        part_id: i64,
+        started_at: DateTime<Local>,
+        ended_at: Option<DateTime<Local>>,
+    ) -> TimeEntry {
+        TimeEntry {
+            id: 0,
+            part_id,
+            started_at,
+            ended_at,
+        }
+    }
+
+    fn part(id: i64, name: &str, parent_id: Option<i64>) -> Part {
+        Part {
+            id,
+            name: name.to_string(),
+            parent_id,
+            ..Default::default()
+        }
+    }
+
+    fn parts(parts: impl IntoIterator<Item = Part>) -> PartsMap {
+        parts.into_iter().map(|part| (part.id, part)).collect()
+    }
+
+    #[test]
+    fn splits_entries_across_days() {
+        let parts = parts([part(1, "Project", None), part(2, "Task", Some(1))]);
+        let entries = [
+            // Tuesday night into Wednesday
+            entry(2, dt(9, 22), Some(dt(10, 2))),
+            // Starts the Sunday before the week
+            entry(2, dt(7, 23), Some(dt(8, 1))),
+            // Ends the Monday after the week
+            entry(1, dt(14, 23), Some(dt(15, 3))),
+            // Entirely in another week
+            entry(1, dt(1, 9), Some(dt(1, 10))),
+        ];
+        let report = WeekReport::new(monday(), entries.iter(), &parts, dt(20, 0));
+        let hours = |row: &WeekReportRow| row.days.map(|d| d.num_hours());
+
+        assert_eq!(report.rows.len(), 2);
+        assert_eq!(report.rows[0].path, "Project");
+        assert_eq!(hours(&report.rows[0]), [0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(report.rows[1].path, "Project / Task");
+        assert_eq!(hours(&report.rows[1]), [1, 2, 2, 0, 0, 0, 0]);
+        assert_eq!(
+            report.day_totals().map(|d| d.num_hours()),
+            [1, 2, 2, 0, 0, 0, 1]
+        );
+        assert_eq!(report.total(), Duration::hours(6));
+    }
+
+    #[test]
+    fn counts_running_entries_until_now() {
+        let parts = parts([part(1, "Project", None)]);
+        let entries = [entry(1, dt(11, 9), None)];
+        let report = WeekReport::new(monday(), entries.iter(), &parts, dt(11, 12));
+        assert_eq!(report.rows[0].days[3], Duration::hours(3));
+        assert_eq!(report.total(), Duration::hours(3));
+    }
+
+    #[test]
+    fn quotes_csv_fields() {
+        let parts = parts([
+            part(1, "Plain", None),
+            part(2, "A, B", None),
+            part(3, "Say \"hi\"", None),
+            part(4, "Two\nlines", None),
+        ]);
+        let entries = (1..=4)
+            .map(|id| entry(id, dt(8, 9), Some(dt(8, 10))))
+            .collect::<Vec<_>>();
+        let report = WeekReport::new(monday(), entries.iter(), &parts, dt(20, 0));
+        let row = "1.00,0.00,0.00,0.00,0.00,0.00,0.00,1.00";
+        assert_eq!(
+            report.to_csv(),
+            format!(
+                "Part,2024-01-08,2024-01-09,2024-01-10,2024-01-11,2024-01-12,2024-01-13,\
+                 2024-01-14,Total\n\
+                 \"A, B\",{row}\n\
+                 Plain,{row}\n\
+                 \"Say \"\"hi\"\"\",{row}\n\
+                 \"Two\nlines\",{row}\n\
+                 Total,4.00,0.00,0.00,0.00,0.00,0.00,0.00,4.00\n"
+            )
+        );
+    }
+}
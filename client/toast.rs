@@ -0,0 +1,221 @@
+// Non-blocking notifications, stacked in a corner of the page, which dismiss themselves after a
+// while (longer for ones that matter more or have actions to take)
+use yew::prelude::*;
+
+// The max number of toasts shown at once, past which the oldest are dismissed
+const MAX_SHOWN: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn class(self) -> &'static str {
+        match self {
+            Severity::Info => "toast-info",
+            Severity::Success => "toast-success",
+            Severity::Warning => "toast-warning",
+            Severity::Error => "toast-error",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Toast {
+    pub severity: Severity,
+    pub message: String,
+    // The buttons that carry out something about the message (e.g., "Undo" or "Retry"), which
+    // dismiss the toast too
+    pub actions: Vec<(String, Callback<()>)>,
+}
+
+impl Toast {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            actions: Vec::new(),
+        }
+    }
+
+    pub fn info(message: impl Into<String>) -> Self {
+        Self::new(Severity::Info, message)
+    }
+
+    pub fn success(message: impl Into<String>) -> Self {
+        Self::new(Severity::Success, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn with_action(mut self, label: &str, onclick: Callback<()>) -> Self {
+        self.actions.push((String::from(label), onclick));
+        self
+    }
+
+    // Returns how long to show the toast for (in ms) unless it's dismissed
+    pub fn duration(&self) -> u32 {
+        let duration = match self.severity {
+            Severity::Info | Severity::Success => 4_000,
+            Severity::Warning => 6_000,
+            Severity::Error => 8_000,
+        };
+        // Leaves time to take the actions
+        if self.actions.is_empty() {
+            duration
+        } else {
+            duration + 4_000
+        }
+    }
+}
+
+// The toasts being shown, oldest first
+#[derive(Default)]
+pub struct Toasts {
+    // (id, toast, times shown)
+    shown: Vec<(u64, Toast, u32)>,
+    next_id: u64,
+}
+
+impl Toasts {
+    // Shows the toast, returning its id. A toast the same as one already shown replaces it,
+    // counting the times it's been shown instead of stacking another.
+    pub fn push(&mut self, toast: Toast) -> u64 {
+        let mut count = 1;
+        if let Some(pos) = self.shown.iter().position(|(_, shown, _)| {
+            shown.severity == toast.severity && shown.message == toast.message
+        }) {
+            count += self.shown.remove(pos).2;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.shown.push((id, toast, count));
+        if self.shown.len() > MAX_SHOWN {
+            self.shown.remove(0);
+        }
+        id
+    }
+
+    // Dismisses the toast, returning whether it was still shown
+    pub fn dismiss(&mut self, id: u64) -> bool {
+        let len = self.shown.len();
+        self.shown.retain(|&(shown_id, _, _)| shown_id != id);
+        self.shown.len() != len
+    }
+
+    pub fn render(&self, ondismiss: Callback<u64>) -> Html {
+        html! {
+            <div id="toasts-div" aria-live="polite">
+                {
+                    self.shown.iter().map(|(id, toast, count)| {
+                        let id = *id;
+                        let actions = toast.actions.iter().map(|(label, onclick)| {
+                            let onclick = onclick.clone();
+                            let ondismiss = ondismiss.clone();
+                            let onclick = Callback::from(move |_| {
+                                onclick.emit(());
+                                ondismiss.emit(id);
+                            });
+                            html! { <button class="toast-action" {onclick}>{ label }</button> }
+                        });
+                        let ondismiss = ondismiss.clone();
+                        let dismiss = Callback::from(move |_| ondismiss.emit(id));
+                        html! {
+                            <div
+                                key={id}
+                                class={classes!("toast", toast.severity.class())}
+                                role={if toast.severity == Severity::Error { "alert" } else { "status" }}
+                            >
+                                <span class="toast-message">{ &toast.message }</span>
+                                if *count > 1 {
+                                    <span class="toast-count">{ format!("×{}", count) }</span>
+                                }
+                                { for actions }
+                                <button class="toast-dismiss" title="Dismiss" onclick={dismiss}>
+                                    { "×" }
+                                </button>
+                            </div>
+                        }
+                    }).collect::<Html>()
+                }
+            </div>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(toasts: &Toasts) -> Vec<(&str, u32)> {
+        toasts
+            .shown
+            .iter()
+            .map(|(_, toast, count)| (toast.message.as_str(), *count))
+            .collect()
+    }
+
+    #[test]
+    fn stacks_toasts_in_order() {
+        let mut toasts = Toasts::default();
+        let first = toasts.push(Toast::info("first"));
+        let second = toasts.push(Toast::error("second"));
+        assert_ne!(first, second);
+        assert_eq!(messages(&toasts), [("first", 1), ("second", 1)]);
+    }
+
+    #[test]
+    fn repeated_toasts_are_counted() {
+        let mut toasts = Toasts::default();
+        let first = toasts.push(Toast::error("failed"));
+        toasts.push(Toast::info("other"));
+        let again = toasts.push(Toast::error("failed"));
+        assert_ne!(first, again);
+        assert_eq!(messages(&toasts), [("other", 1), ("failed", 2)]);
+        // The same message with another severity isn't the same toast
+        toasts.push(Toast::warning("failed"));
+        assert_eq!(
+            messages(&toasts),
+            [("other", 1), ("failed", 2), ("failed", 1)]
+        );
+    }
+
+    #[test]
+    fn dismisses_toasts() {
+        let mut toasts = Toasts::default();
+        let first = toasts.push(Toast::info("first"));
+        toasts.push(Toast::info("second"));
+        assert!(toasts.dismiss(first));
+        assert!(!toasts.dismiss(first));
+        assert_eq!(messages(&toasts), [("second", 1)]);
+    }
+
+    #[test]
+    fn drops_the_oldest_past_the_max() {
+        let mut toasts = Toasts::default();
+        for i in 0..MAX_SHOWN + 2 {
+            toasts.push(Toast::info(i.to_string()));
+        }
+        assert_eq!(toasts.shown.len(), MAX_SHOWN);
+        assert_eq!(messages(&toasts)[0], ("2", 1));
+    }
+
+    #[test]
+    fn actions_extend_the_duration() {
+        let toast = Toast::success("Deleted");
+        let duration = toast.duration();
+        let toast = toast.with_action("Undo", Callback::noop());
+        assert!(toast.duration() > duration);
+        assert!(Toast::error("failed").duration() > Toast::info("done").duration());
+    }
+}
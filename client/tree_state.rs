@@ -0,0 +1,58 @@
+// The expanded/collapsed state of the part tree and which parts show their details. The state is
+// persisted in local storage per user so it survives reloads and re-fetches.
+use crate::console;
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+// The email of the last user to log in (sessions are restored from a cookie, which doesn't tell
+// the client who's logged in)
+const LAST_USER_KEY: &str = "last-user";
+
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TreeState {
+    // Ids of the parts whose children are shown
+    pub expanded: BTreeSet<i64>,
+    // Ids of the parts whose details are shown
+    pub details: BTreeSet<i64>,
+}
+
+impl TreeState {
+    pub fn load(user: &str) -> Self {
+        LocalStorage::get(key(user)).unwrap_or_default()
+    }
+
+    pub fn save(&self, user: &str) {
+        if let Err(e) = LocalStorage::set(key(user), self) {
+            console::log!("error saving tree state: {}", e);
+        }
+    }
+
+    pub fn clear(user: &str) {
+        LocalStorage::delete(key(user));
+    }
+
+    // Forgets the part (e.g., once deleted)
+    pub fn remove(&mut self, id: i64) {
+        self.expanded.remove(&id);
+        self.details.remove(&id);
+    }
+}
+
+fn key(user: &str) -> String {
+    format!("tree-state:{}", user)
+}
+
+pub fn last_user() -> Option<String> {
+    LocalStorage::get(LAST_USER_KEY).ok()
+}
+
+pub fn remember_user(email: &str) {
+    if let Err(e) = LocalStorage::set(LAST_USER_KEY, email) {
+        console::log!("error saving user: {}", e);
+    }
+}
+
+pub fn forget_user() {
+    LocalStorage::delete(LAST_USER_KEY);
+}
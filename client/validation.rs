@@ -0,0 +1,221 @@
+// Validation of the login and account forms, and mapping the server's errors back to their fields
+// The server only limits passwords to 72 bytes (bcrypt's limit)
+const MAX_PASSWORD_BYTES: usize = 72;
+const MIN_PASSWORD_CHARS: usize = 8;
+
+// Returns what's wrong with the email, if anything. Only the shape is checked: something before
+// and after an '@', with a dot in the domain.
+pub fn email_error(email: &str) -> Option<&'static str> {
+    if email.is_empty() {
+        return Some("Enter your email");
+    }
+    let valid = match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && domain.split('.').all(|label| !label.is_empty())
+                && !email.chars().any(char::is_whitespace)
+        }
+        None => false,
+    };
+    (!valid).then_some("Enter a valid email address")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Strength {
+    Weak,
+    Fair,
+    Strong,
+}
+
+impl Strength {
+    pub fn label(self) -> &'static str {
+        match self {
+            Strength::Weak => "Weak",
+            Strength::Fair => "Fair",
+            Strength::Strong => "Strong",
+        }
+    }
+}
+
+// Rates the password by its length and how many kinds of characters (lowercase, uppercase,
+// digits and others) it mixes
+pub fn password_strength(password: &str) -> Strength {
+    let len = password.chars().count();
+    let kinds = [
+        password.chars().any(char::is_lowercase),
+        password.chars().any(char::is_uppercase),
+        password.chars().any(|c| c.is_ascii_digit()),
+        password.chars().any(|c| !c.is_alphanumeric()),
+    ]
+    .into_iter()
+    .filter(|&has| has)
+    .count();
+    if len >= 12 && kinds >= 3 {
+        Strength::Strong
+    } else if len >= MIN_PASSWORD_CHARS && kinds >= 2 {
+        Strength::Fair
+    } else {
+        Strength::Weak
+    }
+}
+
+// Returns what's wrong with a new password (when signing up or changing it), if anything
+pub fn new_password_error(password: &str) -> Option<&'static str> {
+    if password.chars().count() < MIN_PASSWORD_CHARS {
+        Some("Must be at least 8 characters")
+    } else if password.len() > MAX_PASSWORD_BYTES {
+        Some("Must be at most 72 characters")
+    } else if password_strength(password) == Strength::Weak {
+        Some("Must mix at least two of lowercase, uppercase, numbers and symbols")
+    } else {
+        None
+    }
+}
+
+// Returns the message of an error from the server, without the path and location it's formatted
+// with (e.g., "loginUser:1:2: invalid credentials")
+pub fn server_message(err: &str) -> &str {
+    let parts = err.splitn(4, ':').collect::<Vec<_>>();
+    match parts[..] {
+        [_, line, column, message]
+            if line.parse::<u32>().is_ok() && column.parse::<u32>().is_ok() =>
+        {
+            message.trim()
+        }
+        _ => err.trim(),
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldErrors {
+    pub email: Option<String>,
+    pub password: Option<String>,
+    pub confirm: Option<String>,
+    // Errors that aren't about any one field
+    pub form: Option<String>,
+}
+
+impl FieldErrors {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // Maps the (newline separated) errors from the server to the fields they're about
+    pub fn from_server(errors: &str) -> Self {
+        let mut fields = Self::default();
+        for message in errors.lines().map(server_message).filter(|m| !m.is_empty()) {
+            let (field, message) = if message.contains("user already exists") {
+                (
+                    &mut fields.email,
+                    "An account with this email already exists",
+                )
+            } else if message.contains("invalid credentials") {
+                (&mut fields.form, "Incorrect email or password")
+            } else if message.contains("invalid email or password") {
+                (&mut fields.form, "Enter your email and password")
+            } else if message.contains("invalid email") {
+                (&mut fields.email, "Enter a valid email address")
+            } else if message.contains("invalid password") {
+                (&mut fields.password, "Invalid password")
+            } else {
+                (&mut fields.form, message)
+            };
+            // The first error about a field is kept
+            field.get_or_insert_with(|| message.to_string());
+        }
+        fields
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_emails() {
+        assert_eq!(email_error("me@example.com"), None);
+        assert_eq!(email_error("first.last@mail.example.co"), None);
+        assert_eq!(email_error(""), Some("Enter your email"));
+        for email in [
+            "me",
+            "@example.com",
+            "me@",
+            "me@example",
+            "me@example.",
+            "me@.com",
+            "me@@example.com",
+            "me@exa mple.com",
+        ] {
+            assert_eq!(
+                email_error(email),
+                Some("Enter a valid email address"),
+                "{}",
+                email
+            );
+        }
+    }
+
+    #[test]
+    fn rates_passwords() {
+        assert_eq!(password_strength("short1"), Strength::Weak);
+        assert_eq!(password_strength("alllowercase"), Strength::Weak);
+        assert_eq!(password_strength("lowercase1"), Strength::Fair);
+        assert_eq!(password_strength("Lower-and-UPPER1"), Strength::Strong);
+        assert_eq!(password_strength("Short-1"), Strength::Weak);
+    }
+
+    #[test]
+    fn validates_new_passwords() {
+        assert_eq!(
+            new_password_error("abc123"),
+            Some("Must be at least 8 characters")
+        );
+        assert_eq!(
+            new_password_error("abcdefghij"),
+            Some("Must mix at least two of lowercase, uppercase, numbers and symbols")
+        );
+        assert_eq!(new_password_error("abcdefg1"), None);
+        assert_eq!(
+            new_password_error(&"a1".repeat(40)),
+            Some("Must be at most 72 characters")
+        );
+    }
+
+    #[test]
+    fn strips_error_locations() {
+        assert_eq!(
+            server_message("loginUser:1:2: invalid credentials"),
+            "invalid credentials"
+        );
+        assert_eq!(
+            server_message("createUser:0:0: invalid input: invalid email"),
+            "invalid input: invalid email"
+        );
+        assert_eq!(
+            server_message("error sending request: connection refused"),
+            "error sending request: connection refused"
+        );
+    }
+
+    #[test]
+    fn maps_server_errors_to_fields() {
+        let errors = FieldErrors::from_server("createUser:1:2: user already exists");
+        assert_eq!(
+            errors.email.as_deref(),
+            Some("An account with this email already exists")
+        );
+        assert_eq!(errors.form, None);
+
+        let errors = FieldErrors::from_server("loginUser:1:2: invalid credentials");
+        assert_eq!(errors.form.as_deref(), Some("Incorrect email or password"));
+
+        let errors = FieldErrors::from_server(
+            "createUser:1:2: invalid input: invalid password\n<query>:0:0: something else",
+        );
+        assert_eq!(errors.password.as_deref(), Some("Invalid password"));
+        assert_eq!(errors.form.as_deref(), Some("something else"));
+        assert!(FieldErrors::from_server("").is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+// Windowed rendering of long lists: only the rows near the viewport are mounted, with spacers
+// standing in for the rest. Row heights are measured once the rows are mounted (and estimated
+// until then) so the spacers keep the list (and the scroll position) the same size.
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    ops::Range,
+};
+
+// Rows within this distance (in px) of the viewport are mounted too, so quick scrolling doesn't
+// show blank space
+const OVERSCAN: f64 = 300.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Slot {
+    // The row with the given index
+    Row(usize),
+    // Space for rows that aren't mounted (in px)
+    Spacer(f64),
+}
+
+pub struct VirtualList<K> {
+    heights: HashMap<K, f64>,
+    // The height of rows that haven't been measured
+    estimate: f64,
+}
+
+impl<K: Eq + Hash + Clone> VirtualList<K> {
+    pub fn new(estimate: f64) -> Self {
+        Self {
+            heights: HashMap::new(),
+            estimate,
+        }
+    }
+
+    fn height(&self, key: &K) -> f64 {
+        self.heights.get(key).copied().unwrap_or(self.estimate)
+    }
+
+    // Returns the range of rows that are within (or near) the viewport, given as the px offsets
+    // of its top and bottom from the top of the list
+    pub fn window(&self, keys: &[K], top: f64, bottom: f64) -> Range<usize> {
+        let (top, bottom) = (top - OVERSCAN, bottom + OVERSCAN);
+        let mut start = None;
+        let mut offset = 0.0;
+        for (i, key) in keys.iter().enumerate() {
+            offset += self.height(key);
+            if start.is_none() && offset > top {
+                start = Some(i);
+            }
+            if offset >= bottom {
+                return start.unwrap_or(i)..i + 1;
+            }
+        }
+        start.unwrap_or(keys.len())..keys.len()
+    }
+
+    // Returns what to render: the rows in the window and the pinned rows (e.g., the focused one,
+    // so it keeps its state), with spacers for the rows between
+    pub fn slots(&self, keys: &[K], window: Range<usize>, pinned: &[usize]) -> Vec<Slot> {
+        let mut slots = Vec::new();
+        let mut gap = 0.0;
+        for (i, key) in keys.iter().enumerate() {
+            if window.contains(&i) || pinned.contains(&i) {
+                if gap > 0.0 {
+                    slots.push(Slot::Spacer(gap));
+                    gap = 0.0;
+                }
+                slots.push(Slot::Row(i));
+            } else {
+                gap += self.height(key);
+            }
+        }
+        if gap > 0.0 {
+            slots.push(Slot::Spacer(gap));
+        }
+        slots
+    }
+
+    // Records the mounted row's height, returning whether it changed
+    pub fn measure(&mut self, key: K, height: f64) -> bool {
+        let old = self.heights.insert(key, height).unwrap_or(self.estimate);
+        (old - height).abs() >= 0.5
+    }
+
+    // Forgets the heights of rows no longer in the list
+    pub fn retain(&mut self, keys: &[K]) {
+        let keys = keys.iter().collect::<HashSet<_>>();
+        self.heights.retain(|key, _| keys.contains(key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list() -> VirtualList<usize> {
+        VirtualList::new(10.0)
+    }
+
+    #[test]
+    fn windows_estimated_rows() {
+        let keys = (0..1000).collect::<Vec<_>>();
+        assert_eq!(list().window(&keys, 0.0, 100.0), 0..40);
+        assert_eq!(list().window(&keys, 1000.0, 1100.0), 70..140);
+    }
+
+    #[test]
+    fn windows_measured_rows() {
+        let keys = (0..1000).collect::<Vec<_>>();
+        let mut list = list();
+        assert!(list.measure(0, 500.0));
+        assert!(!list.measure(0, 500.0));
+        assert_eq!(list.window(&keys, 0.0, 100.0), 0..1);
+        assert_eq!(list.window(&keys, 1000.0, 1100.0), 21..91);
+    }
+
+    #[test]
+    fn windows_past_the_ends() {
+        let keys = (0..10).collect::<Vec<_>>();
+        assert_eq!(list().window(&keys, 0.0, 1000.0), 0..10);
+        assert_eq!(list().window(&keys, 1000.0, 2000.0), 10..10);
+        assert_eq!(list().window(&[], 0.0, 100.0), 0..0);
+    }
+
+    #[test]
+    fn spaces_unmounted_rows() {
+        let keys = (0..100).collect::<Vec<_>>();
+        assert_eq!(
+            list().slots(&keys, 10..12, &[]),
+            vec![
+                Slot::Spacer(100.0),
+                Slot::Row(10),
+                Slot::Row(11),
+                Slot::Spacer(880.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn mounts_pinned_rows() {
+        let keys = (0..100).collect::<Vec<_>>();
+        assert_eq!(
+            list().slots(&keys, 0..2, &[50, 99]),
+            vec![
+                Slot::Row(0),
+                Slot::Row(1),
+                Slot::Spacer(480.0),
+                Slot::Row(50),
+                Slot::Spacer(480.0),
+                Slot::Row(99),
+            ],
+        );
+    }
+
+    #[test]
+    fn forgets_removed_rows() {
+        let mut list = list();
+        list.measure(1, 50.0);
+        list.measure(2, 50.0);
+        list.retain(&[2]);
+        assert_eq!(list.height(&1), 10.0);
+        assert_eq!(list.height(&2), 50.0);
+    }
+}
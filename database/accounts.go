@@ -0,0 +1,259 @@
+package database
+
+import (
+	"database/sql"
+	"errors"
+	"fmt"
+	"net/mail"
+	"strings"
+
+	"github.com/johnietre/projects-tracker/auth"
+	sqlite3 "github.com/mattn/go-sqlite3"
+)
+
+// A user's data is kept in the tables named after their email (see
+// createTableSql), so changing the email renames them. Other users' data
+// refers to the user by email too (memberships, assignees, and comment and
+// activity authors), which is updated along with it.
+
+// ErrSameEmail represents changing the email to the one the user already has.
+var ErrSameEmail = userInputError("the new email is the same as the current one")
+
+// userTableSuffixes are what the names of the user's tables add to their email
+var userTableSuffixes = []string{
+	"", ":blockers", ":tags", ":changes", ":time_entries", ":comments", ":activity",
+}
+
+// userIndexSuffixes are what the names of the indexes on the user's tables add
+// to their email
+var userIndexSuffixes = []string{
+	":changes_rev", ":assignee", ":trash_root", ":comments_part", ":activity_part",
+}
+
+// ErrInvalidEmail represents an email that isn't a valid address.
+var ErrInvalidEmail = userInputError("invalid email")
+
+// validEmail reports whether the email is a plain address (e.g., without a
+// display name). Since the user's tables are named after their email (quoted
+// in brackets), it can't contain brackets either.
+func validEmail(email string) bool {
+	if strings.ContainsAny(email, "[]") {
+		return false
+	}
+	addr, err := mail.ParseAddress(email)
+	return err == nil && addr.Address == email
+}
+
+func validPassword(password string) bool {
+	l := len(password)
+	return l != 0 && l <= 72
+}
+
+// ChangePassword changes the user's password, which requires their current
+// one. Returns ErrInvalidCreds if the current password is wrong.
+func (db *DB) ChangePassword(email, currentPassword, newPassword string) error {
+	if !validPassword(newPassword) {
+		return userInputError("invalid password")
+	}
+	user, err := auth.NewUser(email, newPassword)
+	if err != nil {
+		return err
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	if _, err := db.lockedCheckPassword(email, currentPassword); err != nil {
+		return err
+	}
+	_, err = db.db.Exec(
+		`UPDATE users SET password_hash=? WHERE email=?`, user.PasswordHash, email,
+	)
+	if err != nil {
+		return err
+	}
+	// A reset link sent before is no longer needed
+	_, err = db.db.Exec(`DELETE FROM password_resets WHERE email=?`, email)
+	return err
+}
+
+// ChangeEmail changes the email the user logs in with (and everything of
+// theirs kept under it) to the new one, which requires their password.
+// Returns ErrUserExist if a user with the new email already exists.
+func (db *DB) ChangeEmail(email, password, newEmail string) (auth.User, error) {
+	if !validEmail(newEmail) {
+		return auth.User{}, ErrInvalidEmail
+	}
+	if newEmail == email {
+		return auth.User{}, ErrSameEmail
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	hash, err := db.lockedCheckPassword(email, password)
+	if err != nil {
+		return auth.User{}, err
+	}
+	// Everything's moved in one transaction so that a failure part way through
+	// leaves the user as they were
+	tx, err := db.db.Begin()
+	if err != nil {
+		return auth.User{}, err
+	}
+	defer tx.Rollback()
+	// The new user is added before the old one's removed so the memberships
+	// always refer to an existing user
+	_, err = tx.Exec(`INSERT INTO users VALUES (?, ?)`, newEmail, hash)
+	if err != nil {
+		if errors.Is(err, sqlite3.ErrConstraintUnique) {
+			err = ErrUserExist
+		}
+		return auth.User{}, err
+	}
+	for _, suffix := range userIndexSuffixes {
+		_, err := tx.Exec(fmt.Sprintf(`DROP INDEX IF EXISTS [%s%s]`, email, suffix))
+		if err != nil {
+			return auth.User{}, err
+		}
+	}
+	// Renaming the tables also updates the foreign keys referring to them
+	for _, suffix := range userTableSuffixes {
+		_, err := tx.Exec(
+			fmt.Sprintf(`ALTER TABLE [%s%[2]s] RENAME TO [%s%[2]s]`, email, suffix, newEmail),
+		)
+		if err != nil {
+			return auth.User{}, err
+		}
+	}
+	// Recreates the indexes under the new email
+	if _, err := tx.Exec(fmt.Sprintf(userTablesSql, newEmail)); err != nil {
+		return auth.User{}, err
+	}
+	for _, column := range []string{"owner", "member"} {
+		_, err := tx.Exec(
+			fmt.Sprintf(`UPDATE memberships SET %[1]s=? WHERE %[1]s=?`, column),
+			newEmail, email,
+		)
+		if err != nil {
+			return auth.User{}, err
+		}
+	}
+	if _, err := tx.Exec(`DELETE FROM users WHERE email=?`, email); err != nil {
+		return auth.User{}, err
+	}
+	emails, err := userEmails(tx)
+	if err != nil {
+		return auth.User{}, err
+	}
+	for _, owner := range emails {
+		if err := reassign(tx, owner, email, &newEmail); err != nil {
+			return auth.User{}, err
+		}
+		for _, suffix := range []string{":comments", ":activity"} {
+			_, err := tx.Exec(
+				fmt.Sprintf(`UPDATE [%s%s] SET author=? WHERE author=?`, owner, suffix),
+				newEmail, email,
+			)
+			if err != nil {
+				return auth.User{}, err
+			}
+		}
+	}
+	if err := tx.Commit(); err != nil {
+		return auth.User{}, err
+	}
+	return auth.User{Email: newEmail}, nil
+}
+
+// DeleteAccount permanently deletes the user along with all their parts, which
+// requires their password. Their projects stop being shared and they're
+// unassigned from other users' parts, but their comments on them are kept.
+func (db *DB) DeleteAccount(email, password string) error {
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	if _, err := db.lockedCheckPassword(email, password); err != nil {
+		return err
+	}
+	// Everything's deleted in one transaction so that a failure part way
+	// through doesn't leave the user half deleted
+	tx, err := db.db.Begin()
+	if err != nil {
+		return err
+	}
+	defer tx.Rollback()
+	// The parts table goes last since the others refer to it
+	for i := len(userTableSuffixes) - 1; i >= 0; i-- {
+		_, err := tx.Exec(
+			fmt.Sprintf(`DROP TABLE IF EXISTS [%s%s]`, email, userTableSuffixes[i]),
+		)
+		if err != nil {
+			return err
+		}
+	}
+	// Their memberships are deleted along with them
+	if _, err := tx.Exec(`DELETE FROM users WHERE email=?`, email); err != nil {
+		return err
+	}
+	emails, err := userEmails(tx)
+	if err != nil {
+		return err
+	}
+	for _, owner := range emails {
+		if err := reassign(tx, owner, email, nil); err != nil {
+			return err
+		}
+	}
+	return tx.Commit()
+}
+
+// lockedCheckPassword returns the user's password hash, or ErrInvalidCreds if
+// the password isn't theirs.
+func (db *DB) lockedCheckPassword(email, password string) (string, error) {
+	var hash string
+	err := db.db.QueryRow(`SELECT password_hash FROM users WHERE email=?`, email).Scan(&hash)
+	if err != nil {
+		if errors.Is(err, sql.ErrNoRows) {
+			err = ErrUserNotExist
+		}
+		return "", err
+	}
+	if !auth.CheckHash(password, hash) {
+		return "", ErrInvalidCreds
+	}
+	return hash, nil
+}
+
+// userEmails returns the emails of all the users.
+func userEmails(q querier) ([]string, error) {
+	rows, err := q.Query(`SELECT email FROM users`)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+	var emails []string
+	for rows.Next() {
+		var email string
+		if err := rows.Scan(&email); err != nil {
+			return nil, err
+		}
+		emails = append(emails, email)
+	}
+	return emails, rows.Err()
+}
+
+// reassign assigns the owner's parts assigned to one user to another (or
+// unassigns them if to is nil).
+func reassign(q querier, owner, from string, to *string) error {
+	ids, err := queryIDs(
+		q, fmt.Sprintf(`SELECT part_id FROM [%s] WHERE assignee=?`, owner), from,
+	)
+	if err != nil || len(ids) == 0 {
+		return err
+	}
+	_, err = q.Exec(
+		fmt.Sprintf(`UPDATE [%s] SET assignee=? WHERE assignee=?`, owner), to, from,
+	)
+	if err != nil {
+		return err
+	}
+	return recordChanges(q, owner, false, ids...)
+}
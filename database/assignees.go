@@ -0,0 +1,111 @@
+package database
+
+import (
+	"errors"
+	"fmt"
+	"sort"
+	"strconv"
+
+	"github.com/johnietre/projects-tracker/graph/model"
+)
+
+// ErrInvalidAssignee represents assigning a part to a user who can't see it.
+var ErrInvalidAssignee = newUserError("the assignee must have access to the part")
+
+// lockedCheckAssignee checks that the assignee can see the owner's part with
+// the given id (nil for a new project): the owner, or a member of the project
+// it's in.
+func (db *DB) lockedCheckAssignee(owner string, id *int64, assignee string) error {
+	if assignee == owner {
+		return nil
+	}
+	if id == nil {
+		return ErrInvalidAssignee
+	}
+	if _, err := db.lockedRole(owner, assignee, *id); err != nil {
+		if errors.Is(err, ErrPartNotExist) {
+			err = ErrInvalidAssignee
+		}
+		return err
+	}
+	return nil
+}
+
+// lockedUnassignMember unassigns the member from the parts of the owner's
+// project (e.g., once it's no longer shared with them).
+func (db *DB) lockedUnassignMember(owner string, projectID int64, member string) error {
+	rows, err := db.db.Query(
+		fmt.Sprintf(`SELECT part_id FROM [%s] WHERE assignee=? AND %s`, owner, subtreeWhere(owner)),
+		member, projectID,
+	)
+	if err != nil {
+		return err
+	}
+	var ids []int64
+	for rows.Next() {
+		var id int64
+		if err := rows.Scan(&id); err != nil {
+			rows.Close()
+			return err
+		}
+		ids = append(ids, id)
+	}
+	rows.Close()
+	if err := rows.Err(); err != nil || len(ids) == 0 {
+		return err
+	}
+	_, err = db.db.Exec(
+		fmt.Sprintf(`UPDATE [%s] SET assignee=NULL WHERE assignee=? AND %s`, owner, subtreeWhere(owner)),
+		member, projectID,
+	)
+	if err != nil {
+		return err
+	}
+	return db.lockedRecordChanges(owner, false, ids...)
+}
+
+// GetAssignedParts gets the parts assigned to the user, both their own and
+// those in projects shared with them, ordered by deadline (parts without one
+// last).
+func (db *DB) GetAssignedParts(email string) ([]*model.AssignedPart, error) {
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	parts, err := db.lockedQueryParts(email, `WHERE assignee=?`, email)
+	if err != nil {
+		return nil, err
+	}
+	assigned := make([]*model.AssignedPart, 0, len(parts))
+	for _, part := range parts {
+		assigned = append(assigned, &model.AssignedPart{Owner: email, Part: part})
+	}
+	memberships, err := db.lockedQueryMemberships(`member=?`, email)
+	if err != nil {
+		return nil, err
+	}
+	for _, m := range memberships {
+		projectID, err := strToEpoch(m.ProjectID)
+		if err != nil {
+			return nil, err
+		}
+		parts, err := db.lockedQueryParts(
+			m.Owner, `WHERE assignee=? AND `+subtreeWhere(m.Owner), email, projectID,
+		)
+		if err != nil {
+			return nil, err
+		}
+		for _, part := range parts {
+			assigned = append(assigned, &model.AssignedPart{Owner: m.Owner, Part: part})
+		}
+	}
+	deadline := func(part *model.Part) int64 {
+		if part.Deadline == nil {
+			return 1<<63 - 1
+		}
+		t, _ := strconv.ParseInt(*part.Deadline, 10, 64)
+		return t
+	}
+	sort.SliceStable(assigned, func(i, j int) bool {
+		return deadline(assigned[i].Part) < deadline(assigned[j].Part)
+	})
+	return assigned, nil
+}
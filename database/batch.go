@@ -0,0 +1,61 @@
+package database
+
+import (
+	"github.com/johnietre/projects-tracker/graph/model"
+)
+
+// BatchResult is the result of a batch operation on a single part
+type BatchResult struct {
+	ID string
+	// Part is the updated part (nil if the part was deleted or on error)
+	Part *model.Part
+	// Err is the error for the part (nil on success)
+	Err error
+}
+
+// BatchUpdateParts applies the same changes to each of the parts with the
+// given ids. A failure for one part doesn't stop the others from being
+// updated, so the returned slice contains a result for each id (in order).
+// The returned error is only non-nil if the changes are invalid.
+func (db *DB) BatchUpdateParts(email string, ids []string, changes map[string]any) ([]BatchResult, error) {
+	upd, err := parsePartChanges(changes)
+	if err != nil {
+		return nil, err
+	}
+
+	results := make([]BatchResult, len(ids))
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	for i, idStr := range ids {
+		results[i].ID = idStr
+		id, err := strToEpoch(idStr)
+		if err != nil {
+			results[i].Err = ErrInvalidID
+			continue
+		}
+		if len(changes) == 0 {
+			results[i].Part, results[i].Err = db.lockedGetPart(email, id)
+		} else {
+			results[i].Part, results[i].Err = db.lockedUpdatePart(email, id, upd)
+		}
+	}
+	return results, nil
+}
+
+// BatchDeleteParts moves each of the parts with the given ids (and all their
+// children) to the trash, returning a result for each id (in order).
+func (db *DB) BatchDeleteParts(email string, ids []string) []BatchResult {
+	results := make([]BatchResult, len(ids))
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	for i, idStr := range ids {
+		results[i].ID = idStr
+		id, err := strToEpoch(idStr)
+		if err != nil {
+			results[i].Err = ErrInvalidID
+			continue
+		}
+		results[i].Err = db.lockedTrashPart(email, id)
+	}
+	return results
+}
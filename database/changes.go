@@ -0,0 +1,133 @@
+package database
+
+import (
+	"fmt"
+	"strconv"
+
+	"github.com/johnietre/projects-tracker/graph/model"
+)
+
+// ErrInvalidCursor represents a malformed changes cursor.
+var ErrInvalidCursor = userInputError("invalid cursor")
+
+// Each change to a part is recorded in the [email:changes] table with the
+// next revision number (replacing the part's previous change), and deleted
+// parts are kept there as tombstones. A cursor is the last revision a client
+// has seen.
+
+// lockedRecordChanges records a change to each of the parts with the given
+// ids.
+func (db *DB) lockedRecordChanges(email string, deleted bool, ids ...int64) error {
+	return recordChanges(db.db, email, deleted, ids...)
+}
+
+// recordChanges is lockedRecordChanges, using the querier (e.g., within a
+// transaction).
+func recordChanges(q querier, email string, deleted bool, ids ...int64) error {
+	for _, id := range ids {
+		_, err := q.Exec(
+			fmt.Sprintf(
+				`INSERT OR REPLACE INTO [%[1]s:changes](part_id,rev,deleted)
+        VALUES (?,(SELECT COALESCE(MAX(rev),0)+1 FROM [%[1]s:changes]),?)`,
+				email,
+			),
+			id, deleted,
+		)
+		if err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// lockedSubtreeIDs returns the id of the part and all of its descendants.
+func (db *DB) lockedSubtreeIDs(email string, id int64) ([]int64, error) {
+	rows, err := db.db.Query(
+		fmt.Sprintf(`WITH RECURSIVE subtree(id) AS (
+      SELECT ?
+      UNION
+      SELECT p.part_id FROM [%s] p JOIN subtree ON p.parent_id=subtree.id
+    ) SELECT id FROM subtree`, email),
+		id,
+	)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+	var ids []int64
+	for rows.Next() {
+		var id int64
+		if err := rows.Scan(&id); err != nil {
+			return nil, err
+		}
+		ids = append(ids, id)
+	}
+	return ids, rows.Err()
+}
+
+func (db *DB) lockedCurrentRev(email string) (int64, error) {
+	var rev int64
+	err := db.db.QueryRow(
+		fmt.Sprintf(`SELECT COALESCE(MAX(rev),0) FROM [%s:changes]`, email),
+	).Scan(&rev)
+	if err != nil && errIsNoTable(err) {
+		err = ErrUserNotExist
+	}
+	return rev, err
+}
+
+// GetPartsSince gets the changes to the user's parts after the given cursor.
+// If the cursor is nil (or from after the latest change, e.g., from a
+// different database), all the parts are returned and Full is set.
+func (db *DB) GetPartsSince(email string, cursor *string) (*model.PartsDelta, error) {
+	var since int64
+	if cursor != nil {
+		var err error
+		if since, err = strToEpoch(*cursor); err != nil {
+			return nil, ErrInvalidCursor
+		}
+	}
+
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	rev, err := db.lockedCurrentRev(email)
+	if err != nil {
+		return nil, err
+	}
+	delta := &model.PartsDelta{
+		Deleted: []string{},
+		Cursor:  strconv.FormatInt(rev, 10),
+	}
+	if cursor == nil || since > rev {
+		delta.Full = true
+		delta.Parts, err = db.lockedQueryParts(email, "")
+		return delta, err
+	}
+	delta.Parts, err = db.lockedQueryParts(
+		email,
+		fmt.Sprintf(
+			`WHERE part_id IN (SELECT part_id FROM [%s:changes] WHERE rev>? AND NOT deleted)`,
+			email,
+		),
+		since,
+	)
+	if err != nil {
+		return nil, err
+	}
+	rows, err := db.db.Query(
+		fmt.Sprintf(`SELECT part_id FROM [%s:changes] WHERE rev>? AND deleted`, email),
+		since,
+	)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+	for rows.Next() {
+		var id int64
+		if err := rows.Scan(&id); err != nil {
+			return nil, err
+		}
+		delta.Deleted = append(delta.Deleted, strconv.FormatInt(id, 10))
+	}
+	return delta, rows.Err()
+}
@@ -0,0 +1,151 @@
+package database
+
+import (
+	"fmt"
+	"strconv"
+	"strings"
+
+	"github.com/johnietre/projects-tracker/graph/model"
+)
+
+const (
+	defaultPageSize = 100
+	maxPageSize     = 500
+)
+
+// ErrInvalidPageSize represents a non-positive number of parts per page.
+var ErrInvalidPageSize = userInputError("first must be positive")
+
+// GetChildren gets a page of the children of the part with the given id (or
+// of the user's projects if it's nil), ordered by id. first is the max number
+// of parts returned (defaulting to defaultPageSize) and after is the end
+// cursor of the previous page, if any.
+func (db *DB) GetChildren(email string, parentIDStr *string, first *int, after *string) (*model.PartConnection, error) {
+	parentID, err := getTimePtr(parentIDStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+	limit := defaultPageSize
+	if first != nil {
+		if *first <= 0 {
+			return nil, ErrInvalidPageSize
+		}
+		limit = *first
+		if limit > maxPageSize {
+			limit = maxPageSize
+		}
+	}
+	// Cursors are the id of the last part on the page
+	var afterID int64
+	if after != nil {
+		if afterID, err = strToEpoch(*after); err != nil {
+			return nil, ErrInvalidCursor
+		}
+	}
+
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	rev, err := db.lockedCurrentRev(email)
+	if err != nil {
+		return nil, err
+	}
+	where, args := `WHERE parent_id IS NULL`, []any{}
+	if parentID != nil {
+		where, args = `WHERE parent_id=?`, append(args, *parentID)
+	}
+	// Get an extra part to know whether there's a next page
+	parts, err := db.lockedQueryParts(
+		email,
+		where+` AND part_id>? ORDER BY part_id LIMIT ?`,
+		append(args, afterID, limit+1)...,
+	)
+	if err != nil {
+		return nil, err
+	}
+	conn := &model.PartConnection{
+		Edges:      make([]*model.PartEdge, 0, limit),
+		PageInfo:   &model.PageInfo{HasNextPage: len(parts) > limit},
+		SyncCursor: strconv.FormatInt(rev, 10),
+	}
+	if len(parts) > limit {
+		parts = parts[:limit]
+	}
+	for _, part := range parts {
+		conn.Edges = append(conn.Edges, &model.PartEdge{Cursor: part.ID, Node: part})
+	}
+	if len(parts) != 0 {
+		conn.PageInfo.EndCursor = &parts[len(parts)-1].ID
+	}
+	return conn, nil
+}
+
+// GetPartTree gets the part with the given id along with its ancestors and all
+// its descendants, so that the part can be shown (or its subtree used) without
+// fetching the pages of children in between. Returns ErrPartNotExist if the
+// part doesn't exist or is in the trash.
+func (db *DB) GetPartTree(email, idStr string) ([]*model.Part, error) {
+	id, err := strToEpoch(idStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	parts, err := db.lockedQueryParts(
+		email,
+		`WHERE `+ancestorsWhere(email)+` OR `+subtreeWhere(email)+` ORDER BY part_id`,
+		id, id,
+	)
+	if err != nil {
+		return nil, err
+	}
+	for _, part := range parts {
+		if part.ID == strconv.FormatInt(id, 10) {
+			return parts, nil
+		}
+	}
+	return nil, ErrPartNotExist
+}
+
+// GetPartsByID gets the parts with the given ids along with their ancestors,
+// so that parts elsewhere in the tree (e.g., blockers) can be shown without
+// fetching the whole tree. Parts that don't exist or are in the trash are left
+// out.
+func (db *DB) GetPartsByID(email string, idStrs []string) ([]*model.Part, error) {
+	if len(idStrs) == 0 {
+		return []*model.Part{}, nil
+	}
+	args := make([]any, len(idStrs))
+	for i, idStr := range idStrs {
+		id, err := strToEpoch(idStr)
+		if err != nil {
+			return nil, ErrInvalidID
+		}
+		args[i] = id
+	}
+	placeholders := strings.TrimSuffix(strings.Repeat("?,", len(args)), ",")
+
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	return db.lockedQueryParts(
+		email,
+		fmt.Sprintf(`WHERE part_id IN (WITH RECURSIVE ancestors(id) AS (
+      SELECT part_id FROM [%s] WHERE part_id IN (%s)
+      UNION
+      SELECT a.parent_id FROM [%s] a JOIN ancestors ON a.part_id=ancestors.id
+      WHERE a.parent_id IS NOT NULL
+    ) SELECT id FROM ancestors) ORDER BY part_id`, email, placeholders, email),
+		args...,
+	)
+}
+
+// ancestorsWhere returns a condition matching the user's part whose id is its
+// one argument and the part's ancestors.
+func ancestorsWhere(email string) string {
+	return fmt.Sprintf(`part_id IN (WITH RECURSIVE ancestors(id) AS (
+      SELECT ?
+      UNION
+      SELECT a.parent_id FROM [%s] a JOIN ancestors ON a.part_id=ancestors.id
+      WHERE a.parent_id IS NOT NULL
+    ) SELECT id FROM ancestors)`, email)
+}
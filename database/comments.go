@@ -0,0 +1,254 @@
+package database
+
+import (
+	"database/sql"
+	"errors"
+	"fmt"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/johnietre/projects-tracker/graph/model"
+	sqlite3 "github.com/mattn/go-sqlite3"
+)
+
+var (
+	// ErrCommentNotExist represents a non-existent comment (or one by another
+	// user when deleting).
+	ErrCommentNotExist = newUserError("comment does not exist")
+	// ErrEmptyComment represents a comment without any text.
+	ErrEmptyComment = userInputError("comment cannot be empty")
+	// ErrInvalidActivity represents an activity entry with an unknown kind or
+	// without a message.
+	ErrInvalidActivity = userInputError("invalid activity entry")
+)
+
+// AddComment adds the author's comment to the owner's part with the given id,
+// replying to the part's comment with the given id if replyToStr isn't nil.
+func (db *DB) AddComment(owner, author, partIDStr string, replyToStr *string, body string) (*model.Comment, error) {
+	partID, err := strToEpoch(partIDStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+	replyTo, err := getTimePtr(replyToStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+	if strings.TrimSpace(body) == "" {
+		return nil, ErrEmptyComment
+	}
+	createdAt := time.Now().Unix()
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	if replyTo != nil {
+		// Replies must be to comments on the same part
+		var replyPartID int64
+		err := db.db.QueryRow(
+			fmt.Sprintf(`SELECT part_id FROM [%s:comments] WHERE comment_id=?`, owner),
+			*replyTo,
+		).Scan(&replyPartID)
+		if err != nil {
+			if errors.Is(err, sql.ErrNoRows) {
+				err = ErrCommentNotExist
+			}
+			return nil, err
+		}
+		if replyPartID != partID {
+			return nil, ErrCommentNotExist
+		}
+	}
+	res, err := db.db.Exec(
+		fmt.Sprintf(
+			`INSERT INTO [%s:comments](part_id,reply_to,author,body,created_at) VALUES (?,?,?,?,?)`,
+			owner,
+		),
+		partID, replyTo, author, body, createdAt,
+	)
+	if err != nil {
+		if errors.Is(err, sqlite3.ErrConstraintForeignKey) {
+			err = ErrPartNotExist
+		} else if errIsNoTable(err) {
+			err = ErrUserNotExist
+		}
+		return nil, err
+	}
+	id, err := res.LastInsertId()
+	if err != nil {
+		return nil, err
+	}
+	return &model.Comment{
+		ID:        strconv.FormatInt(id, 10),
+		PartID:    partIDStr,
+		ReplyTo:   getStrPtr(replyTo),
+		Author:    author,
+		Body:      body,
+		CreatedAt: strconv.FormatInt(createdAt, 10),
+	}, nil
+}
+
+// DeleteComment deletes the author's comment with the given id (and the
+// replies to it) from the owner's part with the given id.
+func (db *DB) DeleteComment(owner, author, partIDStr, idStr string) error {
+	partID, err := strToEpoch(partIDStr)
+	if err != nil {
+		return ErrInvalidID
+	}
+	id, err := strToEpoch(idStr)
+	if err != nil {
+		return ErrInvalidID
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	res, err := db.db.Exec(
+		fmt.Sprintf(
+			`DELETE FROM [%s:comments] WHERE comment_id=? AND part_id=? AND author=?`,
+			owner,
+		),
+		id, partID, author,
+	)
+	if err != nil {
+		if errIsNoTable(err) {
+			err = ErrUserNotExist
+		}
+		return err
+	}
+	if numRows, err := res.RowsAffected(); err != nil {
+		return err
+	} else if numRows == 0 {
+		return ErrCommentNotExist
+	}
+	return nil
+}
+
+// GetComments gets the comments on the owner's part with the given id, oldest
+// first.
+func (db *DB) GetComments(owner, partIDStr string) ([]*model.Comment, error) {
+	partID, err := strToEpoch(partIDStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	rows, err := db.db.Query(
+		fmt.Sprintf(
+			`SELECT comment_id,reply_to,author,body,created_at FROM [%s:comments]
+      WHERE part_id=? ORDER BY comment_id`,
+			owner,
+		),
+		partID,
+	)
+	if err != nil {
+		if errIsNoTable(err) {
+			err = ErrUserNotExist
+		}
+		return nil, err
+	}
+	defer rows.Close()
+	comments := []*model.Comment{}
+	for rows.Next() {
+		var id, createdAt int64
+		var replyTo *int64
+		comment := &model.Comment{PartID: partIDStr}
+		err := rows.Scan(&id, &replyTo, &comment.Author, &comment.Body, &createdAt)
+		if err != nil {
+			return comments, err
+		}
+		comment.ID = strconv.FormatInt(id, 10)
+		comment.ReplyTo = getStrPtr(replyTo)
+		comment.CreatedAt = strconv.FormatInt(createdAt, 10)
+		comments = append(comments, comment)
+	}
+	return comments, rows.Err()
+}
+
+// RecordActivity appends the author's entries to the activity log of the
+// owner's part with the given id. The log can't be changed otherwise.
+func (db *DB) RecordActivity(owner, author, partIDStr string, entries []*model.ActivityInput) ([]*model.Activity, error) {
+	partID, err := strToEpoch(partIDStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+	for _, entry := range entries {
+		if !entry.Kind.IsValid() || strings.TrimSpace(entry.Message) == "" {
+			return nil, ErrInvalidActivity
+		}
+	}
+	createdAt := time.Now().Unix()
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	activity := make([]*model.Activity, 0, len(entries))
+	for _, entry := range entries {
+		res, err := db.db.Exec(
+			fmt.Sprintf(
+				`INSERT INTO [%s:activity](part_id,author,kind,message,created_at) VALUES (?,?,?,?,?)`,
+				owner,
+			),
+			partID, author, entry.Kind.String(), entry.Message, createdAt,
+		)
+		if err != nil {
+			if errors.Is(err, sqlite3.ErrConstraintForeignKey) {
+				err = ErrPartNotExist
+			} else if errIsNoTable(err) {
+				err = ErrUserNotExist
+			}
+			return activity, err
+		}
+		id, err := res.LastInsertId()
+		if err != nil {
+			return activity, err
+		}
+		activity = append(activity, &model.Activity{
+			ID:        strconv.FormatInt(id, 10),
+			PartID:    partIDStr,
+			Author:    author,
+			Kind:      entry.Kind,
+			Message:   entry.Message,
+			CreatedAt: strconv.FormatInt(createdAt, 10),
+		})
+	}
+	return activity, nil
+}
+
+// GetActivity gets the activity log of the owner's part with the given id,
+// oldest first.
+func (db *DB) GetActivity(owner, partIDStr string) ([]*model.Activity, error) {
+	partID, err := strToEpoch(partIDStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	rows, err := db.db.Query(
+		fmt.Sprintf(
+			`SELECT activity_id,author,kind,message,created_at FROM [%s:activity]
+      WHERE part_id=? ORDER BY activity_id`,
+			owner,
+		),
+		partID,
+	)
+	if err != nil {
+		if errIsNoTable(err) {
+			err = ErrUserNotExist
+		}
+		return nil, err
+	}
+	defer rows.Close()
+	activity := []*model.Activity{}
+	for rows.Next() {
+		var id, createdAt int64
+		entry := &model.Activity{PartID: partIDStr}
+		err := rows.Scan(&id, &entry.Author, &entry.Kind, &entry.Message, &createdAt)
+		if err != nil {
+			return activity, err
+		}
+		entry.ID = strconv.FormatInt(id, 10)
+		entry.CreatedAt = strconv.FormatInt(createdAt, 10)
+		activity = append(activity, entry)
+	}
+	return activity, rows.Err()
+}
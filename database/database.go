@@ -56,6 +56,16 @@ var (
 	ErrInvalidID = userInputError("invalid ID")
 	// ErrInvalidTime
 	ErrInvalidTime = userInputError("invalid time format")
+	// ErrBlockerCycle represents a blocker that would create a dependency cycle.
+	ErrBlockerCycle = userInputError("blocker would create a cycle")
+	// ErrParentCycle represents moving a part under itself or one of its
+	// descendants.
+	ErrParentCycle = userInputError("part cannot be moved under itself")
+	// ErrTimeEntryNotExist represents a non-existent time entry.
+	ErrTimeEntryNotExist = newUserError("time entry does not exist")
+	// ErrTimerRunning represents trying to start a timer for a part that
+	// already has a running timer.
+	ErrTimerRunning = newUserError("timer already running")
 	// ErrPartNotExist represents a non-existent part.
 	ErrPartNotExist = newUserError("part does not exist")
 	// ErrUserExist represents an trying to create an existing user.
@@ -70,6 +80,13 @@ type DB struct {
 	mtx sync.RWMutex
 }
 
+// querier runs queries on the database, or in a transaction.
+type querier interface {
+	Exec(query string, args ...any) (sql.Result, error)
+	Query(query string, args ...any) (*sql.Rows, error)
+	QueryRow(query string, args ...any) *sql.Row
+}
+
 // NewDB opens a new SQLite database from the given path
 func NewDB(path string) (*DB, error) {
 	db, err := sql.Open("sqlite3", "file:"+path+"?_fk=ON")
@@ -84,9 +101,74 @@ func NewDB(path string) (*DB, error) {
 		db.Close()
 		return nil, err
 	}
+	if _, err = db.Exec(membershipsTableSql); err != nil {
+		db.Close()
+		return nil, err
+	}
+	if _, err = db.Exec(passwordResetsTableSql); err != nil {
+		db.Close()
+		return nil, err
+	}
+	if err = migrateUserTables(db); err != nil {
+		db.Close()
+		return nil, err
+	}
 	return &DB{db: db}, nil
 }
 
+// migrateUserTables creates any per-user tables missing for existing users
+func migrateUserTables(db *sql.DB) error {
+	rows, err := db.Query(`SELECT email FROM users`)
+	if err != nil {
+		return err
+	}
+	var emails []string
+	for rows.Next() {
+		var email string
+		if err := rows.Scan(&email); err != nil {
+			rows.Close()
+			return err
+		}
+		emails = append(emails, email)
+	}
+	rows.Close()
+	for _, email := range emails {
+		for _, column := range addedPartColumns {
+			if err := migrateColumn(db, email, column); err != nil {
+				return err
+			}
+		}
+		if _, err := db.Exec(fmt.Sprintf(userTablesSql, email)); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// addedPartColumns are the columns added to user parts tables since they were
+// first created
+var addedPartColumns = []string{
+	"assignee TEXT",
+	"trashed_at INTEGER",
+	"trash_root INTEGER",
+	"archived_at INTEGER",
+}
+
+// migrateColumn adds the column (a name followed by its type) to the user's
+// parts table if it's missing.
+func migrateColumn(db *sql.DB, email, column string) error {
+	name := strings.Fields(column)[0]
+	var count int
+	err := db.QueryRow(
+		`SELECT COUNT(*) FROM pragma_table_info(?) WHERE name=?`, email, name,
+	).Scan(&count)
+	if err != nil || count != 0 {
+		return err
+	}
+	_, err = db.Exec(fmt.Sprintf(`ALTER TABLE [%s] ADD COLUMN %s`, email, column))
+	return err
+}
+
 // Close closes the database connection
 func (db *DB) Close() error {
 	db.mtx.Lock()
@@ -103,19 +185,75 @@ CREATE TABLE [%[1]s] (
   deadline TEXT,
   completed_at TEXT,
   parent_id INTEGER,
+  assignee TEXT,
+  trashed_at INTEGER,
+  trash_root INTEGER,
+  archived_at INTEGER,
   FOREIGN KEY(parent_id) REFERENCES [%[1]s](part_id) ON DELETE CASCADE
 );
+` + userTablesSql
+
+// userTablesSql creates the auxiliary tables for a user's parts table
+const userTablesSql = `
+CREATE TABLE IF NOT EXISTS [%[1]s:blockers] (
+  part_id INTEGER NOT NULL,
+  blocker_id INTEGER NOT NULL,
+  PRIMARY KEY(part_id, blocker_id),
+  FOREIGN KEY(part_id) REFERENCES [%[1]s](part_id) ON DELETE CASCADE,
+  FOREIGN KEY(blocker_id) REFERENCES [%[1]s](part_id) ON DELETE CASCADE
+);
+CREATE TABLE IF NOT EXISTS [%[1]s:tags] (
+  part_id INTEGER NOT NULL,
+  tag TEXT NOT NULL,
+  PRIMARY KEY(part_id, tag),
+  FOREIGN KEY(part_id) REFERENCES [%[1]s](part_id) ON DELETE CASCADE
+);
+CREATE TABLE IF NOT EXISTS [%[1]s:changes] (
+  part_id INTEGER PRIMARY KEY,
+  rev INTEGER NOT NULL,
+  deleted INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS [%[1]s:changes_rev] ON [%[1]s:changes](rev);
+CREATE INDEX IF NOT EXISTS [%[1]s:assignee] ON [%[1]s](assignee);
+CREATE INDEX IF NOT EXISTS [%[1]s:trash_root] ON [%[1]s](trash_root);
+CREATE TABLE IF NOT EXISTS [%[1]s:time_entries] (
+  entry_id INTEGER PRIMARY KEY,
+  part_id INTEGER NOT NULL,
+  started_at INTEGER NOT NULL,
+  ended_at INTEGER,
+  FOREIGN KEY(part_id) REFERENCES [%[1]s](part_id) ON DELETE CASCADE
+);
+CREATE TABLE IF NOT EXISTS [%[1]s:comments] (
+  comment_id INTEGER PRIMARY KEY,
+  part_id INTEGER NOT NULL,
+  reply_to INTEGER,
+  author TEXT NOT NULL,
+  body TEXT NOT NULL,
+  created_at INTEGER NOT NULL,
+  FOREIGN KEY(part_id) REFERENCES [%[1]s](part_id) ON DELETE CASCADE,
+  FOREIGN KEY(reply_to) REFERENCES [%[1]s:comments](comment_id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS [%[1]s:comments_part] ON [%[1]s:comments](part_id);
+CREATE TABLE IF NOT EXISTS [%[1]s:activity] (
+  activity_id INTEGER PRIMARY KEY,
+  part_id INTEGER NOT NULL,
+  author TEXT NOT NULL,
+  kind TEXT NOT NULL,
+  message TEXT NOT NULL,
+  created_at INTEGER NOT NULL,
+  FOREIGN KEY(part_id) REFERENCES [%[1]s](part_id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS [%[1]s:activity_part] ON [%[1]s:activity](part_id);
 `
 
 // CreateUser creates a new user with the given email/password input.
 // Returns ErrUserExist if a user with the given email already exists.
 // If the error is not that, it means there was an error creating the user.
 func (db *DB) CreateUser(input model.CreateUserInput) (auth.User, error) {
-	// TODO: Check email
-	if input.Email == "" {
-		return auth.User{}, userInputError("invalid email")
+	if !validEmail(input.Email) {
+		return auth.User{}, ErrInvalidEmail
 	}
-	if l := len(input.Password); l == 0 || l > 72 {
+	if !validPassword(input.Password) {
 		return auth.User{}, userInputError("invalid password")
 	}
 	user, err := auth.NewUser(input.Email, input.Password)
@@ -172,20 +310,37 @@ func (db *DB) CreatePart(email string, input model.CreatePartInput) (*model.Part
 		Deadline:    input.Deadline,
 		CompletedAt: input.CompletedAt,
 		ParentID:    input.ParentID,
+		Assignee:    input.Assignee,
+		BlockedBy:   []string{},
 	}
 	dbPart, err := DBPartFromPart(part)
 	if err != nil {
 		return nil, err
 	}
+	if part.Tags, err = normalizeTags(input.Tags); err != nil {
+		return nil, err
+	}
 
 	db.mtx.Lock()
 	defer db.mtx.Unlock()
+	if dbPart.ParentID != nil {
+		if err := db.lockedCheckNotTrashed(email, *dbPart.ParentID); err != nil {
+			return nil, err
+		}
+	}
+	if dbPart.Assignee != nil {
+		if err := db.lockedCheckAssignee(email, dbPart.ParentID, *dbPart.Assignee); err != nil {
+			return nil, err
+		}
+	}
 	res, err := db.db.Exec(
 		fmt.Sprintf(
-			`INSERT INTO [%s](name,description,deadline,completed_at,parent_id) VALUES (?,?,?,?,?)`,
+			`INSERT INTO [%s](name,description,deadline,completed_at,parent_id,assignee)
+      VALUES (?,?,?,?,?,?)`,
 			email,
 		),
 		dbPart.Name, dbPart.Description, dbPart.Deadline, dbPart.CompletedAt, dbPart.ParentID,
+		dbPart.Assignee,
 	)
 	if err != nil {
 		if errors.Is(err, sqlite3.ErrConstraintUnique) {
@@ -199,6 +354,15 @@ func (db *DB) CreatePart(email string, input model.CreatePartInput) (*model.Part
 	id, err := res.LastInsertId()
 	if err == nil {
 		part.ID = strconv.FormatInt(id, 10)
+		err = db.lockedSetTags(email, id, part.Tags)
+	}
+	if err == nil {
+		// The parent's child count changed too
+		ids := []int64{id}
+		if dbPart.ParentID != nil {
+			ids = append(ids, *dbPart.ParentID)
+		}
+		err = db.lockedRecordChanges(email, false, ids...)
 	}
 	// TODO: do or don't return part on error?
 	return part, err
@@ -214,67 +378,199 @@ func (db *DB) UpdatePart(email string, idStr string, changes map[string]any) (*m
 	if err != nil {
 		return nil, ErrPartNotExist
 	}
-  fields, vals := make([]string, 0, len(changes)), make([]any, 0, len(changes))
+	upd, err := parsePartChanges(changes)
+	if err != nil {
+		return nil, err
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	return db.lockedUpdatePart(email, id, upd)
+}
+
+// partUpdate is a parsed (validated) map of changes to a part
+type partUpdate struct {
+	fields []string
+	vals   []any
+	// parentChanged is whether the parent_id is being changed (to parentID)
+	parentChanged bool
+	parentID      *int64
+	// tagsChanged is whether the tags are being replaced (with tags)
+	tagsChanged bool
+	tags        []string
+	// assignee is the new assignee, if it's being changed to one
+	assignee *string
+}
+
+func parsePartChanges(changes map[string]any) (partUpdate, error) {
+	upd := partUpdate{
+		fields: make([]string, 0, len(changes)),
+		vals:   make([]any, 0, len(changes)),
+	}
 	for fieldName, iVal := range changes {
 		valStr, ok := iVal.(string)
 		if !ok {
-			return nil, userInputError("malformed changes input")
+			return upd, userInputError("malformed changes input")
 		}
 		var val any
 		switch fieldName {
 		case "name":
 			if strings.TrimSpace(valStr) == "" {
-				return nil, userInputError("must provide a name if changing")
+				return upd, userInputError("must provide a name if changing")
 			}
-			val, fields = valStr, append(fields, "name=?")
+			val, upd.fields = valStr, append(upd.fields, "name=?")
 		case "description":
 			if valStr == "" {
 				val = (*string)(nil)
 			} else {
 				val = &valStr
 			}
-      fields = append(fields, "description=?")
+			upd.fields = append(upd.fields, "description=?")
 		case "deadline":
 			ptr, err := getTimePtr(&valStr)
-      if err != nil {
-        return nil, ErrInvalidTime
+			if err != nil {
+				return upd, ErrInvalidTime
 			}
-			val, fields = ptr, append(fields, "deadline=?")
+			val, upd.fields = ptr, append(upd.fields, "deadline=?")
 		case "completed_at":
 			ptr, err := getTimePtr(&valStr)
 			if err != nil {
-				return nil, ErrInvalidTime
+				return upd, ErrInvalidTime
+			}
+			val, upd.fields = ptr, append(upd.fields, "completed_at=?")
+			if ptr == nil {
+				// Only completed projects stay archived
+				upd.fields = append(upd.fields, "archived_at=NULL")
+			}
+		case "parent_id":
+			// An empty string makes the part a project
+			ptr, err := getTimePtr(&valStr)
+			if err != nil {
+				return upd, ErrInvalidID
 			}
-			val, fields = ptr, append(fields, "completed_at=?")
+			upd.parentChanged, upd.parentID = true, ptr
+			val, upd.fields = ptr, append(upd.fields, "parent_id=?")
+			if ptr != nil {
+				// Only projects can be archived
+				upd.fields = append(upd.fields, "archived_at=NULL")
+			}
+		case "tags":
+			// The tags are separated by whitespace and stored in their own table
+			tags, err := normalizeTags(strings.Fields(valStr))
+			if err != nil {
+				return upd, err
+			}
+			upd.tagsChanged, upd.tags = true, tags
+			continue
+		case "assignee":
+			// An empty string unassigns the part
+			if valStr == "" {
+				val = (*string)(nil)
+			} else {
+				val, upd.assignee = &valStr, &valStr
+			}
+			upd.fields = append(upd.fields, "assignee=?")
 		default:
-			return nil, userInputError(fmt.Sprintf("invalid field: %s", fieldName))
+			return upd, userInputError(fmt.Sprintf("invalid field: %s", fieldName))
 		}
-		vals = append(vals, val)
+		upd.vals = append(upd.vals, val)
 	}
-  setStr := strings.Join(fields, ",")
+	return upd, nil
+}
 
-	db.mtx.Lock()
-	defer db.mtx.Unlock()
-	res, err := db.db.Exec(
-		fmt.Sprintf(`UPDATE [%s] SET %s WHERE part_id=%d`, email, setStr, id),
-		vals...,
-	)
-	if err != nil {
-		if errIsNoTable(err) {
-			err = ErrUserNotExist
+func (db *DB) lockedUpdatePart(email string, id int64, upd partUpdate) (*model.Part, error) {
+	if upd.parentChanged && upd.parentID != nil {
+		if err := db.lockedCheckNotTrashed(email, *upd.parentID); err != nil {
+			return nil, err
+		}
+		// Check whether the new parent is the part or one of its descendants
+		row := db.db.QueryRow(
+			fmt.Sprintf(`WITH RECURSIVE ancestors(id) AS (
+        SELECT ?
+        UNION
+        SELECT p.parent_id FROM [%s] p JOIN ancestors ON p.part_id=ancestors.id
+        WHERE p.parent_id IS NOT NULL
+      ) SELECT COUNT(*) FROM ancestors WHERE id=?`, email),
+			*upd.parentID, id,
+		)
+		var count int
+		if err := row.Scan(&count); err != nil {
+			if errIsNoTable(err) {
+				err = ErrUserNotExist
+			}
+			return nil, err
+		}
+		if count != 0 {
+			return nil, ErrParentCycle
+		}
+	}
+	if upd.assignee != nil {
+		// The assignee needs access to where the part will be
+		refID := &id
+		if upd.parentChanged && upd.parentID != nil {
+			refID = upd.parentID
+		}
+		if err := db.lockedCheckAssignee(email, refID, *upd.assignee); err != nil {
+			return nil, err
+		}
+	}
+	// Moving the part changes the child counts of its old and new parents
+	changed := []int64{id}
+	if upd.parentChanged {
+		oldParentID, err := db.lockedParentID(email, id)
+		if err != nil {
+			if errors.Is(err, sql.ErrNoRows) {
+				err = ErrPartNotExist
+			}
+			return nil, err
+		}
+		for _, pid := range []*int64{oldParentID, upd.parentID} {
+			if pid != nil {
+				changed = append(changed, *pid)
+			}
+		}
+	}
+	if len(upd.fields) != 0 {
+		setStr := strings.Join(upd.fields, ",")
+		res, err := db.db.Exec(
+			fmt.Sprintf(
+				`UPDATE [%s] SET %s WHERE part_id=%d AND trashed_at IS NULL`, email, setStr, id,
+			),
+			upd.vals...,
+		)
+		if err != nil {
+			if errIsNoTable(err) {
+				err = ErrUserNotExist
+			} else if errors.Is(err, sqlite3.ErrConstraintForeignKey) {
+				// The new parent doesn't exist
+				err = ErrPartNotExist
+			}
+			return nil, err
+		}
+		if numRows, err := res.RowsAffected(); err != nil {
+			return nil, err
+		} else if numRows == 0 {
+			return nil, ErrPartNotExist
+		}
+	} else if _, err := db.lockedGetPart(email, id); err != nil {
+		if errors.Is(err, sql.ErrNoRows) {
+			err = ErrPartNotExist
 		}
 		return nil, err
 	}
-	if numRows, err := res.RowsAffected(); err != nil {
+	if upd.tagsChanged {
+		if err := db.lockedSetTags(email, id, upd.tags); err != nil {
+			return nil, err
+		}
+	}
+	if err := db.lockedRecordChanges(email, false, changed...); err != nil {
 		return nil, err
-	} else if numRows == 0 {
-		return nil, ErrPartNotExist
 	}
 	// TODO: Don't query and just return a part with the diffs?
 	return db.lockedGetPart(email, id)
 }
 
-// DeletePart deletes a part and all its children
+// DeletePart moves a part and all its children to the trash
 func (db *DB) DeletePart(email, idStr string) error {
 	id, err := strToEpoch(idStr)
 	if err != nil {
@@ -283,9 +579,16 @@ func (db *DB) DeletePart(email, idStr string) error {
 
 	db.mtx.Lock()
 	defer db.mtx.Unlock()
-	// TODO: Get affected rows?
-	_, err = db.db.Exec(fmt.Sprintf(`DELETE FROM [%s] WHERE part_id=?`, email), id)
-	return err
+	return db.lockedTrashPart(email, id)
+}
+
+// lockedParentID returns the id of the part's parent (nil for projects).
+func (db *DB) lockedParentID(email string, id int64) (*int64, error) {
+	var parentID *int64
+	err := db.db.QueryRow(
+		fmt.Sprintf(`SELECT parent_id FROM [%s] WHERE part_id=?`, email), id,
+	).Scan(&parentID)
+	return parentID, err
 }
 
 // GetPart gets a single part with the given email and id.
@@ -303,16 +606,150 @@ func (db *DB) GetPart(email, idStr string) (*model.Part, error) {
 func (db *DB) lockedGetPart(email string, id int64) (*model.Part, error) {
 	row := db.db.QueryRow(
 		fmt.Sprintf(
-			`SELECT name,description,deadline,completed_at,parent_id FROM [%s] WHERE part_id=%d`,
+			`SELECT name,description,deadline,completed_at,parent_id,assignee,archived_at,
+      (SELECT COUNT(*) FROM [%[1]s] WHERE parent_id=%[2]d AND trashed_at IS NULL)
+      FROM [%[1]s] WHERE part_id=%[2]d AND trashed_at IS NULL`,
 			email, id,
 		),
 	)
   dbPart := &DBPart{ID: id}
-	err := row.Scan(&dbPart.Name, &dbPart.Description, &dbPart.Deadline, &dbPart.CompletedAt, &dbPart.ParentID)
+	var childCount int
+	err := row.Scan(
+		&dbPart.Name, &dbPart.Description, &dbPart.Deadline, &dbPart.CompletedAt,
+		&dbPart.ParentID, &dbPart.Assignee, &dbPart.ArchivedAt, &childCount,
+	)
+	if err != nil {
+		return nil, err
+	}
+	part := dbPart.ToPart()
+	part.ChildCount = childCount
+	blockers, err := db.lockedGetBlockers(email, &id)
+	if err != nil {
+		return nil, err
+	}
+	part.BlockedBy = blockers[id]
+	tags, err := db.lockedGetTags(email, &id)
+	if err != nil {
+		return nil, err
+	}
+	part.Tags = tags[id]
+	return part, nil
+}
+
+// lockedGetBlockers returns a map of part ids to the ids of the parts blocking
+// them. If id is nil, the blockers for all the user's parts are returned.
+func (db *DB) lockedGetBlockers(email string, id *int64) (map[int64][]string, error) {
+	// Parts in the trash don't block others
+	query := fmt.Sprintf(
+		`SELECT part_id,blocker_id FROM [%[1]s:blockers]
+    WHERE blocker_id IN (SELECT part_id FROM [%[1]s] WHERE trashed_at IS NULL)`,
+		email,
+	)
+	var args []any
+	if id != nil {
+		query += ` AND part_id=?`
+		args = append(args, *id)
+	}
+	rows, err := db.db.Query(query, args...)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+	blockers := make(map[int64][]string)
+	for rows.Next() {
+		var partID, blockerID int64
+		if err := rows.Scan(&partID, &blockerID); err != nil {
+			return nil, err
+		}
+		blockers[partID] = append(blockers[partID], strconv.FormatInt(blockerID, 10))
+	}
+	return blockers, rows.Err()
+}
+
+// AddBlocker marks the part with the given id as blocked by the part with the
+// given blocker id. Returns ErrBlockerCycle if the blocker (transitively)
+// depends on the part.
+func (db *DB) AddBlocker(email, idStr, blockerIDStr string) (*model.Part, error) {
+	id, err := strToEpoch(idStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+	blockerID, err := strToEpoch(blockerIDStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+	if id == blockerID {
+		return nil, ErrBlockerCycle
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	for _, pid := range []int64{id, blockerID} {
+		if err := db.lockedCheckNotTrashed(email, pid); err != nil {
+			return nil, err
+		}
+	}
+	// Check whether the part is already (transitively) a blocker of the blocker
+	row := db.db.QueryRow(
+		fmt.Sprintf(`WITH RECURSIVE deps(id) AS (
+      SELECT blocker_id FROM [%[1]s:blockers] WHERE part_id=?
+      UNION
+      SELECT b.blocker_id FROM [%[1]s:blockers] b JOIN deps ON b.part_id=deps.id
+    ) SELECT COUNT(*) FROM deps WHERE id=?`, email),
+		blockerID, id,
+	)
+	var count int
+	if err := row.Scan(&count); err != nil {
+		return nil, err
+	}
+	if count != 0 {
+		return nil, ErrBlockerCycle
+	}
+	_, err = db.db.Exec(
+		fmt.Sprintf(`INSERT OR IGNORE INTO [%s:blockers](part_id,blocker_id) VALUES (?,?)`, email),
+		id, blockerID,
+	)
+	if err != nil {
+		if errors.Is(err, sqlite3.ErrConstraintForeignKey) {
+			err = ErrPartNotExist
+		}
+		return nil, err
+	}
+	if err := db.lockedRecordChanges(email, false, id); err != nil {
+		return nil, err
+	}
+	return db.lockedGetPart(email, id)
+}
+
+// RemoveBlocker removes the blocker with the given id from the part with the
+// given id.
+func (db *DB) RemoveBlocker(email, idStr, blockerIDStr string) (*model.Part, error) {
+	id, err := strToEpoch(idStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+	blockerID, err := strToEpoch(blockerIDStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	// Only the part is edited, so a blocker that's in the trash can still be removed
+	if err := db.lockedCheckNotTrashed(email, id); err != nil {
+		return nil, err
+	}
+	_, err = db.db.Exec(
+		fmt.Sprintf(`DELETE FROM [%s:blockers] WHERE part_id=? AND blocker_id=?`, email),
+		id, blockerID,
+	)
 	if err != nil {
 		return nil, err
 	}
-	return dbPart.ToPart(), nil
+	if err := db.lockedRecordChanges(email, false, id); err != nil {
+		return nil, err
+	}
+	return db.lockedGetPart(email, id)
 }
 
 // GetParts gets all the parts for the given email.
@@ -322,7 +759,34 @@ func (db *DB) lockedGetPart(email string, id int64) (*model.Part, error) {
 func (db *DB) GetParts(email string) ([]*model.Part, error) {
 	db.mtx.RLock()
 	defer db.mtx.RUnlock()
-	rows, err := db.db.Query(fmt.Sprintf(`SELECT * FROM [%s]`, email))
+	return db.lockedQueryParts(email, "")
+}
+
+// lockedQueryParts gets the user's parts matching the where clause (all the
+// parts if it's empty), behaving like GetParts on errors. Parts in the trash
+// are left out.
+func (db *DB) lockedQueryParts(email, where string, args ...any) ([]*model.Part, error) {
+	return db.lockedQueryPartsIn(email, false, where, args...)
+}
+
+// lockedQueryPartsIn is lockedQueryParts, but for the parts in the trash if
+// trashed is true. The child counts of trashed parts only count the children
+// trashed along with them.
+func (db *DB) lockedQueryPartsIn(email string, trashed bool, where string, args ...any) ([]*model.Part, error) {
+	trashedCond := "IS NULL"
+	if trashed {
+		trashedCond = "IS NOT NULL"
+	}
+	rows, err := db.db.Query(
+		fmt.Sprintf(
+			`SELECT part_id,name,description,deadline,completed_at,parent_id,assignee,
+      archived_at,trashed_at,
+      (SELECT COUNT(*) FROM [%[1]s] c WHERE c.parent_id=p.part_id AND c.trash_root IS p.trash_root)
+      FROM (SELECT * FROM [%[1]s] WHERE trashed_at %[3]s) p %[2]s`,
+			email, where, trashedCond,
+		),
+		args...,
+	)
 	if err != nil {
 		fmt.Println(err)
 		if errIsNoTable(err) {
@@ -332,12 +796,22 @@ func (db *DB) GetParts(email string) ([]*model.Part, error) {
 		return nil, err
 	}
 	defer rows.Close()
+	blockers, err := db.lockedGetBlockers(email, nil)
+	if err != nil {
+		return nil, err
+	}
+	tags, err := db.lockedGetTags(email, nil)
+	if err != nil {
+		return nil, err
+	}
 	var parts []*model.Part
 	for rows.Next() {
 		dbPart := &DBPart{}
+		var childCount int
 		e := rows.Scan(
 			&dbPart.ID, &dbPart.Name, &dbPart.Description,
-			&dbPart.Deadline, &dbPart.CompletedAt, &dbPart.ParentID,
+			&dbPart.Deadline, &dbPart.CompletedAt, &dbPart.ParentID, &dbPart.Assignee,
+			&dbPart.ArchivedAt, &dbPart.TrashedAt, &childCount,
 		)
 		if e != nil {
 			fmt.Println(e)
@@ -345,7 +819,11 @@ func (db *DB) GetParts(email string) ([]*model.Part, error) {
 				err = e
 			}
 		} else {
-			parts = append(parts, dbPart.ToPart())
+			part := dbPart.ToPart()
+			part.BlockedBy = blockers[dbPart.ID]
+			part.Tags = tags[dbPart.ID]
+			part.ChildCount = childCount
+			parts = append(parts, part)
 		}
 	}
 	return parts, err
@@ -364,6 +842,14 @@ type DBPart struct {
 	CompletedAt *int64
 	// ParentID is the optional ID of the parent part
 	ParentID *int64
+	// Assignee is the optional email of the user the part is assigned to
+	Assignee *string
+	// ArchivedAt is the optional time the (completed) project was archived as a
+	// unix epoch
+	ArchivedAt *int64
+	// TrashedAt is the optional time the part was moved to the trash as a unix
+	// epoch
+	TrashedAt *int64
 }
 
 func DBPartFromPart(part *model.Part) (*DBPart, error) {
@@ -388,6 +874,9 @@ func DBPartFromPart(part *model.Part) (*DBPart, error) {
 	if dbPart.ParentID, err = getTimePtr(part.ParentID); err != nil {
 		return nil, ErrInvalidID
 	}
+	if part.Assignee != nil && *part.Assignee != "" {
+		dbPart.Assignee = part.Assignee
+	}
 	return dbPart, nil
 }
 
@@ -399,6 +888,11 @@ func (dbPart *DBPart) ToPart() *model.Part {
 		Deadline:    getStrPtr(dbPart.Deadline),
 		CompletedAt: getStrPtr(dbPart.CompletedAt),
 		ParentID:    getStrPtr(dbPart.ParentID),
+		Assignee:    dbPart.Assignee,
+		ArchivedAt:  getStrPtr(dbPart.ArchivedAt),
+		TrashedAt:   getStrPtr(dbPart.TrashedAt),
+		BlockedBy:   []string{},
+		Tags:        []string{},
 	}
 }
 
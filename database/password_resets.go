@@ -0,0 +1,111 @@
+package database
+
+import (
+	"crypto/rand"
+	"crypto/sha256"
+	"database/sql"
+	"encoding/hex"
+	"errors"
+	"time"
+
+	"github.com/johnietre/projects-tracker/auth"
+)
+
+// Password reset tokens are single-use and expire after resetTokenLifetime.
+// Only their hashes are stored, and a user has at most one at a time.
+const passwordResetsTableSql = `
+CREATE TABLE IF NOT EXISTS password_resets (
+  token_hash TEXT PRIMARY KEY,
+  email TEXT NOT NULL UNIQUE,
+  expires_at INTEGER NOT NULL,
+  FOREIGN KEY(email) REFERENCES users(email) ON DELETE CASCADE
+);
+`
+
+const resetTokenLifetime = time.Hour
+
+// ErrInvalidResetToken represents a password reset token that doesn't exist,
+// was already used, or has expired.
+var ErrInvalidResetToken = newUserError("invalid or expired password reset link")
+
+// CreatePasswordReset creates a password reset token for the user (replacing
+// any they already have). Returns an empty token if the user doesn't exist,
+// which shouldn't be revealed to whoever asked.
+func (db *DB) CreatePasswordReset(email string) (string, error) {
+	tokenBytes := make([]byte, 32)
+	if _, err := rand.Read(tokenBytes); err != nil {
+		return "", err
+	}
+	token := hex.EncodeToString(tokenBytes)
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	if err := db.lockedDeleteExpiredResets(); err != nil {
+		return "", err
+	}
+	var count int
+	err := db.db.QueryRow(`SELECT COUNT(*) FROM users WHERE email=?`, email).Scan(&count)
+	if err != nil || count == 0 {
+		return "", err
+	}
+	_, err = db.db.Exec(
+		`INSERT OR REPLACE INTO password_resets(token_hash,email,expires_at) VALUES (?,?,?)`,
+		hashResetToken(token), email, time.Now().Add(resetTokenLifetime).Unix(),
+	)
+	if err != nil {
+		return "", err
+	}
+	return token, nil
+}
+
+// ResetPassword sets the password of the user the reset token is for, using
+// up the token. Returns ErrInvalidResetToken if the token can't be used.
+func (db *DB) ResetPassword(token, newPassword string) (auth.User, error) {
+	if !validPassword(newPassword) {
+		return auth.User{}, userInputError("invalid password")
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	if err := db.lockedDeleteExpiredResets(); err != nil {
+		return auth.User{}, err
+	}
+	tokenHash := hashResetToken(token)
+	var email string
+	err := db.db.QueryRow(
+		`SELECT email FROM password_resets WHERE token_hash=?`, tokenHash,
+	).Scan(&email)
+	if err != nil {
+		if errors.Is(err, sql.ErrNoRows) {
+			err = ErrInvalidResetToken
+		}
+		return auth.User{}, err
+	}
+	user, err := auth.NewUser(email, newPassword)
+	if err != nil {
+		return auth.User{}, err
+	}
+	_, err = db.db.Exec(`DELETE FROM password_resets WHERE token_hash=?`, tokenHash)
+	if err != nil {
+		return auth.User{}, err
+	}
+	_, err = db.db.Exec(
+		`UPDATE users SET password_hash=? WHERE email=?`, user.PasswordHash, email,
+	)
+	if err != nil {
+		return auth.User{}, err
+	}
+	return auth.User{Email: email}, nil
+}
+
+func (db *DB) lockedDeleteExpiredResets() error {
+	_, err := db.db.Exec(
+		`DELETE FROM password_resets WHERE expires_at<=?`, time.Now().Unix(),
+	)
+	return err
+}
+
+func hashResetToken(token string) string {
+	sum := sha256.Sum256([]byte(token))
+	return hex.EncodeToString(sum[:])
+}
@@ -0,0 +1,320 @@
+package database
+
+import (
+	"database/sql"
+	"errors"
+	"fmt"
+	"strconv"
+
+	"github.com/johnietre/projects-tracker/graph/model"
+	sqlite3 "github.com/mattn/go-sqlite3"
+)
+
+var (
+	// ErrNotProject represents trying to share a part that isn't a project.
+	ErrNotProject = newUserError("only projects can be shared")
+	// ErrShareSelf represents trying to share a project with its owner.
+	ErrShareSelf = newUserError("cannot share a project with yourself")
+	// ErrPermission represents a viewer trying to change a shared part (or an
+	// editor trying to move one out of its project).
+	ErrPermission = newUserError("permission denied")
+	// ErrInvalidRole represents an unknown role.
+	ErrInvalidRole = userInputError("invalid role")
+)
+
+// The memberships table holds who each project is shared with. Shared parts
+// stay in their owner's tables.
+const membershipsTableSql = `
+CREATE TABLE IF NOT EXISTS memberships (
+  owner TEXT NOT NULL,
+  project_id INTEGER NOT NULL,
+  member TEXT NOT NULL,
+  role TEXT NOT NULL,
+  PRIMARY KEY(owner, project_id, member),
+  FOREIGN KEY(owner) REFERENCES users(email) ON DELETE CASCADE,
+  FOREIGN KEY(member) REFERENCES users(email) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS memberships_member ON memberships(member);
+`
+
+// ShareProject shares the owner's project with the given id with the member
+// (replacing their role if it's already shared with them).
+func (db *DB) ShareProject(owner, projectIDStr, member string, role model.Role) (*model.Membership, error) {
+	projectID, err := strToEpoch(projectIDStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+	if !role.IsValid() {
+		return nil, ErrInvalidRole
+	}
+	if member == owner {
+		return nil, ErrShareSelf
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	parentID, err := db.lockedParentID(owner, projectID)
+	if err != nil {
+		if errors.Is(err, sql.ErrNoRows) {
+			err = ErrPartNotExist
+		} else if errIsNoTable(err) {
+			err = ErrUserNotExist
+		}
+		return nil, err
+	}
+	if parentID != nil {
+		return nil, ErrNotProject
+	}
+	_, err = db.db.Exec(
+		`INSERT OR REPLACE INTO memberships(owner,project_id,member,role) VALUES (?,?,?,?)`,
+		owner, projectID, member, role.String(),
+	)
+	if err != nil {
+		if errors.Is(err, sqlite3.ErrConstraintForeignKey) {
+			err = ErrUserNotExist
+		}
+		return nil, err
+	}
+	return &model.Membership{
+		ProjectID: projectIDStr,
+		Owner:     owner,
+		Member:    member,
+		Role:      role,
+	}, nil
+}
+
+// UnshareProject stops sharing the owner's project with the member. Returns
+// whether it was shared with them.
+func (db *DB) UnshareProject(owner, projectIDStr, member string) (bool, error) {
+	projectID, err := strToEpoch(projectIDStr)
+	if err != nil {
+		return false, ErrInvalidID
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	res, err := db.db.Exec(
+		`DELETE FROM memberships WHERE owner=? AND project_id=? AND member=?`,
+		owner, projectID, member,
+	)
+	if err != nil {
+		return false, err
+	}
+	numRows, err := res.RowsAffected()
+	if err != nil || numRows == 0 {
+		return false, err
+	}
+	// Their parts in the project go back to being unassigned
+	return true, db.lockedUnassignMember(owner, projectID, member)
+}
+
+// lockedUnshareDeleted removes the memberships of the owner's projects with
+// the given ids (e.g., once deleted).
+func (db *DB) lockedUnshareDeleted(owner string, ids ...int64) error {
+	for _, id := range ids {
+		_, err := db.db.Exec(
+			`DELETE FROM memberships WHERE owner=? AND project_id=?`, owner, id,
+		)
+		if err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// GetMemberships gets who the owner's projects are shared with.
+func (db *DB) GetMemberships(owner string) ([]*model.Membership, error) {
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	return db.lockedQueryMemberships(`owner=?`, owner)
+}
+
+func (db *DB) lockedQueryMemberships(where string, args ...any) ([]*model.Membership, error) {
+	rows, err := db.db.Query(
+		`SELECT owner,project_id,member,role FROM memberships WHERE `+where+
+			` ORDER BY owner,project_id,member`,
+		args...,
+	)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+	memberships := []*model.Membership{}
+	for rows.Next() {
+		m := &model.Membership{}
+		var projectID int64
+		if err := rows.Scan(&m.Owner, &projectID, &m.Member, &m.Role); err != nil {
+			return nil, err
+		}
+		m.ProjectID = strconv.FormatInt(projectID, 10)
+		memberships = append(memberships, m)
+	}
+	return memberships, rows.Err()
+}
+
+// GetSharedProjects gets the projects shared with the member, along with all
+// their parts.
+func (db *DB) GetSharedProjects(member string) ([]*model.SharedProject, error) {
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	memberships, err := db.lockedQueryMemberships(`member=?`, member)
+	if err != nil {
+		return nil, err
+	}
+	projects := make([]*model.SharedProject, 0, len(memberships))
+	for _, m := range memberships {
+		projectID, err := strToEpoch(m.ProjectID)
+		if err != nil {
+			return nil, err
+		}
+		parts, err := db.lockedQueryParts(m.Owner, `WHERE `+subtreeWhere(m.Owner), projectID)
+		if err != nil {
+			return nil, err
+		}
+		if len(parts) == 0 {
+			// The project is in the trash
+			continue
+		}
+		members, err := db.lockedQueryMemberships(
+			`owner=? AND project_id=?`, m.Owner, projectID,
+		)
+		if err != nil {
+			return nil, err
+		}
+		projects = append(projects, &model.SharedProject{
+			Membership: m, Members: members, Parts: parts,
+		})
+	}
+	return projects, nil
+}
+
+// GetPartsMembers gets who the owner's parts with the given ids are shared
+// with, mapped to the ids of the parts shared with each of them. Parts in the
+// trash are included, since deleting a part moves it there.
+func (db *DB) GetPartsMembers(owner string, idStrs ...string) (map[string]map[string]bool, error) {
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	members := make(map[string]map[string]bool)
+	for _, idStr := range idStrs {
+		id, err := strToEpoch(idStr)
+		if err != nil {
+			return nil, ErrInvalidID
+		}
+		rows, err := db.db.Query(
+			fmt.Sprintf(`WITH RECURSIVE ancestors(id) AS (
+      SELECT ?
+      UNION
+      SELECT p.parent_id FROM [%s] p JOIN ancestors ON p.part_id=ancestors.id
+      WHERE p.parent_id IS NOT NULL
+    ) SELECT DISTINCT member FROM memberships
+    WHERE owner=? AND project_id IN (SELECT id FROM ancestors)`, owner),
+			id, owner,
+		)
+		if err != nil {
+			return nil, err
+		}
+		for rows.Next() {
+			var member string
+			if err := rows.Scan(&member); err != nil {
+				rows.Close()
+				return nil, err
+			}
+			if members[member] == nil {
+				members[member] = make(map[string]bool)
+			}
+			members[member][idStr] = true
+		}
+		err = rows.Err()
+		rows.Close()
+		if err != nil {
+			return nil, err
+		}
+	}
+	return members, nil
+}
+
+// subtreeWhere returns a condition matching the owner's parts in the subtree
+// of the part whose id is its one argument.
+func subtreeWhere(owner string) string {
+	return fmt.Sprintf(`part_id IN (WITH RECURSIVE subtree(id) AS (
+      SELECT ?
+      UNION
+      SELECT c.part_id FROM [%s] c JOIN subtree ON c.parent_id=subtree.id
+    ) SELECT id FROM subtree)`, owner)
+}
+
+// CheckEditor checks that the member can change the owner's parts with the
+// given ids (i.e., they're in projects shared with the member as an editor).
+// Returns ErrPartNotExist if a part isn't shared with the member at all.
+func (db *DB) CheckEditor(owner, member string, idStrs ...string) error {
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	for _, idStr := range idStrs {
+		id, err := strToEpoch(idStr)
+		if err != nil {
+			return ErrInvalidID
+		}
+		role, err := db.lockedRole(owner, member, id)
+		if err != nil {
+			return err
+		}
+		if role != model.RoleEditor {
+			return ErrPermission
+		}
+	}
+	return nil
+}
+
+// CheckMember checks that the member can see the owner's part with the given
+// id (i.e., it's in a project shared with the member). Returns
+// ErrPartNotExist if it isn't.
+func (db *DB) CheckMember(owner, member, idStr string) error {
+	id, err := strToEpoch(idStr)
+	if err != nil {
+		return ErrInvalidID
+	}
+
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	_, err = db.lockedRole(owner, member, id)
+	return err
+}
+
+// lockedRole returns the member's role for the owner's part with the given id
+// (the highest role of the memberships of the part or its ancestors).
+func (db *DB) lockedRole(owner, member string, id int64) (model.Role, error) {
+	rows, err := db.db.Query(
+		fmt.Sprintf(`WITH RECURSIVE ancestors(id) AS (
+      SELECT ?
+      UNION
+      SELECT p.parent_id FROM [%s] p JOIN ancestors ON p.part_id=ancestors.id
+      WHERE p.parent_id IS NOT NULL
+    ) SELECT role FROM memberships
+    WHERE owner=? AND member=? AND project_id IN (SELECT id FROM ancestors)`, owner),
+		id, owner, member,
+	)
+	if err != nil {
+		if errIsNoTable(err) {
+			err = ErrPartNotExist
+		}
+		return "", err
+	}
+	defer rows.Close()
+	var role model.Role
+	for rows.Next() {
+		var r model.Role
+		if err := rows.Scan(&r); err != nil {
+			return "", err
+		}
+		if role != model.RoleEditor {
+			role = r
+		}
+	}
+	if err := rows.Err(); err != nil {
+		return "", err
+	}
+	if role == "" {
+		return "", ErrPartNotExist
+	}
+	return role, nil
+}
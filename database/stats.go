@@ -0,0 +1,159 @@
+package database
+
+import (
+	"database/sql"
+	"errors"
+	"fmt"
+	"strconv"
+
+	"github.com/johnietre/projects-tracker/graph/model"
+)
+
+// The stats are computed here rather than by the client, which only fetches
+// the parts it shows.
+
+// GetStats gets the statistics of the user's parts, with the completions of
+// the parts completed since the given unix epoch. Parts in the trash are left
+// out.
+func (db *DB) GetStats(email, sinceStr string) (*model.Stats, error) {
+	since, err := strToEpoch(sinceStr)
+	if err != nil {
+		return nil, ErrInvalidTime
+	}
+
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	stats := &model.Stats{}
+	stats.Completions, err = db.lockedQueryEpochs(
+		fmt.Sprintf(
+			`SELECT completed_at FROM [%s]
+      WHERE trashed_at IS NULL AND completed_at>=? ORDER BY completed_at`,
+			email,
+		),
+		since,
+	)
+	if err != nil {
+		return nil, err
+	}
+
+	var totalLate int64
+	err = db.db.QueryRow(
+		fmt.Sprintf(
+			`SELECT
+        COUNT(CASE WHEN completed_at<=deadline THEN 1 END),
+        COUNT(CASE WHEN completed_at>deadline THEN 1 END),
+        COALESCE(SUM(CASE WHEN completed_at>deadline THEN completed_at-deadline END),0)
+      FROM [%s]
+      WHERE trashed_at IS NULL AND completed_at IS NOT NULL AND deadline IS NOT NULL`,
+			email,
+		),
+	).Scan(&stats.OnTime, &stats.Late, &totalLate)
+	if err != nil {
+		return nil, err
+	}
+	stats.TotalLate = strconv.FormatInt(totalLate, 10)
+
+	rows, err := db.db.Query(
+		fmt.Sprintf(
+			`WITH RECURSIVE tree(id,project_id) AS (
+        SELECT part_id,part_id FROM [%[1]s]
+        WHERE parent_id IS NULL AND archived_at IS NULL AND trashed_at IS NULL
+        UNION ALL
+        SELECT c.part_id,tree.project_id FROM [%[1]s] c JOIN tree ON c.parent_id=tree.id
+        WHERE c.trashed_at IS NULL
+      ) SELECT p.part_id,p.name,COUNT(CASE WHEN t.completed_at IS NULL THEN 1 END),COUNT(*)
+      FROM tree
+      JOIN [%[1]s] t ON t.part_id=tree.id
+      JOIN [%[1]s] p ON p.part_id=tree.project_id
+      GROUP BY p.part_id ORDER BY 3 DESC, p.part_id`,
+			email,
+		),
+	)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+	stats.OpenWork = []*model.ProjectWork{}
+	for rows.Next() {
+		var projectID int64
+		work := &model.ProjectWork{}
+		if err := rows.Scan(&projectID, &work.Name, &work.Open, &work.Total); err != nil {
+			return nil, err
+		}
+		work.ProjectID = strconv.FormatInt(projectID, 10)
+		stats.OpenWork = append(stats.OpenWork, work)
+	}
+	return stats, rows.Err()
+}
+
+// GetBurndown gets the completions of the parts of the user's project with the
+// given id. Returns ErrPartNotExist if the project doesn't exist or is in the
+// trash.
+func (db *DB) GetBurndown(email, projectIDStr string) (*model.Burndown, error) {
+	projectID, err := strToEpoch(projectIDStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	var deadline *int64
+	err = db.db.QueryRow(
+		fmt.Sprintf(`SELECT deadline FROM [%s] WHERE part_id=? AND trashed_at IS NULL`, email),
+		projectID,
+	).Scan(&deadline)
+	if err != nil {
+		if errors.Is(err, sql.ErrNoRows) {
+			err = ErrPartNotExist
+		} else if errIsNoTable(err) {
+			err = ErrUserNotExist
+		}
+		return nil, err
+	}
+	burndown := &model.Burndown{Deadline: getStrPtr(deadline)}
+	err = db.db.QueryRow(
+		fmt.Sprintf(
+			`SELECT COUNT(*) FROM [%s]
+      WHERE `+subtreeWhere(email)+` AND part_id<>? AND trashed_at IS NULL`,
+			email,
+		),
+		projectID, projectID,
+	).Scan(&burndown.Total)
+	if err != nil {
+		return nil, err
+	}
+	burndown.Completions, err = db.lockedQueryEpochs(
+		fmt.Sprintf(
+			`SELECT completed_at FROM [%s]
+      WHERE `+subtreeWhere(email)+` AND part_id<>? AND trashed_at IS NULL
+      AND completed_at IS NOT NULL ORDER BY completed_at`,
+			email,
+		),
+		projectID, projectID,
+	)
+	if err != nil {
+		return nil, err
+	}
+	return burndown, nil
+}
+
+// lockedQueryEpochs returns the unix epochs selected by the query.
+func (db *DB) lockedQueryEpochs(query string, args ...any) ([]string, error) {
+	rows, err := db.db.Query(query, args...)
+	if err != nil {
+		if errIsNoTable(err) {
+			err = ErrUserNotExist
+		}
+		return nil, err
+	}
+	defer rows.Close()
+	epochs := []string{}
+	for rows.Next() {
+		var epoch int64
+		if err := rows.Scan(&epoch); err != nil {
+			return nil, err
+		}
+		epochs = append(epochs, strconv.FormatInt(epoch, 10))
+	}
+	return epochs, rows.Err()
+}
@@ -0,0 +1,73 @@
+package database
+
+import (
+	"fmt"
+	"strings"
+	"unicode"
+)
+
+// ErrInvalidTag represents a tag containing whitespace.
+var ErrInvalidTag = userInputError("tags cannot contain whitespace")
+
+// normalizeTags strips any leading '#' from the tags and drops empty and
+// duplicate ones.
+func normalizeTags(tags []string) ([]string, error) {
+	normalized := make([]string, 0, len(tags))
+	seen := make(map[string]bool, len(tags))
+	for _, tag := range tags {
+		tag = strings.TrimLeft(strings.TrimSpace(tag), "#")
+		if tag == "" || seen[tag] {
+			continue
+		}
+		if strings.IndexFunc(tag, unicode.IsSpace) != -1 {
+			return nil, ErrInvalidTag
+		}
+		seen[tag] = true
+		normalized = append(normalized, tag)
+	}
+	return normalized, nil
+}
+
+// lockedGetTags returns a map of part ids to the part's tags (sorted). If id
+// is nil, the tags for all the user's parts are returned.
+func (db *DB) lockedGetTags(email string, id *int64) (map[int64][]string, error) {
+	query := fmt.Sprintf(`SELECT part_id,tag FROM [%s:tags]`, email)
+	var args []any
+	if id != nil {
+		query += ` WHERE part_id=?`
+		args = append(args, *id)
+	}
+	rows, err := db.db.Query(query+` ORDER BY tag`, args...)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+	tags := make(map[int64][]string)
+	for rows.Next() {
+		var partID int64
+		var tag string
+		if err := rows.Scan(&partID, &tag); err != nil {
+			return nil, err
+		}
+		tags[partID] = append(tags[partID], tag)
+	}
+	return tags, rows.Err()
+}
+
+// lockedSetTags replaces the tags of the part with the given id.
+func (db *DB) lockedSetTags(email string, id int64, tags []string) error {
+	_, err := db.db.Exec(fmt.Sprintf(`DELETE FROM [%s:tags] WHERE part_id=?`, email), id)
+	if err != nil {
+		return err
+	}
+	for _, tag := range tags {
+		_, err := db.db.Exec(
+			fmt.Sprintf(`INSERT OR IGNORE INTO [%s:tags](part_id,tag) VALUES (?,?)`, email),
+			id, tag,
+		)
+		if err != nil {
+			return err
+		}
+	}
+	return nil
+}
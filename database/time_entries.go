@@ -0,0 +1,197 @@
+package database
+
+import (
+	"database/sql"
+	"errors"
+	"fmt"
+	"strconv"
+	"time"
+
+	"github.com/johnietre/projects-tracker/graph/model"
+	sqlite3 "github.com/mattn/go-sqlite3"
+)
+
+// DBTimeEntry is the database representation of TimeEntry
+type DBTimeEntry struct {
+	ID     int64
+	PartID int64
+	// StartedAt is the start of the entry as a unix epoch
+	StartedAt int64
+	// EndedAt is the optional end of the entry as a unix epoch (nil if the
+	// timer is still running)
+	EndedAt *int64
+}
+
+func (dbEntry *DBTimeEntry) ToTimeEntry() *model.TimeEntry {
+	return &model.TimeEntry{
+		ID:        strconv.FormatInt(dbEntry.ID, 10),
+		PartID:    strconv.FormatInt(dbEntry.PartID, 10),
+		StartedAt: strconv.FormatInt(dbEntry.StartedAt, 10),
+		EndedAt:   getStrPtr(dbEntry.EndedAt),
+	}
+}
+
+// GetTimeEntries gets all the time entries for the given email (except those
+// of parts in the trash).
+func (db *DB) GetTimeEntries(email string) ([]*model.TimeEntry, error) {
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	rows, err := db.db.Query(
+		fmt.Sprintf(
+			`SELECT entry_id,part_id,started_at,ended_at FROM [%[1]s:time_entries]
+      WHERE part_id IN (SELECT part_id FROM [%[1]s] WHERE trashed_at IS NULL)`,
+			email,
+		),
+	)
+	if err != nil {
+		if errIsNoTable(err) {
+			err = ErrUserNotExist
+		}
+		return nil, err
+	}
+	defer rows.Close()
+	entries := []*model.TimeEntry{}
+	for rows.Next() {
+		dbEntry := &DBTimeEntry{}
+		err := rows.Scan(&dbEntry.ID, &dbEntry.PartID, &dbEntry.StartedAt, &dbEntry.EndedAt)
+		if err != nil {
+			return entries, err
+		}
+		entries = append(entries, dbEntry.ToTimeEntry())
+	}
+	return entries, rows.Err()
+}
+
+// StartTimer starts a new running time entry for the part with the given id.
+// Returns ErrTimerRunning if the part already has a running timer.
+func (db *DB) StartTimer(email, partIDStr string) (*model.TimeEntry, error) {
+	partID, err := strToEpoch(partIDStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+	dbEntry := &DBTimeEntry{PartID: partID, StartedAt: time.Now().Unix()}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	row := db.db.QueryRow(
+		fmt.Sprintf(
+			`SELECT COUNT(*) FROM [%s:time_entries] WHERE part_id=? AND ended_at IS NULL`,
+			email,
+		),
+		partID,
+	)
+	var count int
+	if err := row.Scan(&count); err != nil {
+		return nil, err
+	}
+	if count != 0 {
+		return nil, ErrTimerRunning
+	}
+	return db.lockedInsertTimeEntry(email, dbEntry)
+}
+
+// StopTimer stops the running time entry with the given id.
+func (db *DB) StopTimer(email, idStr string) (*model.TimeEntry, error) {
+	id, err := strToEpoch(idStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	res, err := db.db.Exec(
+		fmt.Sprintf(
+			`UPDATE [%s:time_entries] SET ended_at=? WHERE entry_id=? AND ended_at IS NULL`,
+			email,
+		),
+		time.Now().Unix(), id,
+	)
+	if err != nil {
+		return nil, err
+	}
+	if numRows, err := res.RowsAffected(); err != nil {
+		return nil, err
+	} else if numRows == 0 {
+		return nil, ErrTimeEntryNotExist
+	}
+	return db.lockedGetTimeEntry(email, id)
+}
+
+// CreateTimeEntry creates a manual (finished) time entry.
+func (db *DB) CreateTimeEntry(email string, input model.CreateTimeEntryInput) (*model.TimeEntry, error) {
+	partID, err := strToEpoch(input.PartID)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+	startedAt, err := strToEpoch(input.StartedAt)
+	if err != nil {
+		return nil, ErrInvalidTime
+	}
+	endedAt, err := strToEpoch(input.EndedAt)
+	if err != nil {
+		return nil, ErrInvalidTime
+	}
+	if endedAt <= startedAt {
+		return nil, userInputError("entry must end after it starts")
+	}
+	dbEntry := &DBTimeEntry{PartID: partID, StartedAt: startedAt, EndedAt: &endedAt}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	return db.lockedInsertTimeEntry(email, dbEntry)
+}
+
+// DeleteTimeEntry deletes the time entry with the given id.
+func (db *DB) DeleteTimeEntry(email, idStr string) error {
+	id, err := strToEpoch(idStr)
+	if err != nil {
+		return ErrInvalidID
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	_, err = db.db.Exec(
+		fmt.Sprintf(`DELETE FROM [%s:time_entries] WHERE entry_id=?`, email), id,
+	)
+	return err
+}
+
+func (db *DB) lockedInsertTimeEntry(email string, dbEntry *DBTimeEntry) (*model.TimeEntry, error) {
+	res, err := db.db.Exec(
+		fmt.Sprintf(
+			`INSERT INTO [%s:time_entries](part_id,started_at,ended_at) VALUES (?,?,?)`,
+			email,
+		),
+		dbEntry.PartID, dbEntry.StartedAt, dbEntry.EndedAt,
+	)
+	if err != nil {
+		if errors.Is(err, sqlite3.ErrConstraintForeignKey) {
+			err = ErrPartNotExist
+		} else if errIsNoTable(err) {
+			err = ErrUserNotExist
+		}
+		return nil, err
+	}
+	if dbEntry.ID, err = res.LastInsertId(); err != nil {
+		return nil, err
+	}
+	return dbEntry.ToTimeEntry(), nil
+}
+
+func (db *DB) lockedGetTimeEntry(email string, id int64) (*model.TimeEntry, error) {
+	row := db.db.QueryRow(
+		fmt.Sprintf(
+			`SELECT part_id,started_at,ended_at FROM [%s:time_entries] WHERE entry_id=?`,
+			email,
+		),
+		id,
+	)
+	dbEntry := &DBTimeEntry{ID: id}
+	if err := row.Scan(&dbEntry.PartID, &dbEntry.StartedAt, &dbEntry.EndedAt); err != nil {
+		if errors.Is(err, sql.ErrNoRows) {
+			err = ErrTimeEntryNotExist
+		}
+		return nil, err
+	}
+	return dbEntry.ToTimeEntry(), nil
+}
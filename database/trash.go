@@ -0,0 +1,336 @@
+package database
+
+import (
+	"database/sql"
+	"errors"
+	"fmt"
+	"strconv"
+	"time"
+
+	"github.com/johnietre/projects-tracker/graph/model"
+)
+
+// Deleted parts are moved to the trash rather than removed: the part and its
+// descendants get the time they were trashed and the id of the part trashed
+// (the trash root), so the subtree can be restored as it was. Parts trashed
+// earlier keep their own root and are restored separately. Trashed parts are
+// left out of everything but the trash until restored or purged.
+
+var (
+	// ErrNotInTrash represents restoring or purging a part that wasn't
+	// deleted (or was deleted as part of another part's subtree).
+	ErrNotInTrash = newUserError("part is not in the trash")
+	// ErrParentTrashed represents restoring a part whose parent is still in
+	// the trash.
+	ErrParentTrashed = newUserError("the part's parent is in the trash, restore it first")
+	// ErrNotArchivable represents archiving a part that isn't a completed
+	// project.
+	ErrNotArchivable = userInputError("only completed projects can be archived")
+)
+
+// lockedCheckNotTrashed returns ErrPartNotExist if the part with the given id
+// doesn't exist or is in the trash.
+func (db *DB) lockedCheckNotTrashed(email string, id int64) error {
+	var count int
+	err := db.db.QueryRow(
+		fmt.Sprintf(`SELECT COUNT(*) FROM [%s] WHERE part_id=? AND trashed_at IS NULL`, email),
+		id,
+	).Scan(&count)
+	if err != nil {
+		if errIsNoTable(err) {
+			err = ErrUserNotExist
+		}
+		return err
+	}
+	if count == 0 {
+		return ErrPartNotExist
+	}
+	return nil
+}
+
+// lockedTrashPart moves the part with the given id and its descendants to the
+// trash.
+func (db *DB) lockedTrashPart(email string, id int64) error {
+	parentID, err := db.lockedParentID(email, id)
+	if err != nil {
+		if errors.Is(err, sql.ErrNoRows) {
+			err = ErrPartNotExist
+		}
+		return err
+	}
+	ids, err := db.lockedLiveSubtreeIDs(email, id)
+	if err != nil {
+		return err
+	}
+	if len(ids) == 0 {
+		// The part is already in the trash
+		return ErrPartNotExist
+	}
+	_, err = db.db.Exec(
+		fmt.Sprintf(
+			`UPDATE [%s] SET trashed_at=?,trash_root=? WHERE trashed_at IS NULL AND %s`,
+			email, subtreeWhere(email),
+		),
+		time.Now().Unix(), id, id,
+	)
+	if err != nil {
+		return err
+	}
+	if err := db.lockedRecordChanges(email, true, ids...); err != nil {
+		return err
+	}
+	// The parts they blocked aren't blocked by them anymore
+	blocked, err := db.lockedBlockedBy(email, ids)
+	if err != nil {
+		return err
+	}
+	if parentID != nil {
+		// The parent's child count changed
+		blocked = append(blocked, *parentID)
+	}
+	return db.lockedRecordChanges(email, false, blocked...)
+}
+
+// lockedLiveSubtreeIDs returns the ids of the part and its descendants that
+// aren't in the trash.
+func (db *DB) lockedLiveSubtreeIDs(email string, id int64) ([]int64, error) {
+	ids, err := db.lockedQueryIDs(
+		fmt.Sprintf(
+			`SELECT part_id FROM [%s] WHERE trashed_at IS NULL AND %s`,
+			email, subtreeWhere(email),
+		),
+		id,
+	)
+	if err != nil && errIsNoTable(err) {
+		err = ErrUserNotExist
+	}
+	return ids, err
+}
+
+// lockedBlockedBy returns the ids of the parts blocked by any of the parts with
+// the given ids.
+func (db *DB) lockedBlockedBy(email string, ids []int64) ([]int64, error) {
+	var blocked []int64
+	for _, id := range ids {
+		partIDs, err := db.lockedQueryIDs(
+			fmt.Sprintf(`SELECT part_id FROM [%s:blockers] WHERE blocker_id=?`, email), id,
+		)
+		if err != nil {
+			return nil, err
+		}
+		blocked = append(blocked, partIDs...)
+	}
+	return blocked, nil
+}
+
+// lockedQueryIDs returns the ids selected by the query.
+func (db *DB) lockedQueryIDs(query string, args ...any) ([]int64, error) {
+	return queryIDs(db.db, query, args...)
+}
+
+// queryIDs is lockedQueryIDs, using the querier (e.g., within a transaction).
+func queryIDs(q querier, query string, args ...any) ([]int64, error) {
+	rows, err := q.Query(query, args...)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+	var ids []int64
+	for rows.Next() {
+		var id int64
+		if err := rows.Scan(&id); err != nil {
+			return nil, err
+		}
+		ids = append(ids, id)
+	}
+	return ids, rows.Err()
+}
+
+// GetTrash gets the parts the user deleted (but not their descendants deleted
+// along with them), most recently deleted first.
+func (db *DB) GetTrash(email string) ([]*model.Part, error) {
+	db.mtx.RLock()
+	defer db.mtx.RUnlock()
+	parts, err := db.lockedQueryPartsIn(
+		email, true, `WHERE part_id=trash_root ORDER BY trashed_at DESC, part_id`,
+	)
+	if parts == nil {
+		parts = []*model.Part{}
+	}
+	return parts, err
+}
+
+// RestorePart moves the deleted part with the given id and the descendants
+// deleted along with it out of the trash, returning the restored parts
+// (parents before their children).
+func (db *DB) RestorePart(email, idStr string) ([]*model.Part, error) {
+	id, err := strToEpoch(idStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	parentID, err := db.lockedTrashRootParentID(email, id)
+	if err != nil {
+		return nil, err
+	}
+	if parentID != nil {
+		if err := db.lockedCheckNotTrashed(email, *parentID); err != nil {
+			return nil, ErrParentTrashed
+		}
+	}
+	ids, err := db.lockedQueryIDs(
+		fmt.Sprintf(`SELECT part_id FROM [%s] WHERE trash_root=?`, email), id,
+	)
+	if err != nil {
+		return nil, err
+	}
+	_, err = db.db.Exec(
+		fmt.Sprintf(
+			`UPDATE [%s] SET trashed_at=NULL,trash_root=NULL WHERE trash_root=?`, email,
+		),
+		id,
+	)
+	if err != nil {
+		return nil, err
+	}
+	blocked, err := db.lockedBlockedBy(email, ids)
+	if err != nil {
+		return nil, err
+	}
+	changed := append(ids, blocked...)
+	if parentID != nil {
+		changed = append(changed, *parentID)
+	}
+	if err := db.lockedRecordChanges(email, false, changed...); err != nil {
+		return nil, err
+	}
+	parts, err := db.lockedQueryParts(email, `WHERE `+subtreeWhere(email), id)
+	if err != nil {
+		return nil, err
+	}
+	return sortParentsFirst(id, parts), nil
+}
+
+// PurgePart permanently deletes the deleted part with the given id along with
+// all its descendants.
+func (db *DB) PurgePart(email, idStr string) error {
+	id, err := strToEpoch(idStr)
+	if err != nil {
+		return ErrInvalidID
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	parentID, err := db.lockedTrashRootParentID(email, id)
+	if err != nil {
+		return err
+	}
+	// The children are deleted by the database, so get their ids beforehand
+	ids, err := db.lockedSubtreeIDs(email, id)
+	if err != nil {
+		return err
+	}
+	_, err = db.db.Exec(fmt.Sprintf(`DELETE FROM [%s] WHERE part_id=?`, email), id)
+	if err != nil {
+		return err
+	}
+	if err := db.lockedRecordChanges(email, true, ids...); err != nil {
+		return err
+	}
+	if parentID == nil {
+		return db.lockedUnshareDeleted(email, id)
+	}
+	return nil
+}
+
+// lockedTrashRootParentID returns the parent id of the deleted part with the
+// given id, or ErrNotInTrash if it isn't the root of a subtree in the trash.
+func (db *DB) lockedTrashRootParentID(email string, id int64) (*int64, error) {
+	var parentID *int64
+	err := db.db.QueryRow(
+		fmt.Sprintf(`SELECT parent_id FROM [%s] WHERE part_id=? AND trash_root=part_id`, email),
+		id,
+	).Scan(&parentID)
+	if err != nil {
+		if errors.Is(err, sql.ErrNoRows) {
+			err = ErrNotInTrash
+		} else if errIsNoTable(err) {
+			err = ErrUserNotExist
+		}
+		return nil, err
+	}
+	return parentID, nil
+}
+
+// sortParentsFirst orders the parts of the subtree of the part with the given
+// id so that parents come before their children.
+func sortParentsFirst(rootID int64, parts []*model.Part) []*model.Part {
+	rootIDStr := strconv.FormatInt(rootID, 10)
+	children := make(map[string][]*model.Part)
+	var root *model.Part
+	for _, part := range parts {
+		if part.ID == rootIDStr {
+			root = part
+		} else if part.ParentID != nil {
+			children[*part.ParentID] = append(children[*part.ParentID], part)
+		}
+	}
+	if root == nil {
+		return parts
+	}
+	sorted := make([]*model.Part, 0, len(parts))
+	queue := []*model.Part{root}
+	for len(queue) != 0 {
+		part := queue[0]
+		queue = queue[1:]
+		sorted = append(sorted, part)
+		queue = append(queue, children[part.ID]...)
+	}
+	return sorted
+}
+
+// SetArchived archives (or unarchives) the completed project with the given
+// id. Archived projects are hidden from the main view, and reopening or moving
+// one unarchives it.
+func (db *DB) SetArchived(email, idStr string, archived bool) (*model.Part, error) {
+	id, err := strToEpoch(idStr)
+	if err != nil {
+		return nil, ErrInvalidID
+	}
+
+	db.mtx.Lock()
+	defer db.mtx.Unlock()
+	part, err := db.lockedGetPart(email, id)
+	if err != nil {
+		if errors.Is(err, sql.ErrNoRows) {
+			err = ErrPartNotExist
+		} else if errIsNoTable(err) {
+			err = ErrUserNotExist
+		}
+		return nil, err
+	}
+	if archived && (part.ParentID != nil || part.CompletedAt == nil) {
+		return nil, ErrNotArchivable
+	}
+	if (part.ArchivedAt != nil) == archived {
+		return part, nil
+	}
+	var archivedAt *int64
+	if archived {
+		now := time.Now().Unix()
+		archivedAt = &now
+	}
+	_, err = db.db.Exec(
+		fmt.Sprintf(`UPDATE [%s] SET archived_at=? WHERE part_id=?`, email), archivedAt, id,
+	)
+	if err != nil {
+		return nil, err
+	}
+	if err := db.lockedRecordChanges(email, false, id); err != nil {
+		return nil, err
+	}
+	part.ArchivedAt = getStrPtr(archivedAt)
+	return part, nil
+}
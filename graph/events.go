@@ -0,0 +1,91 @@
+package graph
+
+import (
+	"context"
+	"sync"
+
+	"github.com/johnietre/projects-tracker/auth"
+	"github.com/johnietre/projects-tracker/graph/model"
+)
+
+// The number of events buffered for each subscriber. A subscriber that falls
+// further behind is dropped (its channel is closed), which makes the client
+// reconnect and re-sync.
+const eventBufferSize = 64
+
+// partEventBroker fans out part events to each of the user's subscribers.
+type partEventBroker struct {
+	mtx sync.Mutex
+	// The subscribers' channels of each user, mapped to the subscribers' client
+	// ids
+	subs map[string]map[chan *model.PartEvent]string
+}
+
+func newPartEventBroker() *partEventBroker {
+	return &partEventBroker{subs: make(map[string]map[chan *model.PartEvent]string)}
+}
+
+// subscribe returns a channel receiving the user's part events and a function
+// to unsubscribe (which closes the channel if it hasn't been already). The
+// events published by the client with the given id aren't sent to it, since
+// it has already applied them (an empty id receives all of them).
+func (b *partEventBroker) subscribe(email, client string) (<-chan *model.PartEvent, func()) {
+	ch := make(chan *model.PartEvent, eventBufferSize)
+	b.mtx.Lock()
+	defer b.mtx.Unlock()
+	if b.subs[email] == nil {
+		b.subs[email] = make(map[chan *model.PartEvent]string)
+	}
+	b.subs[email][ch] = client
+	return ch, func() {
+		b.mtx.Lock()
+		defer b.mtx.Unlock()
+		b.lockedRemove(email, ch)
+	}
+}
+
+// publish sends the events to the user's subscribers, other than those of the
+// client they came from (the request's, if any).
+func (b *partEventBroker) publish(ctx context.Context, email string, events ...*model.PartEvent) {
+	origin := auth.ClientFromContext(ctx)
+	b.mtx.Lock()
+	defer b.mtx.Unlock()
+	for ch, client := range b.subs[email] {
+		if origin != "" && client == origin {
+			continue
+		}
+		for _, event := range events {
+			select {
+			case ch <- event:
+			default:
+				b.lockedRemove(email, ch)
+			}
+			if _, ok := b.subs[email][ch]; !ok {
+				break
+			}
+		}
+	}
+}
+
+func (b *partEventBroker) lockedRemove(email string, ch chan *model.PartEvent) {
+	if _, ok := b.subs[email][ch]; !ok {
+		return
+	}
+	close(ch)
+	delete(b.subs[email], ch)
+	if len(b.subs[email]) == 0 {
+		delete(b.subs, email)
+	}
+}
+
+func partCreated(part *model.Part) *model.PartEvent {
+	return &model.PartEvent{Kind: model.PartEventKindCreated, ID: part.ID, Part: part}
+}
+
+func partUpdated(part *model.Part) *model.PartEvent {
+	return &model.PartEvent{Kind: model.PartEventKindUpdated, ID: part.ID, Part: part}
+}
+
+func partDeleted(id string) *model.PartEvent {
+	return &model.PartEvent{Kind: model.PartEventKindDeleted, ID: id}
+}
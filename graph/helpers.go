@@ -1,22 +1,145 @@
 package graph
 
 import (
+	"context"
 	"errors"
+	"log"
+	"strings"
 
+	"github.com/johnietre/projects-tracker/auth"
 	"github.com/johnietre/projects-tracker/database"
+	"github.com/johnietre/projects-tracker/graph/model"
+	"github.com/johnietre/projects-tracker/mailer"
 )
 
 type CloseFunc func() error
 
 var serverErr = errors.New("Internal server error")
 
-func NewConfig(dbPath string) (Config, CloseFunc, error) {
+// NewConfig returns the config for the schema, with the database at dbPath.
+// Emails are sent with the mailer and link to the app at appURL.
+func NewConfig(dbPath string, m mailer.Mailer, appURL string) (Config, CloseFunc, error) {
 	db, err := database.NewDB(dbPath)
 	if err != nil {
 		return Config{}, nil, err
 	}
 	r := &Resolver{
-		db: db,
+		db:     db,
+		events: newPartEventBroker(),
+		mailer: m,
+		appURL: strings.TrimSuffix(appURL, "/"),
 	}
 	return Config{Resolvers: r}, CloseFunc(r.CloseDB), nil
 }
+
+// batchResultsToModel converts the results of a batch operation, logging and
+// hiding any non-user errors. action is used for logging (e.g., "updating").
+func batchResultsToModel(email, action string, results []database.BatchResult) []*model.BatchPartResult {
+	modelResults := make([]*model.BatchPartResult, len(results))
+	for i, res := range results {
+		modelRes := &model.BatchPartResult{ID: res.ID, Part: res.Part}
+		if err := res.Err; err != nil {
+			if !errors.As(err, &database.UserError{}) {
+				log.Printf("error %s part (id: %s) for %s: %v", action, res.ID, email, err)
+				err = serverErr
+			}
+			errStr := err.Error()
+			modelRes.Part, modelRes.Error = nil, &errStr
+		}
+		modelResults[i] = modelRes
+	}
+	return modelResults
+}
+
+// publishBatchResults publishes an event for each part the batch operation
+// succeeded on, using newEvent to create them.
+func (r *Resolver) publishBatchResults(
+	ctx context.Context, email string, results []*model.BatchPartResult, newEvent func(*model.BatchPartResult) *model.PartEvent,
+) {
+	events := make([]*model.PartEvent, 0, len(results))
+	for _, res := range results {
+		if res.Error == nil {
+			events = append(events, newEvent(res))
+		}
+	}
+	r.publishParts(ctx, email, events...)
+}
+
+// publishParts publishes the events of the changes to the owner's parts to
+// the owner, and to the members of the projects the parts are in (naming the
+// owner in their events, which are only of the parts shared with them).
+func (r *Resolver) publishParts(ctx context.Context, owner string, events ...*model.PartEvent) {
+	r.events.publish(ctx, owner, events...)
+	ids := make([]string, len(events))
+	for i, event := range events {
+		ids[i] = event.ID
+	}
+	members, err := r.db.GetPartsMembers(owner, ids...)
+	if err != nil {
+		log.Printf("error getting who %s's changed parts are shared with: %v", owner, err)
+		return
+	}
+	for member, shared := range members {
+		memberEvents := make([]*model.PartEvent, 0, len(shared))
+		for _, event := range events {
+			if shared[event.ID] {
+				memberEvent := *event
+				memberEvent.Owner = &owner
+				memberEvents = append(memberEvents, &memberEvent)
+			}
+		}
+		r.events.publish(ctx, member, memberEvents...)
+	}
+}
+
+// partsOwner returns the email of the user whose parts are being changed: the
+// owner of a shared project if given, after checking the user is an editor of
+// the parts with the given ids, otherwise the user's. A nil (or empty) id
+// stands for the owner's projects, which only the owner can change.
+func (r *Resolver) partsOwner(user auth.User, owner *string, ids ...*string) (string, error) {
+	if owner == nil || *owner == "" || *owner == user.Email {
+		return user.Email, nil
+	}
+	idStrs := make([]string, 0, len(ids))
+	for _, id := range ids {
+		if id == nil || *id == "" {
+			return "", database.ErrPermission
+		}
+		idStrs = append(idStrs, *id)
+	}
+	if err := r.db.CheckEditor(*owner, user.Email, idStrs...); err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error checking %s's access to %s's parts: %v", user.Email, *owner, err)
+			err = serverErr
+		}
+		return "", err
+	}
+	return *owner, nil
+}
+
+// partsReader is like partsOwner, but only checks that the user can see the
+// part with the given id (i.e., viewers are allowed).
+func (r *Resolver) partsReader(user auth.User, owner *string, id string) (string, error) {
+	if owner == nil || *owner == "" || *owner == user.Email {
+		return user.Email, nil
+	}
+	if err := r.db.CheckMember(*owner, user.Email, id); err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error checking %s's access to %s's parts: %v", user.Email, *owner, err)
+			err = serverErr
+		}
+		return "", err
+	}
+	return *owner, nil
+}
+
+// changedParentID returns the new parent id in the changes, if it's changing.
+func changedParentID(changes map[string]any) (*string, bool) {
+	parentID, ok := changes["parent_id"]
+	if !ok {
+		return nil, false
+	}
+	// Malformed values are rejected when the changes are parsed
+	parentIDStr, _ := parentID.(string)
+	return &parentIDStr, true
+}
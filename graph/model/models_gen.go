@@ -2,14 +2,100 @@
 
 package model
 
+import (
+	"fmt"
+	"io"
+	"strconv"
+)
+
+// An entry in a part's activity log
+type Activity struct {
+	ID     string `json:"id"`
+	PartID string `json:"part_id"`
+	// The email of the user who made the change
+	Author string       `json:"author"`
+	Kind   ActivityKind `json:"kind"`
+	// A human-readable description of the change
+	Message string `json:"message"`
+	// Unix epoch
+	CreatedAt string `json:"created_at"`
+}
+
+type ActivityInput struct {
+	Kind    ActivityKind `json:"kind"`
+	Message string       `json:"message"`
+}
+
+// A part assigned to the user
+type AssignedPart struct {
+	// The email of the user whose part it is
+	Owner string `json:"owner"`
+	Part  *Part  `json:"part"`
+}
+
+// The result of a batch operation on a single part
+type BatchPartResult struct {
+	ID string `json:"id"`
+	// The updated part (null if the part was deleted or on error)
+	Part *Part `json:"part"`
+	// Null if the operation succeeded
+	Error *string `json:"error"`
+}
+
+// The completions of the parts of a project, for its burndown chart
+type Burndown struct {
+	// The number of parts in the project (besides the project itself)
+	Total int `json:"total"`
+	// Unix epochs of when the completed parts (besides the project itself) were completed, oldest first
+	Completions []string `json:"completions"`
+	// Unix epoch
+	Deadline *string `json:"deadline"`
+}
+
+type ChangeEmailInput struct {
+	// The user's password
+	Password string `json:"password"`
+	NewEmail string `json:"new_email"`
+}
+
+type ChangePasswordInput struct {
+	CurrentPassword string `json:"current_password"`
+	NewPassword     string `json:"new_password"`
+}
+
+// A comment on a part
+type Comment struct {
+	ID     string `json:"id"`
+	PartID string `json:"part_id"`
+	// The ID of the comment this replies to (null for top-level comments)
+	ReplyTo *string `json:"reply_to"`
+	// The email of the user who wrote it
+	Author string `json:"author"`
+	// Markdown
+	Body string `json:"body"`
+	// Unix epoch
+	CreatedAt string `json:"created_at"`
+}
+
 type CreatePartInput struct {
 	Name        string  `json:"name"`
 	Description *string `json:"description"`
 	// RFC3339 datetime
 	Deadline *string `json:"deadline"`
 	// RFC3339 datetime
-	CompletedAt *string `json:"completed_at"`
-	ParentID    *string `json:"parent_id"`
+	CompletedAt *string  `json:"completed_at"`
+	ParentID    *string  `json:"parent_id"`
+	Tags        []string `json:"tags"`
+	// The email of the owner or a member of the project
+	Assignee *string `json:"assignee"`
+}
+
+type CreateTimeEntryInput struct {
+	PartID string `json:"part_id"`
+	// Unix epoch
+	StartedAt string `json:"started_at"`
+	// Unix epoch
+	EndedAt string `json:"ended_at"`
 }
 
 type CreateUserInput struct {
@@ -22,6 +108,21 @@ type LoginUserInput struct {
 	Password string `json:"password"`
 }
 
+// Another user's access to a project
+type Membership struct {
+	ProjectID string `json:"project_id"`
+	// The email of the user whose project it is
+	Owner string `json:"owner"`
+	// The email of the user the project is shared with
+	Member string `json:"member"`
+	Role   Role   `json:"role"`
+}
+
+type PageInfo struct {
+	HasNextPage bool    `json:"hasNextPage"`
+	EndCursor   *string `json:"endCursor"`
+}
+
 type Part struct {
 	ID          string  `json:"id"`
 	Name        string  `json:"name"`
@@ -31,4 +132,234 @@ type Part struct {
 	// RFC3339 datetime
 	CompletedAt *string `json:"completed_at"`
 	ParentID    *string `json:"parent_id"`
+	// IDs of the parts that must be completed before this one
+	BlockedBy []string `json:"blocked_by"`
+	Tags      []string `json:"tags"`
+	// The number of direct children
+	ChildCount int `json:"child_count"`
+	// The email of the user the part is assigned to
+	Assignee *string `json:"assignee"`
+	// Unix epoch (only completed projects can be archived)
+	ArchivedAt *string `json:"archived_at"`
+	// Unix epoch (null unless the part is in the trash)
+	TrashedAt *string `json:"trashed_at"`
+}
+
+// A page of parts (see Query.children)
+type PartConnection struct {
+	Edges    []*PartEdge `json:"edges"`
+	PageInfo *PageInfo   `json:"pageInfo"`
+	// The partsSince cursor the page is current as of
+	SyncCursor string `json:"syncCursor"`
+}
+
+type PartEdge struct {
+	Cursor string `json:"cursor"`
+	Node   *Part  `json:"node"`
+}
+
+// A change to one of the user's parts, or to a part of a project shared with the user
+type PartEvent struct {
+	Kind PartEventKind `json:"kind"`
+	ID   string        `json:"id"`
+	// The part after the change (null if it was deleted)
+	Part *Part `json:"part"`
+	// The email of the part's owner if it's another user's (null for the user's own parts)
+	Owner *string `json:"owner"`
+}
+
+// The changes to the user's parts since a cursor
+type PartsDelta struct {
+	// The parts created or updated since the cursor
+	Parts []*Part `json:"parts"`
+	// IDs of the parts deleted since the cursor
+	Deleted []string `json:"deleted"`
+	// Pass to partsSince to get the changes after these
+	Cursor string `json:"cursor"`
+	// Whether parts holds all the user's parts (any others the client has were deleted)
+	Full bool `json:"full"`
+}
+
+// How much work is left in one of the user's projects
+type ProjectWork struct {
+	ProjectID string `json:"project_id"`
+	Name      string `json:"name"`
+	// The number of incomplete parts in the project (including the project itself)
+	Open  int `json:"open"`
+	Total int `json:"total"`
+}
+
+type ResetPasswordInput struct {
+	// The token from the reset link
+	Token       string `json:"token"`
+	NewPassword string `json:"new_password"`
+}
+
+// A project shared with the user, along with all its parts
+type SharedProject struct {
+	Membership *Membership `json:"membership"`
+	// Who else the project is shared with (including the user)
+	Members []*Membership `json:"members"`
+	Parts   []*Part       `json:"parts"`
+}
+
+// The statistics of the user's parts shown in the stats dashboard
+type Stats struct {
+	// Unix epochs of when the parts completed since the time asked for were completed, oldest first
+	Completions []string `json:"completions"`
+	// The number of parts completed by their deadlines
+	OnTime int `json:"on_time"`
+	// The number of parts completed after their deadlines
+	Late int `json:"late"`
+	// How late the late parts were in total, in seconds
+	TotalLate string `json:"total_late"`
+	// The unarchived projects, those with the most open parts first
+	OpenWork []*ProjectWork `json:"open_work"`
+}
+
+type TimeEntry struct {
+	ID     string `json:"id"`
+	PartID string `json:"part_id"`
+	// Unix epoch
+	StartedAt string `json:"started_at"`
+	// Unix epoch (null while the timer is running)
+	EndedAt *string `json:"ended_at"`
+}
+
+type ActivityKind string
+
+const (
+	ActivityKindCreated       ActivityKind = "CREATED"
+	ActivityKindRenamed       ActivityKind = "RENAMED"
+	ActivityKindDeadlineMoved ActivityKind = "DEADLINE_MOVED"
+	ActivityKindCompleted     ActivityKind = "COMPLETED"
+	ActivityKindReopened      ActivityKind = "REOPENED"
+	ActivityKindMoved         ActivityKind = "MOVED"
+	// Any other change (e.g., to the description)
+	ActivityKindEdited ActivityKind = "EDITED"
+)
+
+var AllActivityKind = []ActivityKind{
+	ActivityKindCreated,
+	ActivityKindRenamed,
+	ActivityKindDeadlineMoved,
+	ActivityKindCompleted,
+	ActivityKindReopened,
+	ActivityKindMoved,
+	ActivityKindEdited,
+}
+
+func (e ActivityKind) IsValid() bool {
+	switch e {
+	case ActivityKindCreated, ActivityKindRenamed, ActivityKindDeadlineMoved, ActivityKindCompleted, ActivityKindReopened, ActivityKindMoved, ActivityKindEdited:
+		return true
+	}
+	return false
+}
+
+func (e ActivityKind) String() string {
+	return string(e)
+}
+
+func (e *ActivityKind) UnmarshalGQL(v interface{}) error {
+	str, ok := v.(string)
+	if !ok {
+		return fmt.Errorf("enums must be strings")
+	}
+
+	*e = ActivityKind(str)
+	if !e.IsValid() {
+		return fmt.Errorf("%s is not a valid ActivityKind", str)
+	}
+	return nil
+}
+
+func (e ActivityKind) MarshalGQL(w io.Writer) {
+	fmt.Fprint(w, strconv.Quote(e.String()))
+}
+
+type PartEventKind string
+
+const (
+	PartEventKindCreated PartEventKind = "CREATED"
+	PartEventKindUpdated PartEventKind = "UPDATED"
+	PartEventKindDeleted PartEventKind = "DELETED"
+)
+
+var AllPartEventKind = []PartEventKind{
+	PartEventKindCreated,
+	PartEventKindUpdated,
+	PartEventKindDeleted,
+}
+
+func (e PartEventKind) IsValid() bool {
+	switch e {
+	case PartEventKindCreated, PartEventKindUpdated, PartEventKindDeleted:
+		return true
+	}
+	return false
+}
+
+func (e PartEventKind) String() string {
+	return string(e)
+}
+
+func (e *PartEventKind) UnmarshalGQL(v interface{}) error {
+	str, ok := v.(string)
+	if !ok {
+		return fmt.Errorf("enums must be strings")
+	}
+
+	*e = PartEventKind(str)
+	if !e.IsValid() {
+		return fmt.Errorf("%s is not a valid PartEventKind", str)
+	}
+	return nil
+}
+
+func (e PartEventKind) MarshalGQL(w io.Writer) {
+	fmt.Fprint(w, strconv.Quote(e.String()))
+}
+
+type Role string
+
+const (
+	// Can see the project's parts
+	RoleViewer Role = "VIEWER"
+	// Can also create, update and delete the project's parts
+	RoleEditor Role = "EDITOR"
+)
+
+var AllRole = []Role{
+	RoleViewer,
+	RoleEditor,
+}
+
+func (e Role) IsValid() bool {
+	switch e {
+	case RoleViewer, RoleEditor:
+		return true
+	}
+	return false
+}
+
+func (e Role) String() string {
+	return string(e)
+}
+
+func (e *Role) UnmarshalGQL(v interface{}) error {
+	str, ok := v.(string)
+	if !ok {
+		return fmt.Errorf("enums must be strings")
+	}
+
+	*e = Role(str)
+	if !e.IsValid() {
+		return fmt.Errorf("%s is not a valid Role", str)
+	}
+	return nil
+}
+
+func (e Role) MarshalGQL(w io.Writer) {
+	fmt.Fprint(w, strconv.Quote(e.String()))
 }
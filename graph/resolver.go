@@ -4,6 +4,7 @@ package graph
 
 import (
 	"github.com/johnietre/projects-tracker/database"
+	"github.com/johnietre/projects-tracker/mailer"
 )
 
 // This file will not be regenerated automatically.
@@ -11,7 +12,11 @@ import (
 // It serves as dependency injection for your app, add any dependencies you require here.
 
 type Resolver struct {
-	db *database.DB
+	db     *database.DB
+	events *partEventBroker
+	mailer mailer.Mailer
+	// The URL the app is served at, which links in emails point to
+	appURL string
 }
 
 func (r *Resolver) CloseDB() error {
@@ -7,6 +7,7 @@ package graph
 import (
 	"context"
 	"errors"
+	"fmt"
 	"log"
 
 	"github.com/johnietre/projects-tracker/auth"
@@ -41,7 +42,7 @@ func (r *mutationResolver) LoginUser(ctx context.Context, input model.LoginUserI
 			log.Printf("error logging user for %s: %v", input.Email, err)
 			err = serverErr
 		}
-		return "", nil
+		return "", err
 	}
 	token, err := auth.GenerateToken(user.Email)
 	if err != nil {
@@ -61,57 +62,417 @@ func (r *mutationResolver) LogoutUser(ctx context.Context) (bool, error) {
 	return true, nil
 }
 
+// RequestPasswordReset is the resolver for the requestPasswordReset field.
+func (r *mutationResolver) RequestPasswordReset(ctx context.Context, email string) (bool, error) {
+	token, err := r.db.CreatePasswordReset(email)
+	if err != nil {
+		log.Printf("error creating password reset for %s: %v", email, err)
+		return false, serverErr
+	}
+	if token == "" {
+		// No such user, which isn't revealed
+		return true, nil
+	}
+	link := fmt.Sprintf("%s/reset?token=%s", r.appURL, token)
+	body := fmt.Sprintf(
+		"Someone (hopefully you) asked to reset your password. "+
+			"To choose a new one, open this link within an hour:\n\n%s\n\n"+
+			"If it wasn't you, you can ignore this email.\n",
+		link,
+	)
+	if err := r.mailer.Send(email, "Reset your password", body); err != nil {
+		log.Printf("error sending password reset email to %s: %v", email, err)
+		return false, serverErr
+	}
+	return true, nil
+}
+
+// ResetPassword is the resolver for the resetPassword field.
+func (r *mutationResolver) ResetPassword(ctx context.Context, input model.ResetPasswordInput) (bool, error) {
+	user, err := r.db.ResetPassword(input.Token, input.NewPassword)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error resetting password: %v", err)
+			err = serverErr
+		}
+		return false, err
+	}
+	log.Printf("password reset for %s", user.Email)
+	return true, nil
+}
+
+// ChangePassword is the resolver for the changePassword field.
+func (r *mutationResolver) ChangePassword(ctx context.Context, input model.ChangePasswordInput) (bool, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return false, ErrAccess
+	}
+	err := r.db.ChangePassword(user.Email, input.CurrentPassword, input.NewPassword)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error changing password for %s: %v", user.Email, err)
+			err = serverErr
+		}
+		return false, err
+	}
+	return true, nil
+}
+
+// ChangeEmail is the resolver for the changeEmail field.
+func (r *mutationResolver) ChangeEmail(ctx context.Context, input model.ChangeEmailInput) (string, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return "", ErrAccess
+	}
+	newUser, err := r.db.ChangeEmail(user.Email, input.Password, input.NewEmail)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf(
+				"error changing email for %s (to: %s): %v", user.Email, input.NewEmail, err,
+			)
+			err = serverErr
+		}
+		return "", err
+	}
+	token, err := auth.GenerateToken(newUser.Email)
+	if err != nil {
+		log.Printf("error generating token for %s: %v", newUser.Email, err)
+		return "", serverErr
+	}
+	auth.AddCookieToContext(ctx, token)
+	return token, nil
+}
+
+// DeleteAccount is the resolver for the deleteAccount field.
+func (r *mutationResolver) DeleteAccount(ctx context.Context, password string) (bool, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return false, ErrAccess
+	}
+	if err := r.db.DeleteAccount(user.Email, password); err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error deleting account for %s: %v", user.Email, err)
+			err = serverErr
+		}
+		return false, err
+	}
+	auth.RemoveCookieFromContext(ctx)
+	return true, nil
+}
+
 // CreatePart is the resolver for the createPart field.
-func (r *mutationResolver) CreatePart(ctx context.Context, input model.CreatePartInput) (*model.Part, error) {
+func (r *mutationResolver) CreatePart(ctx context.Context, input model.CreatePartInput, owner *string) (*model.Part, error) {
 	user, ok := auth.UserFromContext(ctx)
 	if !ok {
 		return nil, ErrAccess
 	}
-	part, err := r.db.CreatePart(user.Email, input)
+	email, err := r.partsOwner(user, owner, input.ParentID)
+	if err != nil {
+		return &model.Part{}, err
+	}
+	part, err := r.db.CreatePart(email, input)
 	if err != nil {
 		if !errors.As(err, &database.UserError{}) {
-			log.Printf("error creating part for %s (input: %v): %v", user.Email, input, err)
+			log.Printf("error creating part for %s (input: %v): %v", email, input, err)
 			err = serverErr
 		}
 		// TODO: Do I NEED to return something on error?
-		part = &model.Part{}
+		return &model.Part{}, err
 	}
-	return part, err
+	r.publishParts(ctx, email, partCreated(part))
+	return part, nil
 }
 
 // UpdatePart is the resolver for the updatePart field.
-func (r *mutationResolver) UpdatePart(ctx context.Context, id string, changes map[string]interface{}) (*model.Part, error) {
+func (r *mutationResolver) UpdatePart(ctx context.Context, id string, changes map[string]interface{}, owner *string) (*model.Part, error) {
 	user, ok := auth.UserFromContext(ctx)
 	if !ok {
 		return nil, ErrAccess
 	}
-	part, err := r.db.UpdatePart(user.Email, id, changes)
+	// Shared parts can only be moved within the projects shared with the user
+	ids := []*string{&id}
+	if parentID, ok := changedParentID(changes); ok {
+		ids = append(ids, parentID)
+	}
+	email, err := r.partsOwner(user, owner, ids...)
+	if err != nil {
+		return &model.Part{}, err
+	}
+	part, err := r.db.UpdatePart(email, id, changes)
 	if err != nil {
 		if !errors.As(err, &database.UserError{}) {
 			log.Printf(
 				"error updating part for %s (id: %s, input: %v): %v",
-				user.Email, id, changes, err,
+				email, id, changes, err,
 			)
 			err = serverErr
 		}
 		// TODO: Do I NEED to return something on error?
 		return &model.Part{}, err
 	}
+	r.publishParts(ctx, email, partUpdated(part))
 	return part, nil
 }
 
 // DeletePart is the resolver for the deletePart field.
-func (r *mutationResolver) DeletePart(ctx context.Context, id string) (string, error) {
+func (r *mutationResolver) DeletePart(ctx context.Context, id string, owner *string) (string, error) {
 	user, ok := auth.UserFromContext(ctx)
 	if !ok {
 		return "", ErrAccess
 	}
-	err := r.db.DeletePart(user.Email, id)
+	email, err := r.partsOwner(user, owner, &id)
+	if err != nil {
+		return "", err
+	}
+	err = r.db.DeletePart(email, id)
 	if err != nil {
 		if !errors.As(err, &database.UserError{}) {
 			log.Printf(
 				"error deleting part (id: %s) for %s: %v",
-				id, user.Email, err,
+				id, email, err,
+			)
+			err = serverErr
+		}
+		return "", err
+	}
+	r.publishParts(ctx, email, partDeleted(id))
+	return id, nil
+}
+
+// BatchUpdateParts is the resolver for the batchUpdateParts field.
+func (r *mutationResolver) BatchUpdateParts(ctx context.Context, ids []string, changes map[string]interface{}) ([]*model.BatchPartResult, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	results, err := r.db.BatchUpdateParts(user.Email, ids, changes)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf(
+				"error batch updating parts for %s (ids: %v, input: %v): %v",
+				user.Email, ids, changes, err,
+			)
+			err = serverErr
+		}
+		return nil, err
+	}
+	modelResults := batchResultsToModel(user.Email, "updating", results)
+	r.publishBatchResults(ctx, user.Email, modelResults, func(res *model.BatchPartResult) *model.PartEvent {
+		return partUpdated(res.Part)
+	})
+	return modelResults, nil
+}
+
+// BatchDeleteParts is the resolver for the batchDeleteParts field.
+func (r *mutationResolver) BatchDeleteParts(ctx context.Context, ids []string) ([]*model.BatchPartResult, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	results := r.db.BatchDeleteParts(user.Email, ids)
+	modelResults := batchResultsToModel(user.Email, "deleting", results)
+	r.publishBatchResults(ctx, user.Email, modelResults, func(res *model.BatchPartResult) *model.PartEvent {
+		return partDeleted(res.ID)
+	})
+	return modelResults, nil
+}
+
+// AddBlocker is the resolver for the addBlocker field.
+func (r *mutationResolver) AddBlocker(ctx context.Context, id string, blockerID string, owner *string) (*model.Part, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	email, err := r.partsOwner(user, owner, &id, &blockerID)
+	if err != nil {
+		return &model.Part{}, err
+	}
+	part, err := r.db.AddBlocker(email, id, blockerID)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf(
+				"error adding blocker for %s (id: %s, blocker: %s): %v",
+				email, id, blockerID, err,
+			)
+			err = serverErr
+		}
+		return &model.Part{}, err
+	}
+	r.publishParts(ctx, email, partUpdated(part))
+	return part, nil
+}
+
+// RemoveBlocker is the resolver for the removeBlocker field.
+func (r *mutationResolver) RemoveBlocker(ctx context.Context, id string, blockerID string, owner *string) (*model.Part, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	email, err := r.partsOwner(user, owner, &id, &blockerID)
+	if err != nil {
+		return &model.Part{}, err
+	}
+	part, err := r.db.RemoveBlocker(email, id, blockerID)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf(
+				"error removing blocker for %s (id: %s, blocker: %s): %v",
+				email, id, blockerID, err,
+			)
+			err = serverErr
+		}
+		return &model.Part{}, err
+	}
+	r.publishParts(ctx, email, partUpdated(part))
+	return part, nil
+}
+
+// StartTimer is the resolver for the startTimer field.
+func (r *mutationResolver) StartTimer(ctx context.Context, partID string) (*model.TimeEntry, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	entry, err := r.db.StartTimer(user.Email, partID)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error starting timer for %s (part id: %s): %v", user.Email, partID, err)
+			err = serverErr
+		}
+		return &model.TimeEntry{}, err
+	}
+	return entry, nil
+}
+
+// StopTimer is the resolver for the stopTimer field.
+func (r *mutationResolver) StopTimer(ctx context.Context, id string) (*model.TimeEntry, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	entry, err := r.db.StopTimer(user.Email, id)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error stopping timer for %s (id: %s): %v", user.Email, id, err)
+			err = serverErr
+		}
+		return &model.TimeEntry{}, err
+	}
+	return entry, nil
+}
+
+// CreateTimeEntry is the resolver for the createTimeEntry field.
+func (r *mutationResolver) CreateTimeEntry(ctx context.Context, input model.CreateTimeEntryInput) (*model.TimeEntry, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	entry, err := r.db.CreateTimeEntry(user.Email, input)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error creating time entry for %s (input: %v): %v", user.Email, input, err)
+			err = serverErr
+		}
+		return &model.TimeEntry{}, err
+	}
+	return entry, nil
+}
+
+// DeleteTimeEntry is the resolver for the deleteTimeEntry field.
+func (r *mutationResolver) DeleteTimeEntry(ctx context.Context, id string) (string, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return "", ErrAccess
+	}
+	if err := r.db.DeleteTimeEntry(user.Email, id); err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error deleting time entry (id: %s) for %s: %v", id, user.Email, err)
+			err = serverErr
+		}
+		return "", err
+	}
+	return id, nil
+}
+
+// ShareProject is the resolver for the shareProject field.
+func (r *mutationResolver) ShareProject(ctx context.Context, projectID string, email string, role model.Role) (*model.Membership, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	membership, err := r.db.ShareProject(user.Email, projectID, email, role)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf(
+				"error sharing project for %s (id: %s, with: %s): %v",
+				user.Email, projectID, email, err,
+			)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return membership, nil
+}
+
+// UnshareProject is the resolver for the unshareProject field.
+func (r *mutationResolver) UnshareProject(ctx context.Context, projectID string, email string) (bool, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return false, ErrAccess
+	}
+	unshared, err := r.db.UnshareProject(user.Email, projectID, email)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf(
+				"error unsharing project for %s (id: %s, with: %s): %v",
+				user.Email, projectID, email, err,
+			)
+			err = serverErr
+		}
+		return false, err
+	}
+	return unshared, nil
+}
+
+// AddComment is the resolver for the addComment field.
+func (r *mutationResolver) AddComment(ctx context.Context, partID string, body string, replyTo *string, owner *string) (*model.Comment, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	email, err := r.partsReader(user, owner, partID)
+	if err != nil {
+		return nil, err
+	}
+	comment, err := r.db.AddComment(email, user.Email, partID, replyTo, body)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf(
+				"error adding comment for %s (owner: %s, part: %s): %v",
+				user.Email, email, partID, err,
+			)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return comment, nil
+}
+
+// DeleteComment is the resolver for the deleteComment field.
+func (r *mutationResolver) DeleteComment(ctx context.Context, partID string, id string, owner *string) (string, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return "", ErrAccess
+	}
+	email, err := r.partsReader(user, owner, partID)
+	if err != nil {
+		return "", err
+	}
+	if err := r.db.DeleteComment(email, user.Email, partID, id); err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf(
+				"error deleting comment for %s (owner: %s, id: %s): %v",
+				user.Email, email, id, err,
 			)
 			err = serverErr
 		}
@@ -120,6 +481,90 @@ func (r *mutationResolver) DeletePart(ctx context.Context, id string) (string, e
 	return id, nil
 }
 
+// RecordActivity is the resolver for the recordActivity field.
+func (r *mutationResolver) RecordActivity(ctx context.Context, partID string, entries []*model.ActivityInput, owner *string) ([]*model.Activity, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	// Only those who can change the part record its changes
+	email, err := r.partsOwner(user, owner, &partID)
+	if err != nil {
+		return nil, err
+	}
+	activity, err := r.db.RecordActivity(email, user.Email, partID, entries)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf(
+				"error recording activity for %s (owner: %s, part: %s): %v",
+				user.Email, email, partID, err,
+			)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return activity, nil
+}
+
+// RestorePart is the resolver for the restorePart field.
+func (r *mutationResolver) RestorePart(ctx context.Context, id string) ([]*model.Part, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	parts, err := r.db.RestorePart(user.Email, id)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error restoring part (id: %s) for %s: %v", id, user.Email, err)
+			err = serverErr
+		}
+		return nil, err
+	}
+	events := make([]*model.PartEvent, 0, len(parts))
+	for _, part := range parts {
+		events = append(events, partCreated(part))
+	}
+	r.publishParts(ctx, user.Email, events...)
+	return parts, nil
+}
+
+// PurgePart is the resolver for the purgePart field.
+func (r *mutationResolver) PurgePart(ctx context.Context, id string) (string, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return "", ErrAccess
+	}
+	if err := r.db.PurgePart(user.Email, id); err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error purging part (id: %s) for %s: %v", id, user.Email, err)
+			err = serverErr
+		}
+		return "", err
+	}
+	return id, nil
+}
+
+// SetArchived is the resolver for the setArchived field.
+func (r *mutationResolver) SetArchived(ctx context.Context, id string, archived bool) (*model.Part, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	part, err := r.db.SetArchived(user.Email, id, archived)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf(
+				"error setting archived (id: %s, archived: %v) for %s: %v",
+				id, archived, user.Email, err,
+			)
+			err = serverErr
+		}
+		return nil, err
+	}
+	r.publishParts(ctx, user.Email, partUpdated(part))
+	return part, nil
+}
+
 // Parts is the resolver for the parts field.
 func (r *queryResolver) Parts(ctx context.Context) ([]*model.Part, error) {
 	user, ok := auth.UserFromContext(ctx)
@@ -137,14 +582,260 @@ func (r *queryResolver) Parts(ctx context.Context) ([]*model.Part, error) {
 	return parts, err
 }
 
+// PartsSince is the resolver for the partsSince field.
+func (r *queryResolver) PartsSince(ctx context.Context, cursor *string) (*model.PartsDelta, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	delta, err := r.db.GetPartsSince(user.Email, cursor)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error getting parts delta for %s: %v", user.Email, err)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return delta, nil
+}
+
+// Children is the resolver for the children field.
+func (r *queryResolver) Children(ctx context.Context, parentID *string, first *int, after *string) (*model.PartConnection, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	conn, err := r.db.GetChildren(user.Email, parentID, first, after)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error getting children for %s: %v", user.Email, err)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return conn, nil
+}
+
+// PartTree is the resolver for the partTree field.
+func (r *queryResolver) PartTree(ctx context.Context, id string) ([]*model.Part, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	parts, err := r.db.GetPartTree(user.Email, id)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error getting part tree (id: %s) for %s: %v", id, user.Email, err)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return parts, nil
+}
+
+// PartsByID is the resolver for the partsByID field.
+func (r *queryResolver) PartsByID(ctx context.Context, ids []string) ([]*model.Part, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	parts, err := r.db.GetPartsByID(user.Email, ids)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error getting parts by id for %s: %v", user.Email, err)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return parts, nil
+}
+
+// TimeEntries is the resolver for the timeEntries field.
+func (r *queryResolver) TimeEntries(ctx context.Context) ([]*model.TimeEntry, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	entries, err := r.db.GetTimeEntries(user.Email)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error getting time entries for %s: %v", user.Email, err)
+			err = serverErr
+		}
+	}
+	return entries, err
+}
+
+// Memberships is the resolver for the memberships field.
+func (r *queryResolver) Memberships(ctx context.Context) ([]*model.Membership, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	memberships, err := r.db.GetMemberships(user.Email)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error getting memberships for %s: %v", user.Email, err)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return memberships, nil
+}
+
+// SharedProjects is the resolver for the sharedProjects field.
+func (r *queryResolver) SharedProjects(ctx context.Context) ([]*model.SharedProject, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	projects, err := r.db.GetSharedProjects(user.Email)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error getting shared projects for %s: %v", user.Email, err)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return projects, nil
+}
+
+// AssignedParts is the resolver for the assignedParts field.
+func (r *queryResolver) AssignedParts(ctx context.Context) ([]*model.AssignedPart, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	parts, err := r.db.GetAssignedParts(user.Email)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error getting assigned parts for %s: %v", user.Email, err)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return parts, nil
+}
+
+// Comments is the resolver for the comments field.
+func (r *queryResolver) Comments(ctx context.Context, partID string, owner *string) ([]*model.Comment, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	email, err := r.partsReader(user, owner, partID)
+	if err != nil {
+		return nil, err
+	}
+	comments, err := r.db.GetComments(email, partID)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error getting comments for %s (owner: %s, part: %s): %v", user.Email, email, partID, err)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return comments, nil
+}
+
+// Activity is the resolver for the activity field.
+func (r *queryResolver) Activity(ctx context.Context, partID string, owner *string) ([]*model.Activity, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	email, err := r.partsReader(user, owner, partID)
+	if err != nil {
+		return nil, err
+	}
+	activity, err := r.db.GetActivity(email, partID)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error getting activity for %s (owner: %s, part: %s): %v", user.Email, email, partID, err)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return activity, nil
+}
+
+// Trash is the resolver for the trash field.
+func (r *queryResolver) Trash(ctx context.Context) ([]*model.Part, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	parts, err := r.db.GetTrash(user.Email)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error getting trash for %s: %v", user.Email, err)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return parts, nil
+}
+
+// Stats is the resolver for the stats field.
+func (r *queryResolver) Stats(ctx context.Context, since string) (*model.Stats, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	stats, err := r.db.GetStats(user.Email, since)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error getting stats for %s: %v", user.Email, err)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return stats, nil
+}
+
+// Burndown is the resolver for the burndown field.
+func (r *queryResolver) Burndown(ctx context.Context, projectID string) (*model.Burndown, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	burndown, err := r.db.GetBurndown(user.Email, projectID)
+	if err != nil {
+		if !errors.As(err, &database.UserError{}) {
+			log.Printf("error getting burndown (id: %s) for %s: %v", projectID, user.Email, err)
+			err = serverErr
+		}
+		return nil, err
+	}
+	return burndown, nil
+}
+
+// PartEvents is the resolver for the partEvents field.
+func (r *subscriptionResolver) PartEvents(ctx context.Context) (<-chan *model.PartEvent, error) {
+	user, ok := auth.UserFromContext(ctx)
+	if !ok {
+		return nil, ErrAccess
+	}
+	events, unsubscribe := r.events.subscribe(user.Email, auth.ClientFromContext(ctx))
+	go func() {
+		<-ctx.Done()
+		unsubscribe()
+	}()
+	return events, nil
+}
+
 // Mutation returns MutationResolver implementation.
 func (r *Resolver) Mutation() MutationResolver { return &mutationResolver{r} }
 
 // Query returns QueryResolver implementation.
 func (r *Resolver) Query() QueryResolver { return &queryResolver{r} }
 
+// Subscription returns SubscriptionResolver implementation.
+func (r *Resolver) Subscription() SubscriptionResolver { return &subscriptionResolver{r} }
+
 type mutationResolver struct{ *Resolver }
 type queryResolver struct{ *Resolver }
+type subscriptionResolver struct{ *Resolver }
 
 // !!! WARNING !!!
 // The code below was going to be deleted when updating resolvers. It has been copied here so you have